@@ -27,6 +27,7 @@ use afe4404::{
         ActiveTiming, AmbientTiming, LedTiming, MeasurementWindowConfiguration, PowerDownTiming,
     },
     modes::ThreeLedsMode,
+    setup::QuickSetup,
     system::{
         DynamicConfiguration,
         State::{Disabled, Enabled},
@@ -53,52 +54,11 @@ fn main() {
     )
     .expect("Failed to initialize I2C bus.");
 
-    let mut frontend = AFE4404::with_three_leds(i2c, 0x58u8, Frequency::new::<megahertz>(4.0));
-
-    frontend.sw_reset().expect("Cannot reset the afe");
-
-    frontend
-        .set_leds_current(&LedCurrentConfiguration::<ThreeLedsMode>::new(
-            ElectricCurrent::new::<milliampere>(30.0),
-            ElectricCurrent::new::<milliampere>(2.0),
-            ElectricCurrent::new::<milliampere>(2.0),
-        ))
-        .expect("Cannot set LEDs current");
-
-    frontend
-        .set_offset_current(&OffsetCurrentConfiguration::<ThreeLedsMode>::new(
-            ElectricCurrent::new::<microampere>(-1.5),
-            ElectricCurrent::new::<microampere>(-3.0),
-            ElectricCurrent::new::<microampere>(-3.0),
-            ElectricCurrent::new::<microampere>(0.0),
-        ))
-        .expect("Cannot set offset current");
-
-    frontend
-        .set_tia_resistors(&ResistorConfiguration::<ThreeLedsMode>::new(
-            ElectricalResistance::new::<kiloohm>(50.0),
-            ElectricalResistance::new::<kiloohm>(50.0),
-        ))
-        .expect("Cannot set tia resistors");
-
-    frontend
-        .set_tia_capacitors(&CapacitorConfiguration::<ThreeLedsMode>::new(
-            Capacitance::new::<picofarad>(5.0),
-            Capacitance::new::<picofarad>(5.0),
-        ))
-        .expect("Cannot set tia capacitors");
-
-    frontend
-        .set_dynamic(&DynamicConfiguration {
-            transmitter: Disabled,
-            adc: Disabled,
-            tia: Enabled,
-            rest_of_adc: Enabled,
-        })
-        .unwrap();
+    let mut delay = esp_idf_hal::delay::Ets;
 
-    frontend
-        .set_measurement_window(&MeasurementWindowConfiguration::<ThreeLedsMode>::new(
+    let setup = QuickSetup::<ThreeLedsMode> {
+        clock: ClockConfiguration::Internal,
+        window: MeasurementWindowConfiguration::<ThreeLedsMode>::new(
             Time::new::<microsecond>(10_000.0),
             ActiveTiming::<ThreeLedsMode>::new(
                 LedTiming {
@@ -144,14 +104,45 @@ fn main() {
                 power_down_st: Time::new::<microsecond>(1368.75),
                 power_down_end: Time::new::<microsecond>(9799.75),
             },
+        ),
+        averages: 1,
+        resistors: ResistorConfiguration::<ThreeLedsMode>::new(
+            ElectricalResistance::new::<kiloohm>(50.0),
+            ElectricalResistance::new::<kiloohm>(50.0),
+        ),
+        capacitors: CapacitorConfiguration::<ThreeLedsMode>::new(
+            Capacitance::new::<picofarad>(5.0),
+            Capacitance::new::<picofarad>(5.0),
+        ),
+        leds_current: LedCurrentConfiguration::<ThreeLedsMode>::new(
+            ElectricCurrent::new::<milliampere>(30.0),
+            ElectricCurrent::new::<milliampere>(2.0),
+            ElectricCurrent::new::<milliampere>(2.0),
+        ),
+    };
+
+    let mut frontend = AFE4404::with_three_leds(i2c, 0x58u8, Frequency::new::<megahertz>(4.0))
+        .init(&setup, &mut delay)
+        .expect("Cannot initialize the afe");
+
+    frontend
+        .set_offset_current(&OffsetCurrentConfiguration::<ThreeLedsMode>::new(
+            ElectricCurrent::new::<microampere>(-1.5),
+            ElectricCurrent::new::<microampere>(-3.0),
+            ElectricCurrent::new::<microampere>(-3.0),
+            ElectricCurrent::new::<microampere>(0.0),
         ))
-        .expect("Cannot set timing window");
+        .expect("Cannot set offset current");
 
     frontend
-        .set_clock_source(ClockConfiguration::Internal)
-        .expect("Cannot set clock source");
+        .set_dynamic(&DynamicConfiguration {
+            transmitter: Disabled,
+            adc: Disabled,
+            tia: Enabled,
+            rest_of_adc: Enabled,
+        })
+        .unwrap();
 
-    let mut delay = esp_idf_hal::delay::Ets;
     delay.delay_ms(200).unwrap();
 
     interrupt_pin
@@ -2,14 +2,27 @@
 
 use embedded_hal::i2c::I2c;
 use embedded_hal::i2c::SevenBitAddress;
+use uom::si::f32::ElectricPotential;
 
-use crate::{device::AFE4404, errors::AfeError, modes::LedMode, register_structs::R3Dh};
+use crate::{
+    device::AFE4404, errors::AfeError, modes::LedMode, register_structs::R3Dh,
+    value_reading::AdcCode,
+};
 
 impl<I2C, MODE> AFE4404<I2C, MODE>
 where
     I2C: I2c<SevenBitAddress>,
     MODE: LedMode,
 {
+    /// Gets the resolution of the adc, that is the voltage of a single code.
+    ///
+    /// # Notes
+    ///
+    /// The full scale of ±1.2 V is divided in ±2097151 codes.
+    pub fn adc_resolution(&self) -> ElectricPotential {
+        AdcCode::quantisation()
+    }
+
     /// Sets the number of averages performed by the adc.
     ///
     /// # Notes
@@ -21,14 +34,14 @@ where
     /// This function returns an error if the I2C bus encounters an error.
     /// Setting a number of averages greater than 16 will result in an error.
     pub fn set_averaging(&mut self, averages: u8) -> Result<u8, AfeError<I2C::Error>> {
-        let r1eh_prev = self.registers.r1Eh.read()?;
+        let r1eh_prev = self.registers.r1Eh().read()?;
 
         if !(1..=16).contains(&averages) {
             return Err(AfeError::NumberOfAveragesOutsideAllowedRange);
         }
 
         self.registers
-            .r1Eh
+            .r1Eh()
             .write(r1eh_prev.with_numav(averages - 1))?;
 
         Ok(averages)
@@ -40,7 +53,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_averaging(&mut self) -> Result<u8, AfeError<I2C::Error>> {
-        let r1eh_prev = self.registers.r1Eh.read()?;
+        let r1eh_prev = self.registers.r1Eh().read()?;
 
         if r1eh_prev.numav() > 15 {
             return Err(AfeError::InvalidRegisterValue { reg_addr: 0x1e });
@@ -70,7 +83,7 @@ where
             _ => return Err(AfeError::DecimationFactorOutsideAllowedRange),
         };
 
-        self.registers.r3Dh.write(
+        self.registers.r3Dh().write(
             R3Dh::new()
                 .with_dec_en(decimation_factor != 1)
                 .with_dec_factor(decimation_reg),
@@ -85,7 +98,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_decimation(&mut self) -> Result<u8, AfeError<I2C::Error>> {
-        let r3dh_prev = self.registers.r3Dh.read()?;
+        let r3dh_prev = self.registers.r3Dh().read()?;
 
         let decimation_factor: u8 = match r3dh_prev.dec_factor() {
             0 => 1,
@@ -98,4 +111,106 @@ where
 
         Ok(decimation_factor)
     }
+
+    /// Gets the effective resolution of the averaged and decimated readings.
+    ///
+    /// # Notes
+    ///
+    /// The resolution of a single code is divided by the square root of the number of averages times the decimation factor.
+    /// The improvement assumes uncorrelated noise, so it is an upper bound of the actual one.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub fn effective_resolution(&mut self) -> Result<ElectricPotential, AfeError<I2C::Error>> {
+        let effective_averages =
+            u16::from(self.get_averaging()?) * u16::from(self.get_decimation()?);
+
+        Ok(self.adc_resolution() / f32::from(effective_averages).sqrt())
+    }
+
+    /// Checks whether the averaged value registers hold decimated data.
+    ///
+    /// # Notes
+    ///
+    /// When the decimation factor is one, the averaged registers (`R3Fh` and `R40h`) just mirror the instantaneous ones.
+    /// When it is greater than one, they are updated at the decimated rate.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub fn averaged_readers_valid(&mut self) -> Result<bool, AfeError<I2C::Error>> {
+        Ok(self.get_decimation()? > 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{errors::AfeError, mock::MockAfe, value_reading::AdcCode};
+
+    #[test]
+    fn averaged_readers_invalid_without_decimation() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        afe.set_decimation(1).unwrap();
+
+        assert_eq!(mock.register(0x3D), 0);
+        assert!(!afe.averaged_readers_valid().unwrap());
+    }
+
+    #[test]
+    fn averaged_readers_valid_with_decimation() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        afe.set_decimation(4).unwrap();
+
+        // Decimation enabled with a factor code of 2.
+        assert_eq!(mock.register(0x3D), 1 << 5 | 2 << 1);
+        assert!(afe.averaged_readers_valid().unwrap());
+    }
+
+    #[test]
+    fn averaged_readers_check_rejects_invalid_decimation() {
+        let mock = MockAfe::new();
+        mock.set_register(0x3D, 1 << 5 | 5 << 1);
+        let mut afe = mock.three_leds();
+
+        assert!(matches!(
+            afe.averaged_readers_valid(),
+            Err(AfeError::InvalidRegisterValue { reg_addr: 0x3d })
+        ));
+    }
+
+    #[test]
+    fn adc_resolution_is_full_scale_over_codes() {
+        use uom::si::electric_potential::volt;
+
+        let afe = MockAfe::new().three_leds();
+
+        let resolution = afe.adc_resolution().get::<volt>();
+        assert!((resolution / (1.2 / 2_097_151.0) - 1.0).abs() < 1e-6);
+        assert_eq!(AdcCode(1).to_voltage(), afe.adc_resolution());
+    }
+
+    #[test]
+    fn effective_resolution_improves_with_averages() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+        let resolution = afe.adc_resolution();
+
+        for (averaging, decimation, improvement) in
+            [(1, 1, 1.0), (4, 1, 2.0), (4, 4, 4.0), (16, 16, 16.0)]
+        {
+            afe.set_averaging(averaging).unwrap();
+            afe.set_decimation(decimation).unwrap();
+
+            let effective = afe.effective_resolution().unwrap();
+            assert!(
+                ((resolution / effective).value - improvement).abs() < 1e-4,
+                "{averaging} averages, decimation {decimation}"
+            );
+        }
+    }
 }
@@ -0,0 +1,658 @@
+//! This module contains the asynchronous [`AFE4404`] driver.
+//!
+//! The asynchronous driver loads the registers needed by each function through the asynchronous I2C bus,
+//! runs the blocking implementation against an in-memory copy of them and writes back the changed registers.
+//! The logic is therefore the same of the blocking driver, only the bus transactions are awaited.
+
+use core::ops::RangeInclusive;
+
+use embedded_hal::i2c::{ErrorType, SevenBitAddress};
+use embedded_hal_async::i2c::I2c;
+use uom::si::f32::Frequency;
+
+use crate::{
+    clock::ClockConfiguration,
+    device::AFE4404,
+    errors::AfeError,
+    led_current::{LedCurrentConfiguration, OffsetCurrentConfiguration},
+    measurement_window::MeasurementWindowConfiguration,
+    modes::{LedMode, ThreeLedsMode, TwoLedsMode, UninitializedMode},
+    register::{is_configuration_register, READ_MODE, WRITE_MODE},
+    system::DynamicConfiguration,
+    tia::{CapacitorConfiguration, ResistorConfiguration},
+    value_reading::Readings,
+};
+
+use shadow::ShadowBus;
+
+mod shadow;
+
+/// The registers used by the LEDs current functions.
+const LEDS_CURRENT_REGISTERS: [u8; 2] = [0x22, 0x23];
+/// The registers used by the clock functions.
+const CLOCK_REGISTERS: [u8; 2] = [0x23, 0x29];
+/// The registers used by the TIA functions.
+const TIA_REGISTERS: [u8; 2] = [0x20, 0x21];
+/// The timing registers used by the measurement window functions, together with `WINDOW_EXTRA_REGISTERS`.
+const WINDOW_REGISTERS: RangeInclusive<u8> = 0x01..=0x1e;
+/// The power-down, LED3 lighting and clock division registers used by the measurement window functions.
+const WINDOW_EXTRA_REGISTERS: [u8; 5] = [0x32, 0x33, 0x36, 0x37, 0x39];
+/// The value registers used by the reading functions.
+const VALUE_REGISTERS: RangeInclusive<u8> = 0x2a..=0x2d;
+
+/// Represents the blocking driver running against the in-memory registers.
+type ShadowAfe<I2C, MODE> = AFE4404<ShadowBus<<I2C as ErrorType>::Error>, MODE>;
+
+/// Represents the [`AFE4404`] device driven through an asynchronous I2C bus.
+///
+/// # Notes
+///
+/// Every function loads the registers it needs before running, so the device is always the source of truth.
+/// Writes are replayed in the same order of the blocking driver, also when the function returns an error,
+/// unless the function read a register not loaded, which is reported as an error without writing.
+pub struct AsyncAFE4404<I2C, MODE>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    i2c: I2C,
+    address: SevenBitAddress,
+    shadow: ShadowBus<I2C::Error>,
+    inner: ShadowAfe<I2C, MODE>,
+}
+
+impl<I2C> AsyncAFE4404<I2C, UninitializedMode>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    /// Creates a new asynchronous AFE4404 instance with three LEDs.
+    pub fn with_three_leds(
+        i2c: I2C,
+        address: SevenBitAddress,
+        clock: Frequency,
+    ) -> AsyncAFE4404<I2C, ThreeLedsMode> {
+        let shadow = ShadowBus::new();
+
+        AsyncAFE4404::<I2C, ThreeLedsMode> {
+            i2c,
+            address,
+            // The inner device only records the transactions, so it needs no initialization.
+            inner: AFE4404::with_three_leds(shadow.clone(), address, clock).into_state(),
+            shadow,
+        }
+    }
+
+    /// Creates a new asynchronous AFE4404 instance with two LEDs.
+    pub fn with_two_leds(
+        i2c: I2C,
+        address: SevenBitAddress,
+        clock: Frequency,
+    ) -> AsyncAFE4404<I2C, TwoLedsMode> {
+        let shadow = ShadowBus::new();
+
+        AsyncAFE4404::<I2C, TwoLedsMode> {
+            i2c,
+            address,
+            // The inner device only records the transactions, so it needs no initialization.
+            inner: AFE4404::with_two_leds(shadow.clone(), address, clock).into_state(),
+            shadow,
+        }
+    }
+}
+
+impl<I2C, MODE> AsyncAFE4404<I2C, MODE>
+where
+    I2C: I2c<SevenBitAddress>,
+    MODE: LedMode,
+{
+    /// Releases the I2C bus.
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+
+    /// Reads the given registers from the device into the in-memory copy.
+    async fn load(
+        &mut self,
+        reg_addrs: impl IntoIterator<Item = u8>,
+    ) -> Result<(), AfeError<I2C::Error>> {
+        for reg_addr in reg_addrs {
+            let configuration = is_configuration_register(reg_addr);
+
+            // Enable register reading flag for configuration registers.
+            if configuration {
+                self.i2c.write(self.address, &READ_MODE).await?;
+            }
+
+            let receive_buffer = self.read_pointer(reg_addr).await;
+
+            // Disable register reading flag even if the reading failed, otherwise the following writes are ignored.
+            let restored = if configuration {
+                self.i2c.write(self.address, &WRITE_MODE).await
+            } else {
+                Ok(())
+            };
+
+            let [high, middle, low] = receive_buffer?;
+            restored?;
+
+            self.shadow
+                .load(reg_addr, u32::from_be_bytes([0, high, middle, low]));
+        }
+
+        Ok(())
+    }
+
+    /// Reads the raw bytes of the given address.
+    async fn read_pointer(&mut self, reg_addr: u8) -> Result<[u8; 3], AfeError<I2C::Error>> {
+        let mut receive_buffer: [u8; 3] = [0, 0, 0];
+
+        self.i2c.write(self.address, &[reg_addr]).await?;
+        self.i2c.read(self.address, &mut receive_buffer).await?;
+
+        Ok(receive_buffer)
+    }
+
+    /// Runs a blocking function against the in-memory copy of the given registers, then writes back the changed ones.
+    async fn run<R>(
+        &mut self,
+        reg_addrs: impl IntoIterator<Item = u8>,
+        function: impl FnOnce(&mut ShadowAfe<I2C, MODE>) -> Result<R, AfeError<I2C::Error>>,
+    ) -> Result<R, AfeError<I2C::Error>> {
+        self.shadow.invalidate();
+        self.load(reg_addrs).await?;
+
+        let result = function(&mut self.inner);
+
+        // The writes computed from a register not loaded are not replayed.
+        if let Some(reg_addr) = self.shadow.take_unloaded_read() {
+            return Err(AfeError::RegisterNotLoaded { reg_addr });
+        }
+
+        for (reg_addr, value) in self.shadow.take_writes() {
+            let [_, high, middle, low] = value.to_be_bytes();
+            self.i2c
+                .write(self.address, &[reg_addr, high, middle, low])
+                .await?;
+        }
+
+        result
+    }
+
+    /// Software resets the [`AFE4404`].
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub async fn sw_reset(&mut self) -> Result<(), AfeError<I2C::Error>> {
+        self.run([], ShadowAfe::<I2C, MODE>::sw_reset).await
+    }
+
+    /// Sets the clock source.
+    ///
+    /// # Notes
+    ///
+    /// See [`AFE4404::set_clock_source`].
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting an internal clock value different from 4MHz will result in an error.
+    /// Setting an output clock division ratio that is not a power of two will result in an error.
+    pub async fn set_clock_source(
+        &mut self,
+        configuration: ClockConfiguration,
+    ) -> Result<ClockConfiguration, AfeError<I2C::Error>> {
+        self.run(CLOCK_REGISTERS, |afe| afe.set_clock_source(configuration))
+            .await
+    }
+
+    /// Gets the clock source.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub async fn get_clock_source(&mut self) -> Result<ClockConfiguration, AfeError<I2C::Error>> {
+        self.run(CLOCK_REGISTERS, ShadowAfe::<I2C, MODE>::get_clock_source)
+            .await
+    }
+
+    /// Sets the number of averages performed by the adc.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a number of averages greater than 16 will result in an error.
+    pub async fn set_averaging(&mut self, averages: u8) -> Result<u8, AfeError<I2C::Error>> {
+        self.run([0x1e], |afe| afe.set_averaging(averages)).await
+    }
+
+    /// Gets the number of averages performed by the adc.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub async fn get_averaging(&mut self) -> Result<u8, AfeError<I2C::Error>> {
+        self.run([0x1e], ShadowAfe::<I2C, MODE>::get_averaging)
+            .await
+    }
+
+    /// Sets the decimation factor.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a wrong decimation factor will result in an error.
+    pub async fn set_decimation(
+        &mut self,
+        decimation_factor: u8,
+    ) -> Result<u8, AfeError<I2C::Error>> {
+        self.run([0x3d], |afe| afe.set_decimation(decimation_factor))
+            .await
+    }
+
+    /// Gets the decimation factor.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub async fn get_decimation(&mut self) -> Result<u8, AfeError<I2C::Error>> {
+        self.run([0x3d], ShadowAfe::<I2C, MODE>::get_decimation)
+            .await
+    }
+
+    /// Sets the functional blocks to disable during dynamic power down.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub async fn set_dynamic(
+        &mut self,
+        configuration: &DynamicConfiguration,
+    ) -> Result<DynamicConfiguration, AfeError<I2C::Error>> {
+        self.run([0x23], |afe| afe.set_dynamic(configuration)).await
+    }
+
+    /// Gets the functional blocks disabled during dynamic power down.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub async fn get_dynamic(&mut self) -> Result<DynamicConfiguration, AfeError<I2C::Error>> {
+        self.run([0x23], ShadowAfe::<I2C, MODE>::get_dynamic).await
+    }
+}
+
+impl<I2C> AsyncAFE4404<I2C, ThreeLedsMode>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    /// Sets the LEDs current.
+    ///
+    /// # Notes
+    ///
+    /// See [`AFE4404::set_leds_current`].
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a current value outside the range 0-100mA will result in an error.
+    pub async fn set_leds_current(
+        &mut self,
+        configuration: &LedCurrentConfiguration<ThreeLedsMode>,
+    ) -> Result<LedCurrentConfiguration<ThreeLedsMode>, AfeError<I2C::Error>> {
+        self.run(LEDS_CURRENT_REGISTERS, |afe| {
+            afe.set_leds_current(configuration)
+        })
+        .await
+    }
+
+    /// Gets the LEDs current.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub async fn get_leds_current(
+        &mut self,
+    ) -> Result<LedCurrentConfiguration<ThreeLedsMode>, AfeError<I2C::Error>> {
+        self.run(
+            LEDS_CURRENT_REGISTERS,
+            ShadowAfe::<I2C, ThreeLedsMode>::get_leds_current,
+        )
+        .await
+    }
+
+    /// Sets the offset cancellation currents.
+    ///
+    /// # Notes
+    ///
+    /// See [`AFE4404::set_offset_current`].
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a current value outside the range -7-7uA will result in an error.
+    pub async fn set_offset_current(
+        &mut self,
+        configuration: &OffsetCurrentConfiguration<ThreeLedsMode>,
+    ) -> Result<OffsetCurrentConfiguration<ThreeLedsMode>, AfeError<I2C::Error>> {
+        self.run([0x3a], |afe| afe.set_offset_current(configuration))
+            .await
+    }
+
+    /// Gets the offset cancellation currents.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub async fn get_offset_current(
+        &mut self,
+    ) -> Result<OffsetCurrentConfiguration<ThreeLedsMode>, AfeError<I2C::Error>> {
+        self.run([0x3a], ShadowAfe::<I2C, ThreeLedsMode>::get_offset_current)
+            .await
+    }
+
+    /// Sets the tia resistors value.
+    ///
+    /// # Notes
+    ///
+    /// See [`AFE4404::set_tia_resistors`].
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a resistor value outside the range 10-2000 kOhm will result in an error.
+    pub async fn set_tia_resistors(
+        &mut self,
+        configuration: &ResistorConfiguration<ThreeLedsMode>,
+    ) -> Result<ResistorConfiguration<ThreeLedsMode>, AfeError<I2C::Error>> {
+        self.run(TIA_REGISTERS, |afe| afe.set_tia_resistors(configuration))
+            .await
+    }
+
+    /// Gets the tia resistors value.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub async fn get_tia_resistors(
+        &mut self,
+    ) -> Result<ResistorConfiguration<ThreeLedsMode>, AfeError<I2C::Error>> {
+        self.run(
+            TIA_REGISTERS,
+            ShadowAfe::<I2C, ThreeLedsMode>::get_tia_resistors,
+        )
+        .await
+    }
+
+    /// Sets the tia capacitors value.
+    ///
+    /// # Notes
+    ///
+    /// See [`AFE4404::set_tia_capacitors`].
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a capacitor value outside the range 2.5-25 pF will result in an error.
+    pub async fn set_tia_capacitors(
+        &mut self,
+        configuration: &CapacitorConfiguration<ThreeLedsMode>,
+    ) -> Result<CapacitorConfiguration<ThreeLedsMode>, AfeError<I2C::Error>> {
+        self.run(TIA_REGISTERS, |afe| afe.set_tia_capacitors(configuration))
+            .await
+    }
+
+    /// Gets the tia capacitors value.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub async fn get_tia_capacitors(
+        &mut self,
+    ) -> Result<CapacitorConfiguration<ThreeLedsMode>, AfeError<I2C::Error>> {
+        self.run(
+            TIA_REGISTERS,
+            ShadowAfe::<I2C, ThreeLedsMode>::get_tia_capacitors,
+        )
+        .await
+    }
+
+    /// Sets the LEDs and Ambient timings of the measurement window.
+    ///
+    /// # Notes
+    ///
+    /// See [`AFE4404::set_measurement_window`].
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a window period too long for the current clock frequency or equal to zero will result in an error.
+    /// Setting a power-down window not satisfying `power_down_st < power_down_end <= period` will result in an error.
+    /// Setting a measurement window violating a timing constraint, see [`TimingViolation`](crate::measurement_window::TimingViolation), will result in an error.
+    pub async fn set_measurement_window(
+        &mut self,
+        configuration: &MeasurementWindowConfiguration<ThreeLedsMode>,
+    ) -> Result<MeasurementWindowConfiguration<ThreeLedsMode>, AfeError<I2C::Error>> {
+        self.run(WINDOW_REGISTERS.chain(WINDOW_EXTRA_REGISTERS), |afe| {
+            afe.set_measurement_window(configuration)
+        })
+        .await
+    }
+
+    /// Gets the LEDs and Ambient timings of the measurement window.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub async fn get_measurement_window(
+        &mut self,
+    ) -> Result<MeasurementWindowConfiguration<ThreeLedsMode>, AfeError<I2C::Error>> {
+        self.run(
+            WINDOW_REGISTERS.chain(WINDOW_EXTRA_REGISTERS),
+            ShadowAfe::<I2C, ThreeLedsMode>::get_measurement_window,
+        )
+        .await
+    }
+
+    /// Reads the sampled values.
+    ///
+    /// # Notes
+    ///
+    /// Call this function after an `ADC_RDY` pulse, data will remain valid until next `ADC_RDY` pulse.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// This function returns an error if the ADC reading falls outside the allowed range.
+    pub async fn read(&mut self) -> Result<Readings<ThreeLedsMode>, AfeError<I2C::Error>> {
+        self.run(VALUE_REGISTERS, ShadowAfe::<I2C, ThreeLedsMode>::read)
+            .await
+    }
+}
+
+impl<I2C> AsyncAFE4404<I2C, TwoLedsMode>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    /// Sets the LEDs current.
+    ///
+    /// # Notes
+    ///
+    /// See [`AFE4404::set_leds_current`].
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a current value outside the range 0-100mA will result in an error.
+    pub async fn set_leds_current(
+        &mut self,
+        configuration: &LedCurrentConfiguration<TwoLedsMode>,
+    ) -> Result<LedCurrentConfiguration<TwoLedsMode>, AfeError<I2C::Error>> {
+        self.run(LEDS_CURRENT_REGISTERS, |afe| {
+            afe.set_leds_current(configuration)
+        })
+        .await
+    }
+
+    /// Gets the LEDs current.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub async fn get_leds_current(
+        &mut self,
+    ) -> Result<LedCurrentConfiguration<TwoLedsMode>, AfeError<I2C::Error>> {
+        self.run(
+            LEDS_CURRENT_REGISTERS,
+            ShadowAfe::<I2C, TwoLedsMode>::get_leds_current,
+        )
+        .await
+    }
+
+    /// Sets the offset cancellation currents.
+    ///
+    /// # Notes
+    ///
+    /// See [`AFE4404::set_offset_current`].
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a current value outside the range -7-7uA will result in an error.
+    pub async fn set_offset_current(
+        &mut self,
+        configuration: &OffsetCurrentConfiguration<TwoLedsMode>,
+    ) -> Result<OffsetCurrentConfiguration<TwoLedsMode>, AfeError<I2C::Error>> {
+        self.run([0x3a], |afe| afe.set_offset_current(configuration))
+            .await
+    }
+
+    /// Gets the offset cancellation currents.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub async fn get_offset_current(
+        &mut self,
+    ) -> Result<OffsetCurrentConfiguration<TwoLedsMode>, AfeError<I2C::Error>> {
+        self.run([0x3a], ShadowAfe::<I2C, TwoLedsMode>::get_offset_current)
+            .await
+    }
+
+    /// Sets the tia resistors value.
+    ///
+    /// # Notes
+    ///
+    /// See [`AFE4404::set_tia_resistors`].
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a resistor value outside the range 10-2000 kOhm will result in an error.
+    pub async fn set_tia_resistors(
+        &mut self,
+        configuration: &ResistorConfiguration<TwoLedsMode>,
+    ) -> Result<ResistorConfiguration<TwoLedsMode>, AfeError<I2C::Error>> {
+        self.run(TIA_REGISTERS, |afe| afe.set_tia_resistors(configuration))
+            .await
+    }
+
+    /// Gets the tia resistors value.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub async fn get_tia_resistors(
+        &mut self,
+    ) -> Result<ResistorConfiguration<TwoLedsMode>, AfeError<I2C::Error>> {
+        self.run(
+            TIA_REGISTERS,
+            ShadowAfe::<I2C, TwoLedsMode>::get_tia_resistors,
+        )
+        .await
+    }
+
+    /// Sets the tia capacitors value.
+    ///
+    /// # Notes
+    ///
+    /// See [`AFE4404::set_tia_capacitors`].
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a capacitor value outside the range 2.5-25 pF will result in an error.
+    pub async fn set_tia_capacitors(
+        &mut self,
+        configuration: &CapacitorConfiguration<TwoLedsMode>,
+    ) -> Result<CapacitorConfiguration<TwoLedsMode>, AfeError<I2C::Error>> {
+        self.run(TIA_REGISTERS, |afe| afe.set_tia_capacitors(configuration))
+            .await
+    }
+
+    /// Gets the tia capacitors value.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub async fn get_tia_capacitors(
+        &mut self,
+    ) -> Result<CapacitorConfiguration<TwoLedsMode>, AfeError<I2C::Error>> {
+        self.run(
+            TIA_REGISTERS,
+            ShadowAfe::<I2C, TwoLedsMode>::get_tia_capacitors,
+        )
+        .await
+    }
+
+    /// Sets the LEDs and Ambient timings of the measurement window.
+    ///
+    /// # Notes
+    ///
+    /// See [`AFE4404::set_measurement_window`].
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a window period too long for the current clock frequency or equal to zero will result in an error.
+    /// Setting a power-down window not satisfying `power_down_st < power_down_end <= period` will result in an error.
+    /// Setting a measurement window violating a timing constraint, see [`TimingViolation`](crate::measurement_window::TimingViolation), will result in an error.
+    pub async fn set_measurement_window(
+        &mut self,
+        configuration: &MeasurementWindowConfiguration<TwoLedsMode>,
+    ) -> Result<MeasurementWindowConfiguration<TwoLedsMode>, AfeError<I2C::Error>> {
+        self.run(WINDOW_REGISTERS.chain(WINDOW_EXTRA_REGISTERS), |afe| {
+            afe.set_measurement_window(configuration)
+        })
+        .await
+    }
+
+    /// Gets the LEDs and Ambient timings of the measurement window.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub async fn get_measurement_window(
+        &mut self,
+    ) -> Result<MeasurementWindowConfiguration<TwoLedsMode>, AfeError<I2C::Error>> {
+        self.run(
+            WINDOW_REGISTERS.chain(WINDOW_EXTRA_REGISTERS),
+            ShadowAfe::<I2C, TwoLedsMode>::get_measurement_window,
+        )
+        .await
+    }
+
+    /// Reads the sampled values.
+    ///
+    /// # Notes
+    ///
+    /// Call this function after an `ADC_RDY` pulse, data will remain valid until next `ADC_RDY` pulse.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// This function returns an error if the ADC reading falls outside the allowed range.
+    pub async fn read(&mut self) -> Result<Readings<TwoLedsMode>, AfeError<I2C::Error>> {
+        self.run(VALUE_REGISTERS, ShadowAfe::<I2C, TwoLedsMode>::read)
+            .await
+    }
+}
@@ -0,0 +1,238 @@
+//! This module contains the in-memory copy of the registers used by the asynchronous driver.
+
+use alloc::{sync::Arc, vec::Vec};
+
+use embedded_hal::i2c::{Error, ErrorType, I2c, Operation, SevenBitAddress};
+use spin::Mutex;
+
+/// The number of registers in the copy, from `R00h` to `R40h`.
+const REGISTERS_COUNT: usize = 0x41;
+
+/// Represents the registers copy shared between all the handles of a [`ShadowBus`].
+struct ShadowState {
+    registers: [u32; REGISTERS_COUNT],
+    loaded: [bool; REGISTERS_COUNT],
+    pointer: u8,
+    writes: Vec<(u8, u32)>,
+    unloaded_read: Option<u8>,
+}
+
+/// Represents an in-memory copy of the registers reachable through the blocking `I2c` trait.
+///
+/// # Notes
+///
+/// The registers are loaded from the device before running a blocking function against the copy,
+/// the writes are recorded in order so that they can be replayed on the device afterwards.
+/// The toggling of the `REG_READ` bit is not recorded, since the copy can always be read.
+/// The copy never returns an error, the error type only matches the one of the actual bus.
+/// A register read before being loaded reads as zero and is reported by `take_unloaded_read()`.
+pub(crate) struct ShadowBus<E> {
+    state: Arc<Mutex<ShadowState>>,
+    error: core::marker::PhantomData<fn() -> E>,
+}
+
+impl<E> Clone for ShadowBus<E> {
+    fn clone(&self) -> Self {
+        Self {
+            state: Arc::clone(&self.state),
+            error: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<E> ShadowBus<E> {
+    /// Creates a new `ShadowBus` with no register loaded.
+    pub(crate) fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ShadowState {
+                registers: [0; REGISTERS_COUNT],
+                loaded: [false; REGISTERS_COUNT],
+                pointer: 0,
+                writes: Vec::new(),
+                unloaded_read: None,
+            })),
+            error: core::marker::PhantomData,
+        }
+    }
+
+    /// Marks all the registers as not loaded and discards the recorded writes.
+    pub(crate) fn invalidate(&self) {
+        let mut state = self.state.lock();
+        state.loaded = [false; REGISTERS_COUNT];
+        state.writes.clear();
+        state.unloaded_read = None;
+    }
+
+    /// Stores the 24 bit content of a register read from the device.
+    pub(crate) fn load(&self, reg_addr: u8, value: u32) {
+        let mut state = self.state.lock();
+        if let Some(register) = state.registers.get_mut(usize::from(reg_addr)) {
+            *register = value & 0x00FF_FFFF;
+            state.loaded[usize::from(reg_addr)] = true;
+        }
+    }
+
+    /// Takes the first register read before being loaded since the last call, if any.
+    pub(crate) fn take_unloaded_read(&self) -> Option<u8> {
+        self.state.lock().unloaded_read.take()
+    }
+
+    /// Takes the writes recorded since the last call, in order.
+    pub(crate) fn take_writes(&self) -> Vec<(u8, u32)> {
+        core::mem::take(&mut self.state.lock().writes)
+    }
+}
+
+impl ShadowState {
+    fn write(&mut self, bytes: &[u8]) {
+        match *bytes {
+            [reg_addr] => self.pointer = reg_addr,
+            // Toggling the `REG_READ` bit only.
+            [0, 0, 0, 0 | 1] => {}
+            [reg_addr, high, middle, low] => {
+                self.pointer = reg_addr;
+                let value = u32::from_be_bytes([0, high, middle, low]);
+                if let Some(register) = self.registers.get_mut(usize::from(reg_addr)) {
+                    *register = value;
+                    self.loaded[usize::from(reg_addr)] = true;
+                }
+                self.writes.push((reg_addr, value));
+            }
+            _ => {}
+        }
+    }
+
+    fn read(&mut self, buffer: &mut [u8]) {
+        let loaded = self
+            .loaded
+            .get(usize::from(self.pointer))
+            .copied()
+            .unwrap_or(false);
+        if !loaded && self.unloaded_read.is_none() {
+            self.unloaded_read = Some(self.pointer);
+        }
+
+        let value = self
+            .registers
+            .get(usize::from(self.pointer))
+            .copied()
+            .unwrap_or(0);
+
+        for (byte, value_byte) in buffer.iter_mut().zip(
+            value.to_be_bytes()[1..]
+                .iter()
+                .chain(core::iter::repeat(&0)),
+        ) {
+            *byte = *value_byte;
+        }
+    }
+}
+
+impl<E> ErrorType for ShadowBus<E>
+where
+    E: Error,
+{
+    type Error = E;
+}
+
+impl<E> I2c<SevenBitAddress> for ShadowBus<E>
+where
+    E: Error,
+{
+    fn read(&mut self, _address: SevenBitAddress, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.state.lock().read(read);
+
+        Ok(())
+    }
+
+    fn write(&mut self, _address: SevenBitAddress, write: &[u8]) -> Result<(), Self::Error> {
+        self.state.lock().write(write);
+
+        Ok(())
+    }
+
+    fn write_iter<B>(&mut self, address: SevenBitAddress, bytes: B) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        let bytes: Vec<u8> = bytes.into_iter().collect();
+
+        self.write(address, &bytes)
+    }
+
+    fn write_read(
+        &mut self,
+        address: SevenBitAddress,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.write(address, write)?;
+
+        self.read(address, read)
+    }
+
+    fn write_iter_read<B>(
+        &mut self,
+        address: SevenBitAddress,
+        bytes: B,
+        read: &mut [u8],
+    ) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        self.write_iter(address, bytes)?;
+
+        self.read(address, read)
+    }
+
+    fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        for operation in operations {
+            match operation {
+                Operation::Read(read) => self.read(address, read)?,
+                Operation::Write(write) => self.write(address, write)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn transaction_iter<'a, O>(
+        &mut self,
+        address: SevenBitAddress,
+        operations: O,
+    ) -> Result<(), Self::Error>
+    where
+        O: IntoIterator<Item = Operation<'a>>,
+    {
+        for mut operation in operations {
+            self.transaction(address, core::slice::from_mut(&mut operation))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unloaded_read_reported() {
+        let mut bus = ShadowBus::<embedded_hal::i2c::ErrorKind>::new();
+        let mut buffer = [0xFF; 3];
+
+        bus.load(0x22, 0x00_0F0F);
+        bus.write_read(0x58, &[0x22], &mut buffer).unwrap();
+        assert_eq!(buffer, [0x00, 0x0F, 0x0F]);
+        assert_eq!(bus.take_unloaded_read(), None);
+
+        bus.write_read(0x58, &[0x23], &mut buffer).unwrap();
+        assert_eq!(buffer, [0, 0, 0]);
+        assert_eq!(bus.take_unloaded_read(), Some(0x23));
+        assert_eq!(bus.take_unloaded_read(), None);
+    }
+}
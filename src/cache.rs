@@ -0,0 +1,411 @@
+//! This module contains the in-memory copy of the configuration registers.
+
+use alloc::vec::Vec;
+
+use embedded_hal::i2c::{I2c, SevenBitAddress};
+
+use crate::{
+    device::AFE4404, errors::AfeError, modes::LedMode, register::is_configuration_register,
+    system::State,
+};
+
+/// The number of registers in the copy, from `R00h` to `R40h`.
+const REGISTERS_COUNT: usize = 0x41;
+
+/// The `SW_RESET` bit of `R00h`.
+const SW_RESET: u32 = 0b1000;
+
+/// Represents a register whose content differs from the expected one.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RegisterMismatch {
+    /// The register address.
+    pub reg_addr: u8,
+    /// The 24 bit content known to the driver.
+    pub expected: u32,
+    /// The 24 bit content read from the device.
+    pub actual: u32,
+}
+
+/// Represents the in-memory copy of the configuration registers.
+///
+/// # Notes
+///
+/// Only the configuration registers are cached, since the value registers change at every sample and `R00h` is write only.
+/// A register is cached after being read or written, a write to `R00h` setting `SW_RESET` invalidates the whole copy, since it resets the device.
+/// While staging, the writes to the configuration registers are kept in the copy as pending until they are committed,
+/// the pending registers are always read from the copy, even when it is disabled.
+pub(crate) struct RegisterCache {
+    enabled: bool,
+    staging: bool,
+    values: [Option<u32>; REGISTERS_COUNT],
+    pending: [bool; REGISTERS_COUNT],
+}
+
+impl RegisterCache {
+    /// Creates a new disabled `RegisterCache`.
+    pub(crate) fn new() -> Self {
+        Self {
+            enabled: false,
+            staging: false,
+            values: [None; REGISTERS_COUNT],
+            pending: [false; REGISTERS_COUNT],
+        }
+    }
+
+    /// Checks whether a register can be cached.
+    fn is_cacheable(reg_addr: u8) -> bool {
+        reg_addr != 0x00 && is_configuration_register(reg_addr)
+    }
+
+    /// Gets the cached 24 bit content of a register.
+    pub(crate) fn get(&self, reg_addr: u8) -> Option<u32> {
+        if !self.enabled && !self.is_pending(reg_addr) {
+            return None;
+        }
+
+        self.values.get(usize::from(reg_addr)).copied().flatten()
+    }
+
+    /// Checks whether a register has a pending write.
+    fn is_pending(&self, reg_addr: u8) -> bool {
+        self.pending
+            .get(usize::from(reg_addr))
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// Keeps the 24 bit content of a register as a pending write, returns whether the write was staged.
+    pub(crate) fn stage(&mut self, reg_addr: u8, value: u32) -> bool {
+        if !self.staging || !Self::is_cacheable(reg_addr) {
+            return false;
+        }
+
+        let index = usize::from(reg_addr);
+        self.values[index] = Some(value & 0x00FF_FFFF);
+        self.pending[index] = true;
+
+        true
+    }
+
+    /// Stores the 24 bit content of a register read from the device, unless it has a pending write.
+    ///
+    /// # Notes
+    ///
+    /// A read never invalidates the copy, even if it targets `R00h`.
+    pub(crate) fn refresh(&mut self, reg_addr: u8, value: u32) {
+        if !Self::is_cacheable(reg_addr) || self.is_pending(reg_addr) || !self.enabled {
+            return;
+        }
+
+        self.values[usize::from(reg_addr)] = Some(value & 0x00FF_FFFF);
+    }
+
+    /// Stores the 24 bit content of a register written to the device.
+    pub(crate) fn store(&mut self, reg_addr: u8, value: u32) {
+        if reg_addr == 0x00 && value & SW_RESET != 0 {
+            self.invalidate();
+        }
+
+        if !Self::is_cacheable(reg_addr) {
+            return;
+        }
+
+        let index = usize::from(reg_addr);
+        if self.enabled {
+            self.values[index] = Some(value & 0x00FF_FFFF);
+        }
+        self.pending[index] = false;
+    }
+
+    /// Discards the cached content of all the registers, including the pending writes.
+    pub(crate) fn invalidate(&mut self) {
+        self.values = [None; REGISTERS_COUNT];
+        self.pending = [false; REGISTERS_COUNT];
+    }
+
+    /// Gets the first pending write, sorted by address.
+    fn first_pending(&self) -> Option<(u8, u32)> {
+        let index = self.pending.iter().position(|&pending| pending)?;
+
+        #[allow(clippy::cast_possible_truncation)]
+        Some((index as u8, self.values[index].unwrap_or(0)))
+    }
+
+    /// Gets the cached content of the registers known to be written to the device, sorted by address.
+    pub(crate) fn expected_values(&self) -> Vec<(u8, u32)> {
+        if !self.enabled {
+            return Vec::new();
+        }
+
+        (0..=u8::MAX)
+            .zip(self.values.iter().zip(self.pending.iter()))
+            .filter_map(|(reg_addr, (value, &pending))| match value {
+                Some(value) if !pending => Some((reg_addr, *value)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Discards the pending writes, so that the registers are read again from the device.
+    fn discard_pending(&mut self) {
+        for (value, pending) in self.values.iter_mut().zip(self.pending.iter_mut()) {
+            if *pending {
+                *value = None;
+                *pending = false;
+            }
+        }
+    }
+}
+
+impl<I2C, MODE> AFE4404<I2C, MODE>
+where
+    I2C: I2c<SevenBitAddress>,
+    MODE: LedMode,
+{
+    /// Enables or disables the in-memory copy of the configuration registers.
+    ///
+    /// # Notes
+    ///
+    /// When enabled, the configuration registers are read from the device only once,
+    /// so the read-modify-write functions only issue the write transaction.
+    /// The copy is disabled by default and is cleared, including the staged writes, when enabled or disabled.
+    /// Use `invalidate_register_cache()` if the device is reset or reconfigured without the driver, for example through the `RESETZ` pin.
+    pub fn set_register_cache(&mut self, state: State) {
+        self.registers.cache.enabled = state == State::Enabled;
+        self.registers.cache.invalidate();
+    }
+
+    /// Gets whether the in-memory copy of the configuration registers is enabled.
+    pub fn get_register_cache(&self) -> State {
+        if self.registers.cache.enabled {
+            State::Enabled
+        } else {
+            State::Disabled
+        }
+    }
+
+    /// Discards the in-memory copy of the configuration registers, so that they are read again from the device.
+    pub fn invalidate_register_cache(&mut self) {
+        self.registers.cache.invalidate();
+    }
+
+    /// Starts staging the writes to the configuration registers, so that they are only sent to the device by `commit()`.
+    ///
+    /// # Notes
+    ///
+    /// The functions called while staging read the staged values back, so any number of them can be combined before committing.
+    /// The registers not staged yet are still read from the device, unless the register cache is enabled and filled.
+    /// The writes to `R00h` are sent immediately, a software reset also discards the staged writes.
+    pub fn stage_writes(&mut self) {
+        self.registers.cache.staging = true;
+    }
+
+    /// Sends the staged writes to the device in a single pass, sorted by address, and stops staging.
+    ///
+    /// # Notes
+    ///
+    /// Each register is written once, with its last staged value.
+    /// If a write fails, the remaining writes stay staged and can be committed again.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub fn commit(&mut self) -> Result<(), AfeError<I2C::Error>> {
+        self.registers.cache.staging = false;
+
+        while let Some((reg_addr, value)) = self.registers.cache.first_pending() {
+            // Any register can be used to reach the bus.
+            self.registers.r00h().write_address(reg_addr, value)?;
+        }
+
+        Ok(())
+    }
+
+    /// Discards the staged writes and stops staging.
+    pub fn discard_staged_writes(&mut self) {
+        self.registers.cache.staging = false;
+        self.registers.cache.discard_pending();
+    }
+
+    /// Reads back the cached configuration registers and compares them with the content known to the driver.
+    ///
+    /// # Notes
+    ///
+    /// Use this function to detect a device silently reset or corrupted, for example by an ESD event or a brown-out.
+    /// Only the registers read or written since the register cache was enabled are checked, the staged writes are skipped.
+    /// The mismatches are sorted by address and the register cache is left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Calling this function with the register cache disabled will result in an error.
+    pub fn verify_configuration(&mut self) -> Result<Vec<RegisterMismatch>, AfeError<I2C::Error>> {
+        self.compare_configuration(|_| true)
+    }
+
+    /// Reads back the cached configuration registers accepted by the filter and compares them with the content known to the driver.
+    pub(crate) fn compare_configuration(
+        &mut self,
+        filter: impl Fn(u8) -> bool,
+    ) -> Result<Vec<RegisterMismatch>, AfeError<I2C::Error>> {
+        if !self.registers.cache.enabled {
+            return Err(AfeError::RegisterCacheDisabled);
+        }
+
+        let mut mismatches = Vec::new();
+
+        for (reg_addr, expected) in self.registers.cache.expected_values() {
+            if !filter(reg_addr) {
+                continue;
+            }
+
+            // Any register can be used to reach the bus.
+            let actual = self.registers.r00h().read_address_uncached(reg_addr)?;
+
+            if actual != expected {
+                mismatches.push(RegisterMismatch {
+                    reg_addr,
+                    expected,
+                    actual,
+                });
+            }
+        }
+
+        Ok(mismatches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uom::si::{
+        electric_current::milliampere,
+        electrical_resistance::kiloohm,
+        f32::{ElectricCurrent, ElectricalResistance},
+    };
+
+    use super::*;
+    use crate::{
+        led_current::LedCurrentConfiguration, measurement_window::MeasurementWindowConfiguration,
+        mock::MockAfe, modes::ThreeLedsMode, tia::ResistorConfiguration,
+    };
+
+    #[test]
+    fn reading_control_register_keeps_cache() {
+        let mut cache = RegisterCache::new();
+        cache.enabled = true;
+        cache.staging = true;
+
+        cache.refresh(0x1E, 0x0103);
+        assert!(cache.stage(0x22, 0x0F));
+
+        cache.refresh(0x00, 0x0000);
+
+        assert_eq!(cache.get(0x1E), Some(0x0103));
+        assert_eq!(cache.get(0x22), Some(0x0F));
+        assert!(cache.is_pending(0x22));
+    }
+
+    #[test]
+    fn only_software_reset_invalidates_cache() {
+        let mut cache = RegisterCache::new();
+        cache.enabled = true;
+        cache.staging = true;
+
+        assert!(cache.stage(0x22, 0x0F));
+        cache.store(0x00, 0b0010);
+        assert_eq!(cache.get(0x22), Some(0x0F));
+
+        cache.store(0x00, SW_RESET);
+        assert_eq!(cache.get(0x22), None);
+    }
+
+    #[test]
+    fn refresh_skips_pending_writes() {
+        let mut cache = RegisterCache::new();
+        cache.enabled = true;
+        cache.staging = true;
+
+        assert!(cache.stage(0x22, 0x0F));
+        cache.refresh(0x22, 0x00);
+
+        assert_eq!(cache.get(0x22), Some(0x0F));
+    }
+
+    /// Reconfigures the timing, the LEDs current, the TIA resistors and the averaging of the device.
+    fn reconfigure(afe: &mut AFE4404<MockAfe, ThreeLedsMode>) {
+        afe.set_measurement_window(&MeasurementWindowConfiguration::default())
+            .unwrap();
+        afe.set_leds_current(&LedCurrentConfiguration::<ThreeLedsMode>::new(
+            ElectricCurrent::new::<milliampere>(20.0),
+            ElectricCurrent::new::<milliampere>(30.0),
+            ElectricCurrent::new::<milliampere>(60.0),
+        ))
+        .unwrap();
+        afe.set_tia_resistors(&ResistorConfiguration::<ThreeLedsMode>::new(
+            ElectricalResistance::new::<kiloohm>(100.0),
+            ElectricalResistance::new::<kiloohm>(250.0),
+        ))
+        .unwrap();
+        afe.set_averaging(4).unwrap();
+    }
+
+    #[test]
+    fn staged_writes_reach_bus_only_on_commit() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        afe.stage_writes();
+        reconfigure(&mut afe);
+
+        assert!(mock.written_registers().is_empty());
+        assert!((0x01..0x41).all(|reg_addr| mock.register(reg_addr) == 0));
+        // The staged values are read back by the getters.
+        assert_eq!(afe.get_averaging().unwrap(), 4);
+
+        afe.commit().unwrap();
+
+        let written = mock.written_registers();
+        assert!(!written.is_empty());
+        // Each register is written once, in address order.
+        assert!(written.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn committed_registers_match_direct_writes() {
+        let staged = MockAfe::new();
+        let direct = MockAfe::new();
+
+        let mut afe = staged.three_leds();
+        afe.stage_writes();
+        reconfigure(&mut afe);
+        afe.commit().unwrap();
+        reconfigure(&mut direct.three_leds());
+
+        let mut written = direct.written_registers();
+        written.sort_unstable();
+        written.dedup();
+        assert_eq!(staged.written_registers(), written);
+        for reg_addr in 0x01..0x41 {
+            assert_eq!(
+                staged.register(reg_addr),
+                direct.register(reg_addr),
+                "R{reg_addr:02X}h"
+            );
+        }
+    }
+
+    #[test]
+    fn discarded_writes_never_reach_bus() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        afe.stage_writes();
+        reconfigure(&mut afe);
+        afe.discard_staged_writes();
+        afe.commit().unwrap();
+
+        assert!(mock.written_registers().is_empty());
+        assert_eq!(afe.get_averaging().unwrap(), 1);
+    }
+}
@@ -0,0 +1,78 @@
+//! This module contains the [`AFE4404`] channels.
+
+/// Represents a channel of the [`AFE4404`].
+///
+/// # Notes
+///
+/// In three LEDs mode the Ambient phase is `Ambient1` and the `Ambient2` phase is used by `Led3`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LedChannel {
+    /// The LED1 phase.
+    Led1,
+    /// The LED2 phase.
+    Led2,
+    /// The LED3 phase.
+    Led3,
+    /// The Ambient1 phase.
+    Ambient1,
+    /// The Ambient2 phase.
+    Ambient2,
+}
+
+/// Represents the wavelength of a LED.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Wavelength {
+    /// A red LED, usually around 660 nm.
+    Red,
+    /// An infrared LED, usually around 940 nm.
+    Infrared,
+    /// A green LED, usually around 530 nm.
+    Green,
+}
+
+/// Represents the wiring of the LEDs on a board, mapping each wavelength to the channel driving it.
+///
+/// # Notes
+///
+/// Keep one map for each board spin, so that the readings can be accessed by wavelength regardless of the wiring.
+/// A wavelength not mounted on the board is mapped to `None`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ChannelMap {
+    red: Option<LedChannel>,
+    infrared: Option<LedChannel>,
+    green: Option<LedChannel>,
+}
+
+impl ChannelMap {
+    /// Creates a new `ChannelMap`.
+    pub fn new(
+        red: Option<LedChannel>,
+        infrared: Option<LedChannel>,
+        green: Option<LedChannel>,
+    ) -> Self {
+        Self {
+            red,
+            infrared,
+            green,
+        }
+    }
+
+    /// Gets the channel driving the given wavelength.
+    pub fn channel(&self, wavelength: Wavelength) -> Option<LedChannel> {
+        match wavelength {
+            Wavelength::Red => self.red,
+            Wavelength::Infrared => self.infrared,
+            Wavelength::Green => self.green,
+        }
+    }
+
+    /// Gets the wavelength driven by the given channel.
+    pub fn wavelength(&self, channel: LedChannel) -> Option<Wavelength> {
+        [Wavelength::Red, Wavelength::Infrared, Wavelength::Green]
+            .into_iter()
+            .find(|&wavelength| self.channel(wavelength) == Some(channel))
+    }
+}
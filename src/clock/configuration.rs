@@ -1,11 +1,12 @@
 /// Represents the clock mode of the [`AFE4404`].
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum ClockConfiguration {
     /// The clock is driven by the internal oscillator at 4 MHz.
     Internal,
     /// The clock is driven by the internal oscillator at 4 MHz and propagated to the `CLK` pin.
     InternalToOutput {
-        /// The division factor of the clock output.
+        /// The division factor of the clock output, a power of two between 1 and 128.
         division_ratio: u8,
     },
     /// The clock is driven by an external oscillator.
@@ -17,25 +17,29 @@ where
 {
     /// Sets the clock source.
     ///
+    /// # Notes
+    ///
+    /// The returned configuration equals the one read by `get_clock_source()` function.
+    ///
     /// # Errors
     ///
     /// This function returns an error if the I2C bus encounters an error.
     /// Setting an internal clock value different from 4MHz will result in an error.
-    /// Setting an output clock division ratio greater than 128 will result in an error.
+    /// Setting an output clock division ratio that is not a power of two will result in an error.
     pub fn set_clock_source(
         &mut self,
         configuration: ClockConfiguration,
     ) -> Result<ClockConfiguration, AfeError<I2C::Error>> {
-        let r23h_prev = self.registers.r23h.read()?;
+        let r23h_prev = self.registers.r23h().read()?;
 
         let (internal, output, reg_ratio) = match configuration {
             ClockConfiguration::Internal => (true, false, 0),
             ClockConfiguration::InternalToOutput { division_ratio } => {
-                #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
-                let reg_ratio = f32::from(division_ratio).log2().round() as u8;
-                if reg_ratio > 7 {
+                if !division_ratio.is_power_of_two() {
                     return Err(AfeError::ClockDivisionRatioOutsideAllowedRange);
                 }
+                #[allow(clippy::cast_possible_truncation)]
+                let reg_ratio = division_ratio.trailing_zeros() as u8;
                 (true, true, reg_ratio)
             }
             ClockConfiguration::External => (false, false, 0),
@@ -46,10 +50,10 @@ where
         }
 
         self.registers
-            .r23h
+            .r23h()
             .write(r23h_prev.with_osc_enable(internal))?;
 
-        self.registers.r29h.write(
+        self.registers.r29h().write(
             R29h::new()
                 .with_enable_clkout(output)
                 .with_clkdiv_clkout(reg_ratio),
@@ -59,7 +63,7 @@ where
             ClockConfiguration::Internal => ClockConfiguration::Internal,
             ClockConfiguration::InternalToOutput { division_ratio: _ } => {
                 ClockConfiguration::InternalToOutput {
-                    division_ratio: 2 ^ reg_ratio,
+                    division_ratio: 1 << reg_ratio,
                 }
             }
             ClockConfiguration::External => ClockConfiguration::External,
@@ -70,15 +74,18 @@ where
     ///
     /// # Errors
     ///
-    /// This function returns an error if the I2C bus encounters an error.
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_clock_source(&mut self) -> Result<ClockConfiguration, AfeError<I2C::Error>> {
-        let r23h_prev = self.registers.r23h.read()?;
-        let r29h_prev = self.registers.r29h.read()?;
+        let r23h_prev = self.registers.r23h().read()?;
+        let r29h_prev = self.registers.r29h().read()?;
 
         Ok(if r23h_prev.osc_enable() {
             if r29h_prev.enable_clkout() {
+                if r29h_prev.clkdiv_clkout() > 7 {
+                    return Err(AfeError::InvalidRegisterValue { reg_addr: 0x29 });
+                }
                 ClockConfiguration::InternalToOutput {
-                    division_ratio: 2 ^ r29h_prev.clkdiv_clkout(),
+                    division_ratio: 1 << r29h_prev.clkdiv_clkout(),
                 }
             } else {
                 ClockConfiguration::Internal
@@ -87,4 +94,132 @@ where
             ClockConfiguration::External
         })
     }
+
+    /// Checks that the clock frequency given to the driver matches the clock source of the [`AFE4404`].
+    ///
+    /// # Notes
+    ///
+    /// The internal oscillator runs at 4MHz, so any other frequency is inconsistent with it.
+    /// The frequency of an external clock cannot be checked.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// This function returns an error if the internal oscillator is enabled and the clock frequency is not 4MHz.
+    pub fn check_clock_consistency(&mut self) -> Result<(), AfeError<I2C::Error>> {
+        let r23h_prev = self.registers.r23h().read()?;
+
+        if r23h_prev.osc_enable() && self.clock != Frequency::new::<megahertz>(4.0) {
+            return Err(AfeError::InternalClockMismatch);
+        }
+
+        Ok(())
+    }
+
+    /// Sets the frequency of the clock driving the [`AFE4404`].
+    ///
+    /// # Notes
+    ///
+    /// Use this function after retuning an external oscillator.
+    /// Any cached timer quantisation is invalidated, so subsequent timing conversions reflect the new frequency immediately.
+    /// The timer counts already written are not changed, so the actual timings scale with the clock.
+    pub fn set_clock_frequency(&mut self, clock: Frequency) {
+        self.clock = clock;
+        self.timing_quantisation = None;
+    }
+
+    /// Gets the frequency of the clock driving the [`AFE4404`].
+    pub fn get_clock_frequency(&self) -> Frequency {
+        self.clock
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uom::si::{f32::Time, time::microsecond};
+
+    use super::*;
+    use crate::mock::MockAfe;
+
+    #[test]
+    fn clock_source_round_trips() {
+        let mut afe = MockAfe::new().three_leds();
+
+        let configurations = [ClockConfiguration::Internal, ClockConfiguration::External]
+            .into_iter()
+            .chain((0..8).map(|code| ClockConfiguration::InternalToOutput {
+                division_ratio: 1 << code,
+            }));
+        for configuration in configurations {
+            assert_eq!(afe.set_clock_source(configuration).unwrap(), configuration);
+            assert_eq!(afe.get_clock_source().unwrap(), configuration);
+        }
+    }
+
+    #[test]
+    fn invalid_division_ratios_rejected() {
+        let mut afe = MockAfe::new().three_leds();
+
+        for division_ratio in [0, 3, 6, 100] {
+            assert!(matches!(
+                afe.set_clock_source(ClockConfiguration::InternalToOutput { division_ratio }),
+                Err(AfeError::ClockDivisionRatioOutsideAllowedRange)
+            ));
+        }
+    }
+
+    #[test]
+    fn invalid_division_code_rejected() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        afe.set_clock_source(ClockConfiguration::InternalToOutput { division_ratio: 1 })
+            .unwrap();
+        mock.set_register(0x29, mock.register(0x29) | 0b1_0000);
+
+        assert!(matches!(
+            afe.get_clock_source(),
+            Err(AfeError::InvalidRegisterValue { reg_addr: 0x29 })
+        ));
+    }
+
+    #[test]
+    fn clock_consistency_checks_internal_oscillator() {
+        let mut afe = MockAfe::new().three_leds();
+
+        afe.set_clock_source(ClockConfiguration::Internal).unwrap();
+        afe.check_clock_consistency().unwrap();
+
+        afe.set_clock_frequency(Frequency::new::<megahertz>(8.0));
+        assert!(matches!(
+            afe.check_clock_consistency(),
+            Err(AfeError::InternalClockMismatch)
+        ));
+
+        afe.set_clock_source(ClockConfiguration::External).unwrap();
+        afe.check_clock_consistency().unwrap();
+    }
+
+    #[test]
+    fn clock_frequency_change_rescales_timings() {
+        let mut afe = MockAfe::new().three_leds();
+        let us = |timing: Time| timing.get::<microsecond>();
+
+        assert!((us(afe.into_timing(400).unwrap()) - 100.0).abs() < 1e-3);
+
+        afe.set_clock_frequency(Frequency::new::<megahertz>(8.0));
+        assert!((us(afe.into_timing(400).unwrap()) - 50.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn clock_frequency_change_invalidates_timing_editor() {
+        let mut afe = MockAfe::new().three_leds();
+        let mut editor = afe.begin_timing_edit().unwrap();
+        let us = |timing: Time| timing.get::<microsecond>();
+
+        assert!((us(editor.into_timing(400).unwrap()) - 100.0).abs() < 1e-3);
+
+        editor.set_clock_frequency(Frequency::new::<megahertz>(2.0));
+        assert!((us(editor.into_timing(400).unwrap()) - 200.0).abs() < 1e-3);
+    }
 }
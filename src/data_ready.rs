@@ -0,0 +1,194 @@
+//! This module contains the [`AFE4404`] driven by its `ADC_RDY` pin.
+
+use embedded_hal::digital::InputPin;
+use embedded_hal::i2c::{I2c, SevenBitAddress};
+
+use crate::{
+    device::AFE4404,
+    errors::AfeError,
+    modes::{LedMode, OneLedMode, ThreeLedsMode, TwoLedsMode},
+    value_reading::Readings,
+};
+
+/// Represents the [`AFE4404`] together with the pin connected to its `ADC_RDY` output.
+///
+/// # Notes
+///
+/// The device is still reachable through [`DataReadyAfe::afe`] to change its configuration.
+/// The pin is either polled through the `InputPin` trait or awaited through the `Wait` trait when the `async` feature is enabled.
+pub struct DataReadyAfe<I2C, MODE, PIN>
+where
+    MODE: LedMode,
+{
+    afe: AFE4404<I2C, MODE>,
+    pin: PIN,
+}
+
+impl<I2C, MODE, PIN> DataReadyAfe<I2C, MODE, PIN>
+where
+    I2C: I2c<SevenBitAddress>,
+    MODE: LedMode,
+{
+    /// Creates a new `DataReadyAfe` from the device and the pin connected to its `ADC_RDY` output.
+    pub fn new(afe: AFE4404<I2C, MODE>, pin: PIN) -> Self {
+        Self { afe, pin }
+    }
+
+    /// Gets a mutable reference of the device.
+    pub fn afe(&mut self) -> &mut AFE4404<I2C, MODE> {
+        &mut self.afe
+    }
+
+    /// Releases the device and the pin.
+    pub fn release(self) -> (AFE4404<I2C, MODE>, PIN) {
+        (self.afe, self.pin)
+    }
+
+    /// Polls the pin until the next rising edge of `ADC_RDY`.
+    ///
+    /// # Notes
+    ///
+    /// A pulse already in progress is skipped, so that the readings are never taken at the end of a pulse.
+    /// The pulse must be longer than the polling interval, otherwise it may be missed.
+    fn poll_rising_edge(&mut self) -> Result<(), AfeError<I2C::Error>>
+    where
+        PIN: InputPin,
+    {
+        while self.pin.is_high().map_err(|_| AfeError::PinError)? {}
+        while self.pin.is_low().map_err(|_| AfeError::PinError)? {}
+
+        Ok(())
+    }
+
+    /// Awaits the next rising edge of `ADC_RDY`.
+    #[cfg(feature = "async")]
+    async fn wait_rising_edge(&mut self) -> Result<(), AfeError<I2C::Error>>
+    where
+        PIN: embedded_hal_async::digital::Wait,
+    {
+        self.pin
+            .wait_for_rising_edge()
+            .await
+            .map_err(|_| AfeError::PinError)
+    }
+}
+
+impl<I2C, PIN> DataReadyAfe<I2C, ThreeLedsMode, PIN>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    /// Waits for the next `ADC_RDY` pulse polling the pin, then reads the sampled values.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// This function returns an error if the pin encounters an error.
+    /// This function returns an error if the ADC reading falls outside the allowed range.
+    pub fn wait_for_reading(&mut self) -> Result<Readings<ThreeLedsMode>, AfeError<I2C::Error>>
+    where
+        PIN: InputPin,
+    {
+        self.poll_rising_edge()?;
+
+        self.afe.read()
+    }
+
+    /// Awaits the next `ADC_RDY` pulse, then reads the sampled values.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// This function returns an error if the pin encounters an error.
+    /// This function returns an error if the ADC reading falls outside the allowed range.
+    #[cfg(feature = "async")]
+    pub async fn wait_for_reading_async(
+        &mut self,
+    ) -> Result<Readings<ThreeLedsMode>, AfeError<I2C::Error>>
+    where
+        PIN: embedded_hal_async::digital::Wait,
+    {
+        self.wait_rising_edge().await?;
+
+        self.afe.read()
+    }
+}
+
+impl<I2C, PIN> DataReadyAfe<I2C, TwoLedsMode, PIN>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    /// Waits for the next `ADC_RDY` pulse polling the pin, then reads the sampled values.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// This function returns an error if the pin encounters an error.
+    /// This function returns an error if the ADC reading falls outside the allowed range.
+    pub fn wait_for_reading(&mut self) -> Result<Readings<TwoLedsMode>, AfeError<I2C::Error>>
+    where
+        PIN: InputPin,
+    {
+        self.poll_rising_edge()?;
+
+        self.afe.read()
+    }
+
+    /// Awaits the next `ADC_RDY` pulse, then reads the sampled values.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// This function returns an error if the pin encounters an error.
+    /// This function returns an error if the ADC reading falls outside the allowed range.
+    #[cfg(feature = "async")]
+    pub async fn wait_for_reading_async(
+        &mut self,
+    ) -> Result<Readings<TwoLedsMode>, AfeError<I2C::Error>>
+    where
+        PIN: embedded_hal_async::digital::Wait,
+    {
+        self.wait_rising_edge().await?;
+
+        self.afe.read()
+    }
+}
+
+impl<I2C, PIN> DataReadyAfe<I2C, OneLedMode, PIN>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    /// Waits for the next `ADC_RDY` pulse polling the pin, then reads the sampled values.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// This function returns an error if the pin encounters an error.
+    /// This function returns an error if the ADC reading falls outside the allowed range.
+    pub fn wait_for_reading(&mut self) -> Result<Readings<OneLedMode>, AfeError<I2C::Error>>
+    where
+        PIN: InputPin,
+    {
+        self.poll_rising_edge()?;
+
+        self.afe.read()
+    }
+
+    /// Awaits the next `ADC_RDY` pulse, then reads the sampled values.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// This function returns an error if the pin encounters an error.
+    /// This function returns an error if the ADC reading falls outside the allowed range.
+    #[cfg(feature = "async")]
+    pub async fn wait_for_reading_async(
+        &mut self,
+    ) -> Result<Readings<OneLedMode>, AfeError<I2C::Error>>
+    where
+        PIN: embedded_hal_async::digital::Wait,
+    {
+        self.wait_rising_edge().await?;
+
+        self.afe.read()
+    }
+}
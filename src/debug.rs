@@ -0,0 +1,139 @@
+//! This module contains the decoders of the register contents into their named fields.
+//!
+//! ```ignore
+//! let dump = frontend.dump_registers().expect("Cannot dump the registers");
+//! println!("{:#?}", DecodedDump::new(&dump));
+//! ```
+
+use core::fmt;
+
+use crate::RegisterAddress;
+
+/// Represents the content of a register, formatted with its named fields.
+///
+/// # Notes
+///
+/// The single bit fields are formatted as booleans, the others as unsigned integers.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct DecodedRegister {
+    address: RegisterAddress,
+    value: u32,
+}
+
+impl DecodedRegister {
+    /// Creates a new `DecodedRegister` from the 24 bit content of a register.
+    pub fn new(address: RegisterAddress, value: u32) -> Self {
+        Self {
+            address,
+            value: value & 0x00FF_FFFF,
+        }
+    }
+
+    /// Creates a new `DecodedRegister` from an address and its 24 bit content, `None` if the address is unknown.
+    pub fn from_raw(reg_addr: u8, value: u32) -> Option<Self> {
+        RegisterAddress::from_address(reg_addr).map(|address| Self::new(address, value))
+    }
+
+    /// Gets the address of the register.
+    pub fn address(&self) -> RegisterAddress {
+        self.address
+    }
+
+    /// Gets the 24 bit content of the register.
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+
+    /// Gets the name and the value of the fields, from the most significant one.
+    pub fn fields(&self) -> impl Iterator<Item = (&'static str, u32)> + '_ {
+        self.address
+            .fields()
+            .map(|(name, offset, width)| (name, (self.value >> offset) & ((1 << width) - 1)))
+    }
+}
+
+// Only the named fields are shown, the raw value is available through `value()`.
+#[allow(clippy::missing_fields_in_debug)]
+impl fmt::Debug for DecodedRegister {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct(self.address.name());
+
+        for (name, offset, width) in self.address.fields() {
+            let value = (self.value >> offset) & ((1 << width) - 1);
+            if width == 1 {
+                debug.field(name, &(value != 0));
+            } else {
+                debug.field(name, &value);
+            }
+        }
+
+        debug.finish()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for DecodedRegister {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "{=str} {{", self.address.name());
+
+        for (name, offset, width) in self.address.fields() {
+            let value = (self.value >> offset) & ((1 << width) - 1);
+            if width == 1 {
+                defmt::write!(f, " {=str}: {=bool}", name, value != 0);
+            } else {
+                defmt::write!(f, " {=str}: {=u32}", name, value);
+            }
+        }
+
+        defmt::write!(f, " }}");
+    }
+}
+
+/// Represents a register dump, as returned by `dump_registers()`, formatted with the named fields of each register.
+///
+/// # Notes
+///
+/// The unknown addresses are formatted as raw address and value pairs.
+#[derive(Copy, Clone)]
+pub struct DecodedDump<'a> {
+    dump: &'a [(u8, u32)],
+}
+
+impl<'a> DecodedDump<'a> {
+    /// Creates a new `DecodedDump` from address and value pairs.
+    pub fn new(dump: &'a [(u8, u32)]) -> Self {
+        Self { dump }
+    }
+}
+
+impl fmt::Debug for DecodedDump<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_list();
+
+        for &(reg_addr, value) in self.dump {
+            match DecodedRegister::from_raw(reg_addr, value) {
+                Some(register) => debug.entry(&register),
+                None => debug.entry(&format_args!("{reg_addr:02X}h: {value:#08X}")),
+            };
+        }
+
+        debug.finish()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for DecodedDump<'_> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "[");
+
+        for &(reg_addr, value) in self.dump {
+            if let Some(register) = DecodedRegister::from_raw(reg_addr, value) {
+                defmt::write!(f, " {}", register);
+            } else {
+                defmt::write!(f, " {=u8:02X}h: {=u32:#08X}", reg_addr, value);
+            }
+        }
+
+        defmt::write!(f, " ]");
+    }
+}
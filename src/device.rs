@@ -1,53 +1,204 @@
 //! This module contains the device initialization functions.
 
-use alloc::sync::Arc;
-use spin::Mutex;
-
 use embedded_hal::i2c::{I2c, SevenBitAddress};
-use uom::si::f32::Frequency;
+use uom::si::f32::{Frequency, Time};
 
 use crate::{
-    modes::{LedMode, ThreeLedsMode, TwoLedsMode, UninitializedMode},
+    modes::{
+        Active, DeviceMode, DeviceState, Initialized, LedMode, OneLedMode, ThreeLedsMode,
+        TwoLedsMode, Uninitialized, UninitializedMode,
+    },
     register_block::RegisterBlock,
+    system::DynamicConfiguration,
 };
 
+/// The I2C address of the [`AFE4404`].
+pub const DEFAULT_ADDRESS: SevenBitAddress = 0x58;
+
 /// Represents the [`AFE4404`] device.
-pub struct AFE4404<I2C, MODE>
+///
+/// # Notes
+///
+/// The I2C bus is owned by the device, so the device is `Send` whenever the I2C bus is `Send`.
+/// It can be moved to another thread or RTOS task, or wrapped in a mutex and shared between them, without any additional feature.
+/// A bus shared with other devices can be passed as any `I2c` implementor, such as the devices of `embedded-hal-bus`.
+/// The state defaults to [`Active`], the only state in which the device can be configured and read.
+///
+/// A device owning a bus that is not `Send` cannot be moved to another thread:
+///
+/// ```compile_fail
+/// use std::rc::Rc;
+///
+/// use afe4404::{device::AFE4404, modes::ThreeLedsMode};
+///
+/// fn is_send<T: Send>() {}
+///
+/// is_send::<AFE4404<Rc<()>, ThreeLedsMode>>();
+/// ```
+pub struct AFE4404<I2C, MODE, STATE = Active>
 where
-    MODE: LedMode,
+    STATE: DeviceState,
 {
     pub(crate) registers: RegisterBlock<I2C>,
     pub(crate) clock: Frequency,
+    pub(crate) timing_quantisation: Option<Time>,
+    pub(crate) inverted_readings: bool,
+    pub(crate) idle_dynamic: Option<DynamicConfiguration>,
     mode: core::marker::PhantomData<MODE>,
+    state: core::marker::PhantomData<STATE>,
+}
+
+impl<I2C, MODE, STATE> AFE4404<I2C, MODE, STATE>
+where
+    MODE: LedMode,
+    STATE: Initialized,
+{
+    /// Gets the lighting mode of the [`AFE4404`].
+    ///
+    /// # Notes
+    ///
+    /// The mode is only available once the [`AFE4404`] has been initialized with `init()`.
+    ///
+    /// ```compile_fail
+    /// use afe4404::{device::AFE4404, modes::{ThreeLedsMode, Uninitialized}};
+    ///
+    /// let _ = AFE4404::<(), ThreeLedsMode, Uninitialized>::mode();
+    /// ```
+    pub const fn mode() -> DeviceMode {
+        MODE::MODE
+    }
 }
 
 impl<I2C> AFE4404<I2C, UninitializedMode>
 where
     I2C: I2c<SevenBitAddress>,
 {
-    /// Creates a new AFE4404 instance with three LEDs.
+    /// Creates a new uninitialized AFE4404 instance with three LEDs.
+    ///
+    /// # Notes
+    ///
+    /// The instance must be initialized with `init()` function before being configured or read.
     pub fn with_three_leds(
         i2c: I2C,
         address: SevenBitAddress,
         clock: Frequency,
-    ) -> AFE4404<I2C, ThreeLedsMode> {
-        AFE4404::<I2C, ThreeLedsMode> {
-            registers: RegisterBlock::new(address, &Arc::new(Mutex::new(i2c))),
+    ) -> AFE4404<I2C, ThreeLedsMode, Uninitialized> {
+        AFE4404::<I2C, ThreeLedsMode, Uninitialized> {
+            registers: RegisterBlock::new(address, i2c),
             clock,
+            timing_quantisation: None,
+            inverted_readings: false,
+            idle_dynamic: None,
             mode: core::marker::PhantomData,
+            state: core::marker::PhantomData,
         }
     }
 
-    /// Creates a new AFE4404 instance with two LEDs.
+    /// Creates a new uninitialized AFE4404 instance with two LEDs.
+    ///
+    /// # Notes
+    ///
+    /// The instance must be initialized with `init()` function before being configured or read.
     pub fn with_two_leds(
         i2c: I2C,
         address: SevenBitAddress,
         clock: Frequency,
-    ) -> AFE4404<I2C, TwoLedsMode> {
-        AFE4404::<I2C, TwoLedsMode> {
-            registers: RegisterBlock::new(address, &Arc::new(Mutex::new(i2c))),
+    ) -> AFE4404<I2C, TwoLedsMode, Uninitialized> {
+        AFE4404::<I2C, TwoLedsMode, Uninitialized> {
+            registers: RegisterBlock::new(address, i2c),
+            clock,
+            timing_quantisation: None,
+            inverted_readings: false,
+            idle_dynamic: None,
+            mode: core::marker::PhantomData,
+            state: core::marker::PhantomData,
+        }
+    }
+
+    /// Creates a new uninitialized AFE4404 instance with a single LED.
+    ///
+    /// # Notes
+    ///
+    /// The instance must be initialized with `init()` function before being configured or read.
+    pub fn with_one_led(
+        i2c: I2C,
+        address: SevenBitAddress,
+        clock: Frequency,
+    ) -> AFE4404<I2C, OneLedMode, Uninitialized> {
+        AFE4404::<I2C, OneLedMode, Uninitialized> {
+            registers: RegisterBlock::new(address, i2c),
             clock,
+            timing_quantisation: None,
+            inverted_readings: false,
+            idle_dynamic: None,
             mode: core::marker::PhantomData,
+            state: core::marker::PhantomData,
         }
     }
 }
+
+impl<I2C, MODE, STATE> AFE4404<I2C, MODE, STATE>
+where
+    I2C: I2c<SevenBitAddress>,
+    MODE: LedMode,
+    STATE: DeviceState,
+{
+    /// Releases the I2C bus, consuming the [`AFE4404`].
+    ///
+    /// # Notes
+    ///
+    /// An active device keeps running with its current configuration, use `sw_power_down()` before releasing to stop it.
+    /// The bus can then be handed to other devices, or used to create a new instance with a different address.
+    pub fn release(self) -> I2C {
+        self.registers.release()
+    }
+
+    /// Moves the [`AFE4404`] into another power state.
+    pub(crate) fn into_state<NEXT: DeviceState>(self) -> AFE4404<I2C, MODE, NEXT> {
+        AFE4404 {
+            registers: self.registers,
+            clock: self.clock,
+            timing_quantisation: self.timing_quantisation,
+            inverted_readings: self.inverted_readings,
+            idle_dynamic: self.idle_dynamic,
+            mode: core::marker::PhantomData,
+            state: core::marker::PhantomData,
+        }
+    }
+}
+
+/// Asserts at compile time that the device is `Send` whenever the I2C bus is `Send`.
+#[allow(dead_code)]
+fn assert_send<I2C: Send, MODE: LedMode + Send, STATE: DeviceState + Send>() {
+    fn is_send<T: Send>() {}
+
+    is_send::<AFE4404<I2C, MODE, STATE>>();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        mock::MockAfe,
+        modes::{DeviceMode, OneLedMode, PoweredDown, ThreeLedsMode, TwoLedsMode},
+    };
+
+    use super::AFE4404;
+
+    #[test]
+    fn mode_matches_typestate() {
+        assert_eq!(AFE4404::<MockAfe, ThreeLedsMode>::mode(), DeviceMode::Three);
+        assert_eq!(AFE4404::<MockAfe, TwoLedsMode>::mode(), DeviceMode::Two);
+        assert_eq!(AFE4404::<MockAfe, OneLedMode>::mode(), DeviceMode::One);
+    }
+
+    #[test]
+    fn mode_kept_when_powered_down() {
+        let mock = MockAfe::new();
+        let _afe = mock.two_leds().sw_power_down().ok().unwrap();
+
+        assert_eq!(
+            AFE4404::<MockAfe, TwoLedsMode, PoweredDown>::mode(),
+            DeviceMode::Two
+        );
+    }
+}
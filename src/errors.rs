@@ -1,31 +1,211 @@
+use core::fmt;
+
 use thiserror_no_std::Error;
 
+use crate::{
+    channel::LedChannel,
+    measurement_window::{PhaseKind, TimingViolation},
+};
+
+/// Represents an error of the [`AFE4404`].
 #[derive(Error, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum AfeError<I2CError: embedded_hal::i2c::Error> {
+    /// The I2C bus encountered an error.
     #[error("I2C error")]
-    I2CError(#[from] I2CError),
+    I2CError(
+        #[from]
+        #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
+        I2CError,
+    ),
+    /// The I2C answer has an unexpected length.
     #[error("incorrect I2C answer length (expected: {}, received: {})", .expected, .received)]
-    IncorrectAnswerLength { expected: usize, received: usize },
+    IncorrectAnswerLength {
+        /// The expected length.
+        expected: usize,
+        /// The received length.
+        received: usize,
+    },
+    /// The requested LED current falls outside the allowed range.
     #[error("the requested LED current falls outside the allowed range")]
     LedCurrentOutsideAllowedRange,
+    /// The requested offset current falls outside the allowed range.
     #[error("the requested offset current falls outside the allowed range")]
     OffsetCurrentOutsideAllowedRange,
+    /// The requested resistor value falls outside the allowed range.
     #[error("the requested resistor value falls outside the allowed range")]
     ResistorValueOutsideAllowedRange,
+    /// The requested capacitor value falls outside the allowed range.
     #[error("the requested capacitor value falls outside the allowed range")]
     CapacitorValueOutsideAllowedRange,
+    /// The ADC reading falls outside the allowed range.
     #[error("the ADC reading falls outside the allowed range")]
     AdcReadingOutsideAllowedRange,
+    /// The requested window period falls outside the allowed range for the current clock frequency.
     #[error("the requested window period falls outside the allowed range for the current clock frequency")]
     WindowPeriodOutsideAllowedRange,
+    /// The requested internal clock is not 4MHz.
     #[error("the requested internal clock is not 4MHz")]
     IncorrectInternalClock,
+    /// The requested number of averages falls outside the allowed range.
     #[error("the requested number of averages falls outside the allowed range")]
     NumberOfAveragesOutsideAllowedRange,
+    /// The decimation factor falls outside the allowed range.
     #[error("the decimation factor falls outside the allowed range")]
     DecimationFactorOutsideAllowedRange,
+    /// A register contains an invalid value.
     #[error("invalid value in register {:02X}h", .reg_addr)]
-    InvalidRegisterValue { reg_addr: u8 },
+    InvalidRegisterValue {
+        /// The register address.
+        reg_addr: u8,
+    },
+    /// The requested clock division ratio falls outside the allowed range.
     #[error("the requested clock division ratio falls outside the allowed range")]
     ClockDivisionRatioOutsideAllowedRange,
+    /// The average LED current exceeds the eye safety limit.
+    #[error("the average LED current exceeds the eye safety limit")]
+    EyeSafetyExceeded,
+    /// The measured sample rate does not match the configured one.
+    #[error("the measured sample rate does not match the configured one")]
+    ClockMismatch,
+    /// The clock frequency does not match the internal oscillator.
+    #[error("the clock frequency does not match the internal oscillator")]
+    InternalClockMismatch,
+    /// The requested power-down window falls outside the allowed range.
+    #[error("the requested power-down window falls outside the allowed range")]
+    PowerDownWindowOutsideAllowedRange,
+    /// The input short is enabled.
+    #[error("the input short is enabled")]
+    InputShortEnabled,
+    /// The delay encountered an error.
+    #[error("delay error")]
+    DelayError,
+    /// The number of samples is not enough.
+    #[error("the number of samples is not enough")]
+    NotEnoughSamples,
+    /// A LED current is not zero but its lighting window is empty.
+    #[error("the {:?} current is not zero but its lighting window is empty", .channel)]
+    InconsistentLedCurrent {
+        /// The channel.
+        channel: LedChannel,
+    },
+    /// The channel is not available for the requested operation.
+    #[error("the {:?} channel is not available for the requested operation", .channel)]
+    ChannelNotAvailable {
+        /// The channel.
+        channel: LedChannel,
+    },
+    /// The device did not answer the probe correctly.
+    #[error("the device did not answer the probe correctly")]
+    ProbeFailed,
+    /// The value read back from a register does not match the written one.
+    #[error("the value read back from register {:02X}h does not match the written one", .reg_addr)]
+    WriteVerificationFailed {
+        /// The register address.
+        reg_addr: u8,
+        /// The channel whose field does not match, if the register holds the fields of several channels.
+        channel: Option<LedChannel>,
+    },
+    /// The average LEDs power exceeds the thermal budget.
+    #[error("the average LEDs power exceeds the thermal budget")]
+    ThermalBudgetExceeded,
+    /// Two phases of the same kind belonging to different channels overlap.
+    #[error("the {:?} phases of {:?} and {:?} overlap", .kind, .first, .second)]
+    PhaseOverlap {
+        /// The kind of the phases.
+        kind: PhaseKind,
+        /// The channel of the phase starting first.
+        first: LedChannel,
+        /// The channel of the other phase.
+        second: LedChannel,
+    },
+    /// The data ready pin encountered an error.
+    #[error("data ready pin error")]
+    PinError,
+    /// The wait strategy encountered an error.
+    #[error("wait error")]
+    WaitError,
+    /// A new `ADC_RDY` pulse arrived while reading the values.
+    #[error("a new sample arrived while reading the values")]
+    ReadingOverrun,
+    /// The I2C bus kept encountering transient errors after all the retries allowed by the retry policy.
+    #[error("I2C error after all the retries")]
+    BusRetriesExhausted(#[cfg_attr(feature = "defmt", defmt(Debug2Format))] I2CError),
+    /// The register cache is disabled.
+    #[error("the register cache is disabled")]
+    RegisterCacheDisabled,
+    /// The register cannot be read.
+    #[error("register {:02X}h is write only", .reg_addr)]
+    WriteOnlyRegister {
+        /// The register address.
+        reg_addr: u8,
+    },
+    /// The asynchronous driver read a register not loaded from the device.
+    #[error("register {:02X}h was read before being loaded from the device", .reg_addr)]
+    RegisterNotLoaded {
+        /// The register address.
+        reg_addr: u8,
+    },
+    /// A phase of the measurement window violates a timing constraint.
+    #[error("the {:?} phase of {:?} violates a timing constraint: {:?}", .kind, .channel, .violation)]
+    InvalidTiming {
+        /// The channel of the phase.
+        channel: LedChannel,
+        /// The kind of the phase.
+        kind: PhaseKind,
+        /// The violated constraint.
+        violation: TimingViolation,
+    },
+}
+
+/// Represents an error of a state transition of the [`AFE4404`], giving back the device in its previous state.
+///
+/// # Notes
+///
+/// The device owns the I2C bus, so it is returned with the error to let the bus be recovered with `release()`,
+/// or the transition be attempted again.
+pub struct TransitionError<DEVICE, I2CError: embedded_hal::i2c::Error> {
+    /// The device, in the state it had before the transition.
+    pub device: DEVICE,
+    /// The error encountered during the transition.
+    pub error: AfeError<I2CError>,
+}
+
+impl<DEVICE, I2CError> fmt::Debug for TransitionError<DEVICE, I2CError>
+where
+    I2CError: embedded_hal::i2c::Error,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransitionError")
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<DEVICE, I2CError> fmt::Display for TransitionError<DEVICE, I2CError>
+where
+    I2CError: embedded_hal::i2c::Error,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<DEVICE, I2CError> defmt::Format for TransitionError<DEVICE, I2CError>
+where
+    I2CError: embedded_hal::i2c::Error,
+{
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "TransitionError {{ error: {} }}", self.error);
+    }
+}
+
+impl<DEVICE, I2CError> From<TransitionError<DEVICE, I2CError>> for AfeError<I2CError>
+where
+    I2CError: embedded_hal::i2c::Error,
+{
+    fn from(transition_error: TransitionError<DEVICE, I2CError>) -> Self {
+        transition_error.error
+    }
 }
@@ -0,0 +1,272 @@
+//! This module contains the adapters to drive the [`AFE4404`](crate::device::AFE4404) with `embedded-hal` 0.2 peripherals.
+//!
+//! The I2C bus and the delay are wrapped into a [`Hal02`] before being given to the driver:
+//!
+//! ```ignore
+//! let mut frontend = AFE4404::with_three_leds(Hal02::new(i2c), 0x58u8, Frequency::new::<megahertz>(4.0))
+//!     .init(&setup, &mut Hal02::new(delay))
+//!     .expect("Cannot initialize the afe");
+//! ```
+
+use alloc::vec::Vec;
+
+use embedded_hal::i2c::{Error, ErrorKind, ErrorType, I2c, Operation, SevenBitAddress};
+use embedded_hal_02::blocking::{delay, i2c};
+
+/// Represents an `embedded-hal` 0.2 peripheral exposed through the `embedded-hal` 1.0 traits.
+///
+/// # Notes
+///
+/// An I2C bus must implement the blocking `Read`, `Write` and `WriteRead` traits, a delay must implement the blocking `DelayUs<u32>` trait.
+/// The I2C transactions are executed as separate operations, since `embedded-hal` 0.2 has no transactional trait in common use.
+/// The `embedded-hal` 0.2 errors carry no kind, so they are reported as `ErrorKind::Other` and never retried,
+/// unless the bus is wrapped by `with_error_kind()`.
+pub struct Hal02<T, K = ()> {
+    inner: T,
+    error_kind: K,
+}
+
+impl<T> Hal02<T> {
+    /// Creates a new `Hal02` wrapping an `embedded-hal` 0.2 peripheral.
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            error_kind: (),
+        }
+    }
+}
+
+impl<T, E> Hal02<T, fn(&E) -> ErrorKind> {
+    /// Creates a new `Hal02` wrapping an `embedded-hal` 0.2 I2C bus, classifying its errors with the given function.
+    ///
+    /// # Notes
+    ///
+    /// Classify the transient errors as `ErrorKind::Bus`, `ErrorKind::ArbitrationLoss` or `ErrorKind::NoAcknowledge`,
+    /// so that they are retried according to the [`RetryPolicy`](crate::retry::RetryPolicy).
+    pub fn with_error_kind(inner: T, error_kind: fn(&E) -> ErrorKind) -> Self {
+        Self { inner, error_kind }
+    }
+}
+
+impl<T, K> Hal02<T, K> {
+    /// Releases the wrapped peripheral.
+    pub fn release(self) -> T {
+        self.inner
+    }
+}
+
+/// Represents the classification of the errors of an `embedded-hal` 0.2 I2C bus.
+pub trait ErrorKindMapping<E> {
+    /// Gets the kind of the given error.
+    fn kind(&self, error: &E) -> ErrorKind;
+}
+
+/// Reports every error as `ErrorKind::Other`.
+impl<E> ErrorKindMapping<E> for () {
+    fn kind(&self, _error: &E) -> ErrorKind {
+        ErrorKind::Other
+    }
+}
+
+/// Reports the kind returned by the function.
+impl<E> ErrorKindMapping<E> for fn(&E) -> ErrorKind {
+    fn kind(&self, error: &E) -> ErrorKind {
+        self(error)
+    }
+}
+
+/// Represents an error of an `embedded-hal` 0.2 I2C bus.
+#[derive(Debug)]
+pub struct Hal02Error<E> {
+    /// The error of the bus.
+    pub error: E,
+    kind: ErrorKind,
+}
+
+impl<E> Hal02Error<E> {
+    /// Creates a new `Hal02Error` of the given kind.
+    pub fn with_kind(error: E, kind: ErrorKind) -> Self {
+        Self { error, kind }
+    }
+}
+
+impl<E> Error for Hal02Error<E>
+where
+    E: core::fmt::Debug,
+{
+    fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+}
+
+impl<I2C, K> Hal02<I2C, K> {
+    /// Wraps an error of the bus, classifying it.
+    fn wrap_error<E>(&self, error: E) -> Hal02Error<E>
+    where
+        K: ErrorKindMapping<E>,
+    {
+        let kind = self.error_kind.kind(&error);
+
+        Hal02Error::with_kind(error, kind)
+    }
+}
+
+impl<I2C, K, E> ErrorType for Hal02<I2C, K>
+where
+    I2C: i2c::Read<Error = E> + i2c::Write<Error = E> + i2c::WriteRead<Error = E>,
+    K: ErrorKindMapping<E>,
+    E: core::fmt::Debug,
+{
+    type Error = Hal02Error<E>;
+}
+
+impl<I2C, K, E> I2c<SevenBitAddress> for Hal02<I2C, K>
+where
+    I2C: i2c::Read<Error = E> + i2c::Write<Error = E> + i2c::WriteRead<Error = E>,
+    K: ErrorKindMapping<E>,
+    E: core::fmt::Debug,
+{
+    fn read(&mut self, address: SevenBitAddress, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.inner
+            .read(address, read)
+            .map_err(|error| self.wrap_error(error))
+    }
+
+    fn write(&mut self, address: SevenBitAddress, write: &[u8]) -> Result<(), Self::Error> {
+        self.inner
+            .write(address, write)
+            .map_err(|error| self.wrap_error(error))
+    }
+
+    fn write_iter<B>(&mut self, address: SevenBitAddress, bytes: B) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        let bytes: Vec<u8> = bytes.into_iter().collect();
+
+        I2c::write(self, address, &bytes)
+    }
+
+    fn write_read(
+        &mut self,
+        address: SevenBitAddress,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .write_read(address, write, read)
+            .map_err(|error| self.wrap_error(error))
+    }
+
+    fn write_iter_read<B>(
+        &mut self,
+        address: SevenBitAddress,
+        bytes: B,
+        read: &mut [u8],
+    ) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        let bytes: Vec<u8> = bytes.into_iter().collect();
+
+        I2c::write_read(self, address, &bytes, read)
+    }
+
+    fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        for operation in operations {
+            match operation {
+                Operation::Read(read) => I2c::read(self, address, read)?,
+                Operation::Write(write) => I2c::write(self, address, write)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn transaction_iter<'a, O>(
+        &mut self,
+        address: SevenBitAddress,
+        operations: O,
+    ) -> Result<(), Self::Error>
+    where
+        O: IntoIterator<Item = Operation<'a>>,
+    {
+        for mut operation in operations {
+            self.transaction(address, core::slice::from_mut(&mut operation))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<D> embedded_hal::delay::DelayUs for Hal02<D>
+where
+    D: delay::DelayUs<u32>,
+{
+    type Error = core::convert::Infallible;
+
+    fn delay_us(&mut self, us: u32) -> Result<(), Self::Error> {
+        self.inner.delay_us(us);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Represents an `embedded-hal` 0.2 bus never acknowledging.
+    struct Nack;
+
+    impl i2c::Read for Nack {
+        type Error = u8;
+
+        fn read(&mut self, _address: u8, _buffer: &mut [u8]) -> Result<(), Self::Error> {
+            Err(2)
+        }
+    }
+
+    impl i2c::Write for Nack {
+        type Error = u8;
+
+        fn write(&mut self, _address: u8, _bytes: &[u8]) -> Result<(), Self::Error> {
+            Err(2)
+        }
+    }
+
+    impl i2c::WriteRead for Nack {
+        type Error = u8;
+
+        fn write_read(
+            &mut self,
+            _address: u8,
+            _bytes: &[u8],
+            _buffer: &mut [u8],
+        ) -> Result<(), Self::Error> {
+            Err(2)
+        }
+    }
+
+    #[test]
+    fn errors_classified_by_mapping() {
+        let mut unclassified = Hal02::new(Nack);
+        let error = I2c::write(&mut unclassified, 0x58, &[0]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Other);
+
+        let mut classified = Hal02::with_error_kind(Nack, |&code: &u8| match code {
+            2 => ErrorKind::NoAcknowledge(embedded_hal::i2c::NoAcknowledgeSource::Address),
+            _ => ErrorKind::Other,
+        });
+        let error = I2c::write(&mut classified, 0x58, &[0]).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            ErrorKind::NoAcknowledge(embedded_hal::i2c::NoAcknowledgeSource::Address)
+        );
+        assert_eq!(error.error, 2);
+    }
+}
@@ -1,12 +1,24 @@
-use uom::si::{electric_current::milliampere, f32::ElectricCurrent};
+use uom::si::{
+    electric_current::milliampere,
+    f32::{ElectricCurrent, ElectricPotential, Power, Time},
+    time::microsecond,
+};
 
-use crate::modes::{LedMode, ThreeLedsMode, TwoLedsMode};
+use crate::{
+    channel::LedChannel,
+    measurement_window::{MeasurementWindowConfiguration, PhaseKind},
+    modes::{LedMode, OneLedMode, ThreeLedsMode, TwoLedsMode},
+};
 
 /// Represents the currents of the LEDs.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct LedCurrentConfiguration<MODE: LedMode> {
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     led1: ElectricCurrent,
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     led2: ElectricCurrent,
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     led3: ElectricCurrent,
     mode: core::marker::PhantomData<MODE>,
 }
@@ -20,19 +32,47 @@ where
         &self.led1
     }
 
-    /// Gets an immutable reference of the current of LED2.
-    pub fn led2(&self) -> &ElectricCurrent {
-        &self.led2
-    }
-
     /// Gets a mutable reference of the current of LED1.
     pub fn led1_mut(&mut self) -> &mut ElectricCurrent {
         &mut self.led1
     }
 
-    /// Gets a mutable reference of the current of LED2.
-    pub fn led2_mut(&mut self) -> &mut ElectricCurrent {
-        &mut self.led2
+    /// Estimates the average currents and power drawn by the LEDs with the given measurement window.
+    ///
+    /// # Notes
+    ///
+    /// The average current of a LED is its current multiplied by the lighting duty cycle of the measurement window.
+    /// The power is the total average current multiplied by the voltage of the LED supply.
+    /// A window with a zero period lights no LED, so its budget is zero.
+    pub fn power_budget(
+        &self,
+        window: &MeasurementWindowConfiguration<MODE>,
+        led_supply: ElectricPotential,
+    ) -> PowerBudget {
+        let mut average_currents = [ElectricCurrent::new::<milliampere>(0.0); 3];
+        let period = *window.period();
+        for phase in window.channel_phases() {
+            let (index, current) = match (phase.channel, phase.kind) {
+                _ if period <= Time::new::<microsecond>(0.0) => break,
+                (LedChannel::Led1, PhaseKind::Lighting) => (0, self.led1),
+                (LedChannel::Led2, PhaseKind::Lighting) => (1, self.led2),
+                (LedChannel::Led3, PhaseKind::Lighting) => (2, self.led3),
+                _ => continue,
+            };
+
+            average_currents[index] += current * ((phase.end - phase.start) / period).value;
+        }
+
+        let total_current = average_currents.iter().fold(
+            ElectricCurrent::new::<milliampere>(0.0),
+            |total, &current| total + current,
+        );
+
+        PowerBudget {
+            average_currents,
+            total_current,
+            power: led_supply * total_current,
+        }
     }
 }
 
@@ -47,6 +87,16 @@ impl LedCurrentConfiguration<ThreeLedsMode> {
         }
     }
 
+    /// Gets an immutable reference of the current of LED2.
+    pub fn led2(&self) -> &ElectricCurrent {
+        &self.led2
+    }
+
+    /// Gets a mutable reference of the current of LED2.
+    pub fn led2_mut(&mut self) -> &mut ElectricCurrent {
+        &mut self.led2
+    }
+
     /// Gets an immutable reference of the current of LED3.
     pub fn led3(&self) -> &ElectricCurrent {
         &self.led3
@@ -68,18 +118,86 @@ impl LedCurrentConfiguration<TwoLedsMode> {
             mode: core::marker::PhantomData,
         }
     }
+
+    /// Gets an immutable reference of the current of LED2.
+    pub fn led2(&self) -> &ElectricCurrent {
+        &self.led2
+    }
+
+    /// Gets a mutable reference of the current of LED2.
+    pub fn led2_mut(&mut self) -> &mut ElectricCurrent {
+        &mut self.led2
+    }
+}
+
+impl LedCurrentConfiguration<OneLedMode> {
+    /// Creates a new `LedCurrentConfiguration`.
+    pub fn new(led1: ElectricCurrent) -> Self {
+        Self {
+            led1,
+            led2: ElectricCurrent::new::<milliampere>(0.0),
+            led3: ElectricCurrent::new::<milliampere>(0.0),
+            mode: core::marker::PhantomData,
+        }
+    }
+}
+
+/// Drives every LED with 10 mA, a moderate current within the 0-50 mA range.
+impl Default for LedCurrentConfiguration<ThreeLedsMode> {
+    fn default() -> Self {
+        let current = ElectricCurrent::new::<milliampere>(10.0);
+
+        Self::new(current, current, current)
+    }
+}
+
+/// Drives every LED with 10 mA, a moderate current within the 0-50 mA range.
+impl Default for LedCurrentConfiguration<TwoLedsMode> {
+    fn default() -> Self {
+        let current = ElectricCurrent::new::<milliampere>(10.0);
+
+        Self::new(current, current)
+    }
+}
+
+/// Drives the LED with 10 mA, a moderate current within the 0-50 mA range.
+impl Default for LedCurrentConfiguration<OneLedMode> {
+    fn default() -> Self {
+        Self::new(ElectricCurrent::new::<milliampere>(10.0))
+    }
 }
 
 /// Represents the offset currents of the LEDs.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct OffsetCurrentConfiguration<MODE: LedMode> {
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     led1: ElectricCurrent,
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     led2: ElectricCurrent,
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     ambient1: ElectricCurrent,
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     ambient2_or_led3: ElectricCurrent,
     mode: core::marker::PhantomData<MODE>,
 }
 
+/// Disables the offset cancellation of every phase, as after a reset.
+impl<MODE> Default for OffsetCurrentConfiguration<MODE>
+where
+    MODE: LedMode,
+{
+    fn default() -> Self {
+        Self {
+            led1: ElectricCurrent::new::<milliampere>(0.0),
+            led2: ElectricCurrent::new::<milliampere>(0.0),
+            ambient1: ElectricCurrent::new::<milliampere>(0.0),
+            ambient2_or_led3: ElectricCurrent::new::<milliampere>(0.0),
+            mode: core::marker::PhantomData,
+        }
+    }
+}
+
 impl<MODE> OffsetCurrentConfiguration<MODE>
 where
     MODE: LedMode,
@@ -89,20 +207,10 @@ where
         &self.led1
     }
 
-    /// Gets an immutable reference of the offset current of LED2.
-    pub fn led2(&self) -> &ElectricCurrent {
-        &self.led2
-    }
-
     /// Gets a mutable reference of the offset current of LED1.
     pub fn led1_mut(&mut self) -> &mut ElectricCurrent {
         &mut self.led1
     }
-
-    /// Gets a mutable reference of the offset current of LED2.
-    pub fn led2_mut(&mut self) -> &mut ElectricCurrent {
-        &mut self.led2
-    }
 }
 
 impl OffsetCurrentConfiguration<ThreeLedsMode> {
@@ -122,6 +230,16 @@ impl OffsetCurrentConfiguration<ThreeLedsMode> {
         }
     }
 
+    /// Gets an immutable reference of the offset current of LED2.
+    pub fn led2(&self) -> &ElectricCurrent {
+        &self.led2
+    }
+
+    /// Gets a mutable reference of the offset current of LED2.
+    pub fn led2_mut(&mut self) -> &mut ElectricCurrent {
+        &mut self.led2
+    }
+
     /// Gets an immutable reference of the offset current of LED3.
     pub fn led3(&self) -> &ElectricCurrent {
         &self.ambient2_or_led3
@@ -160,6 +278,16 @@ impl OffsetCurrentConfiguration<TwoLedsMode> {
         }
     }
 
+    /// Gets an immutable reference of the offset current of LED2.
+    pub fn led2(&self) -> &ElectricCurrent {
+        &self.led2
+    }
+
+    /// Gets a mutable reference of the offset current of LED2.
+    pub fn led2_mut(&mut self) -> &mut ElectricCurrent {
+        &mut self.led2
+    }
+
     /// Gets an immutable reference of the ambient1 offset current.
     pub fn ambient1(&self) -> &ElectricCurrent {
         &self.ambient1
@@ -180,3 +308,50 @@ impl OffsetCurrentConfiguration<TwoLedsMode> {
         &mut self.ambient2_or_led3
     }
 }
+
+impl OffsetCurrentConfiguration<OneLedMode> {
+    /// Creates a new `OffsetCurrentConfiguration` for the single LED mode.
+    pub fn new(led1: ElectricCurrent, ambient: ElectricCurrent) -> Self {
+        Self {
+            led1,
+            led2: ElectricCurrent::new::<milliampere>(0.0),
+            ambient1: ambient,
+            ambient2_or_led3: ElectricCurrent::new::<milliampere>(0.0),
+            mode: core::marker::PhantomData,
+        }
+    }
+
+    /// Gets an immutable reference of the ambient offset current.
+    pub fn ambient(&self) -> &ElectricCurrent {
+        &self.ambient1
+    }
+
+    /// Gets a mutable reference of the ambient offset current.
+    pub fn ambient_mut(&mut self) -> &mut ElectricCurrent {
+        &mut self.ambient1
+    }
+}
+
+/// Represents the transmit side configuration: the LEDs current and the offset cancellation currents.
+pub type TxConfiguration<MODE> = (
+    LedCurrentConfiguration<MODE>,
+    OffsetCurrentConfiguration<MODE>,
+);
+
+/// Represents the applied LEDs current together with the residual error of each LED.
+pub type LedCurrentWithError<MODE> = (LedCurrentConfiguration<MODE>, [ElectricCurrent; 3]);
+
+/// Represents the average currents and power drawn by the LEDs over the measurement window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PowerBudget {
+    /// The average currents of LED1, LED2 and LED3, zero for the unused LEDs.
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
+    pub average_currents: [ElectricCurrent; 3],
+    /// The sum of the average currents.
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
+    pub total_current: ElectricCurrent,
+    /// The average power drawn from the LED supply.
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
+    pub power: Power,
+}
@@ -7,6 +7,7 @@ use uom::si::{
 };
 
 use crate::{
+    channel::LedChannel,
     device::AFE4404,
     errors::AfeError,
     modes::{LedMode, ThreeLedsMode, TwoLedsMode},
@@ -44,8 +45,8 @@ where
         &mut self,
         current: ElectricCurrent,
     ) -> Result<ElectricCurrent, AfeError<I2C::Error>> {
-        let r22h_prev = self.registers.r22h.read()?;
-        let r23h_prev = self.registers.r23h.read()?;
+        let r22h_prev = self.registers.r22h().read()?;
+        let r23h_prev = self.registers.r23h().read()?;
 
         let high_current = current.get::<milliampere>() > 50.0
             || (r23h_prev.iled_2x() && (r22h_prev.iled2() > 31 || r22h_prev.iled3() > 31));
@@ -69,14 +70,14 @@ where
             Self::scale_current(r22h_prev.iled3(), r23h_prev.iled_2x(), high_current),
         ];
 
-        self.registers.r22h.write(
+        self.registers.r22h().write(
             R22h::new()
                 .with_iled1(values[0])
                 .with_iled2(values[1])
                 .with_iled3(values[2]),
         )?;
         self.registers
-            .r23h
+            .r23h()
             .write(r23h_prev.with_iled_2x(high_current))?;
 
         Ok(f32::from(values[0]) * quantisation)
@@ -97,8 +98,8 @@ where
         &mut self,
         current: ElectricCurrent,
     ) -> Result<ElectricCurrent, AfeError<I2C::Error>> {
-        let r22h_prev = self.registers.r22h.read()?;
-        let r23h_prev = self.registers.r23h.read()?;
+        let r22h_prev = self.registers.r22h().read()?;
+        let r23h_prev = self.registers.r23h().read()?;
 
         let high_current = current.get::<milliampere>() > 50.0
             || (r23h_prev.iled_2x() && (r22h_prev.iled1() > 31 || r22h_prev.iled3() > 31));
@@ -122,14 +123,14 @@ where
             Self::scale_current(r22h_prev.iled3(), r23h_prev.iled_2x(), high_current),
         ];
 
-        self.registers.r22h.write(
+        self.registers.r22h().write(
             R22h::new()
                 .with_iled1(values[0])
                 .with_iled2(values[1])
                 .with_iled3(values[2]),
         )?;
         self.registers
-            .r23h
+            .r23h()
             .write(r23h_prev.with_iled_2x(high_current))?;
 
         Ok(f32::from(values[1]) * quantisation)
@@ -141,8 +142,8 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     pub fn get_led1_current(&mut self) -> Result<ElectricCurrent, AfeError<I2C::Error>> {
-        let r22h_prev = self.registers.r22h.read()?;
-        let r23h_prev = self.registers.r23h.read()?;
+        let r22h_prev = self.registers.r22h().read()?;
+        let r23h_prev = self.registers.r23h().read()?;
 
         let range = if r23h_prev.iled_2x() {
             ElectricCurrent::new::<milliampere>(100.0)
@@ -160,8 +161,8 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     pub fn get_led2_current(&mut self) -> Result<ElectricCurrent, AfeError<I2C::Error>> {
-        let r22h_prev = self.registers.r22h.read()?;
-        let r23h_prev = self.registers.r23h.read()?;
+        let r22h_prev = self.registers.r22h().read()?;
+        let r23h_prev = self.registers.r23h().read()?;
 
         let range = if r23h_prev.iled_2x() {
             ElectricCurrent::new::<milliampere>(100.0)
@@ -173,6 +174,164 @@ where
         Ok(f32::from(r22h_prev.iled2()) * quantisation)
     }
 
+    /// Gets the LED current of the given channel.
+    ///
+    /// # Notes
+    ///
+    /// Ambient channels never light a LED, so their current is always zero.
+    pub(crate) fn get_channel_current(
+        &mut self,
+        channel: LedChannel,
+    ) -> Result<ElectricCurrent, AfeError<I2C::Error>> {
+        let r22h_prev = self.registers.r22h().read()?;
+        let r23h_prev = self.registers.r23h().read()?;
+
+        let range = if r23h_prev.iled_2x() {
+            ElectricCurrent::new::<milliampere>(100.0)
+        } else {
+            ElectricCurrent::new::<milliampere>(50.0)
+        };
+        let quantisation = range / 63.0;
+
+        let value = match channel {
+            LedChannel::Led1 => r22h_prev.iled1(),
+            LedChannel::Led2 => r22h_prev.iled2(),
+            LedChannel::Led3 => r22h_prev.iled3(),
+            LedChannel::Ambient1 | LedChannel::Ambient2 => 0,
+        };
+
+        Ok(f32::from(value) * quantisation)
+    }
+
+    /// Gets the LED current unit step.
+    ///
+    /// # Notes
+    ///
+    /// The unit step is 0.8 mA in the 0-50 mA range and 1.6 mA in the 0-100 mA range, every LED current is a multiple of it.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub fn led_current_step(&mut self) -> Result<ElectricCurrent, AfeError<I2C::Error>> {
+        let r23h_prev = self.registers.r23h().read()?;
+
+        let range = if r23h_prev.iled_2x() {
+            ElectricCurrent::new::<milliampere>(100.0)
+        } else {
+            ElectricCurrent::new::<milliampere>(50.0)
+        };
+
+        Ok(range / 63.0)
+    }
+
+    /// Computes the closest LED current the setters would apply, without any I2C transaction.
+    ///
+    /// # Notes
+    ///
+    /// The current range is 0-100 mA when `double_range` is `true` and 0-50 mA otherwise.
+    /// Currents outside the range are clamped, while the setters would return an error.
+    pub fn quantise_current(
+        &self,
+        current: ElectricCurrent,
+        double_range: bool,
+    ) -> ElectricCurrent {
+        let range = if double_range {
+            ElectricCurrent::new::<milliampere>(100.0)
+        } else {
+            ElectricCurrent::new::<milliampere>(50.0)
+        };
+
+        let quantisation = range / 63.0;
+
+        (current / quantisation).value.round().clamp(0.0, 63.0) * quantisation
+    }
+
+    /// Computes the code and the range bit the setters would use for the current of a LED, without writing any register.
+    ///
+    /// # Notes
+    ///
+    /// The other LEDs are assumed to keep their current, so the range bit is `true` for the 0-100 mA range,
+    /// which is selected for currents greater than 50 mA or when another LED already needs it.
+    /// The code is the current divided by the range quantisation, rounded to the closest integer.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a current greater than 100 mA or lower than 0 mA will result in an error.
+    /// Setting the current of an Ambient channel will result in an error.
+    pub fn current_to_code(
+        &mut self,
+        led: LedChannel,
+        current: ElectricCurrent,
+    ) -> Result<(u8, bool), AfeError<I2C::Error>> {
+        let r22h_prev = self.registers.r22h().read()?;
+        let r23h_prev = self.registers.r23h().read()?;
+
+        let others = match led {
+            LedChannel::Led1 => [r22h_prev.iled2(), r22h_prev.iled3()],
+            LedChannel::Led2 => [r22h_prev.iled1(), r22h_prev.iled3()],
+            LedChannel::Led3 => [r22h_prev.iled1(), r22h_prev.iled2()],
+            LedChannel::Ambient1 | LedChannel::Ambient2 => {
+                return Err(AfeError::ChannelNotAvailable { channel: led })
+            }
+        };
+
+        if current.get::<milliampere>() < 0.0 || current.get::<milliampere>() > 100.0 {
+            return Err(AfeError::LedCurrentOutsideAllowedRange);
+        }
+
+        let double_range = current.get::<milliampere>() > 50.0
+            || (r23h_prev.iled_2x() && others.iter().any(|&code| code > 31));
+        let range = if double_range {
+            ElectricCurrent::new::<milliampere>(100.0)
+        } else {
+            ElectricCurrent::new::<milliampere>(50.0)
+        };
+
+        let quantisation = range / 63.0;
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let code = (current / quantisation).value.round() as u8;
+
+        Ok((code, double_range))
+    }
+
+    /// Sets the current of a LED as an integer number of unit steps.
+    ///
+    /// # Notes
+    ///
+    /// The current range is not changed, see [`AFE4404::led_current_step`] for the unit step.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a number of steps greater than 63 will result in an error.
+    /// Setting the current of an Ambient channel will result in an error.
+    pub fn set_led_current_steps(
+        &mut self,
+        led: LedChannel,
+        steps: u8,
+    ) -> Result<ElectricCurrent, AfeError<I2C::Error>> {
+        let r22h_prev = self.registers.r22h().read()?;
+
+        if steps > 63 {
+            return Err(AfeError::LedCurrentOutsideAllowedRange);
+        }
+
+        let r22h = match led {
+            LedChannel::Led1 => r22h_prev.with_iled1(steps),
+            LedChannel::Led2 => r22h_prev.with_iled2(steps),
+            LedChannel::Led3 => r22h_prev.with_iled3(steps),
+            LedChannel::Ambient1 | LedChannel::Ambient2 => {
+                return Err(AfeError::ChannelNotAvailable { channel: led })
+            }
+        };
+
+        self.registers.r22h().write(r22h)?;
+
+        Ok(f32::from(steps) * self.led_current_step()?)
+    }
+
     /// Sets the offset cancellation current of the LED1.
     ///
     /// # Errors
@@ -183,7 +342,7 @@ where
         &mut self,
         offset: ElectricCurrent,
     ) -> Result<ElectricCurrent, AfeError<I2C::Error>> {
-        let r3ah_prev = self.registers.r3Ah.read()?;
+        let r3ah_prev = self.registers.r3Ah().read()?;
 
         let range = ElectricCurrent::new::<microampere>(7.0);
         let quantisation = range / 15.0;
@@ -198,7 +357,7 @@ where
             offset.get::<microampere>() < 0.0,
         );
 
-        self.registers.r3Ah.write(
+        self.registers.r3Ah().write(
             r3ah_prev
                 .with_i_offdac_led1(value.0)
                 .with_pol_offdac_led1(value.1),
@@ -217,7 +376,7 @@ where
         &mut self,
         offset: ElectricCurrent,
     ) -> Result<ElectricCurrent, AfeError<I2C::Error>> {
-        let r3ah_prev = self.registers.r3Ah.read()?;
+        let r3ah_prev = self.registers.r3Ah().read()?;
 
         let range = ElectricCurrent::new::<microampere>(7.0);
         let quantisation = range / 15.0;
@@ -232,7 +391,7 @@ where
             offset.get::<microampere>() < 0.0,
         );
 
-        self.registers.r3Ah.write(
+        self.registers.r3Ah().write(
             r3ah_prev
                 .with_i_offdac_led2(value.0)
                 .with_pol_offdac_led2(value.1),
@@ -247,7 +406,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     pub fn get_offset_led1_current(&mut self) -> Result<ElectricCurrent, AfeError<I2C::Error>> {
-        let r3ah_prev = self.registers.r3Ah.read()?;
+        let r3ah_prev = self.registers.r3Ah().read()?;
 
         let range = ElectricCurrent::new::<microampere>(7.0);
         let quantisation = range / 15.0;
@@ -267,7 +426,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     pub fn get_offset_led2_current(&mut self) -> Result<ElectricCurrent, AfeError<I2C::Error>> {
-        let r3ah_prev = self.registers.r3Ah.read()?;
+        let r3ah_prev = self.registers.r3Ah().read()?;
 
         let range = ElectricCurrent::new::<microampere>(7.0);
         let quantisation = range / 15.0;
@@ -301,8 +460,8 @@ where
         &mut self,
         current: ElectricCurrent,
     ) -> Result<ElectricCurrent, AfeError<I2C::Error>> {
-        let r22h_prev = self.registers.r22h.read()?;
-        let r23h_prev = self.registers.r23h.read()?;
+        let r22h_prev = self.registers.r22h().read()?;
+        let r23h_prev = self.registers.r23h().read()?;
 
         let high_current = current.get::<milliampere>() > 50.0
             || (r23h_prev.iled_2x() && (r22h_prev.iled1() > 31 || r22h_prev.iled2() > 31));
@@ -326,14 +485,14 @@ where
             (current / quantisation).value.round() as u8,
         ];
 
-        self.registers.r22h.write(
+        self.registers.r22h().write(
             R22h::new()
                 .with_iled1(values[0])
                 .with_iled2(values[1])
                 .with_iled3(values[2]),
         )?;
         self.registers
-            .r23h
+            .r23h()
             .write(r23h_prev.with_iled_2x(high_current))?;
 
         Ok(f32::from(values[2]) * quantisation)
@@ -345,8 +504,8 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     pub fn get_led3_current(&mut self) -> Result<ElectricCurrent, AfeError<I2C::Error>> {
-        let r22h_prev = self.registers.r22h.read()?;
-        let r23h_prev = self.registers.r23h.read()?;
+        let r22h_prev = self.registers.r22h().read()?;
+        let r23h_prev = self.registers.r23h().read()?;
 
         let range = if r23h_prev.iled_2x() {
             ElectricCurrent::new::<milliampere>(100.0)
@@ -368,7 +527,7 @@ where
         &mut self,
         offset: ElectricCurrent,
     ) -> Result<ElectricCurrent, AfeError<I2C::Error>> {
-        let r3ah_prev = self.registers.r3Ah.read()?;
+        let r3ah_prev = self.registers.r3Ah().read()?;
 
         let range = ElectricCurrent::new::<microampere>(7.0);
         let quantisation = range / 15.0;
@@ -383,7 +542,7 @@ where
             offset.get::<microampere>() < 0.0,
         );
 
-        self.registers.r3Ah.write(
+        self.registers.r3Ah().write(
             r3ah_prev
                 .with_i_offdac_amb2_or_i_offdac_led3(value.0)
                 .with_pol_offdac_amb2_or_pol_offdac_led3(value.1),
@@ -402,7 +561,7 @@ where
         &mut self,
         offset: ElectricCurrent,
     ) -> Result<ElectricCurrent, AfeError<I2C::Error>> {
-        let r3ah_prev = self.registers.r3Ah.read()?;
+        let r3ah_prev = self.registers.r3Ah().read()?;
 
         let range = ElectricCurrent::new::<microampere>(7.0);
         let quantisation = range / 15.0;
@@ -417,7 +576,7 @@ where
             offset.get::<microampere>() < 0.0,
         );
 
-        self.registers.r3Ah.write(
+        self.registers.r3Ah().write(
             r3ah_prev
                 .with_i_offdac_amb1(value.0)
                 .with_pol_offdac_amb1(value.1),
@@ -432,7 +591,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     pub fn get_offset_led3_current(&mut self) -> Result<ElectricCurrent, AfeError<I2C::Error>> {
-        let r3ah_prev = self.registers.r3Ah.read()?;
+        let r3ah_prev = self.registers.r3Ah().read()?;
 
         let range = ElectricCurrent::new::<microampere>(7.0);
         let quantisation = range / 15.0;
@@ -452,7 +611,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     pub fn get_offset_amb_current(&mut self) -> Result<ElectricCurrent, AfeError<I2C::Error>> {
-        let r3ah_prev = self.registers.r3Ah.read()?;
+        let r3ah_prev = self.registers.r3Ah().read()?;
 
         let range = ElectricCurrent::new::<microampere>(7.0);
         let quantisation = range / 15.0;
@@ -481,7 +640,7 @@ where
         &mut self,
         offset: ElectricCurrent,
     ) -> Result<ElectricCurrent, AfeError<I2C::Error>> {
-        let r3ah_prev = self.registers.r3Ah.read()?;
+        let r3ah_prev = self.registers.r3Ah().read()?;
 
         let range = ElectricCurrent::new::<microampere>(7.0);
         let quantisation = range / 15.0;
@@ -496,7 +655,7 @@ where
             offset.get::<microampere>() < 0.0,
         );
 
-        self.registers.r3Ah.write(
+        self.registers.r3Ah().write(
             r3ah_prev
                 .with_i_offdac_amb1(value.0)
                 .with_pol_offdac_amb1(value.1),
@@ -515,7 +674,7 @@ where
         &mut self,
         offset: ElectricCurrent,
     ) -> Result<ElectricCurrent, AfeError<I2C::Error>> {
-        let r3ah_prev = self.registers.r3Ah.read()?;
+        let r3ah_prev = self.registers.r3Ah().read()?;
 
         let range = ElectricCurrent::new::<microampere>(7.0);
         let quantisation = range / 15.0;
@@ -530,7 +689,7 @@ where
             offset.get::<microampere>() < 0.0,
         );
 
-        self.registers.r3Ah.write(
+        self.registers.r3Ah().write(
             r3ah_prev
                 .with_i_offdac_amb2_or_i_offdac_led3(value.0)
                 .with_pol_offdac_amb2_or_pol_offdac_led3(value.1),
@@ -545,7 +704,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     pub fn get_offset_amb1_current(&mut self) -> Result<ElectricCurrent, AfeError<I2C::Error>> {
-        let r3ah_prev = self.registers.r3Ah.read()?;
+        let r3ah_prev = self.registers.r3Ah().read()?;
 
         let range = ElectricCurrent::new::<microampere>(7.0);
         let quantisation = range / 15.0;
@@ -565,7 +724,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     pub fn get_offset_amb2_current(&mut self) -> Result<ElectricCurrent, AfeError<I2C::Error>> {
-        let r3ah_prev = self.registers.r3Ah.read()?;
+        let r3ah_prev = self.registers.r3Ah().read()?;
 
         let range = ElectricCurrent::new::<microampere>(7.0);
         let quantisation = range / 15.0;
@@ -579,3 +738,151 @@ where
             })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockAfe;
+
+    fn milliamperes(current: f32) -> ElectricCurrent {
+        ElectricCurrent::new::<milliampere>(current)
+    }
+
+    #[test]
+    fn current_to_code_spans_both_ranges() {
+        let mut afe = MockAfe::new().three_leds();
+
+        for (current, expected) in [
+            (0.0, (0, false)),
+            (25.0, (32, false)),
+            (50.0, (63, false)),
+            (50.1, (32, true)),
+            (75.0, (47, true)),
+            (100.0, (63, true)),
+        ] {
+            assert_eq!(
+                afe.current_to_code(LedChannel::Led1, milliamperes(current))
+                    .unwrap(),
+                expected
+            );
+        }
+
+        for current in [-0.1, 100.1] {
+            assert!(matches!(
+                afe.current_to_code(LedChannel::Led1, milliamperes(current)),
+                Err(AfeError::LedCurrentOutsideAllowedRange)
+            ));
+        }
+        assert!(matches!(
+            afe.current_to_code(LedChannel::Ambient1, milliamperes(10.0)),
+            Err(AfeError::ChannelNotAvailable {
+                channel: LedChannel::Ambient1
+            })
+        ));
+    }
+
+    #[test]
+    fn current_to_code_keeps_range_needed_by_other_leds() {
+        let mut afe = MockAfe::new().three_leds();
+
+        afe.set_led2_current(milliamperes(80.0)).unwrap();
+
+        assert_eq!(
+            afe.current_to_code(LedChannel::Led1, milliamperes(20.0))
+                .unwrap(),
+            (13, true)
+        );
+        // The LED2 is the one needing the expanded range, so setting it lower restores the normal range.
+        assert_eq!(
+            afe.current_to_code(LedChannel::Led2, milliamperes(20.0))
+                .unwrap(),
+            (25, false)
+        );
+    }
+
+    #[test]
+    fn current_to_code_matches_setters() {
+        let mut afe = MockAfe::new().three_leds();
+
+        for (led1, led2) in [(20.0, 10.0), (49.9, 50.1), (20.0, 20.0), (90.0, 30.0)] {
+            afe.set_led2_current(milliamperes(led2)).unwrap();
+
+            let (code, double_range) = afe
+                .current_to_code(LedChannel::Led1, milliamperes(led1))
+                .unwrap();
+            let applied = afe.set_led1_current(milliamperes(led1)).unwrap();
+            let step = afe.led_current_step().unwrap();
+
+            assert_eq!(applied, f32::from(code) * step);
+            assert_eq!(step > milliamperes(1.0), double_range);
+        }
+    }
+
+    #[test]
+    fn led_current_steps_in_normal_range() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        let step = afe.led_current_step().unwrap();
+        assert!((step - milliamperes(50.0 / 63.0)).abs() < milliamperes(1e-6));
+
+        afe.set_led_current_steps(LedChannel::Led1, 5).unwrap();
+        let current = afe.set_led_current_steps(LedChannel::Led2, 10).unwrap();
+
+        assert_eq!(current, 10.0 * step);
+        assert_eq!(afe.get_channel_current(LedChannel::Led2).unwrap(), current);
+        // ILED1 is bits 0-5 and ILED2 bits 6-11 of R22h.
+        assert_eq!(mock.register(0x22), 10 << 6 | 5);
+    }
+
+    #[test]
+    fn led_current_steps_in_double_range() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+        // ILED_2X is bit 17 of R23h.
+        mock.set_register(0x23, 1 << 17);
+
+        let step = afe.led_current_step().unwrap();
+        assert!((step - milliamperes(100.0 / 63.0)).abs() < milliamperes(1e-6));
+
+        let current = afe.set_led_current_steps(LedChannel::Led3, 63).unwrap();
+        assert!((current - milliamperes(100.0)).abs() < milliamperes(1e-4));
+        assert_eq!(mock.register(0x23), 1 << 17);
+    }
+
+    #[test]
+    fn invalid_led_current_steps_rejected() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        assert!(matches!(
+            afe.set_led_current_steps(LedChannel::Led1, 64),
+            Err(AfeError::LedCurrentOutsideAllowedRange)
+        ));
+        assert!(matches!(
+            afe.set_led_current_steps(LedChannel::Ambient2, 1),
+            Err(AfeError::ChannelNotAvailable {
+                channel: LedChannel::Ambient2
+            })
+        ));
+        assert_eq!(mock.register(0x22), 0);
+    }
+
+    #[test]
+    fn quantised_current_matches_setter() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        for current in [0.0, 7.3, 19.9, 49.6, 60.0, 87.1] {
+            let double_range = current > 50.0;
+            mock.reset_transactions();
+            let quantised = afe.quantise_current(milliamperes(current), double_range);
+            assert_eq!(mock.transactions(), 0);
+
+            assert_eq!(
+                afe.set_led1_current(milliamperes(current)).unwrap(),
+                quantised
+            );
+        }
+    }
+}
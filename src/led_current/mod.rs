@@ -1,22 +1,209 @@
 //! This module contains the LEDs current and offset current related functions.
 
+use embedded_hal::delay::DelayUs;
 use embedded_hal::i2c::I2c;
 use embedded_hal::i2c::SevenBitAddress;
 use uom::si::electric_current::{microampere, milliampere};
-use uom::si::f32::ElectricCurrent;
+use uom::si::f32::{ElectricCurrent, ElectricPotential, ElectricalResistance, Power};
+use uom::si::time::microsecond;
 
 use crate::{
+    channel::LedChannel,
     device::AFE4404,
     errors::AfeError,
-    modes::{ThreeLedsMode, TwoLedsMode},
+    modes::{LedMode, OneLedMode, ThreeLedsMode, TwoLedsMode},
     register_structs::{R22h, R3Ah},
+    value_reading::AdcCode,
 };
 
-pub use configuration::{LedCurrentConfiguration, OffsetCurrentConfiguration};
+pub use configuration::{
+    LedCurrentConfiguration, LedCurrentWithError, OffsetCurrentConfiguration, PowerBudget,
+    TxConfiguration,
+};
 
 mod configuration;
 pub mod low_level;
 
+impl<I2C, MODE> AFE4404<I2C, MODE>
+where
+    I2C: I2c<SevenBitAddress>,
+    MODE: LedMode,
+{
+    /// Computes the average current of the given channel over the measurement window.
+    fn get_average_current(
+        &mut self,
+        channel: LedChannel,
+    ) -> Result<ElectricCurrent, AfeError<I2C::Error>> {
+        let current = self.get_channel_current(channel)?;
+        let (lighting_st, lighting_end) = self.get_channel_lighting(channel)?;
+        let period = self.get_window_period()?;
+
+        if lighting_end <= lighting_st {
+            return Ok(ElectricCurrent::new::<milliampere>(0.0));
+        }
+
+        Ok(current * ((lighting_end - lighting_st) / period).value)
+    }
+
+    /// Checks that the average current of a LED stays below the eye safety limit.
+    ///
+    /// # Notes
+    ///
+    /// The average current is the LED current multiplied by the lighting duty cycle of the measurement window.
+    /// Ambient channels never light a LED, so they always pass the check.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// An average current above `max_avg_current` will result in an error.
+    pub fn check_eye_safety(
+        &mut self,
+        led: LedChannel,
+        max_avg_current: ElectricCurrent,
+    ) -> Result<(), AfeError<I2C::Error>> {
+        if self.get_average_current(led)? > max_avg_current {
+            return Err(AfeError::EyeSafetyExceeded);
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the average power dissipated by the LEDs stays below a thermal budget.
+    ///
+    /// # Notes
+    ///
+    /// The average power is the LED `forward_voltage` multiplied by the sum of the average currents of LED1, LED2 and LED3.
+    /// The same forward voltage is assumed for all the LEDs.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// An average power above `max_avg_power` will result in an error.
+    pub fn thermal_check(
+        &mut self,
+        forward_voltage: ElectricPotential,
+        max_avg_power: Power,
+    ) -> Result<(), AfeError<I2C::Error>> {
+        let mut average_current = ElectricCurrent::new::<milliampere>(0.0);
+        for led in [LedChannel::Led1, LedChannel::Led2, LedChannel::Led3] {
+            average_current += self.get_average_current(led)?;
+        }
+
+        if forward_voltage * average_current > max_avg_power {
+            return Err(AfeError::ThermalBudgetExceeded);
+        }
+
+        Ok(())
+    }
+
+    /// Checks that every LED with a non-zero current has a non-empty lighting window.
+    ///
+    /// # Notes
+    ///
+    /// A LED left with a non-zero current and a degenerate lighting window (start equal to end) may still draw power.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// A LED with a non-zero current and an empty lighting window will result in an error.
+    pub fn check_tx_consistency(&mut self) -> Result<(), AfeError<I2C::Error>> {
+        for channel in [LedChannel::Led1, LedChannel::Led2, LedChannel::Led3] {
+            let current = self.get_channel_current(channel)?;
+            let (lighting_st, lighting_end) = self.get_channel_lighting(channel)?;
+
+            if current.value > 0.0 && lighting_end == lighting_st {
+                return Err(AfeError::InconsistentLedCurrent { channel });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the signed offset codes, waits for new samples and returns the raw ADC codes.
+    ///
+    /// # Notes
+    ///
+    /// The codes are in the order LED1, LED2, Ambient1, Ambient2 (LED3 in three LEDs mode).
+    fn measure_with_offset_codes<D: DelayUs>(
+        &mut self,
+        codes: [i8; 4],
+        delay: &mut D,
+        settle_us: u32,
+    ) -> Result<[i32; 4], AfeError<I2C::Error>> {
+        self.registers.r3Ah().write(
+            R3Ah::new()
+                .with_i_offdac_led1(codes[0].unsigned_abs())
+                .with_pol_offdac_led1(codes[0] < 0)
+                .with_i_offdac_led2(codes[1].unsigned_abs())
+                .with_pol_offdac_led2(codes[1] < 0)
+                .with_i_offdac_amb1(codes[2].unsigned_abs())
+                .with_pol_offdac_amb1(codes[2] < 0)
+                .with_i_offdac_amb2_or_i_offdac_led3(codes[3].unsigned_abs())
+                .with_pol_offdac_amb2_or_pol_offdac_led3(codes[3] < 0),
+        )?;
+
+        delay
+            .delay_us(settle_us)
+            .map_err(|_| AfeError::DelayError)?;
+
+        self.get_raw_codes()
+    }
+
+    /// Finds and applies the offset codes that bring each channel reading closest to zero.
+    ///
+    /// # Notes
+    ///
+    /// The reading of each channel is assumed to be monotonic in the offset code, so a binary search is performed on all channels at once.
+    fn calibrate_offset_codes<D: DelayUs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), AfeError<I2C::Error>> {
+        // Wait two periods so that a full window is sampled with the new offsets.
+        #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+        let settle_us = (self.get_window_period()?.get::<microsecond>() * 2.0).ceil() as u32;
+
+        let mut low = [-15_i8; 4];
+        let mut high = [15_i8; 4];
+        let readings_low = self.measure_with_offset_codes(low, delay, settle_us)?;
+        let readings_high = self.measure_with_offset_codes(high, delay, settle_us)?;
+
+        let mut increasing = [false; 4];
+        let mut best = [(0_i8, 0_i32); 4];
+        for i in 0..4 {
+            increasing[i] = readings_high[i] >= readings_low[i];
+            best[i] = if readings_low[i].unsigned_abs() <= readings_high[i].unsigned_abs() {
+                (low[i], readings_low[i])
+            } else {
+                (high[i], readings_high[i])
+            };
+        }
+
+        while low.iter().zip(high.iter()).any(|(l, h)| h - l > 1) {
+            let mut middle = [0_i8; 4];
+            for i in 0..4 {
+                middle[i] = low[i] + (high[i] - low[i]) / 2;
+            }
+
+            let readings = self.measure_with_offset_codes(middle, delay, settle_us)?;
+
+            for i in 0..4 {
+                if readings[i].unsigned_abs() < best[i].1.unsigned_abs() {
+                    best[i] = (middle[i], readings[i]);
+                }
+                if (readings[i] > 0) == increasing[i] {
+                    high[i] = middle[i];
+                } else {
+                    low[i] = middle[i];
+                }
+            }
+        }
+
+        self.measure_with_offset_codes(best.map(|(code, _)| code), delay, settle_us)?;
+
+        Ok(())
+    }
+}
+
 impl<I2C> AFE4404<I2C, ThreeLedsMode>
 where
     I2C: I2c<SevenBitAddress>,
@@ -36,7 +223,7 @@ where
         &mut self,
         configuration: &LedCurrentConfiguration<ThreeLedsMode>,
     ) -> Result<LedCurrentConfiguration<ThreeLedsMode>, AfeError<I2C::Error>> {
-        let r23h_prev = self.registers.r23h.read()?;
+        let r23h_prev = self.registers.r23h().read()?;
 
         let high_current: bool = configuration.led1().get::<milliampere>() > 50.0
             || configuration.led2().get::<milliampere>() > 50.0
@@ -67,14 +254,14 @@ where
             (*configuration.led3() / quantisation).value.round() as u8,
         ];
 
-        self.registers.r22h.write(
+        self.registers.r22h().write(
             R22h::new()
                 .with_iled1(values[0])
                 .with_iled2(values[1])
                 .with_iled3(values[2]),
         )?;
         self.registers
-            .r23h
+            .r23h()
             .write(r23h_prev.with_iled_2x(high_current))?;
 
         Ok(LedCurrentConfiguration::<ThreeLedsMode>::new(
@@ -84,6 +271,32 @@ where
         ))
     }
 
+    /// Sets the LEDs current, returning also the residual error of each LED.
+    ///
+    /// # Notes
+    ///
+    /// The residuals are the requested currents minus the applied ones, in the order LED1, LED2, LED3.
+    /// Each residual is within half a quantisation step.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a current value outside the range 0-100mA will result in an error.
+    pub fn set_leds_current_with_error(
+        &mut self,
+        configuration: &LedCurrentConfiguration<ThreeLedsMode>,
+    ) -> Result<LedCurrentWithError<ThreeLedsMode>, AfeError<I2C::Error>> {
+        let applied = self.set_leds_current(configuration)?;
+
+        let residuals = [
+            *configuration.led1() - *applied.led1(),
+            *configuration.led2() - *applied.led2(),
+            *configuration.led3() - *applied.led3(),
+        ];
+
+        Ok((applied, residuals))
+    }
+
     /// Gets the LEDs current.
     ///
     /// # Errors
@@ -92,8 +305,8 @@ where
     pub fn get_leds_current(
         &mut self,
     ) -> Result<LedCurrentConfiguration<ThreeLedsMode>, AfeError<I2C::Error>> {
-        let r22h_prev = self.registers.r22h.read()?;
-        let r23h_prev = self.registers.r23h.read()?;
+        let r22h_prev = self.registers.r22h().read()?;
+        let r23h_prev = self.registers.r23h().read()?;
 
         let range = if r23h_prev.iled_2x() {
             ElectricCurrent::new::<milliampere>(100.0)
@@ -154,7 +367,7 @@ where
             ),
         ];
 
-        self.registers.r3Ah.write(
+        self.registers.r3Ah().write(
             R3Ah::new()
                 .with_i_offdac_led1(values[0].0)
                 .with_pol_offdac_led1(values[0].1)
@@ -181,7 +394,7 @@ where
     pub fn get_offset_current(
         &mut self,
     ) -> Result<OffsetCurrentConfiguration<ThreeLedsMode>, AfeError<I2C::Error>> {
-        let r3ah_prev = self.registers.r3Ah.read()?;
+        let r3ah_prev = self.registers.r3Ah().read()?;
 
         let range = ElectricCurrent::new::<microampere>(7.0);
         let quantisation = range / 15.0;
@@ -217,6 +430,213 @@ where
                 },
         ))
     }
+
+    /// Sets the offset cancellation currents and verifies them reading the register back.
+    ///
+    /// # Notes
+    ///
+    /// The register is read back from the device, bypassing the register cache.
+    /// The sign and the magnitude of each channel are compared with the applied values, allowing a difference of one unit step.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a current value outside the range -7-7uA will result in an error.
+    /// A channel read back different from the applied value will result in an error.
+    pub fn set_offset_current_checked(
+        &mut self,
+        configuration: &OffsetCurrentConfiguration<ThreeLedsMode>,
+    ) -> Result<OffsetCurrentConfiguration<ThreeLedsMode>, AfeError<I2C::Error>> {
+        let quantisation = ElectricCurrent::new::<microampere>(7.0) / 15.0;
+
+        let applied = self.set_offset_current(configuration)?;
+        let r3ah_read_back = self.registers.r3Ah().read_uncached()?;
+
+        let read_back = |polarity: bool, code: u8| {
+            f32::from(code) * quantisation * if polarity { -1.0 } else { 1.0 }
+        };
+
+        for (channel, applied, read_back) in [
+            (
+                LedChannel::Led1,
+                *applied.led1(),
+                read_back(
+                    r3ah_read_back.pol_offdac_led1(),
+                    r3ah_read_back.i_offdac_led1(),
+                ),
+            ),
+            (
+                LedChannel::Led2,
+                *applied.led2(),
+                read_back(
+                    r3ah_read_back.pol_offdac_led2(),
+                    r3ah_read_back.i_offdac_led2(),
+                ),
+            ),
+            (
+                LedChannel::Led3,
+                *applied.led3(),
+                read_back(
+                    r3ah_read_back.pol_offdac_amb2_or_pol_offdac_led3(),
+                    r3ah_read_back.i_offdac_amb2_or_i_offdac_led3(),
+                ),
+            ),
+            (
+                LedChannel::Ambient1,
+                *applied.ambient(),
+                read_back(
+                    r3ah_read_back.pol_offdac_amb1(),
+                    r3ah_read_back.i_offdac_amb1(),
+                ),
+            ),
+        ] {
+            if (applied - read_back).abs() > quantisation {
+                return Err(AfeError::WriteVerificationFailed {
+                    reg_addr: 0x3A,
+                    channel: Some(channel),
+                });
+            }
+        }
+
+        Ok(applied)
+    }
+
+    /// Calibrates the offset cancellation currents so that the dark reading of each channel is centered at zero.
+    ///
+    /// # Notes
+    ///
+    /// The LEDs must be off or the photodiode covered while calibrating.
+    /// After each offset change the function waits two window periods using `delay`.
+    /// The calibrated offsets are applied and returned.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// This function returns an error if the ADC reading falls outside the allowed range.
+    /// This function returns an error if the delay encounters an error.
+    pub fn calibrate_all_offsets<D: DelayUs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<OffsetCurrentConfiguration<ThreeLedsMode>, AfeError<I2C::Error>> {
+        self.calibrate_offset_codes(delay)?;
+
+        self.get_offset_current()
+    }
+
+    /// Gets the LEDs current and the offset cancellation currents together.
+    ///
+    /// # Notes
+    ///
+    /// Each of the registers `R22h`, `R23h` and `R3Ah` is read once, both configurations are decoded from the same values.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    #[allow(clippy::similar_names)]
+    pub fn get_tx_configuration(
+        &mut self,
+    ) -> Result<TxConfiguration<ThreeLedsMode>, AfeError<I2C::Error>> {
+        let r22h_prev = self.registers.r22h().read()?;
+        let r23h_prev = self.registers.r23h().read()?;
+        let r3ah_prev = self.registers.r3Ah().read()?;
+
+        let range = if r23h_prev.iled_2x() {
+            ElectricCurrent::new::<milliampere>(100.0)
+        } else {
+            ElectricCurrent::new::<milliampere>(50.0)
+        };
+        let quantisation = range / 63.0;
+
+        let offset_quantisation = ElectricCurrent::new::<microampere>(7.0) / 15.0;
+        let offset = |polarity: bool, code: u8| {
+            f32::from(code) * offset_quantisation * if polarity { -1.0 } else { 1.0 }
+        };
+
+        Ok((
+            LedCurrentConfiguration::<ThreeLedsMode>::new(
+                f32::from(r22h_prev.iled1()) * quantisation,
+                f32::from(r22h_prev.iled2()) * quantisation,
+                f32::from(r22h_prev.iled3()) * quantisation,
+            ),
+            OffsetCurrentConfiguration::<ThreeLedsMode>::new(
+                offset(r3ah_prev.pol_offdac_led1(), r3ah_prev.i_offdac_led1()),
+                offset(r3ah_prev.pol_offdac_led2(), r3ah_prev.i_offdac_led2()),
+                offset(
+                    r3ah_prev.pol_offdac_amb2_or_pol_offdac_led3(),
+                    r3ah_prev.i_offdac_amb2_or_i_offdac_led3(),
+                ),
+                offset(r3ah_prev.pol_offdac_amb1(), r3ah_prev.i_offdac_amb1()),
+            ),
+        ))
+    }
+
+    /// Sets the current of a LED and the TIA resistor used during its sample phase together.
+    ///
+    /// # Notes
+    ///
+    /// This function automatically rounds the current and the resistor values to the closest actual values, which are returned.
+    /// LED1 shares the resistor1 with Ambient, LED2 shares the resistor2 with LED3, so the gain of the sharing channel changes too.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a current value outside the range 0-100mA will result in an error.
+    /// Setting a resistor value outside the range 10-2000 kOhm will result in an error.
+    /// Tuning an Ambient channel will result in an error.
+    pub fn tune_channel(
+        &mut self,
+        led: LedChannel,
+        current: ElectricCurrent,
+        resistor: ElectricalResistance,
+    ) -> Result<(ElectricCurrent, ElectricalResistance), AfeError<I2C::Error>> {
+        match led {
+            LedChannel::Led1 => Ok((
+                self.set_led1_current(current)?,
+                self.set_tia_resistor1(resistor)?,
+            )),
+            LedChannel::Led2 => Ok((
+                self.set_led2_current(current)?,
+                self.set_tia_resistor2(resistor)?,
+            )),
+            LedChannel::Led3 => Ok((
+                self.set_led3_current(current)?,
+                self.set_tia_resistor2(resistor)?,
+            )),
+            _ => Err(AfeError::ChannelNotAvailable { channel: led }),
+        }
+    }
+
+    /// Sets the LEDs offset cancellation currents to cancel the measured ambient light.
+    ///
+    /// # Notes
+    ///
+    /// The ambient photocurrent is computed from the Ambient reading, the TIA resistor1 and the Ambient offset current.
+    /// The offset current of the LEDs is set equal to the ambient photocurrent, clamped to the range -7-7uA, so that it is subtracted from the LEDs readings.
+    /// The Ambient offset current is not changed.
+    /// After calling this function, a wait time of `tCHANNEL` should be applied before high-accuracy readings.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// This function returns an error if the ADC reading falls outside the allowed range.
+    pub fn auto_offset_from_ambient(
+        &mut self,
+    ) -> Result<OffsetCurrentConfiguration<ThreeLedsMode>, AfeError<I2C::Error>> {
+        let code = AdcCode::from_register(self.registers.r2Dh().read()?.aled1val())
+            .ok_or(AfeError::AdcReadingOutsideAllowedRange)?;
+        let offsets_prev = self.get_offset_current()?;
+
+        let ambient = code.to_voltage() / self.get_tia_resistor1()? + *offsets_prev.ambient();
+        let offset =
+            ElectricCurrent::new::<microampere>(ambient.get::<microampere>().clamp(-7.0, 7.0));
+
+        self.set_offset_current(&OffsetCurrentConfiguration::<ThreeLedsMode>::new(
+            offset,
+            offset,
+            offset,
+            *offsets_prev.ambient(),
+        ))
+    }
 }
 
 impl<I2C> AFE4404<I2C, TwoLedsMode>
@@ -232,7 +652,7 @@ where
         &mut self,
         configuration: &LedCurrentConfiguration<TwoLedsMode>,
     ) -> Result<LedCurrentConfiguration<TwoLedsMode>, AfeError<I2C::Error>> {
-        let r23h_prev = self.registers.r23h.read()?;
+        let r23h_prev = self.registers.r23h().read()?;
 
         let high_current = *configuration.led1() > ElectricCurrent::new::<milliampere>(50.0)
             || *configuration.led2() > ElectricCurrent::new::<milliampere>(50.0);
@@ -259,14 +679,14 @@ where
             (*configuration.led2() / quantisation).value.round() as u8,
         ];
 
-        self.registers.r22h.write(
+        self.registers.r22h().write(
             R22h::new()
                 .with_iled1(values[0])
                 .with_iled2(values[1])
                 .with_iled3(0u8),
         )?;
         self.registers
-            .r23h
+            .r23h()
             .write(r23h_prev.with_iled_2x(high_current))?;
 
         Ok(LedCurrentConfiguration::<TwoLedsMode>::new(
@@ -275,6 +695,33 @@ where
         ))
     }
 
+    /// Sets the LEDs current, returning also the residual error of each LED.
+    ///
+    /// # Notes
+    ///
+    /// The residuals are the requested currents minus the applied ones, in the order LED1, LED2, LED3.
+    /// In two LEDs mode the LED3 residual is always zero.
+    /// Each residual is within half a quantisation step.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a current value outside the range 0-100mA will result in an error.
+    pub fn set_leds_current_with_error(
+        &mut self,
+        configuration: &LedCurrentConfiguration<TwoLedsMode>,
+    ) -> Result<LedCurrentWithError<TwoLedsMode>, AfeError<I2C::Error>> {
+        let applied = self.set_leds_current(configuration)?;
+
+        let residuals = [
+            *configuration.led1() - *applied.led1(),
+            *configuration.led2() - *applied.led2(),
+            ElectricCurrent::new::<milliampere>(0.0),
+        ];
+
+        Ok((applied, residuals))
+    }
+
     /// Gets the LEDs current.
     ///
     /// # Errors
@@ -283,8 +730,8 @@ where
     pub fn get_leds_current(
         &mut self,
     ) -> Result<LedCurrentConfiguration<TwoLedsMode>, AfeError<I2C::Error>> {
-        let r22h_prev = self.registers.r22h.read()?;
-        let r23h_prev = self.registers.r23h.read()?;
+        let r22h_prev = self.registers.r22h().read()?;
+        let r23h_prev = self.registers.r23h().read()?;
 
         let high_current = r23h_prev.iled_2x();
         let range = if high_current {
@@ -349,7 +796,7 @@ where
             ),
         ];
 
-        self.registers.r3Ah.write(
+        self.registers.r3Ah().write(
             R3Ah::new()
                 .with_i_offdac_led1(values[0].0)
                 .with_pol_offdac_led1(values[0].1)
@@ -376,7 +823,7 @@ where
     pub fn get_offset_current(
         &mut self,
     ) -> Result<OffsetCurrentConfiguration<TwoLedsMode>, AfeError<I2C::Error>> {
-        let r3ah_prev = self.registers.r3Ah.read()?;
+        let r3ah_prev = self.registers.r3Ah().read()?;
 
         let range = ElectricCurrent::new::<microampere>(7.0);
         let quantisation = range / 15.0;
@@ -412,4 +859,923 @@ where
                 },
         ))
     }
+
+    /// Sets the offset cancellation currents and verifies them reading the register back.
+    ///
+    /// # Notes
+    ///
+    /// The register is read back from the device, bypassing the register cache.
+    /// The sign and the magnitude of each channel are compared with the applied values, allowing a difference of one unit step.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a current value outside the range -7-7uA will result in an error.
+    /// A channel read back different from the applied value will result in an error.
+    pub fn set_offset_current_checked(
+        &mut self,
+        configuration: &OffsetCurrentConfiguration<TwoLedsMode>,
+    ) -> Result<OffsetCurrentConfiguration<TwoLedsMode>, AfeError<I2C::Error>> {
+        let quantisation = ElectricCurrent::new::<microampere>(7.0) / 15.0;
+
+        let applied = self.set_offset_current(configuration)?;
+        let r3ah_read_back = self.registers.r3Ah().read_uncached()?;
+
+        let read_back = |polarity: bool, code: u8| {
+            f32::from(code) * quantisation * if polarity { -1.0 } else { 1.0 }
+        };
+
+        for (channel, applied, read_back) in [
+            (
+                LedChannel::Led1,
+                *applied.led1(),
+                read_back(
+                    r3ah_read_back.pol_offdac_led1(),
+                    r3ah_read_back.i_offdac_led1(),
+                ),
+            ),
+            (
+                LedChannel::Led2,
+                *applied.led2(),
+                read_back(
+                    r3ah_read_back.pol_offdac_led2(),
+                    r3ah_read_back.i_offdac_led2(),
+                ),
+            ),
+            (
+                LedChannel::Ambient1,
+                *applied.ambient1(),
+                read_back(
+                    r3ah_read_back.pol_offdac_amb1(),
+                    r3ah_read_back.i_offdac_amb1(),
+                ),
+            ),
+            (
+                LedChannel::Ambient2,
+                *applied.ambient2(),
+                read_back(
+                    r3ah_read_back.pol_offdac_amb2_or_pol_offdac_led3(),
+                    r3ah_read_back.i_offdac_amb2_or_i_offdac_led3(),
+                ),
+            ),
+        ] {
+            if (applied - read_back).abs() > quantisation {
+                return Err(AfeError::WriteVerificationFailed {
+                    reg_addr: 0x3A,
+                    channel: Some(channel),
+                });
+            }
+        }
+
+        Ok(applied)
+    }
+
+    /// Calibrates the offset cancellation currents so that the dark reading of each channel is centered at zero.
+    ///
+    /// # Notes
+    ///
+    /// The LEDs must be off or the photodiode covered while calibrating.
+    /// After each offset change the function waits two window periods using `delay`.
+    /// The calibrated offsets are applied and returned.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// This function returns an error if the ADC reading falls outside the allowed range.
+    /// This function returns an error if the delay encounters an error.
+    pub fn calibrate_all_offsets<D: DelayUs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<OffsetCurrentConfiguration<TwoLedsMode>, AfeError<I2C::Error>> {
+        self.calibrate_offset_codes(delay)?;
+
+        self.get_offset_current()
+    }
+
+    /// Gets the LEDs current and the offset cancellation currents together.
+    ///
+    /// # Notes
+    ///
+    /// Each of the registers `R22h`, `R23h` and `R3Ah` is read once, both configurations are decoded from the same values.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    #[allow(clippy::similar_names)]
+    pub fn get_tx_configuration(
+        &mut self,
+    ) -> Result<TxConfiguration<TwoLedsMode>, AfeError<I2C::Error>> {
+        let r22h_prev = self.registers.r22h().read()?;
+        let r23h_prev = self.registers.r23h().read()?;
+        let r3ah_prev = self.registers.r3Ah().read()?;
+
+        let range = if r23h_prev.iled_2x() {
+            ElectricCurrent::new::<milliampere>(100.0)
+        } else {
+            ElectricCurrent::new::<milliampere>(50.0)
+        };
+        let quantisation = range / 63.0;
+
+        let offset_quantisation = ElectricCurrent::new::<microampere>(7.0) / 15.0;
+        let offset = |polarity: bool, code: u8| {
+            f32::from(code) * offset_quantisation * if polarity { -1.0 } else { 1.0 }
+        };
+
+        Ok((
+            LedCurrentConfiguration::<TwoLedsMode>::new(
+                f32::from(r22h_prev.iled1()) * quantisation,
+                f32::from(r22h_prev.iled2()) * quantisation,
+            ),
+            OffsetCurrentConfiguration::<TwoLedsMode>::new(
+                offset(r3ah_prev.pol_offdac_led1(), r3ah_prev.i_offdac_led1()),
+                offset(r3ah_prev.pol_offdac_led2(), r3ah_prev.i_offdac_led2()),
+                offset(r3ah_prev.pol_offdac_amb1(), r3ah_prev.i_offdac_amb1()),
+                offset(
+                    r3ah_prev.pol_offdac_amb2_or_pol_offdac_led3(),
+                    r3ah_prev.i_offdac_amb2_or_i_offdac_led3(),
+                ),
+            ),
+        ))
+    }
+
+    /// Sets the current of a LED and the TIA resistor used during its sample phase together.
+    ///
+    /// # Notes
+    ///
+    /// This function automatically rounds the current and the resistor values to the closest actual values, which are returned.
+    /// LED1 shares the resistor1 with Ambient1, LED2 shares the resistor2 with Ambient2, so the gain of the sharing channel changes too.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a current value outside the range 0-100mA will result in an error.
+    /// Setting a resistor value outside the range 10-2000 kOhm will result in an error.
+    /// Tuning a channel different from LED1 or LED2 will result in an error.
+    pub fn tune_channel(
+        &mut self,
+        led: LedChannel,
+        current: ElectricCurrent,
+        resistor: ElectricalResistance,
+    ) -> Result<(ElectricCurrent, ElectricalResistance), AfeError<I2C::Error>> {
+        match led {
+            LedChannel::Led1 => Ok((
+                self.set_led1_current(current)?,
+                self.set_tia_resistor1(resistor)?,
+            )),
+            LedChannel::Led2 => Ok((
+                self.set_led2_current(current)?,
+                self.set_tia_resistor2(resistor)?,
+            )),
+            _ => Err(AfeError::ChannelNotAvailable { channel: led }),
+        }
+    }
+
+    /// Sets the LEDs offset cancellation currents to cancel the measured ambient light.
+    ///
+    /// # Notes
+    ///
+    /// The ambient photocurrent is computed from the Ambient1 reading, the TIA resistor1 and the Ambient1 offset current.
+    /// The offset current of the LEDs is set equal to the ambient photocurrent, clamped to the range -7-7uA, so that it is subtracted from the LEDs readings.
+    /// The Ambient offset currents are not changed.
+    /// After calling this function, a wait time of `tCHANNEL` should be applied before high-accuracy readings.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// This function returns an error if the ADC reading falls outside the allowed range.
+    pub fn auto_offset_from_ambient(
+        &mut self,
+    ) -> Result<OffsetCurrentConfiguration<TwoLedsMode>, AfeError<I2C::Error>> {
+        let code = AdcCode::from_register(self.registers.r2Dh().read()?.aled1val())
+            .ok_or(AfeError::AdcReadingOutsideAllowedRange)?;
+        let offsets_prev = self.get_offset_current()?;
+
+        let ambient = code.to_voltage() / self.get_tia_resistor1()? + *offsets_prev.ambient1();
+        let offset =
+            ElectricCurrent::new::<microampere>(ambient.get::<microampere>().clamp(-7.0, 7.0));
+
+        self.set_offset_current(&OffsetCurrentConfiguration::<TwoLedsMode>::new(
+            offset,
+            offset,
+            *offsets_prev.ambient1(),
+            *offsets_prev.ambient2(),
+        ))
+    }
+}
+
+impl<I2C> AFE4404<I2C, OneLedMode>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    /// Sets the LED current.
+    ///
+    /// # Notes
+    ///
+    /// This function automatically expands the current range to 0-100 mA if the current is above 50 mA.
+    /// The unused LED2 and LED3 are switched off.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a current value outside the range 0-100mA will result in an error.
+    pub fn set_leds_current(
+        &mut self,
+        configuration: &LedCurrentConfiguration<OneLedMode>,
+    ) -> Result<LedCurrentConfiguration<OneLedMode>, AfeError<I2C::Error>> {
+        let r23h_prev = self.registers.r23h().read()?;
+
+        let high_current = *configuration.led1() > ElectricCurrent::new::<milliampere>(50.0);
+
+        let range = if high_current {
+            ElectricCurrent::new::<milliampere>(100.0)
+        } else {
+            ElectricCurrent::new::<milliampere>(50.0)
+        };
+
+        let quantisation = range / 63.0;
+
+        if *configuration.led1() > range || configuration.led1().get::<milliampere>() < 0.0 {
+            return Err(AfeError::LedCurrentOutsideAllowedRange);
+        }
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let value = (*configuration.led1() / quantisation).value.round() as u8;
+
+        self.registers.r22h().write(
+            R22h::new()
+                .with_iled1(value)
+                .with_iled2(0u8)
+                .with_iled3(0u8),
+        )?;
+        self.registers
+            .r23h()
+            .write(r23h_prev.with_iled_2x(high_current))?;
+
+        Ok(LedCurrentConfiguration::<OneLedMode>::new(
+            f32::from(value) * quantisation,
+        ))
+    }
+
+    /// Gets the LED current.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub fn get_leds_current(
+        &mut self,
+    ) -> Result<LedCurrentConfiguration<OneLedMode>, AfeError<I2C::Error>> {
+        let r22h_prev = self.registers.r22h().read()?;
+        let r23h_prev = self.registers.r23h().read()?;
+
+        let range = if r23h_prev.iled_2x() {
+            ElectricCurrent::new::<milliampere>(100.0)
+        } else {
+            ElectricCurrent::new::<milliampere>(50.0)
+        };
+        let quantisation = range / 63.0;
+
+        Ok(LedCurrentConfiguration::<OneLedMode>::new(
+            f32::from(r22h_prev.iled1()) * quantisation,
+        ))
+    }
+
+    /// Sets the offset cancellation currents.
+    ///
+    /// # Notes
+    ///
+    /// The offset cancellation of the unused LED2 and Ambient2 phases is disabled.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a current value outside the range -7-7uA will result in an error.
+    pub fn set_offset_current(
+        &mut self,
+        configuration: &OffsetCurrentConfiguration<OneLedMode>,
+    ) -> Result<OffsetCurrentConfiguration<OneLedMode>, AfeError<I2C::Error>> {
+        let range = ElectricCurrent::new::<microampere>(7.0);
+        let quantisation = range / 15.0;
+
+        if *configuration.led1() > range
+            || *configuration.ambient() > range
+            || *configuration.led1() < -range
+            || *configuration.ambient() < -range
+        {
+            return Err(AfeError::OffsetCurrentOutsideAllowedRange);
+        }
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let values: [(u8, bool); 2] = [
+            (
+                (configuration.led1().abs() / quantisation).value.round() as u8,
+                configuration.led1().value < 0.0,
+            ),
+            (
+                (configuration.ambient().abs() / quantisation).value.round() as u8,
+                configuration.ambient().value < 0.0,
+            ),
+        ];
+
+        self.registers.r3Ah().write(
+            R3Ah::new()
+                .with_i_offdac_led1(values[0].0)
+                .with_pol_offdac_led1(values[0].1)
+                .with_i_offdac_amb1(values[1].0)
+                .with_pol_offdac_amb1(values[1].1),
+        )?;
+        Ok(OffsetCurrentConfiguration::<OneLedMode>::new(
+            f32::from(values[0].0) * quantisation * if values[0].1 { -1.0 } else { 1.0 },
+            f32::from(values[1].0) * quantisation * if values[1].1 { -1.0 } else { 1.0 },
+        ))
+    }
+
+    /// Gets the offset cancellation currents.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub fn get_offset_current(
+        &mut self,
+    ) -> Result<OffsetCurrentConfiguration<OneLedMode>, AfeError<I2C::Error>> {
+        let r3ah_prev = self.registers.r3Ah().read()?;
+
+        let range = ElectricCurrent::new::<microampere>(7.0);
+        let quantisation = range / 15.0;
+
+        Ok(OffsetCurrentConfiguration::<OneLedMode>::new(
+            f32::from(r3ah_prev.i_offdac_led1())
+                * quantisation
+                * if r3ah_prev.pol_offdac_led1() {
+                    -1.0
+                } else {
+                    1.0
+                },
+            f32::from(r3ah_prev.i_offdac_amb1())
+                * quantisation
+                * if r3ah_prev.pol_offdac_amb1() {
+                    -1.0
+                } else {
+                    1.0
+                },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uom::si::{
+        electric_potential::volt, electrical_resistance::kiloohm, f32::Time, power::milliwatt,
+    };
+
+    use super::*;
+    use crate::{
+        led_current::{LedCurrentConfiguration, OffsetCurrentConfiguration, PowerBudget},
+        measurement_window::MeasurementWindowConfiguration,
+        mock::MockAfe,
+        system::State,
+    };
+
+    /// Lights LED1 for a quarter of a 100 µs window at about 20 mA, returning the expected average current.
+    fn quarter_duty_led1(afe: &mut AFE4404<MockAfe, ThreeLedsMode>) -> ElectricCurrent {
+        afe.set_window_counter(399).unwrap();
+        afe.set_led1_lighting_st(Time::new::<microsecond>(0.0))
+            .unwrap();
+        afe.set_led1_lighting_end(Time::new::<microsecond>(25.0))
+            .unwrap();
+
+        afe.set_led1_current(ElectricCurrent::new::<milliampere>(20.0))
+            .unwrap()
+            / 4.0
+    }
+
+    /// Gets a negative offset current of the given number of unit steps.
+    fn negative_steps(steps: u8) -> ElectricCurrent {
+        -f32::from(steps) * ElectricCurrent::new::<microampere>(7.0) / 15.0
+    }
+
+    /// Models a dark reading decreasing linearly by 1000 codes for each offset step, updated on every delay.
+    struct LinearOffsetModel {
+        mock: MockAfe,
+        /// The dark readings with no offset in the order LED1, LED2, Ambient1, Ambient2 (LED3 in three LEDs mode).
+        dark: [i32; 4],
+    }
+
+    impl LinearOffsetModel {
+        /// Gets the signed offset steps written in `R3Ah`, in the same order of the dark readings.
+        fn steps(&self) -> [i32; 4] {
+            let r3ah = self.mock.register(0x3A);
+
+            // The offset code and polarity bit offsets inside R3Ah.
+            [5, 15, 10, 0].map(|shift| {
+                let code = i32::try_from((r3ah >> shift) & 0xF).unwrap();
+                if (r3ah >> (shift + 4)) & 1 == 1 {
+                    -code
+                } else {
+                    code
+                }
+            })
+        }
+    }
+
+    impl DelayUs for LinearOffsetModel {
+        type Error = core::convert::Infallible;
+
+        fn delay_us(&mut self, _us: u32) -> Result<(), Self::Error> {
+            let steps = self.steps();
+
+            for (i, reg_addr) in [0x2C, 0x2A, 0x2D, 0x2B].into_iter().enumerate() {
+                let reading = self.dark[i] - 1000 * steps[i];
+                self.mock
+                    .set_register(reg_addr, u32::from_ne_bytes(reading.to_ne_bytes()));
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn negative_offset_currents_verified_in_three_leds_mode() {
+        let mut afe = MockAfe::new().three_leds();
+
+        let configuration = OffsetCurrentConfiguration::<ThreeLedsMode>::new(
+            negative_steps(1),
+            negative_steps(5),
+            negative_steps(10),
+            negative_steps(15),
+        );
+        let applied = afe.set_offset_current_checked(&configuration).unwrap();
+        let read_back = afe.get_offset_current().unwrap();
+
+        for (applied, read_back) in [
+            (*applied.led1(), *read_back.led1()),
+            (*applied.led2(), *read_back.led2()),
+            (*applied.led3(), *read_back.led3()),
+            (*applied.ambient(), *read_back.ambient()),
+        ] {
+            assert!(applied.get::<microampere>() < 0.0);
+            assert!((applied - read_back).get::<microampere>().abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn negative_offset_currents_verified_in_two_leds_mode() {
+        let mut afe = MockAfe::new().two_leds();
+
+        let configuration = OffsetCurrentConfiguration::<TwoLedsMode>::new(
+            negative_steps(1),
+            negative_steps(5),
+            negative_steps(10),
+            negative_steps(15),
+        );
+        let applied = afe.set_offset_current_checked(&configuration).unwrap();
+        let read_back = afe.get_offset_current().unwrap();
+
+        for (applied, read_back) in [
+            (*applied.led1(), *read_back.led1()),
+            (*applied.led2(), *read_back.led2()),
+            (*applied.ambient1(), *read_back.ambient1()),
+            (*applied.ambient2(), *read_back.ambient2()),
+        ] {
+            assert!(applied.get::<microampere>() < 0.0);
+            assert!((applied - read_back).get::<microampere>().abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn offset_currents_verified_on_device_with_register_cache() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+        afe.set_register_cache(State::Enabled);
+
+        mock.set_writes_ignored(true);
+        let configuration = OffsetCurrentConfiguration::<ThreeLedsMode>::new(
+            negative_steps(5),
+            negative_steps(0),
+            negative_steps(0),
+            negative_steps(0),
+        );
+
+        assert!(matches!(
+            afe.set_offset_current_checked(&configuration),
+            Err(AfeError::WriteVerificationFailed {
+                reg_addr: 0x3A,
+                channel: Some(LedChannel::Led1),
+            })
+        ));
+    }
+
+    #[test]
+    fn tx_configuration_reads_each_register_once() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        afe.set_leds_current(&LedCurrentConfiguration::<ThreeLedsMode>::new(
+            ElectricCurrent::new::<milliampere>(10.0),
+            ElectricCurrent::new::<milliampere>(60.0),
+            ElectricCurrent::new::<milliampere>(30.0),
+        ))
+        .unwrap();
+        afe.set_offset_current(&OffsetCurrentConfiguration::<ThreeLedsMode>::new(
+            negative_steps(3),
+            -negative_steps(4),
+            negative_steps(5),
+            -negative_steps(6),
+        ))
+        .unwrap();
+
+        mock.reset_transactions();
+        let (leds, offsets) = afe.get_tx_configuration().unwrap();
+        // Three configuration reads, each setting and clearing the register reading flag.
+        assert_eq!(mock.transactions(), 3 * 4);
+
+        assert_eq!(leds, afe.get_leds_current().unwrap());
+        assert_eq!(offsets, afe.get_offset_current().unwrap());
+    }
+
+    #[test]
+    fn tx_configuration_matches_getters_in_two_leds_mode() {
+        let mock = MockAfe::new();
+        let mut afe = mock.two_leds();
+
+        afe.set_leds_current(&LedCurrentConfiguration::<TwoLedsMode>::new(
+            ElectricCurrent::new::<milliampere>(10.0),
+            ElectricCurrent::new::<milliampere>(20.0),
+        ))
+        .unwrap();
+        afe.set_offset_current(&OffsetCurrentConfiguration::<TwoLedsMode>::new(
+            negative_steps(3),
+            -negative_steps(4),
+            negative_steps(5),
+            -negative_steps(6),
+        ))
+        .unwrap();
+
+        mock.reset_transactions();
+        let (leds, offsets) = afe.get_tx_configuration().unwrap();
+        assert_eq!(mock.transactions(), 3 * 4);
+
+        assert_eq!(leds, afe.get_leds_current().unwrap());
+        assert_eq!(offsets, afe.get_offset_current().unwrap());
+    }
+
+    #[test]
+    fn average_current_follows_lighting_duty() {
+        let mut afe = MockAfe::new().three_leds();
+        let expected = quarter_duty_led1(&mut afe);
+
+        let average = afe.get_average_current(LedChannel::Led1).unwrap();
+        assert!((average - expected).get::<milliampere>().abs() < 1e-4);
+        assert_eq!(
+            afe.get_average_current(LedChannel::Led2).unwrap(),
+            ElectricCurrent::new::<milliampere>(0.0)
+        );
+    }
+
+    #[test]
+    fn eye_safety_passes_at_limit() {
+        let mut afe = MockAfe::new().three_leds();
+        quarter_duty_led1(&mut afe);
+
+        let average = afe.get_average_current(LedChannel::Led1).unwrap();
+        assert!(afe.check_eye_safety(LedChannel::Led1, average).is_ok());
+    }
+
+    #[test]
+    fn eye_safety_exceeded_just_below_average_current() {
+        let mut afe = MockAfe::new().three_leds();
+        quarter_duty_led1(&mut afe);
+
+        let average = afe.get_average_current(LedChannel::Led1).unwrap();
+        assert!(matches!(
+            afe.check_eye_safety(LedChannel::Led1, average * 0.99),
+            Err(AfeError::EyeSafetyExceeded)
+        ));
+    }
+
+    #[test]
+    fn eye_safety_passes_just_above_average_current() {
+        let mut afe = MockAfe::new().three_leds();
+        quarter_duty_led1(&mut afe);
+
+        let average = afe.get_average_current(LedChannel::Led1).unwrap();
+        assert!(afe
+            .check_eye_safety(LedChannel::Led1, average * 1.01)
+            .is_ok());
+    }
+
+    #[test]
+    fn eye_safety_passes_for_ambient_channels() {
+        let mut afe = MockAfe::new().three_leds();
+        quarter_duty_led1(&mut afe);
+
+        let limit = ElectricCurrent::new::<milliampere>(0.0);
+        assert!(afe.check_eye_safety(LedChannel::Ambient1, limit).is_ok());
+        assert!(afe.check_eye_safety(LedChannel::Ambient2, limit).is_ok());
+    }
+
+    #[test]
+    fn led_current_residuals_within_one_step_in_three_leds_mode() {
+        let mut afe = MockAfe::new().three_leds();
+        let requested = [10.3, 25.1, 77.7].map(ElectricCurrent::new::<milliampere>);
+
+        let (applied, residuals) = afe
+            .set_leds_current_with_error(&LedCurrentConfiguration::<ThreeLedsMode>::new(
+                requested[0],
+                requested[1],
+                requested[2],
+            ))
+            .unwrap();
+        let step = afe.led_current_step().unwrap();
+
+        for (residual, (requested, applied)) in residuals.iter().zip(requested.iter().zip([
+            *applied.led1(),
+            *applied.led2(),
+            *applied.led3(),
+        ])) {
+            assert_eq!(*residual, *requested - applied);
+            assert!(residual.abs() <= step / 2.0);
+        }
+    }
+
+    #[test]
+    fn led_current_residuals_within_one_step_in_two_leds_mode() {
+        let mut afe = MockAfe::new().two_leds();
+        let requested = [10.3, 25.1].map(ElectricCurrent::new::<milliampere>);
+
+        let (applied, residuals) = afe
+            .set_leds_current_with_error(&LedCurrentConfiguration::<TwoLedsMode>::new(
+                requested[0],
+                requested[1],
+            ))
+            .unwrap();
+        let step = afe.led_current_step().unwrap();
+
+        assert_eq!(residuals[0], requested[0] - *applied.led1());
+        assert_eq!(residuals[1], requested[1] - *applied.led2());
+        assert!(residuals[..2]
+            .iter()
+            .all(|residual| residual.abs() <= step / 2.0));
+        assert_eq!(residuals[2], ElectricCurrent::new::<milliampere>(0.0));
+    }
+
+    #[test]
+    fn all_offsets_calibrated_in_three_leds_mode() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+        let mut model = LinearOffsetModel {
+            mock: mock.clone(),
+            dark: [5150, -7000, 11_800, -3400],
+        };
+
+        let offsets = afe.calibrate_all_offsets(&mut model).unwrap();
+
+        assert_eq!(model.steps(), [5, -7, 12, -3]);
+        assert_eq!(offsets, afe.get_offset_current().unwrap());
+    }
+
+    #[test]
+    fn all_offsets_calibrated_in_two_leds_mode() {
+        let mock = MockAfe::new();
+        let mut afe = mock.two_leds();
+        let mut model = LinearOffsetModel {
+            mock: mock.clone(),
+            dark: [-15_000, 15_000, 0, 2600],
+        };
+
+        let offsets = afe.calibrate_all_offsets(&mut model).unwrap();
+
+        assert_eq!(model.steps(), [-15, 15, 0, 3]);
+        assert_eq!(offsets, afe.get_offset_current().unwrap());
+    }
+
+    #[test]
+    fn tx_consistent_with_lit_leds() {
+        let mut afe = MockAfe::new().three_leds();
+        afe.set_measurement_window(&MeasurementWindowConfiguration::default())
+            .unwrap();
+        afe.set_leds_current(&LedCurrentConfiguration::<ThreeLedsMode>::new(
+            ElectricCurrent::new::<milliampere>(10.0),
+            ElectricCurrent::new::<milliampere>(20.0),
+            ElectricCurrent::new::<milliampere>(30.0),
+        ))
+        .unwrap();
+
+        afe.check_tx_consistency().unwrap();
+    }
+
+    #[test]
+    fn tx_consistent_with_all_leds_off() {
+        let mut afe = MockAfe::new().three_leds();
+
+        // Every lighting window is empty, but every current is zero.
+        afe.check_tx_consistency().unwrap();
+    }
+
+    #[test]
+    fn tx_inconsistent_with_current_on_empty_lighting_window() {
+        let mut afe = MockAfe::new().three_leds();
+        afe.set_measurement_window(&MeasurementWindowConfiguration::default())
+            .unwrap();
+        afe.set_led3_current(ElectricCurrent::new::<milliampere>(5.0))
+            .unwrap();
+
+        let lighting_st = afe.get_led3_lighting_st().unwrap();
+        afe.set_led3_lighting_end(lighting_st).unwrap();
+
+        assert!(matches!(
+            afe.check_tx_consistency(),
+            Err(AfeError::InconsistentLedCurrent {
+                channel: LedChannel::Led3
+            })
+        ));
+    }
+
+    #[test]
+    fn tuning_sets_current_and_resistor_registers() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        let (current, resistor) = afe
+            .tune_channel(
+                LedChannel::Led2,
+                ElectricCurrent::new::<milliampere>(20.0),
+                ElectricalResistance::new::<kiloohm>(100.0),
+            )
+            .unwrap();
+
+        // Only the ILED2 field and the separate resistor are written, with separate gains enabled.
+        assert_eq!(mock.register(0x22), 25 << 6);
+        assert_eq!(mock.register(0x20), 1 << 15 | 2);
+        assert_eq!(mock.register(0x21), 0);
+        assert!((current.get::<milliampere>() - 19.84).abs() < 0.01);
+        assert!((resistor.get::<kiloohm>() - 100.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn tuning_ambient_channel_fails() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        let result = afe.tune_channel(
+            LedChannel::Ambient1,
+            ElectricCurrent::new::<milliampere>(20.0),
+            ElectricalResistance::new::<kiloohm>(100.0),
+        );
+
+        assert!(matches!(
+            result,
+            Err(AfeError::ChannelNotAvailable {
+                channel: LedChannel::Ambient1
+            })
+        ));
+        assert_eq!(mock.transactions(), 0);
+    }
+
+    /// Loads an Ambient reading of the given voltage, measured with the given TIA resistor1.
+    fn load_ambient(
+        mock: &MockAfe,
+        afe: &mut AFE4404<MockAfe, ThreeLedsMode>,
+        volts: f32,
+        kiloohms: f32,
+    ) {
+        afe.set_tia_resistor1(ElectricalResistance::new::<kiloohm>(kiloohms))
+            .unwrap();
+        let code = AdcCode::from_voltage(ElectricPotential::new::<volt>(volts));
+        mock.set_register(0x2D, u32::try_from(code.0).unwrap());
+    }
+
+    /// Gets the signed offset steps written in `R3Ah`, in the order LED1, LED2, Ambient, LED3.
+    fn offset_steps(mock: &MockAfe) -> [i32; 4] {
+        LinearOffsetModel {
+            mock: mock.clone(),
+            dark: [0; 4],
+        }
+        .steps()
+    }
+
+    #[test]
+    fn offset_from_ambient_cancels_photocurrent() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+        // 0.5 V on 250 kΩ is 2 µA, plus an Ambient offset of 2 steps (0.93 µA).
+        load_ambient(&mock, &mut afe, 0.5, 250.0);
+        afe.set_offset_current(&OffsetCurrentConfiguration::<ThreeLedsMode>::new(
+            ElectricCurrent::new::<microampere>(0.0),
+            ElectricCurrent::new::<microampere>(0.0),
+            ElectricCurrent::new::<microampere>(0.0),
+            -negative_steps(2),
+        ))
+        .unwrap();
+
+        let offsets = afe.auto_offset_from_ambient().unwrap();
+
+        // 2.93 µA rounds to 6 steps, the Ambient offset is kept.
+        assert_eq!(offset_steps(&mock), [6, 6, 2, 6]);
+        assert!((offsets.led1().get::<microampere>() - 2.8).abs() < 1e-3);
+    }
+
+    #[test]
+    fn offset_from_ambient_clamped_to_range() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+        // 1 V on 100 kΩ is 10 µA, beyond the 7 µA offset range.
+        load_ambient(&mock, &mut afe, 1.0, 100.0);
+
+        afe.auto_offset_from_ambient().unwrap();
+
+        assert_eq!(offset_steps(&mock), [15, 15, 0, 15]);
+    }
+
+    /// Lights LED2 for another quarter of the window at about 10 mA next to LED1, returning the expected average power at 2 V.
+    fn quarter_duty_led1_and_led2(afe: &mut AFE4404<MockAfe, ThreeLedsMode>) -> Power {
+        let led1 = quarter_duty_led1(afe);
+        afe.set_led2_lighting_st(Time::new::<microsecond>(50.0))
+            .unwrap();
+        afe.set_led2_lighting_end(Time::new::<microsecond>(75.0))
+            .unwrap();
+        let led2 = afe
+            .set_led2_current(ElectricCurrent::new::<milliampere>(10.0))
+            .unwrap()
+            / 4.0;
+
+        ElectricPotential::new::<volt>(2.0) * (led1 + led2)
+    }
+
+    #[test]
+    fn thermal_check_passes_within_budget() {
+        let mut afe = MockAfe::new().three_leds();
+        let power = quarter_duty_led1_and_led2(&mut afe);
+
+        assert!(afe
+            .thermal_check(ElectricPotential::new::<volt>(2.0), power * 1.01)
+            .is_ok());
+    }
+
+    #[test]
+    fn thermal_check_fails_over_budget() {
+        let mut afe = MockAfe::new().three_leds();
+        let power = quarter_duty_led1_and_led2(&mut afe);
+
+        assert!(matches!(
+            afe.thermal_check(ElectricPotential::new::<volt>(2.0), power * 0.99),
+            Err(AfeError::ThermalBudgetExceeded)
+        ));
+        // A lower forward voltage brings the same currents back within the budget.
+        assert!(afe
+            .thermal_check(ElectricPotential::new::<volt>(1.9), power * 0.99)
+            .is_ok());
+    }
+
+    /// Reads the LEDs current and the measurement window back from the device and estimates their power budget at 2 V.
+    fn read_back_budget(afe: &mut AFE4404<MockAfe, ThreeLedsMode>) -> PowerBudget {
+        let currents = afe.get_leds_current().unwrap();
+        let window = afe.get_measurement_window().unwrap();
+
+        currents.power_budget(&window, ElectricPotential::new::<volt>(2.0))
+    }
+
+    #[test]
+    fn power_budget_matches_device_duty_cycle() {
+        let mut afe = MockAfe::new().three_leds();
+        let power = quarter_duty_led1_and_led2(&mut afe);
+
+        let budget = read_back_budget(&mut afe);
+
+        assert!((budget.power - power).get::<milliwatt>().abs() < 1e-3);
+        assert!(budget.average_currents[2].get::<milliampere>().abs() < f32::EPSILON);
+        let sum = budget.average_currents[0] + budget.average_currents[1];
+        assert!((budget.total_current - sum).get::<milliampere>().abs() < 1e-4);
+    }
+
+    #[test]
+    fn power_budget_of_zero_period_is_zero() {
+        let mut afe = MockAfe::new().three_leds();
+        quarter_duty_led1_and_led2(&mut afe);
+        let currents = afe.get_leds_current().unwrap();
+        let mut window = afe.get_measurement_window().unwrap();
+        *window.period_mut() = Time::new::<microsecond>(0.0);
+
+        let budget = currents.power_budget(&window, ElectricPotential::new::<volt>(2.0));
+
+        // The duty cycle of a zero period would otherwise be infinite.
+        assert!(budget.power.get::<milliwatt>().abs() < f32::EPSILON);
+        assert!(budget
+            .average_currents
+            .iter()
+            .all(|current| current.get::<milliampere>().abs() < f32::EPSILON));
+    }
+
+    #[test]
+    fn power_budget_needs_a_valid_window() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+        quarter_duty_led1_and_led2(&mut afe);
+        mock.set_register(0x39, 2);
+
+        assert!(afe.get_leds_current().is_ok());
+        assert!(matches!(
+            afe.get_measurement_window(),
+            Err(AfeError::InvalidRegisterValue { reg_addr: 0x39 })
+        ));
+    }
 }
@@ -7,6 +7,7 @@
 #![warn(clippy::alloc_instead_of_core)]
 #![warn(clippy::unwrap_in_result)]
 #![warn(clippy::unwrap_used)]
+#![cfg_attr(test, allow(clippy::unwrap_used))]
 // #![warn(clippy::missing_docs_in_private_items)]
 #![allow(clippy::must_use_candidate)]
 #![no_std]
@@ -15,18 +16,38 @@
 
 extern crate alloc;
 
-include!(concat!(env!("OUT_DIR"), "/register_block.rs"));
+use register_map::{register_block, register_structs, RegisterWritable};
+
+pub use register_map::RegisterAddress;
 
 pub mod adc;
+#[cfg(feature = "async")]
+pub mod asynch;
+pub mod cache;
+pub mod channel;
 pub mod clock;
+pub mod data_ready;
+pub mod debug;
 pub mod device;
 mod errors;
+#[cfg(feature = "hal-02")]
+pub mod hal02;
 pub mod led_current;
+pub mod lint;
 pub mod measurement_window;
+#[cfg(any(test, feature = "test-util"))]
+pub mod mock;
 pub mod modes;
+pub mod observer;
+pub mod prelude;
 mod register;
+mod register_map;
+pub mod retry;
+pub mod setup;
+pub mod shared_bus;
 pub mod system;
 pub mod tia;
 pub mod value_reading;
-
-// TODO: Prelude.
+pub mod watchdog;
+#[cfg(feature = "serde")]
+pub mod wire;
@@ -0,0 +1,213 @@
+//! This module contains the [`AFE4404`] configuration linter.
+
+use alloc::vec::Vec;
+
+use embedded_hal::i2c::{I2c, SevenBitAddress};
+
+use crate::{
+    channel::LedChannel, device::AFE4404, errors::AfeError, modes::LedMode, system::State,
+};
+
+/// Represents a non-fatal configuration problem of the [`AFE4404`].
+///
+/// # Notes
+///
+/// The slot shared between LED3 and Ambient2 is always reported as `LedChannel::Led3`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AfeWarning {
+    /// The timer engine is disabled, no sample will be acquired.
+    TimerDisabled,
+    /// The clock division register contains an invalid value, timings were not checked.
+    InvalidClockDivision,
+    /// The photodiode is disconnected, the readings are determined only by the offset currents.
+    PhotodiodeDisconnected,
+    /// The TIA inputs are shorted, the readings do not contain any photocurrent.
+    InputShortEnabled,
+    /// The sample windows of two channels overlap.
+    SampleWindowsOverlap {
+        /// The first channel.
+        first: LedChannel,
+        /// The second channel.
+        second: LedChannel,
+    },
+    /// A LED has a non-zero current but an empty lighting window.
+    CurrentWithoutLighting {
+        /// The LED channel.
+        channel: LedChannel,
+    },
+    /// A LED is lit, but never during its sample window.
+    LightingOutsideSample {
+        /// The LED channel.
+        channel: LedChannel,
+    },
+    /// A channel has a non-zero offset current but an empty sample window.
+    OffsetWithoutSample {
+        /// The channel.
+        channel: LedChannel,
+    },
+}
+
+impl<I2C, MODE> AFE4404<I2C, MODE>
+where
+    I2C: I2c<SevenBitAddress>,
+    MODE: LedMode,
+{
+    /// Checks the whole configuration and returns all the problems found.
+    ///
+    /// # Notes
+    ///
+    /// Unlike the other checks, this function does not stop at the first problem.
+    /// The timer engine, the clock division, the photodiode and input short state,
+    /// the sample windows overlaps, the LED currents against the lighting windows and the offset currents are checked.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    #[allow(clippy::similar_names)]
+    pub fn lint(&mut self) -> Result<Vec<AfeWarning>, AfeError<I2C::Error>> {
+        let mut warnings = Vec::new();
+
+        let r1eh_prev = self.registers.r1Eh().read()?;
+        let r39h_prev = self.registers.r39h().read()?;
+        let r3ah_prev = self.registers.r3Ah().read()?;
+
+        if !r1eh_prev.timeren() {
+            warnings.push(AfeWarning::TimerDisabled);
+        }
+
+        let rx_control = self.get_rx_control()?;
+        if rx_control.photodiode == State::Disabled {
+            warnings.push(AfeWarning::PhotodiodeDisconnected);
+        }
+        if rx_control.input_short {
+            warnings.push(AfeWarning::InputShortEnabled);
+        }
+
+        if !matches!(r39h_prev.clkdiv_prf(), 0 | 4 | 5 | 6 | 7) {
+            warnings.push(AfeWarning::InvalidClockDivision);
+            return Ok(warnings);
+        }
+
+        let slots = [
+            (LedChannel::Led1, r3ah_prev.i_offdac_led1()),
+            (LedChannel::Led2, r3ah_prev.i_offdac_led2()),
+            (LedChannel::Led3, r3ah_prev.i_offdac_amb2_or_i_offdac_led3()),
+            (LedChannel::Ambient1, r3ah_prev.i_offdac_amb1()),
+        ];
+
+        let mut samples = Vec::with_capacity(slots.len());
+        for (channel, offset) in slots {
+            let (sample_st, sample_end) = self.get_channel_sample(channel)?;
+            if sample_end <= sample_st {
+                if offset != 0 {
+                    warnings.push(AfeWarning::OffsetWithoutSample { channel });
+                }
+                continue;
+            }
+            samples.push((channel, sample_st, sample_end));
+        }
+
+        for (i, &(first, first_st, first_end)) in samples.iter().enumerate() {
+            for &(second, second_st, second_end) in &samples[i + 1..] {
+                if first_st < second_end && second_st < first_end {
+                    warnings.push(AfeWarning::SampleWindowsOverlap { first, second });
+                }
+            }
+        }
+
+        for channel in [LedChannel::Led1, LedChannel::Led2, LedChannel::Led3] {
+            let current = self.get_channel_current(channel)?;
+            let (lighting_st, lighting_end) = self.get_channel_lighting(channel)?;
+
+            if current.value > 0.0 {
+                if lighting_end <= lighting_st {
+                    warnings.push(AfeWarning::CurrentWithoutLighting { channel });
+                } else if self.sample_lighting_overlap(channel)?.value <= 0.0 {
+                    warnings.push(AfeWarning::LightingOutsideSample { channel });
+                }
+            }
+        }
+
+        Ok(warnings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AfeWarning;
+    use crate::{
+        channel::LedChannel, measurement_window::MeasurementWindowConfiguration, mock::MockAfe,
+    };
+
+    #[test]
+    fn messy_configuration_reports_every_warning() {
+        let mock = MockAfe::new();
+        // Timer disabled, photodiode disconnected and input short enabled.
+        mock.set_register(0x1E, 0);
+        mock.set_register(0x31, 1 << 10 | 1 << 5);
+        // LED1 sample overlaps the Ambient1 sample, LED2 has a sample but no lighting.
+        mock.set_register(0x07, 900);
+        mock.set_register(0x08, 1199);
+        mock.set_register(0x0B, 1000);
+        mock.set_register(0x0C, 1300);
+        mock.set_register(0x01, 2000);
+        mock.set_register(0x02, 2299);
+        mock.set_register(0x03, 800);
+        mock.set_register(0x04, 1199);
+        // LED3 is lit without a sample window and has an offset current.
+        mock.set_register(0x36, 3000);
+        mock.set_register(0x37, 3100);
+        mock.set_register(0x3A, 3);
+        // All the LEDs have a current.
+        mock.set_register(0x22, 0x0A | 0x0A << 6 | 0x0A << 12);
+        let mut afe = mock.three_leds();
+
+        let warnings = afe.lint().unwrap();
+
+        assert_eq!(
+            warnings,
+            [
+                AfeWarning::TimerDisabled,
+                AfeWarning::PhotodiodeDisconnected,
+                AfeWarning::InputShortEnabled,
+                AfeWarning::OffsetWithoutSample {
+                    channel: LedChannel::Led3
+                },
+                AfeWarning::SampleWindowsOverlap {
+                    first: LedChannel::Led1,
+                    second: LedChannel::Ambient1
+                },
+                AfeWarning::CurrentWithoutLighting {
+                    channel: LedChannel::Led2
+                },
+                AfeWarning::LightingOutsideSample {
+                    channel: LedChannel::Led3
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_clock_division_skips_timing_checks() {
+        let mock = MockAfe::new();
+        mock.set_register(0x1E, 1 << 8);
+        mock.set_register(0x39, 1);
+        mock.set_register(0x22, 10);
+        let mut afe = mock.three_leds();
+
+        let warnings = afe.lint().unwrap();
+
+        assert_eq!(warnings, [AfeWarning::InvalidClockDivision]);
+    }
+
+    #[test]
+    fn clean_configuration_reports_nothing() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+        afe.set_measurement_window(&MeasurementWindowConfiguration::default())
+            .unwrap();
+
+        assert_eq!(afe.lint().unwrap(), []);
+    }
+}
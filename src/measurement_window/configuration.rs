@@ -1,15 +1,46 @@
+use embedded_hal::i2c::Error;
 use uom::si::{f32::Time, time::microsecond};
 
-use crate::modes::{LedMode, ThreeLedsMode, TwoLedsMode};
+use alloc::{format, string::String, vec::Vec};
+
+use crate::{
+    channel::LedChannel,
+    errors::AfeError,
+    modes::{DeviceMode, LedMode, OneLedMode, ThreeLedsMode, TwoLedsMode},
+};
 
 /// Represents a period of the measurement window.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct MeasurementWindowConfiguration<MODE: LedMode> {
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     period: Time,
     active_timing_configuration: ActiveTiming<MODE>,
     inactive_timing_configuration: PowerDownTiming,
 }
 
+/// Uses the datasheet reference configuration, a 100 Hz measurement window with a 4 MHz clock.
+///
+/// # Notes
+///
+/// The dynamic blocks are powered down from the end of the last conversion to 200 µs before the end of the window.
+impl<MODE> Default for MeasurementWindowConfiguration<MODE>
+where
+    MODE: LedMode,
+    ActiveTiming<MODE>: Default,
+{
+    fn default() -> Self {
+        MeasurementWindowConfiguration::new(
+            Time::new::<microsecond>(10_000.0),
+            ActiveTiming::default(),
+            PowerDownTiming::new(
+                Time::new::<microsecond>(1918.75),
+                Time::new::<microsecond>(9799.75),
+            ),
+        )
+    }
+}
+
 impl<MODE> MeasurementWindowConfiguration<MODE>
 where
     MODE: LedMode,
@@ -56,10 +87,335 @@ where
     pub fn inactive_timing_configuration_mut(&mut self) -> &mut PowerDownTiming {
         &mut self.inactive_timing_configuration
     }
+
+    /// Creates a copy of the measurement window with a different period, scaling every phase proportionally.
+    ///
+    /// # Notes
+    ///
+    /// The phases keep their relative position and duty cycle, so a valid window remains valid when the period grows.
+    /// Shrinking the period also shrinks the reset and conversion phases, which may become too short.
+    #[must_use]
+    pub fn rescaled(&self, period: Time) -> Self {
+        let factor = (period / self.period).value;
+        let active = &self.active_timing_configuration;
+
+        MeasurementWindowConfiguration {
+            period,
+            active_timing_configuration: ActiveTiming {
+                led1: active.led1.scaled(factor),
+                led2: active.led2.scaled(factor),
+                led3: active.led3.scaled(factor),
+                ambient1: active.ambient1.scaled(factor),
+                ambient2: active.ambient2.scaled(factor),
+                mode: core::marker::PhantomData,
+            },
+            inactive_timing_configuration: PowerDownTiming::new(
+                self.inactive_timing_configuration.power_down_st * factor,
+                self.inactive_timing_configuration.power_down_end * factor,
+            ),
+        }
+    }
+
+    /// Gets all the timings of the measurement window, excluding the period.
+    pub(crate) fn timings(&self) -> Vec<Time> {
+        let active = &self.active_timing_configuration;
+        let inactive = &self.inactive_timing_configuration;
+
+        [active.led1, active.led2, active.led3]
+            .iter()
+            .flat_map(|led| {
+                [
+                    led.lighting_st,
+                    led.lighting_end,
+                    led.sample_st,
+                    led.sample_end,
+                    led.reset_st,
+                    led.reset_end,
+                    led.conv_st,
+                    led.conv_end,
+                ]
+            })
+            .chain(
+                [active.ambient1, active.ambient2]
+                    .iter()
+                    .flat_map(|ambient| {
+                        [
+                            ambient.sample_st,
+                            ambient.sample_end,
+                            ambient.reset_st,
+                            ambient.reset_end,
+                            ambient.conv_st,
+                            ambient.conv_end,
+                        ]
+                    }),
+            )
+            .chain([inactive.power_down_st, inactive.power_down_end])
+            .collect()
+    }
+
+    /// Gets the name, start and end of every phase of the measurement window.
+    pub(crate) fn phases(&self) -> Vec<(String, Time, Time)> {
+        let active = &self.active_timing_configuration;
+        let inactive = &self.inactive_timing_configuration;
+
+        let leds: &[(&str, &LedTiming)] = match MODE::MODE {
+            DeviceMode::Two => &[("LED1", &active.led1), ("LED2", &active.led2)],
+            DeviceMode::One => &[("LED1", &active.led1)],
+            DeviceMode::Three => &[
+                ("LED1", &active.led1),
+                ("LED2", &active.led2),
+                ("LED3", &active.led3),
+            ],
+        };
+        let ambients: &[(&str, &AmbientTiming)] = if MODE::MODE == DeviceMode::Two {
+            &[
+                ("Ambient1", &active.ambient1),
+                ("Ambient2", &active.ambient2),
+            ]
+        } else {
+            &[("Ambient", &active.ambient1)]
+        };
+
+        let mut phases = Vec::new();
+        for (name, led) in leds {
+            phases.push((
+                format!("{name} lighting"),
+                led.lighting_st,
+                led.lighting_end,
+            ));
+            phases.push((format!("{name} sample"), led.sample_st, led.sample_end));
+            phases.push((format!("{name} reset"), led.reset_st, led.reset_end));
+            phases.push((format!("{name} conversion"), led.conv_st, led.conv_end));
+        }
+        for (name, ambient) in ambients {
+            phases.push((
+                format!("{name} sample"),
+                ambient.sample_st,
+                ambient.sample_end,
+            ));
+            phases.push((format!("{name} reset"), ambient.reset_st, ambient.reset_end));
+            phases.push((
+                format!("{name} conversion"),
+                ambient.conv_st,
+                ambient.conv_end,
+            ));
+        }
+        phases.push((
+            String::from("Power-down"),
+            inactive.power_down_st,
+            inactive.power_down_end,
+        ));
+
+        phases
+    }
+
+    /// Gets the non-empty phases of every channel, in channel order.
+    pub(crate) fn channel_phases(&self) -> Vec<ChannelPhase> {
+        let mut phases = self.all_channel_phases();
+        phases.retain(|phase| phase.end > phase.start);
+
+        phases
+    }
+
+    /// Creates a measurement window with every phase empty.
+    pub(crate) fn empty(period: Time) -> Self {
+        MeasurementWindowConfiguration {
+            period,
+            active_timing_configuration: ActiveTiming {
+                led1: LedTiming::default(),
+                led2: LedTiming::default(),
+                led3: LedTiming::default(),
+                ambient1: AmbientTiming::default(),
+                ambient2: AmbientTiming::default(),
+                mode: core::marker::PhantomData,
+            },
+            inactive_timing_configuration: PowerDownTiming::new(Time::default(), Time::default()),
+        }
+    }
+
+    /// Sets the start and end of a phase of a channel, the lighting phase of an ambient channel is ignored.
+    pub(crate) fn set_channel_phase(&mut self, phase: &ChannelPhase) {
+        let active = &mut self.active_timing_configuration;
+
+        let led = match phase.channel {
+            LedChannel::Led1 => Some(&mut active.led1),
+            LedChannel::Led2 => Some(&mut active.led2),
+            LedChannel::Led3 => Some(&mut active.led3),
+            LedChannel::Ambient1 | LedChannel::Ambient2 => None,
+        };
+        let (start, end) = if let Some(led) = led {
+            match phase.kind {
+                PhaseKind::Lighting => (&mut led.lighting_st, &mut led.lighting_end),
+                PhaseKind::Sample => (&mut led.sample_st, &mut led.sample_end),
+                PhaseKind::Reset => (&mut led.reset_st, &mut led.reset_end),
+                PhaseKind::Conversion => (&mut led.conv_st, &mut led.conv_end),
+            }
+        } else {
+            let ambient = if phase.channel == LedChannel::Ambient2 {
+                &mut active.ambient2
+            } else {
+                &mut active.ambient1
+            };
+            match phase.kind {
+                PhaseKind::Lighting => return,
+                PhaseKind::Sample => (&mut ambient.sample_st, &mut ambient.sample_end),
+                PhaseKind::Reset => (&mut ambient.reset_st, &mut ambient.reset_end),
+                PhaseKind::Conversion => (&mut ambient.conv_st, &mut ambient.conv_end),
+            }
+        };
+
+        *start = phase.start;
+        *end = phase.end;
+    }
+
+    /// Gets all the phases of every channel, including the empty ones, in channel order.
+    pub(crate) fn all_channel_phases(&self) -> Vec<ChannelPhase> {
+        let active = &self.active_timing_configuration;
+
+        let leds: &[(LedChannel, &LedTiming)] = match MODE::MODE {
+            DeviceMode::Two => &[
+                (LedChannel::Led1, &active.led1),
+                (LedChannel::Led2, &active.led2),
+            ],
+            DeviceMode::One => &[(LedChannel::Led1, &active.led1)],
+            DeviceMode::Three => &[
+                (LedChannel::Led1, &active.led1),
+                (LedChannel::Led2, &active.led2),
+                (LedChannel::Led3, &active.led3),
+            ],
+        };
+        let ambients: &[(LedChannel, &AmbientTiming)] = if MODE::MODE == DeviceMode::Two {
+            &[
+                (LedChannel::Ambient1, &active.ambient1),
+                (LedChannel::Ambient2, &active.ambient2),
+            ]
+        } else {
+            &[(LedChannel::Ambient1, &active.ambient1)]
+        };
+
+        // The sample, reset and conversion phases of a LED are the same of an ambient.
+        let adc_phases = |channel: LedChannel, timing: &AmbientTiming| {
+            [
+                (PhaseKind::Sample, timing.sample_st, timing.sample_end),
+                (PhaseKind::Reset, timing.reset_st, timing.reset_end),
+                (PhaseKind::Conversion, timing.conv_st, timing.conv_end),
+            ]
+            .map(|(kind, start, end)| ChannelPhase {
+                channel,
+                kind,
+                start,
+                end,
+            })
+        };
+
+        let mut phases = Vec::new();
+        for &(channel, led) in leds {
+            phases.push(ChannelPhase {
+                channel,
+                kind: PhaseKind::Lighting,
+                start: led.lighting_st,
+                end: led.lighting_end,
+            });
+            phases.extend(adc_phases(
+                channel,
+                &AmbientTiming::mirroring(led, Time::default()),
+            ));
+        }
+        for &(channel, ambient) in ambients {
+            phases.extend(adc_phases(channel, ambient));
+        }
+
+        phases
+    }
+
+    /// Checks the timing constraints of the measurement window, returning an error for the first phase violating one.
+    ///
+    /// # Notes
+    ///
+    /// The empty phases are not used, so they are not checked.
+    pub(crate) fn validate<E: Error>(&self) -> Result<(), AfeError<E>> {
+        self.find_violation().map_or(Ok(()), |(phase, violation)| {
+            Err(AfeError::InvalidTiming {
+                channel: phase.channel,
+                kind: phase.kind,
+                violation,
+            })
+        })
+    }
+
+    /// Finds the first phase violating a timing constraint.
+    fn find_violation(&self) -> Option<(ChannelPhase, TimingViolation)> {
+        let power_down = &self.inactive_timing_configuration;
+        let all_phases = self.all_channel_phases();
+
+        if let Some(phase) = all_phases.iter().find(|phase| phase.end < phase.start) {
+            return Some((*phase, TimingViolation::EndBeforeStart));
+        }
+
+        let phases: Vec<&ChannelPhase> = all_phases
+            .iter()
+            .filter(|phase| phase.end > phase.start)
+            .collect();
+
+        for &phase in &phases {
+            if phase.end > self.period {
+                return Some((*phase, TimingViolation::OutsidePeriod));
+            }
+
+            match phase.kind {
+                PhaseKind::Reset if phase.end - phase.start < min_reset_width() => {
+                    return Some((*phase, TimingViolation::ResetTooShort));
+                }
+                PhaseKind::Sample => {
+                    let lighting = phases.iter().find(|lighting| {
+                        lighting.kind == PhaseKind::Lighting && lighting.channel == phase.channel
+                    });
+                    if let Some(lighting) = lighting {
+                        if phase.start < lighting.start || phase.end > lighting.end {
+                            return Some((*phase, TimingViolation::SampleOutsideLighting));
+                        }
+                    }
+                }
+                PhaseKind::Conversion => {
+                    let other = phases.iter().find(|other| {
+                        other.kind == PhaseKind::Conversion
+                            && other.channel != phase.channel
+                            && other.start < phase.end
+                            && phase.start < other.end
+                    });
+                    if let Some(other) = other {
+                        return Some((
+                            *phase,
+                            TimingViolation::ConversionOverlap {
+                                other: other.channel,
+                            },
+                        ));
+                    }
+                    if power_down.power_down_st < phase.end
+                        && phase.start < power_down.power_down_end
+                    {
+                        return Some((*phase, TimingViolation::ClippedByPowerDown));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        None
+    }
+}
+
+/// Gets the minimum width of the ADC reset phases, two cycles of the 4 MHz clock.
+fn min_reset_width() -> Time {
+    Time::new::<microsecond>(0.5)
 }
 
+/// Represents a phase of the measurement window as its name, start and end in microseconds.
+pub type SchedulePhase = (String, f32, f32);
+
 /// Represents the active phase of the measurement window.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ActiveTiming<MODE: LedMode> {
     led1: LedTiming,
     led2: LedTiming,
@@ -78,20 +434,10 @@ where
         &self.led1
     }
 
-    /// Gets an immutable reference of the LED2 timings.
-    pub fn led2(&self) -> &LedTiming {
-        &self.led2
-    }
-
     /// Gets a mutable reference of the LED1 timings.
     pub fn led1_mut(&mut self) -> &mut LedTiming {
         &mut self.led1
     }
-
-    /// Gets a mutable reference of the LED2 timings.
-    pub fn led2_mut(&mut self) -> &mut LedTiming {
-        &mut self.led2
-    }
 }
 
 impl ActiveTiming<ThreeLedsMode> {
@@ -107,6 +453,16 @@ impl ActiveTiming<ThreeLedsMode> {
         }
     }
 
+    /// Gets an immutable reference of the LED2 timings.
+    pub fn led2(&self) -> &LedTiming {
+        &self.led2
+    }
+
+    /// Gets a mutable reference of the LED2 timings.
+    pub fn led2_mut(&mut self) -> &mut LedTiming {
+        &mut self.led2
+    }
+
     /// Gets an immutable reference of the LED3 timings.
     pub fn led3(&self) -> &LedTiming {
         &self.led3
@@ -146,6 +502,16 @@ impl ActiveTiming<TwoLedsMode> {
         }
     }
 
+    /// Gets an immutable reference of the LED2 timings.
+    pub fn led2(&self) -> &LedTiming {
+        &self.led2
+    }
+
+    /// Gets a mutable reference of the LED2 timings.
+    pub fn led2_mut(&mut self) -> &mut LedTiming {
+        &mut self.led2
+    }
+
     /// Gets an immutable reference of the ambient1 timings.
     pub fn ambient1(&self) -> &AmbientTiming {
         &self.ambient1
@@ -167,44 +533,189 @@ impl ActiveTiming<TwoLedsMode> {
     }
 }
 
+impl ActiveTiming<OneLedMode> {
+    /// Creates a new active timing configuration.
+    pub fn new(led1: LedTiming, ambient: AmbientTiming) -> Self {
+        ActiveTiming {
+            led1,
+            led2: LedTiming::default(),
+            led3: LedTiming::default(),
+            ambient1: ambient,
+            ambient2: AmbientTiming::default(),
+            mode: core::marker::PhantomData,
+        }
+    }
+
+    /// Gets an immutable reference of the ambient timings.
+    pub fn ambient(&self) -> &AmbientTiming {
+        &self.ambient1
+    }
+
+    /// Gets a mutable reference of the ambient timings.
+    pub fn ambient_mut(&mut self) -> &mut AmbientTiming {
+        &mut self.ambient1
+    }
+}
+
+/// Creates a LED timing from the register counts of the datasheet reference configuration, at 4 MHz.
+fn reference_led(
+    lighting: (u16, u16),
+    sample: (u16, u16),
+    reset: (u16, u16),
+    conv: (u16, u16),
+) -> LedTiming {
+    let count = |value: u16| Time::new::<microsecond>(f32::from(value) / 4.0);
+
+    LedTiming {
+        lighting_st: count(lighting.0),
+        lighting_end: count(lighting.1),
+        sample_st: count(sample.0),
+        sample_end: count(sample.1),
+        reset_st: count(reset.0),
+        reset_end: count(reset.1),
+        conv_st: count(conv.0),
+        conv_end: count(conv.1),
+    }
+}
+
+/// Creates an ambient timing from the register counts of the datasheet reference configuration, at 4 MHz.
+fn reference_ambient(sample: (u16, u16), reset: (u16, u16), conv: (u16, u16)) -> AmbientTiming {
+    AmbientTiming::mirroring(
+        &reference_led((0, 0), sample, reset, conv),
+        Time::new::<microsecond>(0.0),
+    )
+}
+
+/// Uses the timings of the datasheet reference configuration, a 100 Hz measurement window with a 4 MHz clock.
+impl Default for ActiveTiming<ThreeLedsMode> {
+    fn default() -> Self {
+        ActiveTiming::<ThreeLedsMode>::new(
+            reference_led((800, 1199), (900, 1199), (6538, 6544), (6546, 7006)),
+            reference_led((0, 399), (100, 399), (5600, 5606), (5608, 6067)),
+            reference_led((400, 799), (500, 799), (6069, 6075), (6077, 6536)),
+            reference_ambient((1300, 1599), (7008, 7014), (7016, 7475)),
+        )
+    }
+}
+
+/// Uses the timings of the datasheet reference configuration, a 100 Hz measurement window with a 4 MHz clock.
+impl Default for ActiveTiming<TwoLedsMode> {
+    fn default() -> Self {
+        ActiveTiming::<TwoLedsMode>::new(
+            reference_led((800, 1199), (900, 1199), (6538, 6544), (6546, 7006)),
+            reference_led((0, 399), (100, 399), (5600, 5606), (5608, 6067)),
+            reference_ambient((1300, 1599), (7008, 7014), (7016, 7475)),
+            reference_ambient((500, 799), (6069, 6075), (6077, 6536)),
+        )
+    }
+}
+
+/// Uses the LED1 and Ambient1 timings of the datasheet reference configuration, a 100 Hz measurement window with a 4 MHz clock.
+impl Default for ActiveTiming<OneLedMode> {
+    fn default() -> Self {
+        ActiveTiming::<OneLedMode>::new(
+            reference_led((800, 1199), (900, 1199), (6538, 6544), (6546, 7006)),
+            reference_ambient((1300, 1599), (7008, 7014), (7016, 7475)),
+        )
+    }
+}
+
 /// Represents the timings of a single LED phase.
-#[derive(Copy, Clone, Debug, Default)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct LedTiming {
     /// The time at which the LED is turned on.
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     pub lighting_st: Time,
     /// The time at which the LED is turned off.
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     pub lighting_end: Time,
     /// The time at which the ADC starts sampling.
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     pub sample_st: Time,
     /// The time at which the ADC stops sampling.
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     pub sample_end: Time,
     /// The time at which the ADC starts resetting.
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     pub reset_st: Time,
     /// The time at which the ADC stops resetting.
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     pub reset_end: Time,
     /// The time at which the ADC starts converting.
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     pub conv_st: Time,
     /// The time at which the ADC stops converting.
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     pub conv_end: Time,
 }
 
 /// Represents the timings of the ambient phase.
-#[derive(Copy, Clone, Debug, Default)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct AmbientTiming {
     /// The time at which the ADC starts sampling.
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     pub sample_st: Time,
     /// The time at which the ADC stops sampling.
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     pub sample_end: Time,
     /// The time at which the ADC starts resetting.
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     pub reset_st: Time,
     /// The time at which the ADC stops resetting.
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     pub reset_end: Time,
     /// The time at which the ADC starts converting.
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     pub conv_st: Time,
     /// The time at which the ADC stops converting.
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     pub conv_end: Time,
 }
 
+impl AmbientTiming {
+    /// Creates an ambient timing with the same sample, reset and conversion phases of a LED, shifted by `offset`.
+    pub fn mirroring(led: &LedTiming, offset: Time) -> Self {
+        Self {
+            sample_st: led.sample_st + offset,
+            sample_end: led.sample_end + offset,
+            reset_st: led.reset_st + offset,
+            reset_end: led.reset_end + offset,
+            conv_st: led.conv_st + offset,
+            conv_end: led.conv_end + offset,
+        }
+    }
+
+    /// Multiplies every timing by `factor`.
+    fn scaled(&self, factor: f32) -> Self {
+        Self {
+            sample_st: self.sample_st * factor,
+            sample_end: self.sample_end * factor,
+            reset_st: self.reset_st * factor,
+            reset_end: self.reset_end * factor,
+            conv_st: self.conv_st * factor,
+            conv_end: self.conv_end * factor,
+        }
+    }
+}
+
+impl LedTiming {
+    /// Multiplies every timing by `factor`.
+    fn scaled(&self, factor: f32) -> Self {
+        Self {
+            lighting_st: self.lighting_st * factor,
+            lighting_end: self.lighting_end * factor,
+            sample_st: self.sample_st * factor,
+            sample_end: self.sample_end * factor,
+            reset_st: self.reset_st * factor,
+            reset_end: self.reset_end * factor,
+            conv_st: self.conv_st * factor,
+            conv_end: self.conv_end * factor,
+        }
+    }
+}
+
 impl From<AmbientTiming> for LedTiming {
     fn from(other: AmbientTiming) -> Self {
         Self {
@@ -222,11 +733,19 @@ impl From<AmbientTiming> for LedTiming {
 }
 
 /// Represents the inactive phase of the measurement window.
-#[derive(Copy, Clone, Debug)]
+///
+/// # Notes
+///
+/// The power-down window must satisfy `power_down_st < power_down_end <= period`.
+/// It typically spans the idle tail of the measurement window, after the last conversion.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct PowerDownTiming {
     /// The time at which the dynamic blocks are powered down.
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     pub power_down_st: Time,
     /// The time at which the dynamic blocks are powered up.
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     pub power_down_end: Time,
 }
 
@@ -239,3 +758,255 @@ impl PowerDownTiming {
         }
     }
 }
+
+/// Represents a timing constraint violated by a phase of the measurement window.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TimingViolation {
+    /// The phase ends before it starts.
+    EndBeforeStart,
+    /// The phase ends after the end of the measurement window.
+    OutsidePeriod,
+    /// The ADC reset phase is too short to reset the ADC.
+    ResetTooShort,
+    /// The sample phase of a LED is not contained in its lighting phase.
+    SampleOutsideLighting,
+    /// The conversion phase overlaps the conversion phase of another channel.
+    ConversionOverlap {
+        /// The channel of the other conversion phase.
+        other: LedChannel,
+    },
+    /// The conversion phase overlaps the dynamic power-down window.
+    ClippedByPowerDown,
+}
+
+/// Represents the kind of a phase of the measurement window.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PhaseKind {
+    /// The LED is turned on.
+    Lighting,
+    /// The ADC samples the TIA output.
+    Sample,
+    /// The ADC is reset.
+    Reset,
+    /// The ADC converts the sampled value.
+    Conversion,
+}
+
+/// Represents a phase of a channel in the measurement window.
+///
+/// # Notes
+///
+/// The slot shared between LED3 and Ambient2 is reported as `LedChannel::Led3` in three LEDs mode and as `LedChannel::Ambient2` in two LEDs mode.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ChannelPhase {
+    /// The channel.
+    pub channel: LedChannel,
+    /// The kind of the phase.
+    pub kind: PhaseKind,
+    /// The time at which the phase starts.
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
+    pub start: Time,
+    /// The time at which the phase ends.
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
+    pub end: Time,
+}
+
+/// Represents the phases of the measurement window, sorted by start time.
+///
+/// # Notes
+///
+/// Empty phases are omitted.
+/// Phases of the same kind belonging to different channels never overlap.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Schedule<MODE: LedMode> {
+    phases: Vec<ChannelPhase>,
+    mode: core::marker::PhantomData<MODE>,
+}
+
+impl<MODE> Schedule<MODE>
+where
+    MODE: LedMode,
+{
+    /// Sorts the phases by start time, returning the first pair of overlapping phases of the same kind if any.
+    pub(crate) fn new(mut phases: Vec<ChannelPhase>) -> Result<Self, (ChannelPhase, ChannelPhase)> {
+        phases.sort_by(|a, b| a.start.value.total_cmp(&b.start.value));
+
+        for (i, second) in phases.iter().enumerate() {
+            if let Some(first) = phases[..i]
+                .iter()
+                .find(|first| first.kind == second.kind && first.end > second.start)
+            {
+                return Err((*first, *second));
+            }
+        }
+
+        Ok(Self {
+            phases,
+            mode: core::marker::PhantomData,
+        })
+    }
+
+    /// Gets the phases sorted by start time.
+    pub fn phases(&self) -> &[ChannelPhase] {
+        &self.phases
+    }
+
+    /// Returns an iterator over the phases sorted by start time.
+    pub fn iter(&self) -> core::slice::Iter<'_, ChannelPhase> {
+        self.phases.iter()
+    }
+}
+
+/// Iterates over the phases sorted by start time.
+impl<'a, MODE> IntoIterator for &'a Schedule<MODE>
+where
+    MODE: LedMode,
+{
+    type Item = &'a ChannelPhase;
+    type IntoIter = core::slice::Iter<'a, ChannelPhase>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.phases.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference() -> MeasurementWindowConfiguration<ThreeLedsMode> {
+        MeasurementWindowConfiguration::default()
+    }
+
+    fn us(value: f32) -> Time {
+        Time::new::<microsecond>(value)
+    }
+
+    fn violation(
+        configuration: &MeasurementWindowConfiguration<ThreeLedsMode>,
+    ) -> Option<(LedChannel, PhaseKind, TimingViolation)> {
+        match configuration.validate::<embedded_hal::i2c::ErrorKind>() {
+            Err(AfeError::InvalidTiming {
+                channel,
+                kind,
+                violation,
+            }) => Some((channel, kind, violation)),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn reference_configuration_valid() {
+        assert_eq!(violation(&reference()), None);
+    }
+
+    #[test]
+    fn end_before_start_rejected() {
+        let mut configuration = reference();
+        let led1 = configuration.active_timing_configuration_mut().led1_mut();
+        led1.sample_end = led1.sample_st - us(1.0);
+
+        assert_eq!(
+            violation(&configuration),
+            Some((
+                LedChannel::Led1,
+                PhaseKind::Sample,
+                TimingViolation::EndBeforeStart
+            ))
+        );
+    }
+
+    #[test]
+    fn outside_period_rejected() {
+        let mut configuration = reference();
+        let led1 = configuration.active_timing_configuration_mut().led1_mut();
+        led1.lighting_end = us(10_100.0);
+
+        assert_eq!(
+            violation(&configuration),
+            Some((
+                LedChannel::Led1,
+                PhaseKind::Lighting,
+                TimingViolation::OutsidePeriod
+            ))
+        );
+    }
+
+    #[test]
+    fn reset_too_short_rejected() {
+        let mut configuration = reference();
+        let led1 = configuration.active_timing_configuration_mut().led1_mut();
+        led1.reset_end = led1.reset_st + us(0.25);
+
+        assert_eq!(
+            violation(&configuration),
+            Some((
+                LedChannel::Led1,
+                PhaseKind::Reset,
+                TimingViolation::ResetTooShort
+            ))
+        );
+    }
+
+    #[test]
+    fn sample_outside_lighting_rejected() {
+        let mut configuration = reference();
+        let led1 = configuration.active_timing_configuration_mut().led1_mut();
+        led1.sample_end = led1.lighting_end + us(1.0);
+
+        assert_eq!(
+            violation(&configuration),
+            Some((
+                LedChannel::Led1,
+                PhaseKind::Sample,
+                TimingViolation::SampleOutsideLighting
+            ))
+        );
+    }
+
+    #[test]
+    fn conversion_overlap_rejected() {
+        let mut configuration = reference();
+        let led2_conv_end = configuration.active_timing_configuration().led2().conv_end;
+        configuration
+            .active_timing_configuration_mut()
+            .led3_mut()
+            .conv_st = led2_conv_end - us(1.0);
+
+        assert_eq!(
+            violation(&configuration),
+            Some((
+                LedChannel::Led2,
+                PhaseKind::Conversion,
+                TimingViolation::ConversionOverlap {
+                    other: LedChannel::Led3
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn clipped_by_power_down_rejected() {
+        let mut configuration = reference();
+        let ambient_conv_end = configuration
+            .active_timing_configuration()
+            .ambient()
+            .conv_end;
+        configuration
+            .inactive_timing_configuration_mut()
+            .power_down_st = ambient_conv_end - us(1.0);
+
+        assert_eq!(
+            violation(&configuration),
+            Some((
+                LedChannel::Ambient1,
+                PhaseKind::Conversion,
+                TimingViolation::ClippedByPowerDown
+            ))
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use core::ops::{Deref, DerefMut};
+
+use crate::{device::AFE4404, modes::LedMode};
+
+/// Represents a batch of timing edits of the [`AFE4404`].
+///
+/// # Notes
+///
+/// Created by [`AFE4404::begin_timing_edit`], it dereferences to the [`AFE4404`].
+/// The timer quantisation is cached until the editor is dropped.
+pub struct TimingEditor<'a, I2C, MODE: LedMode> {
+    afe: &'a mut AFE4404<I2C, MODE>,
+}
+
+impl<'a, I2C, MODE> TimingEditor<'a, I2C, MODE>
+where
+    MODE: LedMode,
+{
+    pub(crate) fn new(afe: &'a mut AFE4404<I2C, MODE>) -> Self {
+        Self { afe }
+    }
+}
+
+impl<I2C, MODE> Deref for TimingEditor<'_, I2C, MODE>
+where
+    MODE: LedMode,
+{
+    type Target = AFE4404<I2C, MODE>;
+
+    fn deref(&self) -> &Self::Target {
+        self.afe
+    }
+}
+
+impl<I2C, MODE> DerefMut for TimingEditor<'_, I2C, MODE>
+where
+    MODE: LedMode,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.afe
+    }
+}
+
+impl<I2C, MODE> Drop for TimingEditor<'_, I2C, MODE>
+where
+    MODE: LedMode,
+{
+    fn drop(&mut self) {
+        self.afe.timing_quantisation = None;
+    }
+}
@@ -4,13 +4,15 @@ use embedded_hal::i2c::{I2c, SevenBitAddress};
 use uom::si::f32::Time;
 
 use crate::{
+    channel::LedChannel,
     device::AFE4404,
     errors::AfeError,
-    modes::{LedMode, ThreeLedsMode, TwoLedsMode},
+    measurement_window::{PhaseKind, TimingEditor},
+    modes::{LedMode, OneLedMode, ThreeLedsMode, TwoLedsMode},
     register_structs::{
         R01h, R02h, R03h, R04h, R05h, R06h, R07h, R08h, R09h, R0Ah, R0Bh, R0Ch, R0Dh, R0Eh, R0Fh,
-        R10h, R11h, R12h, R13h, R14h, R15h, R16h, R17h, R18h, R19h, R1Ah, R1Bh, R1Ch, R32h, R33h,
-        R36h, R37h,
+        R10h, R11h, R12h, R13h, R14h, R15h, R16h, R17h, R18h, R19h, R1Ah, R1Bh, R1Ch, R1Dh, R32h,
+        R33h, R36h, R37h, R39h,
     },
 };
 
@@ -19,13 +21,17 @@ where
     I2C: I2c<SevenBitAddress>,
     MODE: LedMode,
 {
-    /// Converts a 'Time' into a tuple of `Time` rounded to the closest actual value and register value.
-    #[allow(clippy::wrong_self_convention)]
-    pub(crate) fn from_timing(
-        &mut self,
-        timing: Time,
-    ) -> Result<(Time, u16), AfeError<I2C::Error>> {
-        let r39h_prev = self.registers.r39h.read()?;
+    /// Gets the duration of a single timer count.
+    ///
+    /// # Notes
+    ///
+    /// Inside a [`TimingEditor`] the value is read once and cached, otherwise `R39h` is read on every call.
+    pub(crate) fn timing_quantisation(&mut self) -> Result<Time, AfeError<I2C::Error>> {
+        if let Some(quantisation) = self.timing_quantisation {
+            return Ok(quantisation);
+        }
+
+        let r39h_prev = self.registers.r39h().read()?;
 
         let clk_div: f32 = match r39h_prev.clkdiv_prf() {
             0 => 1.0,
@@ -35,7 +41,17 @@ where
             7 => 16.0,
             _ => return Err(AfeError::InvalidRegisterValue { reg_addr: 0x39 }),
         };
-        let quantisation = clk_div / self.clock;
+
+        Ok(clk_div / self.clock)
+    }
+
+    /// Converts a 'Time' into a tuple of `Time` rounded to the closest actual value and register value.
+    #[allow(clippy::wrong_self_convention)]
+    pub(crate) fn from_timing(
+        &mut self,
+        timing: Time,
+    ) -> Result<(Time, u16), AfeError<I2C::Error>> {
+        let quantisation = self.timing_quantisation()?;
 
         #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
         let value = (timing / quantisation).value.round() as u16;
@@ -46,19 +62,104 @@ where
     /// Converts a register value into a `Time`.
     #[allow(clippy::wrong_self_convention)]
     pub(crate) fn into_timing(&mut self, reg_value: u16) -> Result<Time, AfeError<I2C::Error>> {
-        let r39h_prev = self.registers.r39h.read()?;
+        let quantisation = self.timing_quantisation()?;
 
-        let clk_div: f32 = match r39h_prev.clkdiv_prf() {
-            0 => 1.0,
-            4 => 2.0,
-            5 => 4.0,
-            6 => 8.0,
-            7 => 16.0,
-            _ => return Err(AfeError::InvalidRegisterValue { reg_addr: 0x39 }),
+        Ok(f32::from(reg_value) * quantisation)
+    }
+
+    /// Computes the closest timing the setters would apply, without writing any register.
+    ///
+    /// # Notes
+    ///
+    /// The timer quantisation is read from `R39h`, unless inside a [`TimingEditor`].
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub fn quantise_timing(&mut self, timing: Time) -> Result<Time, AfeError<I2C::Error>> {
+        Ok(self.from_timing(timing)?.0)
+    }
+
+    /// Begins a batch of timing edits, reading the timer quantisation only once.
+    ///
+    /// # Notes
+    ///
+    /// The returned [`TimingEditor`] gives access to all the [`AFE4404`] functions.
+    /// While it is alive the low level timing setters and getters do not read `R39h`.
+    /// Changing the window period or the clock frequency invalidates the cached value.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub fn begin_timing_edit(
+        &mut self,
+    ) -> Result<TimingEditor<'_, I2C, MODE>, AfeError<I2C::Error>> {
+        self.timing_quantisation = None;
+        self.timing_quantisation = Some(self.timing_quantisation()?);
+
+        Ok(TimingEditor::new(self))
+    }
+
+    /// Gets the lighting start and end timings of the given channel.
+    ///
+    /// # Notes
+    ///
+    /// Ambient channels never light a LED, so their lighting window is always empty.
+    pub(crate) fn get_channel_lighting(
+        &mut self,
+        channel: LedChannel,
+    ) -> Result<(Time, Time), AfeError<I2C::Error>> {
+        let (lighting_st, lighting_end) = match channel {
+            LedChannel::Led1 => (
+                self.registers.r03h().read()?.led1ledstc(),
+                self.registers.r04h().read()?.led1ledendc(),
+            ),
+            LedChannel::Led2 => (
+                self.registers.r09h().read()?.led2ledstc(),
+                self.registers.r0Ah().read()?.led2ledendc(),
+            ),
+            LedChannel::Led3 => (
+                self.registers.r36h().read()?.led3ledstc(),
+                self.registers.r37h().read()?.led3ledendc(),
+            ),
+            LedChannel::Ambient1 | LedChannel::Ambient2 => (0, 0),
         };
-        let quantisation = clk_div / self.clock;
 
-        Ok(f32::from(reg_value) * quantisation)
+        Ok((
+            self.into_timing(lighting_st)?,
+            self.into_timing(lighting_end)?,
+        ))
+    }
+
+    /// Gets the sample start and end timings of the given channel.
+    ///
+    /// # Notes
+    ///
+    /// LED3 and Ambient2 share the same registers.
+    pub(crate) fn get_channel_sample(
+        &mut self,
+        channel: LedChannel,
+    ) -> Result<(Time, Time), AfeError<I2C::Error>> {
+        let (sample_st, sample_end) = match channel {
+            LedChannel::Led1 => (
+                self.registers.r07h().read()?.led1stc(),
+                self.registers.r08h().read()?.led1endc(),
+            ),
+            LedChannel::Led2 => (
+                self.registers.r01h().read()?.led2stc(),
+                self.registers.r02h().read()?.led2endc(),
+            ),
+            LedChannel::Led3 | LedChannel::Ambient2 => (
+                self.registers.r05h().read()?.aled2stc_or_led3stc(),
+                self.registers.r06h().read()?.aled2endc_or_led3endc(),
+            ),
+            LedChannel::Ambient1 => (
+                self.registers.r0Bh().read()?.aled1stc(),
+                self.registers.r0Ch().read()?.aled1endc(),
+            ),
+        };
+
+        Ok((self.into_timing(sample_st)?, self.into_timing(sample_end)?))
     }
 
     /// Sets the LED1 lighting start timing.
@@ -74,7 +175,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r03h
+            .r03h()
             .write(R03h::new().with_led1ledstc(value.1))?;
 
         Ok(value.0)
@@ -93,7 +194,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r04h
+            .r04h()
             .write(R04h::new().with_led1ledendc(value.1))?;
 
         Ok(value.0)
@@ -112,7 +213,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r07h
+            .r07h()
             .write(R07h::new().with_led1stc(value.1))?;
 
         Ok(value.0)
@@ -131,7 +232,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r08h
+            .r08h()
             .write(R08h::new().with_led1endc(value.1))?;
 
         Ok(value.0)
@@ -150,7 +251,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r19h
+            .r19h()
             .write(R19h::new().with_adcrststct2(value.1))?;
 
         Ok(value.0)
@@ -169,7 +270,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r1Ah
+            .r1Ah()
             .write(R1Ah::new().with_adcrstendct2(value.1))?;
 
         Ok(value.0)
@@ -188,7 +289,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r11h
+            .r11h()
             .write(R11h::new().with_led1convst(value.1))?;
 
         Ok(value.0)
@@ -207,7 +308,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r12h
+            .r12h()
             .write(R12h::new().with_led1convend(value.1))?;
 
         Ok(value.0)
@@ -226,7 +327,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r09h
+            .r09h()
             .write(R09h::new().with_led2ledstc(value.1))?;
 
         Ok(value.0)
@@ -245,7 +346,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r0Ah
+            .r0Ah()
             .write(R0Ah::new().with_led2ledendc(value.1))?;
 
         Ok(value.0)
@@ -264,7 +365,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r01h
+            .r01h()
             .write(R01h::new().with_led2stc(value.1))?;
 
         Ok(value.0)
@@ -283,7 +384,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r02h
+            .r02h()
             .write(R02h::new().with_led2endc(value.1))?;
 
         Ok(value.0)
@@ -302,7 +403,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r15h
+            .r15h()
             .write(R15h::new().with_adcrststct0(value.1))?;
 
         Ok(value.0)
@@ -321,7 +422,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r16h
+            .r16h()
             .write(R16h::new().with_adcrstendct0(value.1))?;
 
         Ok(value.0)
@@ -340,7 +441,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r0Dh
+            .r0Dh()
             .write(R0Dh::new().with_led2convst(value.1))?;
 
         Ok(value.0)
@@ -359,7 +460,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r0Eh
+            .r0Eh()
             .write(R0Eh::new().with_led2convend(value.1))?;
 
         Ok(value.0)
@@ -381,7 +482,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r32h
+            .r32h()
             .write(R32h::new().with_pdncyclestc(value.1))?;
 
         Ok(value.0)
@@ -403,7 +504,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r33h
+            .r33h()
             .write(R33h::new().with_pdncycleendc(value.1))?;
 
         Ok(value.0)
@@ -415,20 +516,115 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_window_period(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r1dh_prev = self.registers.r1Dh.read()?;
+        let r1dh_prev = self.registers.r1Dh().read()?;
 
         let value = self.into_timing(r1dh_prev.prpct() + 1)?;
 
         Ok(value)
     }
 
+    /// Gets the window period counter, that is the value of `PRPCT` written by the period setters.
+    ///
+    /// # Notes
+    ///
+    /// The window period lasts `counter + 1` timer counts.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub fn get_window_counter(&mut self) -> Result<u16, AfeError<I2C::Error>> {
+        Ok(self.registers.r1Dh().read()?.prpct())
+    }
+
+    /// Sets the window period counter, writing `PRPCT` directly.
+    ///
+    /// # Notes
+    ///
+    /// The window period lasts `counter + 1` timer counts, see `get_timing_quantisation()`.
+    /// The phases are not moved, so they should be checked to fit inside the new period.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub fn set_window_counter(&mut self, counter: u16) -> Result<u16, AfeError<I2C::Error>> {
+        self.registers
+            .r1Dh()
+            .write(R1Dh::new().with_prpct(counter))?;
+
+        Ok(counter)
+    }
+
+    /// Sets the division ratio of the clock driving the timer engine, writing `CLKDIV_PRF` directly.
+    ///
+    /// # Notes
+    ///
+    /// The allowed ratios are 1, 2, 4, 8 and 16.
+    /// The timer counts are not changed, so the period and every phase are scaled by the ratio change.
+    /// The measurement window functions keep this ratio while the period is unchanged,
+    /// but `set_measurement_window()` and any period change select the minimal ratio for the period again.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a division ratio different from the allowed ones will result in an error.
+    pub fn set_window_clock_division(&mut self, ratio: u8) -> Result<u8, AfeError<I2C::Error>> {
+        let reg_value = match ratio {
+            1 => 0,
+            2 => 4,
+            4 => 5,
+            8 => 6,
+            16 => 7,
+            _ => return Err(AfeError::ClockDivisionRatioOutsideAllowedRange),
+        };
+
+        self.registers
+            .r39h()
+            .write(R39h::new().with_clkdiv_prf(reg_value))?;
+        if let Some(quantisation) = self.timing_quantisation.as_mut() {
+            *quantisation = f32::from(ratio) / self.clock;
+        }
+
+        Ok(ratio)
+    }
+
+    /// Gets the division ratio of the clock driving the timer engine.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub fn get_window_clock_division(&mut self) -> Result<u8, AfeError<I2C::Error>> {
+        let r39h_prev = self.registers.r39h().read()?;
+
+        match r39h_prev.clkdiv_prf() {
+            0 => Ok(1),
+            4 => Ok(2),
+            5 => Ok(4),
+            6 => Ok(8),
+            7 => Ok(16),
+            _ => Err(AfeError::InvalidRegisterValue { reg_addr: 0x39 }),
+        }
+    }
+
+    /// Gets the duration of a single timer count, the step of every timing.
+    ///
+    /// # Notes
+    ///
+    /// The timer quantisation is read from `R39h`, unless inside a [`TimingEditor`].
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub fn get_timing_quantisation(&mut self) -> Result<Time, AfeError<I2C::Error>> {
+        self.timing_quantisation()
+    }
+
     /// Gets the LED1 lighting start timing.
     ///
     /// # Errors
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_led1_lighting_st(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r03h_prev = self.registers.r03h.read()?;
+        let r03h_prev = self.registers.r03h().read()?;
 
         let value = self.into_timing(r03h_prev.led1ledstc())?;
 
@@ -441,7 +637,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_led1_lighting_end(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r04h_prev = self.registers.r04h.read()?;
+        let r04h_prev = self.registers.r04h().read()?;
 
         let value = self.into_timing(r04h_prev.led1ledendc())?;
 
@@ -454,7 +650,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_led1_sample_st(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r07h_prev = self.registers.r07h.read()?;
+        let r07h_prev = self.registers.r07h().read()?;
 
         let value = self.into_timing(r07h_prev.led1stc())?;
 
@@ -467,7 +663,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_led1_sample_end(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r08h_prev = self.registers.r08h.read()?;
+        let r08h_prev = self.registers.r08h().read()?;
 
         let value = self.into_timing(r08h_prev.led1endc())?;
 
@@ -480,7 +676,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_led1_reset_st(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r19h_prev = self.registers.r19h.read()?;
+        let r19h_prev = self.registers.r19h().read()?;
 
         let value = self.into_timing(r19h_prev.adcrststct2())?;
 
@@ -493,7 +689,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_led1_reset_end(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r1ah_prev = self.registers.r1Ah.read()?;
+        let r1ah_prev = self.registers.r1Ah().read()?;
 
         let value = self.into_timing(r1ah_prev.adcrstendct2())?;
 
@@ -506,7 +702,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_led1_conv_st(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r11h_prev = self.registers.r11h.read()?;
+        let r11h_prev = self.registers.r11h().read()?;
 
         let value = self.into_timing(r11h_prev.led1convst())?;
 
@@ -519,7 +715,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_led1_conv_end(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r12h_prev = self.registers.r12h.read()?;
+        let r12h_prev = self.registers.r12h().read()?;
 
         let value = self.into_timing(r12h_prev.led1convend())?;
 
@@ -532,7 +728,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_led2_lighting_st(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r09h_prev = self.registers.r09h.read()?;
+        let r09h_prev = self.registers.r09h().read()?;
 
         let value = self.into_timing(r09h_prev.led2ledstc())?;
 
@@ -545,7 +741,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_led2_lighting_end(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r0ah_prev = self.registers.r0Ah.read()?;
+        let r0ah_prev = self.registers.r0Ah().read()?;
 
         let value = self.into_timing(r0ah_prev.led2ledendc())?;
 
@@ -558,7 +754,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_led2_sample_st(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r01h_prev = self.registers.r01h.read()?;
+        let r01h_prev = self.registers.r01h().read()?;
 
         let value = self.into_timing(r01h_prev.led2stc())?;
 
@@ -571,7 +767,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_led2_sample_end(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r02h_prev = self.registers.r02h.read()?;
+        let r02h_prev = self.registers.r02h().read()?;
 
         let value = self.into_timing(r02h_prev.led2endc())?;
 
@@ -584,7 +780,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_led2_reset_st(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r15h_prev = self.registers.r15h.read()?;
+        let r15h_prev = self.registers.r15h().read()?;
 
         let value = self.into_timing(r15h_prev.adcrststct0())?;
 
@@ -597,7 +793,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_led2_reset_end(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r16h_prev = self.registers.r16h.read()?;
+        let r16h_prev = self.registers.r16h().read()?;
 
         let value = self.into_timing(r16h_prev.adcrstendct0())?;
 
@@ -610,7 +806,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_led2_conv_st(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r0dh_prev = self.registers.r0Dh.read()?;
+        let r0dh_prev = self.registers.r0Dh().read()?;
 
         let value = self.into_timing(r0dh_prev.led2convst())?;
 
@@ -623,7 +819,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_led2_conv_end(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r0eh_prev = self.registers.r0Eh.read()?;
+        let r0eh_prev = self.registers.r0Eh().read()?;
 
         let value = self.into_timing(r0eh_prev.led2convend())?;
 
@@ -636,7 +832,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_dynamic_power_down_st(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r32h_prev = self.registers.r32h.read()?;
+        let r32h_prev = self.registers.r32h().read()?;
 
         let value = self.into_timing(r32h_prev.pdncyclestc())?;
 
@@ -649,7 +845,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_dynamic_power_down_end(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r33h_prev = self.registers.r33h.read()?;
+        let r33h_prev = self.registers.r33h().read()?;
 
         let value = self.into_timing(r33h_prev.pdncycleendc())?;
 
@@ -676,6 +872,26 @@ where
         Ok(*configuration.period())
     }
 
+    /// Sets the window period, scaling every phase of the measurement window proportionally.
+    ///
+    /// # Notes
+    ///
+    /// Unlike `set_window_period()`, the phases keep their position relative to the period, so they stay inside the window.
+    /// See [`MeasurementWindowConfiguration::rescaled`](crate::measurement_window::MeasurementWindowConfiguration::rescaled).
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// Setting a window period too long for the current clock frequency or equal to zero will result in an error.
+    /// Setting a window period so short that a phase violates a timing constraint will result in an error.
+    pub fn rescale_window_period(&mut self, period: Time) -> Result<Time, AfeError<I2C::Error>> {
+        let configuration_prev = self.get_measurement_window()?;
+
+        let configuration = self.set_measurement_window(&configuration_prev.rescaled(period))?;
+
+        Ok(*configuration.period())
+    }
+
     /// Sets the LED3 lighting start timing.
     ///
     /// # Notes
@@ -689,7 +905,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r36h
+            .r36h()
             .write(R36h::new().with_led3ledstc(value.1))?;
 
         Ok(value.0)
@@ -708,7 +924,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r37h
+            .r37h()
             .write(R37h::new().with_led3ledendc(value.1))?;
 
         Ok(value.0)
@@ -727,7 +943,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r05h
+            .r05h()
             .write(R05h::new().with_aled2stc_or_led3stc(value.1))?;
 
         Ok(value.0)
@@ -746,7 +962,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r06h
+            .r06h()
             .write(R06h::new().with_aled2endc_or_led3endc(value.1))?;
 
         Ok(value.0)
@@ -765,7 +981,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r17h
+            .r17h()
             .write(R17h::new().with_adcrststct1(value.1))?;
 
         Ok(value.0)
@@ -784,7 +1000,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r18h
+            .r18h()
             .write(R18h::new().with_adcrstendct1(value.1))?;
 
         Ok(value.0)
@@ -803,7 +1019,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r0Fh
+            .r0Fh()
             .write(R0Fh::new().with_aled2convst_or_led3convst(value.1))?;
 
         Ok(value.0)
@@ -822,7 +1038,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r10h
+            .r10h()
             .write(R10h::new().with_aled2convend_or_led3convend(value.1))?;
 
         Ok(value.0)
@@ -841,7 +1057,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r0Bh
+            .r0Bh()
             .write(R0Bh::new().with_aled1stc(value.1))?;
 
         Ok(value.0)
@@ -860,7 +1076,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r0Ch
+            .r0Ch()
             .write(R0Ch::new().with_aled1endc(value.1))?;
 
         Ok(value.0)
@@ -879,7 +1095,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r1Bh
+            .r1Bh()
             .write(R1Bh::new().with_adcrststct3(value.1))?;
 
         Ok(value.0)
@@ -898,7 +1114,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r1Ch
+            .r1Ch()
             .write(R1Ch::new().with_adcrstendct3(value.1))?;
 
         Ok(value.0)
@@ -917,7 +1133,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r13h
+            .r13h()
             .write(R13h::new().with_aled1convst(value.1))?;
 
         Ok(value.0)
@@ -936,7 +1152,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r14h
+            .r14h()
             .write(R14h::new().with_aled1convend(value.1))?;
 
         Ok(value.0)
@@ -948,7 +1164,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_led3_lighting_st(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r36h_prev = self.registers.r36h.read()?;
+        let r36h_prev = self.registers.r36h().read()?;
 
         let value = self.into_timing(r36h_prev.led3ledstc())?;
 
@@ -961,7 +1177,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_led3_lighting_end(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r37h_prev = self.registers.r37h.read()?;
+        let r37h_prev = self.registers.r37h().read()?;
 
         let value = self.into_timing(r37h_prev.led3ledendc())?;
 
@@ -974,7 +1190,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_led3_sample_st(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r05h_prev = self.registers.r05h.read()?;
+        let r05h_prev = self.registers.r05h().read()?;
 
         let value = self.into_timing(r05h_prev.aled2stc_or_led3stc())?;
 
@@ -987,7 +1203,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_led3_sample_end(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r06h_prev = self.registers.r06h.read()?;
+        let r06h_prev = self.registers.r06h().read()?;
 
         let value = self.into_timing(r06h_prev.aled2endc_or_led3endc())?;
 
@@ -1000,7 +1216,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_led3_reset_st(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r17h_prev = self.registers.r17h.read()?;
+        let r17h_prev = self.registers.r17h().read()?;
 
         let value = self.into_timing(r17h_prev.adcrststct1())?;
 
@@ -1013,7 +1229,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_led3_reset_end(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r18h_prev = self.registers.r18h.read()?;
+        let r18h_prev = self.registers.r18h().read()?;
 
         let value = self.into_timing(r18h_prev.adcrstendct1())?;
 
@@ -1026,7 +1242,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_led3_conv_st(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r0fh_prev = self.registers.r0Fh.read()?;
+        let r0fh_prev = self.registers.r0Fh().read()?;
 
         let value = self.into_timing(r0fh_prev.aled2convst_or_led3convst())?;
 
@@ -1039,7 +1255,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_led3_conv_end(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r10h_prev = self.registers.r10h.read()?;
+        let r10h_prev = self.registers.r10h().read()?;
 
         let value = self.into_timing(r10h_prev.aled2convend_or_led3convend())?;
 
@@ -1052,7 +1268,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_ambient_sample_st(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r0bh_prev = self.registers.r0Bh.read()?;
+        let r0bh_prev = self.registers.r0Bh().read()?;
 
         let value = self.into_timing(r0bh_prev.aled1stc())?;
 
@@ -1065,7 +1281,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_ambient_sample_end(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r0ch_prev = self.registers.r0Ch.read()?;
+        let r0ch_prev = self.registers.r0Ch().read()?;
 
         let value = self.into_timing(r0ch_prev.aled1endc())?;
 
@@ -1078,7 +1294,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_ambient_reset_st(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r1bh_prev = self.registers.r1Bh.read()?;
+        let r1bh_prev = self.registers.r1Bh().read()?;
 
         let value = self.into_timing(r1bh_prev.adcrststct3())?;
 
@@ -1091,7 +1307,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_ambient_reset_end(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r1ch_prev = self.registers.r1Ch.read()?;
+        let r1ch_prev = self.registers.r1Ch().read()?;
 
         let value = self.into_timing(r1ch_prev.adcrstendct3())?;
 
@@ -1104,7 +1320,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_ambient_conv_st(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r13h_prev = self.registers.r13h.read()?;
+        let r13h_prev = self.registers.r13h().read()?;
 
         let value = self.into_timing(r13h_prev.aled1convst())?;
 
@@ -1117,7 +1333,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_ambient_conv_end(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r14h_prev = self.registers.r14h.read()?;
+        let r14h_prev = self.registers.r14h().read()?;
 
         let value = self.into_timing(r14h_prev.aled1convend())?;
 
@@ -1144,6 +1360,26 @@ where
         Ok(*configuration.period())
     }
 
+    /// Sets the window period, scaling every phase of the measurement window proportionally.
+    ///
+    /// # Notes
+    ///
+    /// Unlike `set_window_period()`, the phases keep their position relative to the period, so they stay inside the window.
+    /// See [`MeasurementWindowConfiguration::rescaled`](crate::measurement_window::MeasurementWindowConfiguration::rescaled).
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// Setting a window period too long for the current clock frequency or equal to zero will result in an error.
+    /// Setting a window period so short that a phase violates a timing constraint will result in an error.
+    pub fn rescale_window_period(&mut self, period: Time) -> Result<Time, AfeError<I2C::Error>> {
+        let configuration_prev = self.get_measurement_window()?;
+
+        let configuration = self.set_measurement_window(&configuration_prev.rescaled(period))?;
+
+        Ok(*configuration.period())
+    }
+
     /// Sets the Ambient1 sample start timing.
     ///
     /// # Notes
@@ -1157,7 +1393,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r0Bh
+            .r0Bh()
             .write(R0Bh::new().with_aled1stc(value.1))?;
 
         Ok(value.0)
@@ -1176,7 +1412,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r0Ch
+            .r0Ch()
             .write(R0Ch::new().with_aled1endc(value.1))?;
 
         Ok(value.0)
@@ -1195,7 +1431,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r1Bh
+            .r1Bh()
             .write(R1Bh::new().with_adcrststct3(value.1))?;
 
         Ok(value.0)
@@ -1214,7 +1450,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r1Ch
+            .r1Ch()
             .write(R1Ch::new().with_adcrstendct3(value.1))?;
 
         Ok(value.0)
@@ -1233,7 +1469,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r13h
+            .r13h()
             .write(R13h::new().with_aled1convst(value.1))?;
 
         Ok(value.0)
@@ -1252,7 +1488,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r14h
+            .r14h()
             .write(R14h::new().with_aled1convend(value.1))?;
 
         Ok(value.0)
@@ -1271,7 +1507,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r05h
+            .r05h()
             .write(R05h::new().with_aled2stc_or_led3stc(value.1))?;
 
         Ok(value.0)
@@ -1290,7 +1526,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r06h
+            .r06h()
             .write(R06h::new().with_aled2endc_or_led3endc(value.1))?;
 
         Ok(value.0)
@@ -1309,7 +1545,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r17h
+            .r17h()
             .write(R17h::new().with_adcrststct1(value.1))?;
 
         Ok(value.0)
@@ -1328,7 +1564,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r18h
+            .r18h()
             .write(R18h::new().with_adcrstendct1(value.1))?;
 
         Ok(value.0)
@@ -1347,7 +1583,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r0Fh
+            .r0Fh()
             .write(R0Fh::new().with_aled2convst_or_led3convst(value.1))?;
 
         Ok(value.0)
@@ -1366,7 +1602,7 @@ where
         let value = self.from_timing(timing)?;
 
         self.registers
-            .r10h
+            .r10h()
             .write(R10h::new().with_aled2convend_or_led3convend(value.1))?;
 
         Ok(value.0)
@@ -1378,7 +1614,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_ambient1_sample_st(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r0bh_prev = self.registers.r0Bh.read()?;
+        let r0bh_prev = self.registers.r0Bh().read()?;
 
         let value = self.into_timing(r0bh_prev.aled1stc())?;
 
@@ -1391,7 +1627,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_ambient1_sample_end(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r0ch_prev = self.registers.r0Ch.read()?;
+        let r0ch_prev = self.registers.r0Ch().read()?;
 
         let value = self.into_timing(r0ch_prev.aled1endc())?;
 
@@ -1404,7 +1640,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_ambient1_reset_st(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r1bh_prev = self.registers.r1Bh.read()?;
+        let r1bh_prev = self.registers.r1Bh().read()?;
 
         let value = self.into_timing(r1bh_prev.adcrststct3())?;
 
@@ -1417,7 +1653,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_ambient1_reset_end(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r1ch_prev = self.registers.r1Ch.read()?;
+        let r1ch_prev = self.registers.r1Ch().read()?;
 
         let value = self.into_timing(r1ch_prev.adcrstendct3())?;
 
@@ -1430,7 +1666,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_ambient1_conv_st(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r13h_prev = self.registers.r13h.read()?;
+        let r13h_prev = self.registers.r13h().read()?;
 
         let value = self.into_timing(r13h_prev.aled1convst())?;
 
@@ -1443,7 +1679,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_ambient1_conv_end(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r14h_prev = self.registers.r14h.read()?;
+        let r14h_prev = self.registers.r14h().read()?;
 
         let value = self.into_timing(r14h_prev.aled1convend())?;
 
@@ -1456,7 +1692,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_ambient2_sample_st(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r05h_prev = self.registers.r05h.read()?;
+        let r05h_prev = self.registers.r05h().read()?;
 
         let value = self.into_timing(r05h_prev.aled2stc_or_led3stc())?;
 
@@ -1469,7 +1705,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_ambient2_sample_end(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r06h_prev = self.registers.r06h.read()?;
+        let r06h_prev = self.registers.r06h().read()?;
 
         let value = self.into_timing(r06h_prev.aled2endc_or_led3endc())?;
 
@@ -1482,7 +1718,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_ambient2_reset_st(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r17h_prev = self.registers.r17h.read()?;
+        let r17h_prev = self.registers.r17h().read()?;
 
         let value = self.into_timing(r17h_prev.adcrststct1())?;
 
@@ -1495,7 +1731,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_ambient2_reset_end(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r18h_prev = self.registers.r18h.read()?;
+        let r18h_prev = self.registers.r18h().read()?;
 
         let value = self.into_timing(r18h_prev.adcrstendct1())?;
 
@@ -1508,7 +1744,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_ambient2_conv_st(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r0fh_prev = self.registers.r0Fh.read()?;
+        let r0fh_prev = self.registers.r0Fh().read()?;
 
         let value = self.into_timing(r0fh_prev.aled2convst_or_led3convst())?;
 
@@ -1521,10 +1757,233 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_ambient2_conv_end(&mut self) -> Result<Time, AfeError<I2C::Error>> {
-        let r10h_prev = self.registers.r10h.read()?;
+        let r10h_prev = self.registers.r10h().read()?;
 
         let value = self.into_timing(r10h_prev.aled2convend_or_led3convend())?;
 
         Ok(value)
     }
 }
+
+impl<I2C> AFE4404<I2C, OneLedMode>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    /// Sets the window period.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// Setting a window period too long for the current clock frequency or equal to zero will result in an error.
+    pub fn set_window_period(&mut self, period: Time) -> Result<Time, AfeError<I2C::Error>> {
+        let mut configuration_prev = self.get_measurement_window()?;
+
+        *configuration_prev.period_mut() = period;
+
+        let configuration = self.set_measurement_window(&configuration_prev)?;
+
+        Ok(*configuration.period())
+    }
+
+    /// Sets the window period, scaling every phase of the measurement window proportionally.
+    ///
+    /// # Notes
+    ///
+    /// Unlike `set_window_period()`, the phases keep their position relative to the period, so they stay inside the window.
+    /// See [`MeasurementWindowConfiguration::rescaled`](crate::measurement_window::MeasurementWindowConfiguration::rescaled).
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// Setting a window period too long for the current clock frequency or equal to zero will result in an error.
+    /// Setting a window period so short that a phase violates a timing constraint will result in an error.
+    pub fn rescale_window_period(&mut self, period: Time) -> Result<Time, AfeError<I2C::Error>> {
+        let configuration_prev = self.get_measurement_window()?;
+
+        let configuration = self.set_measurement_window(&configuration_prev.rescaled(period))?;
+
+        Ok(*configuration.period())
+    }
+}
+
+/// Gets the start and end timing registers of the given phase.
+///
+/// # Notes
+///
+/// LED3 and Ambient2 share the same registers, Ambient channels have no lighting registers.
+pub(crate) fn phase_registers(channel: LedChannel, kind: PhaseKind) -> Option<(u8, u8)> {
+    Some(match (channel, kind) {
+        (LedChannel::Led1, PhaseKind::Lighting) => (0x03, 0x04),
+        (LedChannel::Led1, PhaseKind::Sample) => (0x07, 0x08),
+        (LedChannel::Led1, PhaseKind::Reset) => (0x19, 0x1A),
+        (LedChannel::Led1, PhaseKind::Conversion) => (0x11, 0x12),
+        (LedChannel::Led2, PhaseKind::Lighting) => (0x09, 0x0A),
+        (LedChannel::Led2, PhaseKind::Sample) => (0x01, 0x02),
+        (LedChannel::Led2, PhaseKind::Reset) => (0x15, 0x16),
+        (LedChannel::Led2, PhaseKind::Conversion) => (0x0D, 0x0E),
+        (LedChannel::Led3, PhaseKind::Lighting) => (0x36, 0x37),
+        (LedChannel::Led3 | LedChannel::Ambient2, PhaseKind::Sample) => (0x05, 0x06),
+        (LedChannel::Led3 | LedChannel::Ambient2, PhaseKind::Reset) => (0x17, 0x18),
+        (LedChannel::Led3 | LedChannel::Ambient2, PhaseKind::Conversion) => (0x0F, 0x10),
+        (LedChannel::Ambient1, PhaseKind::Sample) => (0x0B, 0x0C),
+        (LedChannel::Ambient1, PhaseKind::Reset) => (0x1B, 0x1C),
+        (LedChannel::Ambient1, PhaseKind::Conversion) => (0x13, 0x14),
+        (LedChannel::Ambient1 | LedChannel::Ambient2, PhaseKind::Lighting) => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use uom::si::{
+        f32::Time,
+        time::{microsecond, nanosecond},
+    };
+
+    use crate::{
+        device::AFE4404, errors::AfeError, measurement_window::MeasurementWindowConfiguration,
+        mock::MockAfe, modes::ThreeLedsMode,
+    };
+
+    /// Sets the eight LED1 edges, each of the setters converts the timing with the timer quantisation.
+    fn set_led1_edges(afe: &mut AFE4404<MockAfe, ThreeLedsMode>) {
+        let us = Time::new::<microsecond>;
+
+        afe.set_led1_lighting_st(us(0.0)).unwrap();
+        afe.set_led1_lighting_end(us(99.0)).unwrap();
+        afe.set_led1_sample_st(us(25.0)).unwrap();
+        afe.set_led1_sample_end(us(99.0)).unwrap();
+        afe.set_led1_reset_st(us(100.0)).unwrap();
+        afe.set_led1_reset_end(us(101.0)).unwrap();
+        afe.set_led1_conv_st(us(102.0)).unwrap();
+        afe.set_led1_conv_end(us(367.0)).unwrap();
+    }
+
+    #[test]
+    fn timing_setters_read_clock_division_on_every_call() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        set_led1_edges(&mut afe);
+
+        // Each setter reads R39h (4 transactions) and writes its register (1 transaction).
+        assert_eq!(mock.transactions(), 8 * 5);
+    }
+
+    #[test]
+    fn timing_editor_reads_clock_division_once() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        {
+            let mut editor = afe.begin_timing_edit().unwrap();
+            set_led1_edges(&mut editor);
+        }
+
+        // A single read of R39h (4 transactions) and the eight writes.
+        assert_eq!(mock.transactions(), 4 + 8);
+        assert_eq!(mock.register(0x12), 1468);
+    }
+
+    #[test]
+    fn timing_editor_cache_dropped_with_editor() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        {
+            let mut editor = afe.begin_timing_edit().unwrap();
+            editor
+                .set_led1_conv_end(Time::new::<microsecond>(100.0))
+                .unwrap();
+        }
+        assert_eq!(mock.register(0x12), 400);
+
+        mock.set_register(0x39, 5);
+        afe.set_led1_conv_end(Time::new::<microsecond>(100.0))
+            .unwrap();
+        assert_eq!(mock.register(0x12), 100);
+    }
+
+    #[test]
+    fn window_counter_round_trips() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        for counter in [0, 1, 39_999, u16::MAX] {
+            assert_eq!(afe.set_window_counter(counter).unwrap(), counter);
+            assert_eq!(afe.get_window_counter().unwrap(), counter);
+            assert_eq!(mock.register(0x1D), u32::from(counter));
+        }
+    }
+
+    #[test]
+    fn window_clock_division_round_trips() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        for (ratio, reg_value) in [(1, 0), (2, 4), (4, 5), (8, 6), (16, 7)] {
+            assert_eq!(afe.set_window_clock_division(ratio).unwrap(), ratio);
+            assert_eq!(afe.get_window_clock_division().unwrap(), ratio);
+            assert_eq!(mock.register(0x39) & 0b111, reg_value);
+            assert_eq!(
+                afe.get_timing_quantisation().unwrap(),
+                Time::new::<nanosecond>(250.0) * f32::from(ratio)
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_window_clock_division_rejected() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        afe.set_window_clock_division(4).unwrap();
+        for ratio in [0, 3, 32] {
+            assert!(matches!(
+                afe.set_window_clock_division(ratio),
+                Err(AfeError::ClockDivisionRatioOutsideAllowedRange)
+            ));
+        }
+        assert_eq!(afe.get_window_clock_division().unwrap(), 4);
+
+        mock.set_register(0x39, 2);
+        assert!(matches!(
+            afe.get_window_clock_division(),
+            Err(AfeError::InvalidRegisterValue { reg_addr: 0x39 })
+        ));
+    }
+
+    #[test]
+    fn window_counter_matches_window_setter() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        // A 20 ms period needs the clock divided by 2, giving 0.5 µs counts.
+        let mut configuration = MeasurementWindowConfiguration::<ThreeLedsMode>::default();
+        *configuration.period_mut() = Time::new::<microsecond>(20_000.0);
+        let period = *afe.set_measurement_window(&configuration).unwrap().period();
+        let counter = afe.get_window_counter().unwrap();
+
+        assert_eq!(counter, 39_999);
+        assert_eq!(u32::from(counter), mock.register(0x1D));
+        let counts = (period / afe.get_timing_quantisation().unwrap()).value;
+        assert!((counts - f32::from(counter + 1)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn quantised_timing_matches_setter() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        // 0.25 µs counts with the undivided clock, then 1 µs counts with the clock divided by 4.
+        for ratio in [1, 4] {
+            afe.set_window_clock_division(ratio).unwrap();
+
+            for timing in [0.0, 100.1, 225.4, 1_234.567] {
+                let timing = Time::new::<microsecond>(timing);
+                let quantised = afe.quantise_timing(timing).unwrap();
+
+                assert_eq!(afe.set_led1_lighting_st(timing).unwrap(), quantised);
+            }
+        }
+    }
+}
@@ -1,27 +1,590 @@
 //! This module contains the measurement window related functions.
 
+use alloc::vec::Vec;
+use embedded_hal::delay::DelayUs;
 use embedded_hal::i2c::I2c;
 use embedded_hal::i2c::SevenBitAddress;
-use uom::si::f32::Time;
+use uom::si::{
+    f32::{Frequency, Time},
+    time::microsecond,
+};
 
 use crate::{
+    channel::LedChannel,
     device::AFE4404,
     errors::AfeError,
-    modes::{ThreeLedsMode, TwoLedsMode},
+    modes::{LedMode, OneLedMode, ThreeLedsMode, TwoLedsMode},
     register_structs::{
-        R01h, R02h, R03h, R04h, R05h, R06h, R07h, R08h, R09h, R0Ah, R0Bh, R0Ch, R0Dh, R0Eh, R0Fh,
-        R10h, R11h, R12h, R13h, R14h, R15h, R16h, R17h, R18h, R19h, R1Ah, R1Bh, R1Ch, R1Dh, R32h,
-        R33h, R36h, R37h, R39h,
+        R00h, R01h, R02h, R03h, R04h, R05h, R06h, R07h, R08h, R09h, R0Ah, R0Bh, R0Ch, R0Dh, R0Eh,
+        R0Fh, R10h, R11h, R12h, R13h, R14h, R15h, R16h, R17h, R18h, R19h, R1Ah, R1Bh, R1Ch, R1Dh,
+        R32h, R33h, R36h, R37h, R39h,
     },
 };
 
 pub use configuration::{
-    ActiveTiming, AmbientTiming, LedTiming, MeasurementWindowConfiguration, PowerDownTiming,
+    ActiveTiming, AmbientTiming, ChannelPhase, LedTiming, MeasurementWindowConfiguration,
+    PhaseKind, PowerDownTiming, Schedule, SchedulePhase, TimingViolation,
 };
+pub use editor::TimingEditor;
 
 mod configuration;
+mod editor;
 pub mod low_level;
 
+impl<I2C, MODE> AFE4404<I2C, MODE>
+where
+    I2C: I2c<SevenBitAddress>,
+    MODE: LedMode,
+{
+    /// Gets the sample interval, that is the window period multiplied by the decimation factor.
+    ///
+    /// # Notes
+    ///
+    /// This is the interval between two consecutive `ADC_RDY` pulses.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub fn sample_interval(&mut self) -> Result<Time, AfeError<I2C::Error>> {
+        Ok(self.get_window_period()? * f32::from(self.get_decimation()?))
+    }
+
+    /// Gets the output data rate, that is the frequency of the `ADC_RDY` pulses.
+    ///
+    /// # Notes
+    ///
+    /// The rate is the inverse of the sample interval, which takes the decimation factor into account.
+    /// The averages are performed inside each measurement window, so the number of averages does not lower the rate, it only shortens the time available for each conversion.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub fn get_output_data_rate(&mut self) -> Result<Frequency, AfeError<I2C::Error>> {
+        Ok(1.0 / self.sample_interval()?)
+    }
+
+    /// Gets the sample rate, that is the inverse of the sample interval.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    #[deprecated(note = "use `get_output_data_rate()` instead")]
+    pub fn get_sample_rate(&mut self) -> Result<Frequency, AfeError<I2C::Error>> {
+        self.get_output_data_rate()
+    }
+
+    /// Gets the duration of the overlap between the lighting window and the sample window of a LED.
+    ///
+    /// # Notes
+    ///
+    /// A channel reading lower than expected may be caused by a LED turned on only during a part of the sample window.
+    /// Ambient channels never light a LED, so their overlap is always zero.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub fn sample_lighting_overlap(
+        &mut self,
+        led: LedChannel,
+    ) -> Result<Time, AfeError<I2C::Error>> {
+        let (lighting_st, lighting_end) = self.get_channel_lighting(led)?;
+        let (sample_st, sample_end) = self.get_channel_sample(led)?;
+
+        let overlap_st = if lighting_st > sample_st {
+            lighting_st
+        } else {
+            sample_st
+        };
+        let overlap_end = if lighting_end < sample_end {
+            lighting_end
+        } else {
+            sample_end
+        };
+
+        Ok(if overlap_end > overlap_st {
+            overlap_end - overlap_st
+        } else {
+            Time::new::<microsecond>(0.0)
+        })
+    }
+
+    /// Gets the time left in the measurement window after the last conversion.
+    ///
+    /// # Notes
+    ///
+    /// The budget is the window period minus the latest conversion end across all the channels.
+    /// It is the room available to add phases or lengthen the sample phases, zero is returned if the conversions exceed the period.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub fn remaining_window_budget(&mut self) -> Result<Time, AfeError<I2C::Error>> {
+        let latest_conv_end = [
+            self.registers.r0Eh().read()?.led2convend(),
+            self.registers.r10h().read()?.aled2convend_or_led3convend(),
+            self.registers.r12h().read()?.led1convend(),
+            self.registers.r14h().read()?.aled1convend(),
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or(0);
+
+        let period = self.get_window_period()?;
+        let latest_conv_end = self.into_timing(latest_conv_end)?;
+
+        Ok(if latest_conv_end < period {
+            period - latest_conv_end
+        } else {
+            Time::new::<microsecond>(0.0)
+        })
+    }
+
+    /// Checks whether a measurement window configuration is representable exactly by the timer.
+    ///
+    /// # Notes
+    ///
+    /// The timer quantisation is derived from the period of the configuration and the clock frequency, as in `set_measurement_window()` function.
+    /// Every timing, including the period, must be a multiple of the quantisation within a hundredth of a count.
+    /// A period too long for the current clock frequency or equal to zero is never exact.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn is_timing_exact(&self, configuration: &MeasurementWindowConfiguration<MODE>) -> bool {
+        let clk_div: f32 =
+            match ((*configuration.period() * self.clock).value / 65536.0).ceil() as u8 {
+                0 => return false,
+                1 => 1.0,
+                2 => 2.0,
+                d if d <= 4 => 4.0,
+                d if d <= 8 => 8.0,
+                d if d <= 16 => 16.0,
+                _ => return false,
+            };
+        let quantisation: Time = clk_div / self.clock;
+
+        configuration
+            .timings()
+            .iter()
+            .chain([*configuration.period()].iter())
+            .all(|timing| {
+                let counts = (*timing / quantisation).value;
+                (counts - counts.round()).abs() <= 1e-2
+            })
+    }
+
+    /// Verifies that a measured sample rate matches the configured one.
+    ///
+    /// # Notes
+    ///
+    /// The `measured` rate is typically obtained timing the `ADC_RDY` pulses with a host timer.
+    /// The `tolerance` is relative, for example `0.01` accepts a 1% deviation.
+    /// A disagreement usually means that the clock frequency passed to the constructor is wrong.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// A measured rate deviating from the configured one more than `tolerance` will result in an error.
+    pub fn verify_sample_rate(
+        &mut self,
+        measured: Frequency,
+        tolerance: f32,
+    ) -> Result<(), AfeError<I2C::Error>> {
+        let expected = self.get_output_data_rate()?;
+
+        if ((measured - expected) / expected).value.abs() > tolerance {
+            return Err(AfeError::ClockMismatch);
+        }
+
+        Ok(())
+    }
+
+    /// Repairs an invalid clock division code in `R39h`.
+    ///
+    /// # Notes
+    ///
+    /// The code is read from the device, bypassing the register cache, and nothing is written if it is valid.
+    /// The division ratio cannot be recovered from the period counter in `R1Dh` alone, so the cached timer quantisation is used when available inside a [`TimingEditor`].
+    /// Otherwise the counter is interpreted as counts of the undivided clock, which keeps `R1Dh` valid and gives the shortest period it can represent.
+    /// After calling this function the measurement window should be checked with `get_measurement_window()`.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn repair_clkdiv(&mut self) -> Result<(), AfeError<I2C::Error>> {
+        let r39h_prev = self.registers.r39h().read_uncached()?;
+
+        if matches!(r39h_prev.clkdiv_prf(), 0 | 4 | 5 | 6 | 7) {
+            return Ok(());
+        }
+
+        let reg_value = match self
+            .timing_quantisation
+            .map(|quantisation| (quantisation * self.clock).value.round() as u8)
+        {
+            Some(2) => 4,
+            Some(4) => 5,
+            Some(8) => 6,
+            Some(16) => 7,
+            _ => 0,
+        };
+
+        self.registers
+            .r39h()
+            .write(r39h_prev.with_clkdiv_prf(reg_value))?;
+        if reg_value == 0 {
+            if let Some(quantisation) = self.timing_quantisation.as_mut() {
+                *quantisation = 1.0 / self.clock;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Verifies that the timer engine is enabled.
+    ///
+    /// # Notes
+    ///
+    /// This function is meant to be called after `set_measurement_window()`, to check that the write of `R1Eh` took effect.
+    /// Without the timer engine no sample is acquired and the readings are never updated.
+    /// `R1Eh` is read from the device, bypassing the register cache.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// A disabled timer engine will result in an error.
+    pub fn verify_timer_enabled(&mut self) -> Result<(), AfeError<I2C::Error>> {
+        if !self.registers.r1Eh().read_uncached()?.timeren() {
+            return Err(AfeError::WriteVerificationFailed {
+                reg_addr: 0x1E,
+                channel: None,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Enters the one-shot mode, stopping the continuous acquisition.
+    ///
+    /// # Notes
+    ///
+    /// The timer engine is disabled, so no measurement window is run until `trigger_single_conversion()` is called.
+    /// The readings keep the values of the last measurement window.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub fn enter_one_shot(&mut self) -> Result<(), AfeError<I2C::Error>> {
+        let r1eh_prev = self.registers.r1Eh().read()?;
+
+        self.registers.r1Eh().write(r1eh_prev.with_timeren(false))?;
+
+        Ok(())
+    }
+
+    /// Exits the one-shot mode, restarting the continuous acquisition from the beginning of the measurement window.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub fn exit_one_shot(&mut self) -> Result<(), AfeError<I2C::Error>> {
+        let r1eh_prev = self.registers.r1Eh().read()?;
+
+        self.hold_timer_counter()?;
+        self.registers.r1Eh().write(r1eh_prev.with_timeren(true))?;
+        self.release_timer_counter()
+    }
+
+    /// Resets the timer counter, restarting the measurement window from the beginning.
+    ///
+    /// # Notes
+    ///
+    /// The measurement window in progress is interrupted and its readings are not updated.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub fn reset_timer_counter(&mut self) -> Result<(), AfeError<I2C::Error>> {
+        self.hold_timer_counter()?;
+        self.release_timer_counter()
+    }
+
+    /// Holds the timer counter in reset, stopping the measurement window at its beginning.
+    ///
+    /// # Notes
+    ///
+    /// The window is aligned to an external event calling `release_timer_counter()` as soon as the event occurs.
+    /// Reading a configuration register clears `R00h` and releases the counter, so no configuration register should be read until then.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub fn hold_timer_counter(&mut self) -> Result<(), AfeError<I2C::Error>> {
+        self.registers
+            .r00h()
+            .write(R00h::new().with_tm_count_rst(true))
+    }
+
+    /// Releases the timer counter held in reset by `hold_timer_counter()`, starting the measurement window.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub fn release_timer_counter(&mut self) -> Result<(), AfeError<I2C::Error>> {
+        self.registers.r00h().write(R00h::new())
+    }
+
+    /// Aligns the measurement windows of several [`AFE4404`]s, restarting them at the same time.
+    ///
+    /// # Notes
+    ///
+    /// All the timer counters are held in reset first, then released one after another.
+    /// The windows are thus offset by the duration of a single I2C write, which is the best alignment achievable without a shared clock.
+    /// The devices should share the same clock and window period, otherwise they drift apart again.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus of any device encounters an error.
+    /// If a counter cannot be held, the counters already held are released again before returning the error.
+    pub fn align_timer_counters(afes: &mut [&mut Self]) -> Result<(), AfeError<I2C::Error>> {
+        for held in 0..afes.len() {
+            if let Err(error) = afes[held].hold_timer_counter() {
+                for afe in &mut afes[..held] {
+                    // The hold error is more relevant than a release error.
+                    let _ = afe.release_timer_counter();
+                }
+
+                return Err(error);
+            }
+        }
+        for afe in afes.iter_mut() {
+            afe.release_timer_counter()?;
+        }
+
+        Ok(())
+    }
+
+    /// Runs a single measurement window, waiting for it to complete.
+    ///
+    /// # Notes
+    ///
+    /// The timer engine is started from the beginning of the measurement window and disabled again after one window period.
+    /// The readings are updated at the end of the last conversion, so they can be read as soon as this function returns.
+    /// With a decimation factor greater than one a single window does not produce a new `ADC_RDY` pulse.
+    /// The timer engine is disabled again even if the delay fails.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// This function returns an error if the delay encounters an error.
+    pub fn trigger_single_conversion<D: DelayUs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), AfeError<I2C::Error>> {
+        #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+        let period_us = self.get_window_period()?.get::<microsecond>().ceil() as u32;
+
+        self.exit_one_shot()?;
+
+        let waited = delay.delay_us(period_us).map_err(|_| AfeError::DelayError);
+
+        self.enter_one_shot()?;
+
+        waited
+    }
+
+    /// Configures the window period so that the `ADC_RDY` pulses occur at the given output data rate.
+    ///
+    /// # Notes
+    ///
+    /// The decimation factor is kept, so the window period is the inverse of the rate multiplied by the decimation factor.
+    /// Every phase is scaled proportionally with the window period, see [`MeasurementWindowConfiguration::rescaled`].
+    /// The returned rate may slightly differ from the requested one because of the timer quantisation.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// Setting a rate requiring a window period too long for the current clock frequency will result in an error.
+    /// Setting a rate so high that a phase violates a timing constraint will result in an error.
+    pub fn configure_for_odr(
+        &mut self,
+        rate: Frequency,
+    ) -> Result<Frequency, AfeError<I2C::Error>> {
+        let decimation = f32::from(self.get_decimation()?);
+
+        let configuration_prev = self.read_window_timings()?;
+        let configuration = configuration_prev.rescaled(1.0 / (rate * decimation));
+        self.write_changed_timings(&configuration_prev, &configuration)?;
+
+        self.get_output_data_rate()
+    }
+
+    /// Reads the timings of the measurement window used by the lighting mode.
+    ///
+    /// # Notes
+    ///
+    /// The registers of the phases not used by the lighting mode are not read, so those phases are left empty.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub(crate) fn read_window_timings(
+        &mut self,
+    ) -> Result<MeasurementWindowConfiguration<MODE>, AfeError<I2C::Error>> {
+        let quantisation = self.timing_quantisation()?;
+        let period = (f32::from(self.registers.r1Dh().read()?.prpct()) + 1.0) * quantisation;
+
+        let mut configuration = MeasurementWindowConfiguration::<MODE>::empty(period);
+        let mut read_timing = |reg_addr: u8| -> Result<Time, AfeError<I2C::Error>> {
+            // Any register can be used to reach the bus.
+            let value = self.registers.r00h().read_address(reg_addr)?;
+
+            #[allow(clippy::cast_possible_truncation)]
+            Ok(f32::from(value as u16) * quantisation)
+        };
+
+        for mut phase in configuration.all_channel_phases() {
+            if let Some((start_reg, end_reg)) =
+                low_level::phase_registers(phase.channel, phase.kind)
+            {
+                phase.start = read_timing(start_reg)?;
+                phase.end = read_timing(end_reg)?;
+                configuration.set_channel_phase(&phase);
+            }
+        }
+        *configuration.inactive_timing_configuration_mut() =
+            PowerDownTiming::new(read_timing(0x32)?, read_timing(0x33)?);
+
+        Ok(configuration)
+    }
+
+    /// Gets the timer counter end value and the `CLKDIV_PRF` register value of a window period, with the resulting timer quantisation.
+    ///
+    /// # Errors
+    ///
+    /// Setting a window period too long for the current clock frequency or equal to zero will result in an error.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn window_counter(&self, period: Time) -> Result<(u16, u8, Time), AfeError<I2C::Error>> {
+        let clk_div = ((period * self.clock).value / 65536.0).ceil() as u8;
+        let clk_div: (f32, u8) = match clk_div {
+            0 => return Err(AfeError::WindowPeriodOutsideAllowedRange),
+            1 => (1.0, 0), // (division ratio, register value).
+            2 => (2.0, 4),
+            d if d <= 4 => (4.0, 5),
+            d if d <= 8 => (8.0, 6),
+            d if d <= 16 => (16.0, 7),
+            _ => return Err(AfeError::WindowPeriodOutsideAllowedRange),
+        };
+        let quantisation: Time = clk_div.0 / self.clock;
+        let counter_max_value = ((period / quantisation).value - 1.0).round() as u16;
+
+        Ok((counter_max_value, clk_div.1, quantisation))
+    }
+
+    /// Writes the timings of the measurement window that differ from the previous ones.
+    ///
+    /// # Notes
+    ///
+    /// The previous timings must be the ones read from the [`AFE4404`], since they are compared using its clock division.
+    /// If the period is unchanged the clock division of the [`AFE4404`] is kept, even if set larger than needed through `set_window_clock_division()`.
+    /// Otherwise the minimal clock division for the new period is used and every timing is written, since the timer quantisation may change.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// Setting a window period too long for the current clock frequency or equal to zero will result in an error.
+    /// Setting a power-down window not satisfying `power_down_st < power_down_end <= period` will result in an error.
+    /// Setting a measurement window violating a timing constraint, see [`TimingViolation`], will result in an error.
+    #[allow(clippy::similar_names)]
+    pub(crate) fn write_changed_timings(
+        &mut self,
+        configuration_prev: &MeasurementWindowConfiguration<MODE>,
+        configuration: &MeasurementWindowConfiguration<MODE>,
+    ) -> Result<(), AfeError<I2C::Error>> {
+        // The device may use a clock division other than the minimal one, set through the low level accessors.
+        let r1dh_prev = self.registers.r1Dh().read()?;
+        let r39h_prev = self.registers.r39h().read()?;
+
+        let clk_div_prev: f32 = match r39h_prev.clkdiv_prf() {
+            0 => 1.0,
+            4 => 2.0,
+            5 => 4.0,
+            6 => 8.0,
+            7 => 16.0,
+            _ => return Err(AfeError::InvalidRegisterValue { reg_addr: 0x39 }),
+        };
+        let quantisation_prev = clk_div_prev / self.clock;
+        let counter_prev = f32::from(r1dh_prev.prpct()) + 1.0;
+        let (counter_max_value, clk_div, quantisation) =
+            if ((*configuration.period() / quantisation_prev).value - counter_prev).abs() < 0.5 {
+                (r1dh_prev.prpct(), r39h_prev.clkdiv_prf(), quantisation_prev)
+            } else {
+                self.window_counter(*configuration.period())?
+            };
+        let rescaling = (counter_max_value, clk_div) != (r1dh_prev.prpct(), r39h_prev.clkdiv_prf());
+
+        let power_down = configuration.inactive_timing_configuration();
+        if power_down.power_down_st >= power_down.power_down_end
+            || power_down.power_down_end > *configuration.period()
+        {
+            return Err(AfeError::PowerDownWindowOutsideAllowedRange);
+        }
+        configuration.validate()?;
+
+        if rescaling {
+            self.registers
+                .r1Dh()
+                .write(R1Dh::new().with_prpct(counter_max_value))?;
+            self.registers
+                .r39h()
+                .write(R39h::new().with_clkdiv_prf(clk_div))?;
+            if let Some(quantisation_cached) = self.timing_quantisation.as_mut() {
+                *quantisation_cached = quantisation;
+            }
+        }
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let code = |timing: Time, quantisation: Time| (timing / quantisation).value.round() as u16;
+
+        let power_down_prev = configuration_prev.inactive_timing_configuration();
+        let timings = configuration_prev
+            .all_channel_phases()
+            .into_iter()
+            .zip(configuration.all_channel_phases())
+            .filter_map(|(phase_prev, phase)| {
+                let (start_reg, end_reg) = low_level::phase_registers(phase.channel, phase.kind)?;
+
+                Some([
+                    (start_reg, phase_prev.start, phase.start),
+                    (end_reg, phase_prev.end, phase.end),
+                ])
+            })
+            .flatten()
+            .chain([
+                (
+                    0x32,
+                    power_down_prev.power_down_st,
+                    power_down.power_down_st,
+                ),
+                (
+                    0x33,
+                    power_down_prev.power_down_end,
+                    power_down.power_down_end,
+                ),
+            ]);
+
+        for (reg_addr, timing_prev, timing) in timings {
+            let value = code(timing, quantisation);
+            if rescaling || value != code(timing_prev, quantisation_prev) {
+                // Any register can be used to reach the bus.
+                self.registers
+                    .r00h()
+                    .write_address(reg_addr, u32::from(value))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl<I2C> AFE4404<I2C, ThreeLedsMode>
 where
     I2C: I2c<SevenBitAddress>,
@@ -46,6 +609,8 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     /// Setting a window period too long for the current clock frequency or equal to zero will result in an error.
+    /// Setting a power-down window not satisfying `power_down_st < power_down_end <= period` will result in an error.
+    /// Setting a measurement window violating a timing constraint, see [`TimingViolation`], will result in an error.
     pub fn set_measurement_window(
         &mut self,
         configuration: &MeasurementWindowConfiguration<ThreeLedsMode>,
@@ -61,7 +626,15 @@ where
             conv_end: u16,
         }
 
-        let r1eh_prev = self.registers.r1Eh.read()?;
+        let power_down = configuration.inactive_timing_configuration();
+        if power_down.power_down_st >= power_down.power_down_end
+            || power_down.power_down_end > *configuration.period()
+        {
+            return Err(AfeError::PowerDownWindowOutsideAllowedRange);
+        }
+        configuration.validate()?;
+
+        let r1eh_prev = self.registers.r1Eh().read()?;
 
         let clk_div = ((*configuration.period() * self.clock).value / 65536.0).ceil() as u8;
         let clk_div: (f32, u8) = match clk_div {
@@ -79,7 +652,7 @@ where
         let counter_max_value: u16 = (counter - 1.0).round() as u16;
         let quantisation: Time = *configuration.period() / counter;
 
-        let active_values: alloc::vec::Vec<QuantisedValues> = [
+        let active_values: Vec<QuantisedValues> = [
             *configuration.active_timing_configuration().led2(),
             *configuration.active_timing_configuration().led3(),
             *configuration.active_timing_configuration().led1(),
@@ -109,117 +682,120 @@ where
 
         // Enable timer engine.
         self.registers
-            .r1Dh
+            .r1Dh()
             .write(R1Dh::new().with_prpct(counter_max_value))?;
         self.registers
-            .r39h
+            .r39h()
             .write(R39h::new().with_clkdiv_prf(clk_div.1))?;
-        self.registers.r1Eh.write(r1eh_prev.with_timeren(true))?;
+        if let Some(quantisation) = self.timing_quantisation.as_mut() {
+            *quantisation = period_clk_div;
+        }
+        self.registers.r1Eh().write(r1eh_prev.with_timeren(true))?;
 
         // Write led2 registers.
         self.registers
-            .r09h
+            .r09h()
             .write(R09h::new().with_led2ledstc(active_values[0].lighting_st))?;
         self.registers
-            .r0Ah
+            .r0Ah()
             .write(R0Ah::new().with_led2ledendc(active_values[0].lighting_end))?;
         self.registers
-            .r01h
+            .r01h()
             .write(R01h::new().with_led2stc(active_values[0].sample_st))?;
         self.registers
-            .r02h
+            .r02h()
             .write(R02h::new().with_led2endc(active_values[0].sample_end))?;
         self.registers
-            .r15h
+            .r15h()
             .write(R15h::new().with_adcrststct0(active_values[0].reset_st))?;
         self.registers
-            .r16h
+            .r16h()
             .write(R16h::new().with_adcrstendct0(active_values[0].reset_end))?;
         self.registers
-            .r0Dh
+            .r0Dh()
             .write(R0Dh::new().with_led2convst(active_values[0].conv_st))?;
         self.registers
-            .r0Eh
+            .r0Eh()
             .write(R0Eh::new().with_led2convend(active_values[0].conv_end))?;
 
         // Write led3 registers.
         self.registers
-            .r36h
+            .r36h()
             .write(R36h::new().with_led3ledstc(active_values[1].lighting_st))?;
         self.registers
-            .r37h
+            .r37h()
             .write(R37h::new().with_led3ledendc(active_values[1].lighting_end))?;
         self.registers
-            .r05h
+            .r05h()
             .write(R05h::new().with_aled2stc_or_led3stc(active_values[1].sample_st))?;
         self.registers
-            .r06h
+            .r06h()
             .write(R06h::new().with_aled2endc_or_led3endc(active_values[1].sample_end))?;
         self.registers
-            .r17h
+            .r17h()
             .write(R17h::new().with_adcrststct1(active_values[1].reset_st))?;
         self.registers
-            .r18h
+            .r18h()
             .write(R18h::new().with_adcrstendct1(active_values[1].reset_end))?;
         self.registers
-            .r0Fh
+            .r0Fh()
             .write(R0Fh::new().with_aled2convst_or_led3convst(active_values[1].conv_st))?;
         self.registers
-            .r10h
+            .r10h()
             .write(R10h::new().with_aled2convend_or_led3convend(active_values[1].conv_end))?;
 
         // Write led1 registers.
         self.registers
-            .r03h
+            .r03h()
             .write(R03h::new().with_led1ledstc(active_values[2].lighting_st))?;
         self.registers
-            .r04h
+            .r04h()
             .write(R04h::new().with_led1ledendc(active_values[2].lighting_end))?;
         self.registers
-            .r07h
+            .r07h()
             .write(R07h::new().with_led1stc(active_values[2].sample_st))?;
         self.registers
-            .r08h
+            .r08h()
             .write(R08h::new().with_led1endc(active_values[2].sample_end))?;
         self.registers
-            .r19h
+            .r19h()
             .write(R19h::new().with_adcrststct2(active_values[2].reset_st))?;
         self.registers
-            .r1Ah
+            .r1Ah()
             .write(R1Ah::new().with_adcrstendct2(active_values[2].reset_end))?;
         self.registers
-            .r11h
+            .r11h()
             .write(R11h::new().with_led1convst(active_values[2].conv_st))?;
         self.registers
-            .r12h
+            .r12h()
             .write(R12h::new().with_led1convend(active_values[2].conv_end))?;
 
         // Write ambient registers.
         self.registers
-            .r0Bh
+            .r0Bh()
             .write(R0Bh::new().with_aled1stc(active_values[3].sample_st))?;
         self.registers
-            .r0Ch
+            .r0Ch()
             .write(R0Ch::new().with_aled1endc(active_values[3].sample_end))?;
         self.registers
-            .r1Bh
+            .r1Bh()
             .write(R1Bh::new().with_adcrststct3(active_values[3].reset_st))?;
         self.registers
-            .r1Ch
+            .r1Ch()
             .write(R1Ch::new().with_adcrstendct3(active_values[3].reset_end))?;
         self.registers
-            .r13h
+            .r13h()
             .write(R13h::new().with_aled1convst(active_values[3].conv_st))?;
         self.registers
-            .r14h
+            .r14h()
             .write(R14h::new().with_aled1convend(active_values[3].conv_end))?;
 
         // Write dynamic power down registers.
         self.registers
-            .r32h
+            .r32h()
             .write(R32h::new().with_pdncyclestc(power_down_values[0]))?;
         self.registers
-            .r33h
+            .r33h()
             .write(R33h::new().with_pdncycleendc(power_down_values[1]))?;
 
         Ok(MeasurementWindowConfiguration::<ThreeLedsMode>::new(
@@ -280,40 +856,40 @@ where
     pub fn get_measurement_window(
         &mut self,
     ) -> Result<MeasurementWindowConfiguration<ThreeLedsMode>, AfeError<I2C::Error>> {
-        let r01h_prev = self.registers.r01h.read()?;
-        let r02h_prev = self.registers.r02h.read()?;
-        let r03h_prev = self.registers.r03h.read()?;
-        let r04h_prev = self.registers.r04h.read()?;
-        let r05h_prev = self.registers.r05h.read()?;
-        let r06h_prev = self.registers.r06h.read()?;
-        let r07h_prev = self.registers.r07h.read()?;
-        let r08h_prev = self.registers.r08h.read()?;
-        let r09h_prev = self.registers.r09h.read()?;
-        let r0ah_prev = self.registers.r0Ah.read()?;
-        let r0bh_prev = self.registers.r0Bh.read()?;
-        let r0ch_prev = self.registers.r0Ch.read()?;
-        let r0dh_prev = self.registers.r0Dh.read()?;
-        let r0eh_prev = self.registers.r0Eh.read()?;
-        let r0fh_prev = self.registers.r0Fh.read()?;
-        let r10h_prev = self.registers.r10h.read()?;
-        let r11h_prev = self.registers.r11h.read()?;
-        let r12h_prev = self.registers.r12h.read()?;
-        let r13h_prev = self.registers.r13h.read()?;
-        let r14h_prev = self.registers.r14h.read()?;
-        let r15h_prev = self.registers.r15h.read()?;
-        let r16h_prev = self.registers.r16h.read()?;
-        let r17h_prev = self.registers.r17h.read()?;
-        let r18h_prev = self.registers.r18h.read()?;
-        let r19h_prev = self.registers.r19h.read()?;
-        let r1ah_prev = self.registers.r1Ah.read()?;
-        let r1bh_prev = self.registers.r1Bh.read()?;
-        let r1ch_prev = self.registers.r1Ch.read()?;
-        let r1dh_prev = self.registers.r1Dh.read()?;
-        let r32h_prev = self.registers.r32h.read()?;
-        let r33h_prev = self.registers.r33h.read()?;
-        let r36h_prev = self.registers.r36h.read()?;
-        let r37h_prev = self.registers.r37h.read()?;
-        let r39h_prev = self.registers.r39h.read()?;
+        let r01h_prev = self.registers.r01h().read()?;
+        let r02h_prev = self.registers.r02h().read()?;
+        let r03h_prev = self.registers.r03h().read()?;
+        let r04h_prev = self.registers.r04h().read()?;
+        let r05h_prev = self.registers.r05h().read()?;
+        let r06h_prev = self.registers.r06h().read()?;
+        let r07h_prev = self.registers.r07h().read()?;
+        let r08h_prev = self.registers.r08h().read()?;
+        let r09h_prev = self.registers.r09h().read()?;
+        let r0ah_prev = self.registers.r0Ah().read()?;
+        let r0bh_prev = self.registers.r0Bh().read()?;
+        let r0ch_prev = self.registers.r0Ch().read()?;
+        let r0dh_prev = self.registers.r0Dh().read()?;
+        let r0eh_prev = self.registers.r0Eh().read()?;
+        let r0fh_prev = self.registers.r0Fh().read()?;
+        let r10h_prev = self.registers.r10h().read()?;
+        let r11h_prev = self.registers.r11h().read()?;
+        let r12h_prev = self.registers.r12h().read()?;
+        let r13h_prev = self.registers.r13h().read()?;
+        let r14h_prev = self.registers.r14h().read()?;
+        let r15h_prev = self.registers.r15h().read()?;
+        let r16h_prev = self.registers.r16h().read()?;
+        let r17h_prev = self.registers.r17h().read()?;
+        let r18h_prev = self.registers.r18h().read()?;
+        let r19h_prev = self.registers.r19h().read()?;
+        let r1ah_prev = self.registers.r1Ah().read()?;
+        let r1bh_prev = self.registers.r1Bh().read()?;
+        let r1ch_prev = self.registers.r1Ch().read()?;
+        let r1dh_prev = self.registers.r1Dh().read()?;
+        let r32h_prev = self.registers.r32h().read()?;
+        let r33h_prev = self.registers.r33h().read()?;
+        let r36h_prev = self.registers.r36h().read()?;
+        let r37h_prev = self.registers.r37h().read()?;
+        let r39h_prev = self.registers.r39h().read()?;
 
         let clk_div: f32 = match r39h_prev.clkdiv_prf() {
             0 => 1.0,
@@ -375,13 +951,181 @@ where
             ),
         ))
     }
-}
 
-impl<I2C> AFE4404<I2C, TwoLedsMode>
-where
-    I2C: I2c<SevenBitAddress>,
-{
-    #![allow(
+    /// Reinterprets the slot shared between LED3 and Ambient2 as the LED3 phase.
+    ///
+    /// # Notes
+    ///
+    /// The registers `R05h`, `R06h`, `R0Fh` and `R10h` are shared between the LED3 phase (three LEDs mode) and the Ambient2 phase (two LEDs mode).
+    /// Call this function after switching from two LEDs mode: the LED3 lighting window is aligned to the shared sample window,
+    /// so that the slot samples a lit LED3 instead of the ambient light.
+    /// The lighting window can be refined afterwards with [`AFE4404::set_led3_lighting_st`].
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub fn reinterpret_shared_slot_as_led3(&mut self) -> Result<(), AfeError<I2C::Error>> {
+        let r05h_prev = self.registers.r05h().read()?;
+        let r06h_prev = self.registers.r06h().read()?;
+
+        self.registers
+            .r36h()
+            .write(R36h::new().with_led3ledstc(r05h_prev.aled2stc_or_led3stc()))?;
+        self.registers
+            .r37h()
+            .write(R37h::new().with_led3ledendc(r06h_prev.aled2endc_or_led3endc()))?;
+
+        Ok(())
+    }
+
+    /// Updates the measurement window, writing only the registers whose values changed.
+    ///
+    /// # Notes
+    ///
+    /// The current measurement window is read and passed to the `patch` closure.
+    /// If the period changes the whole measurement window is written, since the timer quantisation may change.
+    /// After calling this function, a wait time of `tCHANNEL` should be applied before high-accuracy readings.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// Setting a window period too long for the current clock frequency or equal to zero will result in an error.
+    /// Setting a power-down window not satisfying `power_down_st < power_down_end <= period` will result in an error.
+    /// Setting a measurement window violating a timing constraint, see [`TimingViolation`], will result in an error.
+    pub fn update_timing_window(
+        &mut self,
+        patch: impl FnOnce(&mut MeasurementWindowConfiguration<ThreeLedsMode>),
+    ) -> Result<MeasurementWindowConfiguration<ThreeLedsMode>, AfeError<I2C::Error>> {
+        let configuration_prev = self.get_measurement_window()?;
+        let mut configuration = configuration_prev;
+        patch(&mut configuration);
+
+        self.write_changed_timings(&configuration_prev, &configuration)?;
+
+        self.get_measurement_window()
+    }
+
+    /// Sets the active phase of the measurement window, keeping the current period and power-down phase.
+    ///
+    /// # Notes
+    ///
+    /// The period and the clock division are read from the [`AFE4404`], only the active phase registers are written.
+    /// After calling this function, a wait time of `tCHANNEL` should be applied before high-accuracy readings.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub fn set_active_timing(
+        &mut self,
+        active: &ActiveTiming<ThreeLedsMode>,
+    ) -> Result<ActiveTiming<ThreeLedsMode>, AfeError<I2C::Error>> {
+        let configuration = self.update_timing_window(|configuration| {
+            *configuration.active_timing_configuration_mut() = *active;
+        })?;
+
+        Ok(*configuration.active_timing_configuration())
+    }
+
+    /// Gets the measurement window as a list of phases, each with its name, start and end in microseconds.
+    ///
+    /// # Notes
+    ///
+    /// This function is meant for quick human inspection or CSV export, use `get_measurement_window()` to get `uom` values.
+    /// The phases are listed LED by LED, followed by the Ambient phases and the power-down phase.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub fn schedule_micros(&mut self) -> Result<Vec<SchedulePhase>, AfeError<I2C::Error>> {
+        Ok(self
+            .get_measurement_window()?
+            .phases()
+            .into_iter()
+            .map(|(name, start, end)| (name, start.get::<microsecond>(), end.get::<microsecond>()))
+            .collect())
+    }
+
+    /// Gets the phases of every channel as a schedule sorted by start time.
+    ///
+    /// # Notes
+    ///
+    /// The lighting phase of a LED is expected to overlap its own sample phase, so only phases of the same kind are checked against each other.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// This function returns an error pinpointing the first pair of overlapping phases of the same kind.
+    pub fn schedule(&mut self) -> Result<Schedule<ThreeLedsMode>, AfeError<I2C::Error>> {
+        Schedule::new(self.get_measurement_window()?.channel_phases()).map_err(|(first, second)| {
+            AfeError::PhaseOverlap {
+                kind: first.kind,
+                first: first.channel,
+                second: second.channel,
+            }
+        })
+    }
+
+    /// Mirrors the sample, reset and conversion phases of a LED into the Ambient phase, shifted by `offset`.
+    ///
+    /// # Notes
+    ///
+    /// The Ambient phase is sampled with the same window shape of the LED, but with all the LEDs off.
+    /// Only the Ambient registers are written.
+    /// After calling this function, a wait time of `tCHANNEL` should be applied before high-accuracy readings.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// Mirroring from a channel different from LED1, LED2 or LED3 will result in an error.
+    pub fn mirror_ambient_from(
+        &mut self,
+        led: LedChannel,
+        offset: Time,
+    ) -> Result<AmbientTiming, AfeError<I2C::Error>> {
+        if !matches!(led, LedChannel::Led1 | LedChannel::Led2 | LedChannel::Led3) {
+            return Err(AfeError::ChannelNotAvailable { channel: led });
+        }
+
+        let configuration = self.update_timing_window(|configuration| {
+            let timing = configuration.active_timing_configuration_mut();
+            let source = match led {
+                LedChannel::Led1 => *timing.led1(),
+                LedChannel::Led2 => *timing.led2(),
+                _ => *timing.led3(),
+            };
+            *timing.ambient_mut() = AmbientTiming::mirroring(&source, offset);
+        })?;
+
+        Ok(*configuration.active_timing_configuration().ambient())
+    }
+
+    /// Disables the Ambient phase, zeroing its sample, reset and conversion timings.
+    ///
+    /// # Notes
+    ///
+    /// This frees the time of the Ambient phase in the measurement window, for example when only the LEDs readings are used.
+    /// The Ambient reading returned by `read()` is no longer converted and should be ignored, it typically reads close to zero.
+    /// The Ambient phase can be enabled again with `set_measurement_window()` or `mirror_ambient_from()`.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub fn disable_ambient(&mut self) -> Result<(), AfeError<I2C::Error>> {
+        self.update_timing_window(|configuration| {
+            *configuration
+                .active_timing_configuration_mut()
+                .ambient_mut() = AmbientTiming::default();
+        })?;
+
+        Ok(())
+    }
+}
+
+impl<I2C> AFE4404<I2C, TwoLedsMode>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    #![allow(
         clippy::cast_precision_loss,
         clippy::cast_sign_loss,
         clippy::cast_possible_truncation,
@@ -401,6 +1145,8 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     /// Setting a window period too long for the current clock frequency or equal to zero will result in an error.
+    /// Setting a power-down window not satisfying `power_down_st < power_down_end <= period` will result in an error.
+    /// Setting a measurement window violating a timing constraint, see [`TimingViolation`], will result in an error.
     pub fn set_measurement_window(
         &mut self,
         configuration: &MeasurementWindowConfiguration<TwoLedsMode>,
@@ -416,7 +1162,15 @@ where
             conv_end: u16,
         }
 
-        let r1eh_prev = self.registers.r1Eh.read()?;
+        let power_down = configuration.inactive_timing_configuration();
+        if power_down.power_down_st >= power_down.power_down_end
+            || power_down.power_down_end > *configuration.period()
+        {
+            return Err(AfeError::PowerDownWindowOutsideAllowedRange);
+        }
+        configuration.validate()?;
+
+        let r1eh_prev = self.registers.r1Eh().read()?;
 
         let clk_div = ((*configuration.period() * self.clock).value / 65536.0).ceil() as u8;
         let clk_div: (f32, u8) = match clk_div {
@@ -434,7 +1188,7 @@ where
         let counter_max_value: u16 = (counter - 1.0).round() as u16;
         let quantisation: Time = *configuration.period() / counter;
 
-        let active_values: alloc::vec::Vec<QuantisedValues> = [
+        let active_values: Vec<QuantisedValues> = [
             *configuration.active_timing_configuration().led2(),
             (*configuration.active_timing_configuration().ambient2()).into(),
             *configuration.active_timing_configuration().led1(),
@@ -464,111 +1218,114 @@ where
 
         // Enable timer engine.
         self.registers
-            .r1Dh
+            .r1Dh()
             .write(R1Dh::new().with_prpct(counter_max_value))?;
         self.registers
-            .r39h
+            .r39h()
             .write(R39h::new().with_clkdiv_prf(clk_div.1))?;
-        self.registers.r1Eh.write(r1eh_prev.with_timeren(true))?;
+        if let Some(quantisation) = self.timing_quantisation.as_mut() {
+            *quantisation = period_clk_div;
+        }
+        self.registers.r1Eh().write(r1eh_prev.with_timeren(true))?;
 
         // Write led2 registers.
         self.registers
-            .r09h
+            .r09h()
             .write(R09h::new().with_led2ledstc(active_values[0].lighting_st))?;
         self.registers
-            .r0Ah
+            .r0Ah()
             .write(R0Ah::new().with_led2ledendc(active_values[0].lighting_end))?;
         self.registers
-            .r01h
+            .r01h()
             .write(R01h::new().with_led2stc(active_values[0].sample_st))?;
         self.registers
-            .r02h
+            .r02h()
             .write(R02h::new().with_led2endc(active_values[0].sample_end))?;
         self.registers
-            .r15h
+            .r15h()
             .write(R15h::new().with_adcrststct0(active_values[0].reset_st))?;
         self.registers
-            .r16h
+            .r16h()
             .write(R16h::new().with_adcrstendct0(active_values[0].reset_end))?;
         self.registers
-            .r0Dh
+            .r0Dh()
             .write(R0Dh::new().with_led2convst(active_values[0].conv_st))?;
         self.registers
-            .r0Eh
+            .r0Eh()
             .write(R0Eh::new().with_led2convend(active_values[0].conv_end))?;
 
         // Write ambient2 registers.
         self.registers
-            .r05h
+            .r05h()
             .write(R05h::new().with_aled2stc_or_led3stc(active_values[1].sample_st))?;
         self.registers
-            .r06h
+            .r06h()
             .write(R06h::new().with_aled2endc_or_led3endc(active_values[1].sample_end))?;
         self.registers
-            .r17h
+            .r17h()
             .write(R17h::new().with_adcrststct1(active_values[1].reset_st))?;
         self.registers
-            .r18h
+            .r18h()
             .write(R18h::new().with_adcrstendct1(active_values[1].reset_end))?;
         self.registers
-            .r0Fh
+            .r0Fh()
             .write(R0Fh::new().with_aled2convst_or_led3convst(active_values[1].conv_st))?;
         self.registers
-            .r10h
+            .r10h()
             .write(R10h::new().with_aled2convend_or_led3convend(active_values[1].conv_end))?;
 
         // Write led1 registers.
         self.registers
-            .r03h
+            .r03h()
             .write(R03h::new().with_led1ledstc(active_values[2].lighting_st))?;
         self.registers
-            .r04h
+            .r04h()
             .write(R04h::new().with_led1ledendc(active_values[2].lighting_end))?;
         self.registers
-            .r07h
+            .r07h()
             .write(R07h::new().with_led1stc(active_values[2].sample_st))?;
         self.registers
-            .r08h
+            .r08h()
             .write(R08h::new().with_led1endc(active_values[2].sample_end))?;
         self.registers
-            .r19h
+            .r19h()
             .write(R19h::new().with_adcrststct2(active_values[2].reset_st))?;
         self.registers
-            .r1Ah
+            .r1Ah()
             .write(R1Ah::new().with_adcrstendct2(active_values[2].reset_end))?;
         self.registers
-            .r11h
+            .r11h()
             .write(R11h::new().with_led1convst(active_values[2].conv_st))?;
         self.registers
-            .r12h
+            .r12h()
             .write(R12h::new().with_led1convend(active_values[2].conv_end))?;
 
         // Write ambient1 registers.
         self.registers
-            .r0Bh
+            .r0Bh()
             .write(R0Bh::new().with_aled1stc(active_values[3].sample_st))?;
         self.registers
-            .r0Ch
+            .r0Ch()
             .write(R0Ch::new().with_aled1endc(active_values[3].sample_end))?;
         self.registers
-            .r1Bh
+            .r1Bh()
             .write(R1Bh::new().with_adcrststct3(active_values[3].reset_st))?;
         self.registers
-            .r1Ch
+            .r1Ch()
             .write(R1Ch::new().with_adcrstendct3(active_values[3].reset_end))?;
         self.registers
-            .r13h
+            .r13h()
             .write(R13h::new().with_aled1convst(active_values[3].conv_st))?;
         self.registers
-            .r14h
+            .r14h()
             .write(R14h::new().with_aled1convend(active_values[3].conv_end))?;
 
         // Write dynamic power down registers.
         self.registers
-            .r32h
+            .r32h()
             .write(R32h::new().with_pdncyclestc(power_down_values[0]))?;
         self.registers
-            .r33h
+            .r33h()
             .write(R33h::new().with_pdncycleendc(power_down_values[1]))?;
 
         Ok(MeasurementWindowConfiguration::<TwoLedsMode>::new(
@@ -627,38 +1384,38 @@ where
     pub fn get_measurement_window(
         &mut self,
     ) -> Result<MeasurementWindowConfiguration<TwoLedsMode>, AfeError<I2C::Error>> {
-        let r01h_prev = self.registers.r01h.read()?;
-        let r02h_prev = self.registers.r02h.read()?;
-        let r03h_prev = self.registers.r03h.read()?;
-        let r04h_prev = self.registers.r04h.read()?;
-        let r05h_prev = self.registers.r05h.read()?;
-        let r06h_prev = self.registers.r06h.read()?;
-        let r07h_prev = self.registers.r07h.read()?;
-        let r08h_prev = self.registers.r08h.read()?;
-        let r09h_prev = self.registers.r09h.read()?;
-        let r0ah_prev = self.registers.r0Ah.read()?;
-        let r0bh_prev = self.registers.r0Bh.read()?;
-        let r0ch_prev = self.registers.r0Ch.read()?;
-        let r0dh_prev = self.registers.r0Dh.read()?;
-        let r0eh_prev = self.registers.r0Eh.read()?;
-        let r0fh_prev = self.registers.r0Fh.read()?;
-        let r10h_prev = self.registers.r10h.read()?;
-        let r11h_prev = self.registers.r11h.read()?;
-        let r12h_prev = self.registers.r12h.read()?;
-        let r13h_prev = self.registers.r13h.read()?;
-        let r14h_prev = self.registers.r14h.read()?;
-        let r15h_prev = self.registers.r15h.read()?;
-        let r16h_prev = self.registers.r16h.read()?;
-        let r17h_prev = self.registers.r17h.read()?;
-        let r18h_prev = self.registers.r18h.read()?;
-        let r19h_prev = self.registers.r19h.read()?;
-        let r1ah_prev = self.registers.r1Ah.read()?;
-        let r1bh_prev = self.registers.r1Bh.read()?;
-        let r1ch_prev = self.registers.r1Ch.read()?;
-        let r1dh_prev = self.registers.r1Dh.read()?;
-        let r32h_prev = self.registers.r32h.read()?;
-        let r33h_prev = self.registers.r33h.read()?;
-        let r39h_prev = self.registers.r39h.read()?;
+        let r01h_prev = self.registers.r01h().read()?;
+        let r02h_prev = self.registers.r02h().read()?;
+        let r03h_prev = self.registers.r03h().read()?;
+        let r04h_prev = self.registers.r04h().read()?;
+        let r05h_prev = self.registers.r05h().read()?;
+        let r06h_prev = self.registers.r06h().read()?;
+        let r07h_prev = self.registers.r07h().read()?;
+        let r08h_prev = self.registers.r08h().read()?;
+        let r09h_prev = self.registers.r09h().read()?;
+        let r0ah_prev = self.registers.r0Ah().read()?;
+        let r0bh_prev = self.registers.r0Bh().read()?;
+        let r0ch_prev = self.registers.r0Ch().read()?;
+        let r0dh_prev = self.registers.r0Dh().read()?;
+        let r0eh_prev = self.registers.r0Eh().read()?;
+        let r0fh_prev = self.registers.r0Fh().read()?;
+        let r10h_prev = self.registers.r10h().read()?;
+        let r11h_prev = self.registers.r11h().read()?;
+        let r12h_prev = self.registers.r12h().read()?;
+        let r13h_prev = self.registers.r13h().read()?;
+        let r14h_prev = self.registers.r14h().read()?;
+        let r15h_prev = self.registers.r15h().read()?;
+        let r16h_prev = self.registers.r16h().read()?;
+        let r17h_prev = self.registers.r17h().read()?;
+        let r18h_prev = self.registers.r18h().read()?;
+        let r19h_prev = self.registers.r19h().read()?;
+        let r1ah_prev = self.registers.r1Ah().read()?;
+        let r1bh_prev = self.registers.r1Bh().read()?;
+        let r1ch_prev = self.registers.r1Ch().read()?;
+        let r1dh_prev = self.registers.r1Dh().read()?;
+        let r32h_prev = self.registers.r32h().read()?;
+        let r33h_prev = self.registers.r33h().read()?;
+        let r39h_prev = self.registers.r39h().read()?;
 
         let clk_div: f32 = match r39h_prev.clkdiv_prf() {
             0 => 1.0,
@@ -718,4 +1475,1229 @@ where
             ),
         ))
     }
+
+    /// Reinterprets the slot shared between LED3 and Ambient2 as the Ambient2 phase.
+    ///
+    /// # Notes
+    ///
+    /// The registers `R05h`, `R06h`, `R0Fh` and `R10h` are shared between the LED3 phase (three LEDs mode) and the Ambient2 phase (two LEDs mode).
+    /// Call this function after switching from three LEDs mode: the LED3 lighting window is cleared,
+    /// so that no LED is lit while the slot samples the ambient light.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub fn reinterpret_shared_slot_as_ambient2(&mut self) -> Result<(), AfeError<I2C::Error>> {
+        self.registers.r36h().write(R36h::new())?;
+        self.registers.r37h().write(R37h::new())?;
+
+        Ok(())
+    }
+
+    /// Updates the measurement window, writing only the registers whose values changed.
+    ///
+    /// # Notes
+    ///
+    /// The current measurement window is read and passed to the `patch` closure.
+    /// If the period changes the whole measurement window is written, since the timer quantisation may change.
+    /// After calling this function, a wait time of `tCHANNEL` should be applied before high-accuracy readings.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// Setting a window period too long for the current clock frequency or equal to zero will result in an error.
+    /// Setting a power-down window not satisfying `power_down_st < power_down_end <= period` will result in an error.
+    /// Setting a measurement window violating a timing constraint, see [`TimingViolation`], will result in an error.
+    pub fn update_timing_window(
+        &mut self,
+        patch: impl FnOnce(&mut MeasurementWindowConfiguration<TwoLedsMode>),
+    ) -> Result<MeasurementWindowConfiguration<TwoLedsMode>, AfeError<I2C::Error>> {
+        let configuration_prev = self.get_measurement_window()?;
+        let mut configuration = configuration_prev;
+        patch(&mut configuration);
+
+        self.write_changed_timings(&configuration_prev, &configuration)?;
+
+        self.get_measurement_window()
+    }
+
+    /// Sets the active phase of the measurement window, keeping the current period and power-down phase.
+    ///
+    /// # Notes
+    ///
+    /// The period and the clock division are read from the [`AFE4404`], only the active phase registers are written.
+    /// After calling this function, a wait time of `tCHANNEL` should be applied before high-accuracy readings.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub fn set_active_timing(
+        &mut self,
+        active: &ActiveTiming<TwoLedsMode>,
+    ) -> Result<ActiveTiming<TwoLedsMode>, AfeError<I2C::Error>> {
+        let configuration = self.update_timing_window(|configuration| {
+            *configuration.active_timing_configuration_mut() = *active;
+        })?;
+
+        Ok(*configuration.active_timing_configuration())
+    }
+
+    /// Gets the measurement window as a list of phases, each with its name, start and end in microseconds.
+    ///
+    /// # Notes
+    ///
+    /// This function is meant for quick human inspection or CSV export, use `get_measurement_window()` to get `uom` values.
+    /// The phases are listed LED by LED, followed by the Ambient phases and the power-down phase.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub fn schedule_micros(&mut self) -> Result<Vec<SchedulePhase>, AfeError<I2C::Error>> {
+        Ok(self
+            .get_measurement_window()?
+            .phases()
+            .into_iter()
+            .map(|(name, start, end)| (name, start.get::<microsecond>(), end.get::<microsecond>()))
+            .collect())
+    }
+
+    /// Gets the phases of every channel as a schedule sorted by start time.
+    ///
+    /// # Notes
+    ///
+    /// The lighting phase of a LED is expected to overlap its own sample phase, so only phases of the same kind are checked against each other.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// This function returns an error pinpointing the first pair of overlapping phases of the same kind.
+    pub fn schedule(&mut self) -> Result<Schedule<TwoLedsMode>, AfeError<I2C::Error>> {
+        Schedule::new(self.get_measurement_window()?.channel_phases()).map_err(|(first, second)| {
+            AfeError::PhaseOverlap {
+                kind: first.kind,
+                first: first.channel,
+                second: second.channel,
+            }
+        })
+    }
+
+    /// Mirrors the sample, reset and conversion phases of a LED into the matching Ambient phase, shifted by `offset`.
+    ///
+    /// # Notes
+    ///
+    /// LED1 is mirrored into Ambient1 and LED2 into Ambient2, so that each LED is paired with its own ambient subtraction.
+    /// The Ambient phase is sampled with the same window shape of the LED, but with all the LEDs off.
+    /// Only the Ambient registers are written.
+    /// After calling this function, a wait time of `tCHANNEL` should be applied before high-accuracy readings.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// Mirroring from a channel different from LED1 or LED2 will result in an error.
+    pub fn mirror_ambient_from(
+        &mut self,
+        led: LedChannel,
+        offset: Time,
+    ) -> Result<AmbientTiming, AfeError<I2C::Error>> {
+        if !matches!(led, LedChannel::Led1 | LedChannel::Led2) {
+            return Err(AfeError::ChannelNotAvailable { channel: led });
+        }
+
+        let configuration = self.update_timing_window(|configuration| {
+            let timing = configuration.active_timing_configuration_mut();
+            if led == LedChannel::Led1 {
+                *timing.ambient1_mut() = AmbientTiming::mirroring(timing.led1(), offset);
+            } else {
+                *timing.ambient2_mut() = AmbientTiming::mirroring(timing.led2(), offset);
+            }
+        })?;
+
+        Ok(if led == LedChannel::Led1 {
+            *configuration.active_timing_configuration().ambient1()
+        } else {
+            *configuration.active_timing_configuration().ambient2()
+        })
+    }
+}
+
+impl<I2C> AFE4404<I2C, OneLedMode>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    #![allow(
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_lossless,
+        clippy::too_many_lines
+    )]
+
+    /// Sets the LED and Ambient timings of the measurement window.
+    ///
+    /// # Notes
+    ///
+    /// This function automatically enables the timer engine.
+    /// The unused LED2 phase and the slot shared between LED3 and Ambient2 are cleared.
+    /// Negative timings will be rounded to zero.
+    /// After calling this function, a wait time of `tCHANNEL` should be applied before high-accuracy readings.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a window period too long for the current clock frequency or equal to zero will result in an error.
+    /// Setting a power-down window not satisfying `power_down_st < power_down_end <= period` will result in an error.
+    /// Setting a measurement window violating a timing constraint, see [`TimingViolation`], will result in an error.
+    pub fn set_measurement_window(
+        &mut self,
+        configuration: &MeasurementWindowConfiguration<OneLedMode>,
+    ) -> Result<MeasurementWindowConfiguration<OneLedMode>, AfeError<I2C::Error>> {
+        struct QuantisedValues {
+            lighting_st: u16,
+            lighting_end: u16,
+            sample_st: u16,
+            sample_end: u16,
+            reset_st: u16,
+            reset_end: u16,
+            conv_st: u16,
+            conv_end: u16,
+        }
+
+        let power_down = configuration.inactive_timing_configuration();
+        if power_down.power_down_st >= power_down.power_down_end
+            || power_down.power_down_end > *configuration.period()
+        {
+            return Err(AfeError::PowerDownWindowOutsideAllowedRange);
+        }
+        configuration.validate()?;
+
+        let r1eh_prev = self.registers.r1Eh().read()?;
+
+        let clk_div = ((*configuration.period() * self.clock).value / 65536.0).ceil() as u8;
+        let clk_div: (f32, u8) = match clk_div {
+            0 => return Err(AfeError::WindowPeriodOutsideAllowedRange),
+            1 => (1.0, 0), // (division ratio, register value).
+            2 => (2.0, 4),
+            d if d <= 4 => (4.0, 5),
+            d if d <= 8 => (8.0, 6),
+            d if d <= 16 => (16.0, 7),
+            _ => return Err(AfeError::WindowPeriodOutsideAllowedRange),
+        };
+        let period_clk: Time = 1.0 / self.clock;
+        let period_clk_div: Time = period_clk * clk_div.0;
+        let counter: f32 = (*configuration.period() / period_clk_div).value;
+        let counter_max_value: u16 = (counter - 1.0).round() as u16;
+        let quantisation: Time = *configuration.period() / counter;
+
+        let active_values: Vec<QuantisedValues> = [
+            *configuration.active_timing_configuration().led1(),
+            (*configuration.active_timing_configuration().ambient()).into(),
+        ]
+        .iter()
+        .map(|timing| QuantisedValues {
+            lighting_st: (timing.lighting_st / quantisation).value.round() as u16,
+            lighting_end: (timing.lighting_end / quantisation).value.round() as u16,
+            sample_st: (timing.sample_st / quantisation).value.round() as u16,
+            sample_end: (timing.sample_end / quantisation).value.round() as u16,
+            reset_st: (timing.reset_st / quantisation).value.round() as u16,
+            reset_end: (timing.reset_end / quantisation).value.round() as u16,
+            conv_st: (timing.conv_st / quantisation).value.round() as u16,
+            conv_end: (timing.conv_end / quantisation).value.round() as u16,
+        })
+        .collect();
+
+        let power_down_values = [
+            (configuration.inactive_timing_configuration().power_down_st / quantisation)
+                .value
+                .round() as u16,
+            (configuration.inactive_timing_configuration().power_down_end / quantisation)
+                .value
+                .round() as u16,
+        ];
+
+        // Enable timer engine.
+        self.registers
+            .r1Dh()
+            .write(R1Dh::new().with_prpct(counter_max_value))?;
+        self.registers
+            .r39h()
+            .write(R39h::new().with_clkdiv_prf(clk_div.1))?;
+        if let Some(quantisation) = self.timing_quantisation.as_mut() {
+            *quantisation = period_clk_div;
+        }
+        self.registers.r1Eh().write(r1eh_prev.with_timeren(true))?;
+
+        // Clear led2 registers.
+        self.registers.r09h().write(R09h::new())?;
+        self.registers.r0Ah().write(R0Ah::new())?;
+        self.registers.r01h().write(R01h::new())?;
+        self.registers.r02h().write(R02h::new())?;
+        self.registers.r15h().write(R15h::new())?;
+        self.registers.r16h().write(R16h::new())?;
+        self.registers.r0Dh().write(R0Dh::new())?;
+        self.registers.r0Eh().write(R0Eh::new())?;
+
+        // Clear the slot shared between led3 and ambient2.
+        self.registers.r36h().write(R36h::new())?;
+        self.registers.r37h().write(R37h::new())?;
+        self.registers.r05h().write(R05h::new())?;
+        self.registers.r06h().write(R06h::new())?;
+        self.registers.r17h().write(R17h::new())?;
+        self.registers.r18h().write(R18h::new())?;
+        self.registers.r0Fh().write(R0Fh::new())?;
+        self.registers.r10h().write(R10h::new())?;
+
+        // Write led1 registers.
+        self.registers
+            .r03h()
+            .write(R03h::new().with_led1ledstc(active_values[0].lighting_st))?;
+        self.registers
+            .r04h()
+            .write(R04h::new().with_led1ledendc(active_values[0].lighting_end))?;
+        self.registers
+            .r07h()
+            .write(R07h::new().with_led1stc(active_values[0].sample_st))?;
+        self.registers
+            .r08h()
+            .write(R08h::new().with_led1endc(active_values[0].sample_end))?;
+        self.registers
+            .r19h()
+            .write(R19h::new().with_adcrststct2(active_values[0].reset_st))?;
+        self.registers
+            .r1Ah()
+            .write(R1Ah::new().with_adcrstendct2(active_values[0].reset_end))?;
+        self.registers
+            .r11h()
+            .write(R11h::new().with_led1convst(active_values[0].conv_st))?;
+        self.registers
+            .r12h()
+            .write(R12h::new().with_led1convend(active_values[0].conv_end))?;
+
+        // Write ambient registers.
+        self.registers
+            .r0Bh()
+            .write(R0Bh::new().with_aled1stc(active_values[1].sample_st))?;
+        self.registers
+            .r0Ch()
+            .write(R0Ch::new().with_aled1endc(active_values[1].sample_end))?;
+        self.registers
+            .r1Bh()
+            .write(R1Bh::new().with_adcrststct3(active_values[1].reset_st))?;
+        self.registers
+            .r1Ch()
+            .write(R1Ch::new().with_adcrstendct3(active_values[1].reset_end))?;
+        self.registers
+            .r13h()
+            .write(R13h::new().with_aled1convst(active_values[1].conv_st))?;
+        self.registers
+            .r14h()
+            .write(R14h::new().with_aled1convend(active_values[1].conv_end))?;
+
+        // Write dynamic power down registers.
+        self.registers
+            .r32h()
+            .write(R32h::new().with_pdncyclestc(power_down_values[0]))?;
+        self.registers
+            .r33h()
+            .write(R33h::new().with_pdncycleendc(power_down_values[1]))?;
+
+        Ok(MeasurementWindowConfiguration::<OneLedMode>::new(
+            (counter_max_value + 1) as f32 * quantisation,
+            ActiveTiming::<OneLedMode>::new(
+                LedTiming {
+                    lighting_st: active_values[0].lighting_st as f32 * quantisation,
+                    lighting_end: active_values[0].lighting_end as f32 * quantisation,
+                    sample_st: active_values[0].sample_st as f32 * quantisation,
+                    sample_end: active_values[0].sample_end as f32 * quantisation,
+                    reset_st: active_values[0].reset_st as f32 * quantisation,
+                    reset_end: active_values[0].reset_end as f32 * quantisation,
+                    conv_st: active_values[0].conv_st as f32 * quantisation,
+                    conv_end: active_values[0].conv_end as f32 * quantisation,
+                },
+                AmbientTiming {
+                    sample_st: active_values[1].sample_st as f32 * quantisation,
+                    sample_end: active_values[1].sample_end as f32 * quantisation,
+                    reset_st: active_values[1].reset_st as f32 * quantisation,
+                    reset_end: active_values[1].reset_end as f32 * quantisation,
+                    conv_st: active_values[1].conv_st as f32 * quantisation,
+                    conv_end: active_values[1].conv_end as f32 * quantisation,
+                },
+            ),
+            PowerDownTiming {
+                power_down_st: power_down_values[0] as f32 * quantisation,
+                power_down_end: power_down_values[1] as f32 * quantisation,
+            },
+        ))
+    }
+
+    /// Gets the LED and Ambient timings of the measurement window.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    #[allow(clippy::similar_names)]
+    pub fn get_measurement_window(
+        &mut self,
+    ) -> Result<MeasurementWindowConfiguration<OneLedMode>, AfeError<I2C::Error>> {
+        let r03h_prev = self.registers.r03h().read()?;
+        let r04h_prev = self.registers.r04h().read()?;
+        let r07h_prev = self.registers.r07h().read()?;
+        let r08h_prev = self.registers.r08h().read()?;
+        let r0bh_prev = self.registers.r0Bh().read()?;
+        let r0ch_prev = self.registers.r0Ch().read()?;
+        let r11h_prev = self.registers.r11h().read()?;
+        let r12h_prev = self.registers.r12h().read()?;
+        let r13h_prev = self.registers.r13h().read()?;
+        let r14h_prev = self.registers.r14h().read()?;
+        let r19h_prev = self.registers.r19h().read()?;
+        let r1ah_prev = self.registers.r1Ah().read()?;
+        let r1bh_prev = self.registers.r1Bh().read()?;
+        let r1ch_prev = self.registers.r1Ch().read()?;
+        let r1dh_prev = self.registers.r1Dh().read()?;
+        let r32h_prev = self.registers.r32h().read()?;
+        let r33h_prev = self.registers.r33h().read()?;
+        let r39h_prev = self.registers.r39h().read()?;
+
+        let clk_div: f32 = match r39h_prev.clkdiv_prf() {
+            0 => 1.0,
+            4 => 2.0,
+            5 => 4.0,
+            6 => 8.0,
+            7 => 16.0,
+            _ => return Err(AfeError::InvalidRegisterValue { reg_addr: 0x39 }),
+        };
+        let period_clk_div = clk_div / self.clock;
+        let period = (r1dh_prev.prpct() + 1) as f32 * period_clk_div;
+        let quantisation = period_clk_div;
+
+        Ok(MeasurementWindowConfiguration::<OneLedMode>::new(
+            period,
+            ActiveTiming::<OneLedMode>::new(
+                LedTiming {
+                    lighting_st: r03h_prev.led1ledstc() as f32 * quantisation,
+                    lighting_end: r04h_prev.led1ledendc() as f32 * quantisation,
+                    sample_st: r07h_prev.led1stc() as f32 * quantisation,
+                    sample_end: r08h_prev.led1endc() as f32 * quantisation,
+                    reset_st: r19h_prev.adcrststct2() as f32 * quantisation,
+                    reset_end: r1ah_prev.adcrstendct2() as f32 * quantisation,
+                    conv_st: r11h_prev.led1convst() as f32 * quantisation,
+                    conv_end: r12h_prev.led1convend() as f32 * quantisation,
+                },
+                AmbientTiming {
+                    sample_st: r0bh_prev.aled1stc() as f32 * quantisation,
+                    sample_end: r0ch_prev.aled1endc() as f32 * quantisation,
+                    reset_st: r1bh_prev.adcrststct3() as f32 * quantisation,
+                    reset_end: r1ch_prev.adcrstendct3() as f32 * quantisation,
+                    conv_st: r13h_prev.aled1convst() as f32 * quantisation,
+                    conv_end: r14h_prev.aled1convend() as f32 * quantisation,
+                },
+            ),
+            PowerDownTiming::new(
+                r32h_prev.pdncyclestc() as f32 * quantisation,
+                r33h_prev.pdncycleendc() as f32 * quantisation,
+            ),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal::{delay::DelayUs, i2c::ErrorKind};
+    use uom::si::{
+        f32::{Frequency, Time},
+        frequency::hertz,
+        time::microsecond,
+    };
+
+    use super::{
+        AmbientTiming, LedTiming, MeasurementWindowConfiguration, PhaseKind, PowerDownTiming,
+    };
+    use crate::{
+        channel::LedChannel,
+        device::AFE4404,
+        errors::AfeError,
+        mock::{BusError, MockAfe},
+        modes::ThreeLedsMode,
+        system::State,
+    };
+
+    /// Creates a three LEDs [`AFE4404`] with a 10 ms window driven by a clock division of 4, larger than the minimal one.
+    fn larger_clock_division(mock: &MockAfe) -> AFE4404<MockAfe, ThreeLedsMode> {
+        let mut afe = mock.three_leds();
+
+        let mut configuration = MeasurementWindowConfiguration::default();
+        *configuration.inactive_timing_configuration_mut() = PowerDownTiming::new(
+            Time::new::<microsecond>(1918.75),
+            Time::new::<microsecond>(2400.0),
+        );
+        afe.set_measurement_window(&configuration).unwrap();
+        afe.set_window_clock_division(4).unwrap();
+        afe.set_window_counter(9_999).unwrap();
+
+        afe
+    }
+
+    #[test]
+    fn timer_counter_reset_keeps_staged_writes() {
+        let mock = MockAfe::new();
+        let mut afe = mock.two_leds();
+
+        afe.stage_writes();
+        afe.set_averaging(4).unwrap();
+        afe.reset_timer_counter().unwrap();
+        afe.commit().unwrap();
+
+        assert_eq!(mock.register(0x1E) & 0x0F, 3);
+    }
+
+    #[test]
+    fn update_timing_window_writes_only_changed_registers() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        afe.set_register_cache(State::Enabled);
+        afe.set_measurement_window(&MeasurementWindowConfiguration::default())
+            .unwrap();
+        afe.get_measurement_window().unwrap();
+        mock.reset_transactions();
+
+        // With the register cache filled, only the writes reach the bus.
+        afe.update_timing_window(|configuration| {
+            configuration
+                .active_timing_configuration_mut()
+                .led1_mut()
+                .sample_end -= Time::new::<microsecond>(1.0);
+        })
+        .unwrap();
+
+        assert_eq!(mock.transactions(), 1);
+    }
+
+    #[test]
+    fn window_timings_read_in_every_mode() {
+        let mock = MockAfe::new();
+
+        let mut afe = mock.three_leds();
+        let configuration = afe
+            .set_measurement_window(&MeasurementWindowConfiguration::default())
+            .unwrap();
+        assert_eq!(afe.read_window_timings().unwrap(), configuration);
+
+        let mut afe = mock.two_leds();
+        let configuration = afe
+            .set_measurement_window(&MeasurementWindowConfiguration::default())
+            .unwrap();
+        assert_eq!(afe.read_window_timings().unwrap(), configuration);
+
+        let mut afe = mock.one_led();
+        let configuration = afe
+            .set_measurement_window(&MeasurementWindowConfiguration::default())
+            .unwrap();
+        assert_eq!(afe.read_window_timings().unwrap(), configuration);
+    }
+
+    #[test]
+    fn configure_for_odr_rescales_window() {
+        let mock = MockAfe::new();
+        let mut afe = mock.one_led();
+
+        let configuration_prev = afe
+            .set_measurement_window(&MeasurementWindowConfiguration::default())
+            .unwrap();
+        let rate = afe
+            .configure_for_odr(Frequency::new::<hertz>(25.0))
+            .unwrap();
+        let configuration = afe.get_measurement_window().unwrap();
+
+        assert!((rate.get::<hertz>() - 25.0).abs() < 0.01);
+        assert_eq!(
+            configuration.active_timing_configuration().led1().sample_st,
+            configuration_prev
+                .active_timing_configuration()
+                .led1()
+                .sample_st
+                * 4.0
+        );
+    }
+
+    #[test]
+    fn update_timing_window_keeps_larger_clock_division() {
+        let mock = MockAfe::new();
+        let mut afe = larger_clock_division(&mock);
+        let configuration_prev = afe.get_measurement_window().unwrap();
+
+        let configuration = afe
+            .update_timing_window(|configuration| {
+                configuration
+                    .inactive_timing_configuration_mut()
+                    .power_down_end = Time::new::<microsecond>(9_800.0);
+            })
+            .unwrap();
+
+        assert_eq!(mock.register(0x39), 5);
+        assert_eq!(mock.register(0x1D), 9_999);
+        assert_eq!(mock.register(0x03), 800);
+        assert_eq!(configuration.period(), configuration_prev.period());
+        assert_eq!(
+            configuration.active_timing_configuration(),
+            configuration_prev.active_timing_configuration()
+        );
+        assert_eq!(
+            configuration.inactive_timing_configuration().power_down_end,
+            Time::new::<microsecond>(9_800.0)
+        );
+    }
+
+    #[test]
+    fn set_active_timing_keeps_unchanged_edges_with_larger_clock_division() {
+        let mock = MockAfe::new();
+        let mut afe = larger_clock_division(&mock);
+        let configuration_prev = afe.get_measurement_window().unwrap();
+
+        let mut active = *configuration_prev.active_timing_configuration();
+        active.led1_mut().sample_end -= Time::new::<microsecond>(1.0);
+        let active = afe.set_active_timing(&active).unwrap();
+        let configuration = afe.get_measurement_window().unwrap();
+
+        let led1_prev = configuration_prev.active_timing_configuration().led1();
+        assert_eq!(
+            active.led1().sample_end,
+            led1_prev.sample_end - Time::new::<microsecond>(1.0)
+        );
+        assert_eq!(active.led1().lighting_st, led1_prev.lighting_st);
+        assert_eq!(active.led1().conv_end, led1_prev.conv_end);
+        assert_eq!(
+            active.led2(),
+            configuration_prev.active_timing_configuration().led2()
+        );
+        assert_eq!(
+            active.led3(),
+            configuration_prev.active_timing_configuration().led3()
+        );
+        assert_eq!(configuration.period(), configuration_prev.period());
+        assert_eq!(
+            configuration.inactive_timing_configuration(),
+            configuration_prev.inactive_timing_configuration()
+        );
+        assert_eq!(mock.register(0x39), 5);
+    }
+
+    #[test]
+    fn configure_for_odr_rescales_from_larger_clock_division() {
+        let mock = MockAfe::new();
+        let mut afe = larger_clock_division(&mock);
+        let configuration_prev = afe.get_measurement_window().unwrap();
+
+        let rate = afe
+            .configure_for_odr(Frequency::new::<hertz>(50.0))
+            .unwrap();
+        let configuration = afe.get_measurement_window().unwrap();
+
+        assert!((rate.get::<hertz>() - 50.0).abs() < 0.01);
+        assert_eq!(mock.register(0x39), 4);
+        for (timing_prev, timing) in configuration_prev
+            .timings()
+            .into_iter()
+            .zip(configuration.timings())
+        {
+            assert!((timing - timing_prev * 2.0).get::<microsecond>().abs() < 0.5);
+        }
+    }
+
+    #[test]
+    fn timer_verified_on_device_with_register_cache() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+        afe.set_register_cache(State::Enabled);
+
+        afe.set_measurement_window(&MeasurementWindowConfiguration::default())
+            .unwrap();
+        afe.verify_timer_enabled().unwrap();
+
+        mock.set_register(0x1E, 0);
+        assert!(matches!(
+            afe.verify_timer_enabled(),
+            Err(AfeError::WriteVerificationFailed {
+                reg_addr: 0x1E,
+                channel: None,
+            })
+        ));
+    }
+
+    #[test]
+    fn shared_slot_reinterpreted_as_ambient2() {
+        let mock = MockAfe::new();
+        mock.three_leds()
+            .set_measurement_window(&MeasurementWindowConfiguration::default())
+            .unwrap();
+        assert_ne!(mock.register(0x37), 0);
+
+        let mut afe = mock.two_leds();
+        afe.reinterpret_shared_slot_as_ambient2().unwrap();
+
+        assert_eq!(mock.register(0x36), 0);
+        assert_eq!(mock.register(0x37), 0);
+        assert_ne!(mock.register(0x06), 0);
+    }
+
+    #[test]
+    fn shared_slot_reinterpreted_as_led3() {
+        let mock = MockAfe::new();
+        mock.two_leds()
+            .set_measurement_window(&MeasurementWindowConfiguration::default())
+            .unwrap();
+        assert_eq!(mock.register(0x37), 0);
+
+        let mut afe = mock.three_leds();
+        afe.reinterpret_shared_slot_as_led3().unwrap();
+
+        assert_eq!(mock.register(0x36), mock.register(0x05));
+        assert_eq!(mock.register(0x37), mock.register(0x06));
+        let led3 = *afe
+            .get_measurement_window()
+            .unwrap()
+            .active_timing_configuration()
+            .led3();
+        assert_eq!(led3.lighting_st, led3.sample_st);
+        assert_eq!(led3.lighting_end, led3.sample_end);
+    }
+
+    #[test]
+    fn sample_rate_verified_within_tolerance() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+        afe.set_window_counter(39_999).unwrap();
+
+        for measured in [99.5, 100.0, 100.5] {
+            assert!(afe
+                .verify_sample_rate(Frequency::new::<hertz>(measured), 0.01)
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn sample_rate_outside_tolerance_rejected() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+        afe.set_window_counter(39_999).unwrap();
+
+        for measured in [98.0, 102.0, 200.0] {
+            assert!(matches!(
+                afe.verify_sample_rate(Frequency::new::<hertz>(measured), 0.01),
+                Err(AfeError::ClockMismatch)
+            ));
+        }
+    }
+
+    #[test]
+    fn inverted_power_down_window_rejected() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        let mut configuration = MeasurementWindowConfiguration::default();
+        let power_down = configuration.inactive_timing_configuration_mut();
+        core::mem::swap(
+            &mut power_down.power_down_st,
+            &mut power_down.power_down_end,
+        );
+
+        assert!(matches!(
+            afe.set_measurement_window(&configuration),
+            Err(AfeError::PowerDownWindowOutsideAllowedRange)
+        ));
+        assert_eq!(mock.transactions(), 0);
+
+        configuration
+            .inactive_timing_configuration_mut()
+            .power_down_st = configuration.inactive_timing_configuration().power_down_end;
+        assert!(matches!(
+            afe.set_measurement_window(&configuration),
+            Err(AfeError::PowerDownWindowOutsideAllowedRange)
+        ));
+    }
+
+    #[test]
+    fn overflowing_power_down_window_rejected() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+        afe.set_measurement_window(&MeasurementWindowConfiguration::default())
+            .unwrap();
+        let period = *afe.get_measurement_window().unwrap().period();
+
+        let result = afe.update_timing_window(|configuration| {
+            configuration
+                .inactive_timing_configuration_mut()
+                .power_down_end = period + Time::new::<microsecond>(1.0);
+        });
+        assert!(matches!(
+            result,
+            Err(AfeError::PowerDownWindowOutsideAllowedRange)
+        ));
+
+        let configuration = afe
+            .update_timing_window(|configuration| {
+                configuration
+                    .inactive_timing_configuration_mut()
+                    .power_down_end = period;
+            })
+            .unwrap();
+        assert_eq!(
+            configuration.inactive_timing_configuration().power_down_end,
+            period
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn sample_interval_is_inverse_of_sample_rate() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+        afe.set_window_counter(39_999).unwrap();
+
+        for (decimation, interval) in [(1, 10_000.0), (4, 40_000.0), (16, 160_000.0)] {
+            afe.set_decimation(decimation).unwrap();
+
+            let sample_interval = afe.sample_interval().unwrap();
+            assert!((sample_interval.get::<microsecond>() - interval).abs() < 1e-2);
+            assert!(
+                ((1.0 / sample_interval).get::<hertz>()
+                    - afe.get_sample_rate().unwrap().get::<hertz>())
+                .abs()
+                    < 1e-4
+            );
+        }
+    }
+
+    /// Asserts that an Ambient phase has the durations of a LED phase, shifted by `offset`.
+    fn assert_mirrors(ambient: &AmbientTiming, led: &LedTiming, offset: Time) {
+        for (ambient, led) in [
+            (ambient.sample_st, led.sample_st),
+            (ambient.sample_end, led.sample_end),
+            (ambient.reset_st, led.reset_st),
+            (ambient.reset_end, led.reset_end),
+            (ambient.conv_st, led.conv_st),
+            (ambient.conv_end, led.conv_end),
+        ] {
+            assert!(((ambient - led - offset).get::<microsecond>()).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn ambient_mirrored_from_led_in_three_leds_mode() {
+        let mut afe = MockAfe::new().three_leds();
+        afe.set_measurement_window(&MeasurementWindowConfiguration::default())
+            .unwrap();
+        let offset = Time::new::<microsecond>(117.5);
+
+        let ambient = afe.mirror_ambient_from(LedChannel::Led1, offset).unwrap();
+
+        let configuration = afe.get_measurement_window().unwrap();
+        let active = configuration.active_timing_configuration();
+        assert_eq!(ambient, *active.ambient());
+        assert_mirrors(&ambient, active.led1(), offset);
+    }
+
+    #[test]
+    fn ambient1_mirrored_from_led1_in_two_leds_mode() {
+        let mut afe = MockAfe::new().two_leds();
+        afe.set_measurement_window(&MeasurementWindowConfiguration::default())
+            .unwrap();
+        let ambient2 = *afe
+            .get_measurement_window()
+            .unwrap()
+            .active_timing_configuration()
+            .ambient2();
+        let offset = Time::new::<microsecond>(117.5);
+
+        let ambient = afe.mirror_ambient_from(LedChannel::Led1, offset).unwrap();
+
+        let configuration = afe.get_measurement_window().unwrap();
+        let active = configuration.active_timing_configuration();
+        assert_eq!(ambient, *active.ambient1());
+        assert_eq!(ambient2, *active.ambient2());
+        assert_mirrors(&ambient, active.led1(), offset);
+    }
+
+    #[test]
+    fn ambient_not_mirrored_from_ambient() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        assert!(matches!(
+            afe.mirror_ambient_from(LedChannel::Ambient1, Time::new::<microsecond>(0.0)),
+            Err(AfeError::ChannelNotAvailable {
+                channel: LedChannel::Ambient1
+            })
+        ));
+        assert_eq!(mock.transactions(), 0);
+    }
+
+    #[test]
+    fn sample_lighting_overlap_full_partial_and_zero() {
+        let mut afe = MockAfe::new().three_leds();
+        let us = Time::new::<microsecond>;
+        afe.set_led1_sample_st(us(25.0)).unwrap();
+        afe.set_led1_sample_end(us(99.0)).unwrap();
+
+        for (lighting, overlap) in [
+            ((0.0, 100.0), 74.0),
+            ((50.0, 150.0), 49.0),
+            ((30.0, 40.0), 10.0),
+            ((100.0, 200.0), 0.0),
+            ((0.0, 0.0), 0.0),
+        ] {
+            afe.set_led1_lighting_st(us(lighting.0)).unwrap();
+            afe.set_led1_lighting_end(us(lighting.1)).unwrap();
+
+            let measured = afe.sample_lighting_overlap(LedChannel::Led1).unwrap();
+            assert!((measured.get::<microsecond>() - overlap).abs() < 1e-3);
+        }
+
+        assert!(
+            afe.sample_lighting_overlap(LedChannel::Ambient1)
+                .unwrap()
+                .get::<microsecond>()
+                .abs()
+                < 1e-3
+        );
+    }
+
+    #[test]
+    fn default_window_timing_is_exact() {
+        let mock = MockAfe::new();
+        let afe = mock.three_leds();
+
+        assert!(afe.is_timing_exact(&MeasurementWindowConfiguration::default()));
+    }
+
+    #[test]
+    fn sub_count_timing_is_lossy() {
+        let mock = MockAfe::new();
+        let afe = mock.three_leds();
+
+        // 0.1 µs is not a multiple of the 0.25 µs quantisation.
+        let mut configuration = MeasurementWindowConfiguration::<ThreeLedsMode>::default();
+        configuration
+            .active_timing_configuration_mut()
+            .led2_mut()
+            .sample_st += Time::new::<microsecond>(0.1);
+        assert!(!afe.is_timing_exact(&configuration));
+
+        // A period requiring a clock division above 16 is never exact.
+        let mut configuration = MeasurementWindowConfiguration::<ThreeLedsMode>::default();
+        *configuration.period_mut() = Time::new::<microsecond>(300_000.0);
+        assert!(!afe.is_timing_exact(&configuration));
+    }
+
+    #[test]
+    fn timer_enabled_verified_after_window() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        afe.set_measurement_window(&MeasurementWindowConfiguration::default())
+            .unwrap();
+
+        afe.verify_timer_enabled().unwrap();
+    }
+
+    #[test]
+    fn dropped_timer_enable_fails_verification() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        // The device acknowledges the writes without storing them, while the cache keeps the written values.
+        mock.set_writes_ignored(true);
+        afe.set_measurement_window(&MeasurementWindowConfiguration::default())
+            .unwrap();
+        mock.set_writes_ignored(false);
+
+        assert_eq!(mock.register(0x1E), 0);
+        assert!(matches!(
+            afe.verify_timer_enabled(),
+            Err(AfeError::WriteVerificationFailed {
+                reg_addr: 0x1E,
+                channel: None
+            })
+        ));
+    }
+
+    #[test]
+    fn schedule_micros_lists_default_window() {
+        let mock = MockAfe::new();
+        let mut afe = mock.two_leds();
+        afe.set_measurement_window(&MeasurementWindowConfiguration::default())
+            .unwrap();
+
+        let expected = [
+            ("LED1 lighting", 200.0, 299.75),
+            ("LED1 sample", 225.0, 299.75),
+            ("LED1 reset", 1634.5, 1636.0),
+            ("LED1 conversion", 1636.5, 1751.5),
+            ("LED2 lighting", 0.0, 99.75),
+            ("LED2 sample", 25.0, 99.75),
+            ("LED2 reset", 1400.0, 1401.5),
+            ("LED2 conversion", 1402.0, 1516.75),
+            ("Ambient1 sample", 325.0, 399.75),
+            ("Ambient1 reset", 1752.0, 1753.5),
+            ("Ambient1 conversion", 1754.0, 1868.75),
+            ("Ambient2 sample", 125.0, 199.75),
+            ("Ambient2 reset", 1517.25, 1518.75),
+            ("Ambient2 conversion", 1519.25, 1634.0),
+            ("Power-down", 1918.75, 9799.75),
+        ];
+
+        let schedule = afe.schedule_micros().unwrap();
+        assert_eq!(schedule.len(), expected.len());
+        for ((name, start, end), (expected_name, expected_start, expected_end)) in
+            schedule.into_iter().zip(expected)
+        {
+            assert_eq!(name, expected_name);
+            assert!((start - expected_start).abs() < 1e-3, "{name} start");
+            assert!((end - expected_end).abs() < 1e-3, "{name} end");
+        }
+    }
+
+    #[test]
+    fn invalid_clock_division_repaired_to_undivided_clock() {
+        let mock = MockAfe::new();
+        mock.set_register(0x1D, 39_999);
+        mock.set_register(0x39, 2);
+        let mut afe = mock.three_leds();
+        assert!(afe.get_window_period().is_err());
+
+        afe.repair_clkdiv().unwrap();
+
+        assert_eq!(mock.register(0x39), 0);
+        assert!((afe.get_window_period().unwrap().get::<microsecond>() - 10_000.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn invalid_clock_division_repaired_from_edited_quantisation() {
+        let mock = MockAfe::new();
+        let mut afe = larger_clock_division(&mock);
+        let mut editor = afe.begin_timing_edit().unwrap();
+
+        mock.set_register(0x39, 2);
+        editor.repair_clkdiv().unwrap();
+
+        assert_eq!(mock.register(0x39), 5);
+    }
+
+    #[test]
+    fn valid_clock_division_not_rewritten() {
+        let mock = MockAfe::new();
+        mock.set_register(0x39, 5);
+        let mut afe = mock.three_leds();
+
+        afe.repair_clkdiv().unwrap();
+
+        assert_eq!(mock.register(0x39), 5);
+        // A single uncached configuration read.
+        assert_eq!(mock.transactions(), 4);
+    }
+
+    #[test]
+    fn disabled_ambient_registers_zeroed() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+        afe.set_measurement_window(&MeasurementWindowConfiguration::default())
+            .unwrap();
+        let timings_prev: alloc::vec::Vec<u32> = (0x01..=0x1C)
+            .map(|reg_addr| mock.register(reg_addr))
+            .collect();
+
+        afe.disable_ambient().unwrap();
+
+        // Ambient sample, conversion and reset registers.
+        let ambient = [0x0B, 0x0C, 0x13, 0x14, 0x1B, 0x1C];
+        for (reg_addr, prev) in (0x01..=0x1C).zip(timings_prev) {
+            if ambient.contains(&reg_addr) {
+                assert_ne!(prev, 0);
+                assert_eq!(mock.register(reg_addr), 0);
+            } else {
+                assert_eq!(mock.register(reg_addr), prev);
+            }
+        }
+    }
+
+    #[test]
+    fn remaining_budget_after_latest_conversion() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+        afe.set_measurement_window(&MeasurementWindowConfiguration::default())
+            .unwrap();
+
+        // The Ambient conversion ends last, at 1868.75 µs of the 10 ms period.
+        let budget = afe.remaining_window_budget().unwrap();
+        assert!((budget.get::<microsecond>() - 8_131.25).abs() < 1e-2);
+
+        // A period shorter than the conversions leaves no room.
+        afe.set_window_counter(5_000).unwrap();
+        assert_eq!(
+            afe.remaining_window_budget().unwrap(),
+            Time::new::<microsecond>(0.0)
+        );
+    }
+
+    #[test]
+    fn schedule_sorted_without_overlaps() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+        afe.set_measurement_window(&MeasurementWindowConfiguration::default())
+            .unwrap();
+
+        let schedule = afe.schedule().unwrap();
+
+        // Four phases for each LED and three for the Ambient.
+        assert_eq!(schedule.phases().len(), 15);
+        for (i, second) in schedule.iter().enumerate() {
+            for first in &schedule.phases()[..i] {
+                assert!(first.start <= second.start);
+                assert!(first.kind != second.kind || first.end <= second.start);
+            }
+        }
+    }
+
+    #[test]
+    fn schedule_reports_first_overlapping_pair() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+        afe.set_measurement_window(&MeasurementWindowConfiguration::default())
+            .unwrap();
+        // The Ambient sample now starts inside the LED1 sample.
+        mock.set_register(0x0B, 1000);
+
+        assert!(matches!(
+            afe.schedule(),
+            Err(AfeError::PhaseOverlap {
+                kind: PhaseKind::Sample,
+                first: LedChannel::Led1,
+                second: LedChannel::Ambient1,
+            })
+        ));
+    }
+
+    /// Represents a delay recording whether the timer engine is enabled and the requested duration, optionally failing.
+    struct WindowDelay {
+        mock: MockAfe,
+        recorded: Option<(bool, u32)>,
+        fails: bool,
+    }
+
+    impl WindowDelay {
+        fn new(mock: &MockAfe, fails: bool) -> Self {
+            Self {
+                mock: mock.clone(),
+                recorded: None,
+                fails,
+            }
+        }
+    }
+
+    impl DelayUs for WindowDelay {
+        type Error = ();
+
+        fn delay_us(&mut self, us: u32) -> Result<(), Self::Error> {
+            self.recorded = Some((self.mock.register(0x1E) & 1 << 8 != 0, us));
+
+            if self.fails {
+                Err(())
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    /// Creates a three LEDs [`AFE4404`] with the default 10 ms window in one-shot mode.
+    fn one_shot(mock: &MockAfe) -> AFE4404<MockAfe, ThreeLedsMode> {
+        let mut afe = mock.three_leds();
+        afe.set_measurement_window(&MeasurementWindowConfiguration::default())
+            .unwrap();
+        afe.enter_one_shot().unwrap();
+        assert_eq!(mock.register(0x1E) & 1 << 8, 0);
+        mock.reset_transactions();
+
+        afe
+    }
+
+    #[test]
+    fn single_conversion_runs_one_window() {
+        let mock = MockAfe::new();
+        let mut afe = one_shot(&mock);
+        let mut delay = WindowDelay::new(&mock, false);
+
+        afe.trigger_single_conversion(&mut delay).unwrap();
+
+        // The timer engine runs for one period, restarted from the beginning of the window.
+        assert_eq!(delay.recorded, Some((true, 10_000)));
+        assert_eq!(mock.register(0x1E) & 1 << 8, 0);
+        assert!(mock
+            .control_writes()
+            .windows(2)
+            .any(|pair| pair == [0b10, 0]));
+    }
+
+    #[test]
+    fn single_conversion_delay_error_stops_timer() {
+        let mock = MockAfe::new();
+        let mut afe = one_shot(&mock);
+        let mut delay = WindowDelay::new(&mock, true);
+
+        assert!(matches!(
+            afe.trigger_single_conversion(&mut delay),
+            Err(AfeError::DelayError)
+        ));
+        assert_eq!(delay.recorded, Some((true, 10_000)));
+        assert_eq!(mock.register(0x1E) & 1 << 8, 0);
+    }
+
+    #[test]
+    fn single_conversion_rejects_invalid_clock_division() {
+        let mock = MockAfe::new();
+        let mut afe = one_shot(&mock);
+        mock.set_register(0x39, 2);
+        let mut delay = WindowDelay::new(&mock, false);
+
+        assert!(matches!(
+            afe.trigger_single_conversion(&mut delay),
+            Err(AfeError::InvalidRegisterValue { reg_addr: 0x39 })
+        ));
+        assert_eq!(delay.recorded, None);
+        assert!(mock.written_registers().is_empty());
+    }
+
+    #[test]
+    fn held_timer_counter_released() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        afe.hold_timer_counter().unwrap();
+        afe.release_timer_counter().unwrap();
+
+        assert_eq!(mock.control_writes(), [0b10, 0]);
+    }
+
+    #[test]
+    fn aligned_counters_all_held_before_release() {
+        let mock = MockAfe::new();
+        let mut first = mock.three_leds();
+        let mut second = mock.three_leds();
+
+        AFE4404::align_timer_counters(&mut [&mut first, &mut second]).unwrap();
+
+        assert_eq!(mock.control_writes(), [0b10, 0b10, 0, 0]);
+    }
+
+    #[test]
+    fn failed_hold_releases_held_counters() {
+        let mock = MockAfe::new();
+        let mut first = mock.flaky_three_leds(0, ErrorKind::Bus);
+        let mut second = mock.flaky_three_leds(1, ErrorKind::Bus);
+
+        assert!(matches!(
+            AFE4404::align_timer_counters(&mut [&mut first, &mut second]),
+            Err(AfeError::I2CError(BusError(ErrorKind::Bus)))
+        ));
+        // The first counter is not left stuck in reset.
+        assert_eq!(mock.control_writes(), [0b10, 0]);
+    }
 }
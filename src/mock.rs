@@ -0,0 +1,513 @@
+//! This module contains an in-memory model of the [`AFE4404`] registers, used to test configuration code without hardware.
+
+use alloc::{sync::Arc, vec::Vec};
+
+use embedded_hal::i2c::{ErrorType, I2c, Operation, SevenBitAddress};
+use spin::Mutex;
+#[cfg(test)]
+use uom::si::{f32::Frequency, frequency::megahertz};
+
+#[cfg(test)]
+use embedded_hal::i2c::{Error, ErrorKind};
+
+#[cfg(test)]
+use crate::{
+    device::{AFE4404, DEFAULT_ADDRESS},
+    modes::{OneLedMode, ThreeLedsMode, TwoLedsMode},
+};
+
+/// The number of registers in the model, from `R00h` to `R40h`.
+const REGISTERS_COUNT: usize = 0x41;
+
+/// Represents the state shared between all the handles of a [`MockAfe`].
+#[derive(Debug)]
+struct MockState {
+    registers: [u32; REGISTERS_COUNT],
+    pointer: u8,
+    reg_read: bool,
+    writes_ignored: bool,
+    transactions: usize,
+    written: Vec<u8>,
+    control: Vec<u32>,
+}
+
+/// Represents an in-memory [`AFE4404`] reachable through the `I2c` trait.
+///
+/// # Notes
+///
+/// Clones share the same registers, so a clone can be kept to seed the value registers or inspect the written ones after the bus is moved into the driver.
+/// The `REG_READ` bit of `R00h` is honored: configuration registers read as zero while it is cleared and writes are ignored while it is set.
+/// Setting the `SW_RESET` bit clears all the registers.
+/// The physical address is not checked.
+#[derive(Clone, Debug)]
+pub struct MockAfe {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl Default for MockAfe {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockAfe {
+    /// Creates a new `MockAfe` with all the registers cleared.
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(MockState {
+                registers: [0; REGISTERS_COUNT],
+                pointer: 0,
+                reg_read: false,
+                writes_ignored: false,
+                transactions: 0,
+                written: Vec::new(),
+                control: Vec::new(),
+            })),
+        }
+    }
+
+    /// Gets the 24 bit content of a register, regardless of the `REG_READ` bit.
+    ///
+    /// # Notes
+    ///
+    /// Addresses outside the register map read as zero.
+    pub fn register(&self, reg_addr: u8) -> u32 {
+        self.state
+            .lock()
+            .registers
+            .get(usize::from(reg_addr))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Sets the 24 bit content of a register, regardless of the `REG_READ` bit.
+    ///
+    /// # Notes
+    ///
+    /// This is typically used to seed the value registers `R2Ah`-`R2Fh` and `R3Fh`-`R40h`.
+    /// Addresses outside the register map are ignored.
+    pub fn set_register(&self, reg_addr: u8, value: u32) {
+        if let Some(register) = self.state.lock().registers.get_mut(usize::from(reg_addr)) {
+            *register = value & 0x00FF_FFFF;
+        }
+    }
+
+    /// Gets the number of I2C read and write transactions received so far.
+    pub fn transactions(&self) -> usize {
+        self.state.lock().transactions
+    }
+
+    /// Resets the number of I2C transactions received to zero and clears the written registers and control words.
+    pub fn reset_transactions(&self) {
+        let mut state = self.state.lock();
+        state.transactions = 0;
+        state.written.clear();
+        state.control.clear();
+    }
+
+    /// Gets the addresses of the registers written so far, in the order of the transactions.
+    ///
+    /// # Notes
+    ///
+    /// The writes to `R00h` are not listed, since they are issued by every configuration register read.
+    /// The writes ignored by the model are listed too.
+    pub fn written_registers(&self) -> Vec<u8> {
+        self.state.lock().written.clone()
+    }
+
+    /// Gets the 24 bit words written to `R00h` so far, in the order of the transactions.
+    pub fn control_writes(&self) -> Vec<u32> {
+        self.state.lock().control.clone()
+    }
+
+    /// Sets whether the register writes are ignored, modelling a device that acknowledges the transactions without storing them.
+    ///
+    /// # Notes
+    ///
+    /// The writes to `R00h` are still honored, so the registers can be read.
+    pub fn set_writes_ignored(&self, ignored: bool) {
+        self.state.lock().writes_ignored = ignored;
+    }
+}
+
+impl MockState {
+    /// Checks whether a register can only be read with the `REG_READ` bit set.
+    fn is_configuration_register(reg_addr: u8) -> bool {
+        reg_addr < 0x2a || (reg_addr > 0x2f && reg_addr < 0x3f)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.transactions += 1;
+
+        match *bytes {
+            [reg_addr] => self.pointer = reg_addr,
+            [0, high, middle, control] => {
+                self.control
+                    .push(u32::from_be_bytes([0, high, middle, control]));
+                self.reg_read = control & 0b0001 != 0;
+                if control & 0b1000 != 0 {
+                    self.registers = [0; REGISTERS_COUNT];
+                    self.reg_read = false;
+                }
+            }
+            [reg_addr, high, middle, low] => {
+                self.pointer = reg_addr;
+                self.written.push(reg_addr);
+                if self.reg_read || self.writes_ignored {
+                    return;
+                }
+                if let Some(register) = self.registers.get_mut(usize::from(reg_addr)) {
+                    *register = u32::from_be_bytes([0, high, middle, low]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn read(&mut self, buffer: &mut [u8]) {
+        self.transactions += 1;
+
+        let value = if Self::is_configuration_register(self.pointer) && !self.reg_read {
+            0
+        } else {
+            self.registers
+                .get(usize::from(self.pointer))
+                .copied()
+                .unwrap_or(0)
+        };
+
+        for (byte, value_byte) in buffer.iter_mut().zip(
+            value.to_be_bytes()[1..]
+                .iter()
+                .chain(core::iter::repeat(&0)),
+        ) {
+            *byte = *value_byte;
+        }
+    }
+}
+
+impl ErrorType for MockAfe {
+    type Error = core::convert::Infallible;
+}
+
+impl I2c<SevenBitAddress> for MockAfe {
+    fn read(&mut self, _address: SevenBitAddress, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.state.lock().read(read);
+
+        Ok(())
+    }
+
+    fn write(&mut self, _address: SevenBitAddress, write: &[u8]) -> Result<(), Self::Error> {
+        self.state.lock().write(write);
+
+        Ok(())
+    }
+
+    fn write_iter<B>(&mut self, address: SevenBitAddress, bytes: B) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        let bytes: Vec<u8> = bytes.into_iter().collect();
+
+        self.write(address, &bytes)
+    }
+
+    fn write_read(
+        &mut self,
+        address: SevenBitAddress,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.write(address, write)?;
+
+        self.read(address, read)
+    }
+
+    fn write_iter_read<B>(
+        &mut self,
+        address: SevenBitAddress,
+        bytes: B,
+        read: &mut [u8],
+    ) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        self.write_iter(address, bytes)?;
+
+        self.read(address, read)
+    }
+
+    fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        for operation in operations {
+            match operation {
+                Operation::Read(read) => self.read(address, read)?,
+                Operation::Write(write) => self.write(address, write)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn transaction_iter<'a, O>(
+        &mut self,
+        address: SevenBitAddress,
+        operations: O,
+    ) -> Result<(), Self::Error>
+    where
+        O: IntoIterator<Item = Operation<'a>>,
+    {
+        for mut operation in operations {
+            self.transaction(address, core::slice::from_mut(&mut operation))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents a bus error of the given kind, returned by a [`FlakyBus`].
+#[cfg(test)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct BusError(pub(crate) ErrorKind);
+
+#[cfg(test)]
+impl Error for BusError {
+    fn kind(&self) -> ErrorKind {
+        self.0
+    }
+}
+
+/// Represents a [`MockAfe`] failing its first I2C transactions with the given error.
+#[cfg(test)]
+pub(crate) struct FlakyBus {
+    mock: MockAfe,
+    failures: usize,
+    error: BusError,
+}
+
+#[cfg(test)]
+impl FlakyBus {
+    /// Fails if some failures are left, consuming one of them.
+    fn fail(&mut self) -> Result<(), BusError> {
+        if self.failures == 0 {
+            return Ok(());
+        }
+
+        self.failures -= 1;
+
+        Err(self.error)
+    }
+}
+
+#[cfg(test)]
+impl ErrorType for FlakyBus {
+    type Error = BusError;
+}
+
+#[cfg(test)]
+impl I2c<SevenBitAddress> for FlakyBus {
+    fn read(&mut self, address: SevenBitAddress, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.fail()?;
+        self.mock
+            .read(address, read)
+            .map_err(|never| match never {})
+    }
+
+    fn write(&mut self, address: SevenBitAddress, write: &[u8]) -> Result<(), Self::Error> {
+        self.fail()?;
+        self.mock
+            .write(address, write)
+            .map_err(|never| match never {})
+    }
+
+    fn write_iter<B>(&mut self, address: SevenBitAddress, bytes: B) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        self.fail()?;
+        self.mock
+            .write_iter(address, bytes)
+            .map_err(|never| match never {})
+    }
+
+    fn write_read(
+        &mut self,
+        address: SevenBitAddress,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.fail()?;
+        self.mock
+            .write_read(address, write, read)
+            .map_err(|never| match never {})
+    }
+
+    fn write_iter_read<B>(
+        &mut self,
+        address: SevenBitAddress,
+        bytes: B,
+        read: &mut [u8],
+    ) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        self.fail()?;
+        self.mock
+            .write_iter_read(address, bytes, read)
+            .map_err(|never| match never {})
+    }
+
+    fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.fail()?;
+        self.mock
+            .transaction(address, operations)
+            .map_err(|never| match never {})
+    }
+
+    fn transaction_iter<'a, O>(
+        &mut self,
+        address: SevenBitAddress,
+        operations: O,
+    ) -> Result<(), Self::Error>
+    where
+        O: IntoIterator<Item = Operation<'a>>,
+    {
+        self.fail()?;
+        self.mock
+            .transaction_iter(address, operations)
+            .map_err(|never| match never {})
+    }
+}
+
+/// Represents a delay returning immediately, used by the tests running on a [`MockAfe`].
+#[cfg(test)]
+pub(crate) struct NoDelay;
+
+#[cfg(test)]
+impl embedded_hal::delay::DelayUs for NoDelay {
+    type Error = core::convert::Infallible;
+
+    fn delay_us(&mut self, _us: u32) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl MockAfe {
+    /// Creates an active [`AFE4404`] with three LEDs on a clone of this bus, skipping the initialization.
+    pub(crate) fn three_leds(&self) -> AFE4404<Self, ThreeLedsMode> {
+        AFE4404::with_three_leds(self.clone(), DEFAULT_ADDRESS, Self::clock()).into_state()
+    }
+
+    /// Creates an active [`AFE4404`] with two LEDs on a clone of this bus, skipping the initialization.
+    pub(crate) fn two_leds(&self) -> AFE4404<Self, TwoLedsMode> {
+        AFE4404::with_two_leds(self.clone(), DEFAULT_ADDRESS, Self::clock()).into_state()
+    }
+
+    /// Creates an active [`AFE4404`] with one LED on a clone of this bus, skipping the initialization.
+    pub(crate) fn one_led(&self) -> AFE4404<Self, OneLedMode> {
+        AFE4404::with_one_led(self.clone(), DEFAULT_ADDRESS, Self::clock()).into_state()
+    }
+
+    /// Creates an active [`AFE4404`] with three LEDs on a clone of this bus failing its first `failures` transactions with an error of the given kind.
+    pub(crate) fn flaky_three_leds(
+        &self,
+        failures: usize,
+        kind: ErrorKind,
+    ) -> AFE4404<FlakyBus, ThreeLedsMode> {
+        let bus = FlakyBus {
+            mock: self.clone(),
+            failures,
+            error: BusError(kind),
+        };
+
+        AFE4404::with_three_leds(bus, DEFAULT_ADDRESS, Self::clock()).into_state()
+    }
+
+    /// Gets the frequency of the internal clock.
+    fn clock() -> Frequency {
+        Frequency::new::<megahertz>(4.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uom::si::{
+        electric_current::milliampere, electrical_resistance::kiloohm, f32::ElectricCurrent,
+        f32::ElectricalResistance, time::microsecond,
+    };
+
+    use super::*;
+    use crate::{
+        led_current::LedCurrentConfiguration, measurement_window::MeasurementWindowConfiguration,
+        setup::QuickSetup, tia::ResistorConfiguration,
+    };
+
+    #[test]
+    fn configuration_round_trips_in_memory() {
+        let mock = MockAfe::new();
+        let mut afe = mock.two_leds();
+
+        let leds_current = LedCurrentConfiguration::<TwoLedsMode>::new(
+            ElectricCurrent::new::<milliampere>(20.0),
+            ElectricCurrent::new::<milliampere>(40.0),
+        );
+        let window = MeasurementWindowConfiguration::<TwoLedsMode>::default();
+        let resistors = ResistorConfiguration::<TwoLedsMode>::new(
+            ElectricalResistance::new::<kiloohm>(250.0),
+            ElectricalResistance::new::<kiloohm>(100.0),
+        );
+
+        afe.set_leds_current(&leds_current).unwrap();
+        afe.set_measurement_window(&window).unwrap();
+        afe.set_tia_resistors(&resistors).unwrap();
+
+        let leds_current_read = afe.get_leds_current().unwrap();
+        assert!((leds_current_read.led1().get::<milliampere>() - 20.0).abs() < 0.8);
+        assert!((leds_current_read.led2().get::<milliampere>() - 40.0).abs() < 0.8);
+
+        let window_read = afe.get_measurement_window().unwrap();
+        assert!((window_read.period().get::<microsecond>() - 10_000.0).abs() < 0.25);
+        assert!(
+            (window_read.active_timing_configuration().led2().conv_end
+                - window.active_timing_configuration().led2().conv_end)
+                .get::<microsecond>()
+                .abs()
+                < 0.25
+        );
+
+        assert_eq!(afe.get_tia_resistors().unwrap(), resistors);
+    }
+
+    #[test]
+    fn written_registers_visible_from_model() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        afe.set_averaging(4).unwrap();
+
+        assert_eq!(afe.get_averaging().unwrap(), 4);
+        assert_eq!(mock.register(0x1E) & 0x0F, 3);
+    }
+
+    #[test]
+    fn init_configures_the_model() {
+        let mock = MockAfe::new();
+        let setup = QuickSetup::<OneLedMode>::default();
+
+        let mut afe = AFE4404::with_one_led(mock.clone(), DEFAULT_ADDRESS, MockAfe::clock())
+            .init(&setup, &mut NoDelay)
+            .unwrap();
+
+        assert_eq!(afe.get_averaging().unwrap(), setup.averages);
+        assert_eq!(afe.get_tia_resistors().unwrap(), setup.resistors);
+        assert!(mock.transactions() > 0);
+    }
+}
@@ -1,20 +1,85 @@
-//! This module contains the [`AFE4404`] lighting modes.
+//! This module contains the [`AFE4404`] lighting modes and states.
 
-/// Uninitialized mode.
-#[derive(Debug)]
+/// Uninitialized mode, only used to reach the constructors of the [`AFE4404`].
+///
+/// # Notes
+///
+/// It is not a [`LedMode`], the lighting mode is chosen by the constructor.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct UninitializedMode;
 
 /// Three LEDs mode.
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ThreeLedsMode;
 
 /// Two LEDs mode.
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct TwoLedsMode;
 
+/// Single LED mode.
+///
+/// # Notes
+///
+/// Only the LED1 and Ambient phases are used, the LED2 and the slot shared between LED3 and Ambient2 are left empty.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct OneLedMode;
+
+/// Represents the lighting mode of the [`AFE4404`] as a value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DeviceMode {
+    /// Three LEDs mode.
+    Three,
+    /// Two LEDs mode.
+    Two,
+    /// Single LED mode.
+    One,
+}
+
 /// Represents the lighting mode of the [`AFE4404`].
-pub trait LedMode {}
+pub trait LedMode {
+    /// The lighting mode as a value, so that generic code can branch on it.
+    const MODE: DeviceMode;
+}
+
+impl LedMode for ThreeLedsMode {
+    const MODE: DeviceMode = DeviceMode::Three;
+}
+impl LedMode for TwoLedsMode {
+    const MODE: DeviceMode = DeviceMode::Two;
+}
+impl LedMode for OneLedMode {
+    const MODE: DeviceMode = DeviceMode::One;
+}
+
+/// Uninitialized state, the [`AFE4404`] was just created and can only be initialized with `init()`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Uninitialized;
+
+/// Active state, the [`AFE4404`] is initialized, powered up and acquiring.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Active;
+
+/// Powered down state, the [`AFE4404`] was powered down with `sw_power_down()` and can only be powered up again.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PoweredDown;
+
+/// Represents the state of the [`AFE4404`].
+pub trait DeviceState {}
+
+impl DeviceState for Uninitialized {}
+impl DeviceState for Active {}
+impl DeviceState for PoweredDown {}
+
+/// Represents a state of the [`AFE4404`] reached through `init()`.
+pub trait Initialized: DeviceState {}
 
-impl LedMode for UninitializedMode {}
-impl LedMode for ThreeLedsMode {}
-impl LedMode for TwoLedsMode {}
+impl Initialized for Active {}
+impl Initialized for PoweredDown {}
@@ -0,0 +1,96 @@
+//! This module contains the observer of the register traffic.
+
+use alloc::boxed::Box;
+
+use embedded_hal::i2c::{I2c, SevenBitAddress};
+
+use crate::{device::AFE4404, modes::LedMode};
+
+/// Represents the direction of a register access.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Direction {
+    /// The register was read from the device.
+    Read,
+    /// The register was written to the device.
+    Write,
+}
+
+/// Represents an observer of the register accesses, to log, mirror or check the I2C traffic.
+///
+/// # Notes
+///
+/// The observer is called once for each successful register access, after the access.
+/// The toggling of the `REG_READ` bit and the accesses served by the register cache are not reported.
+/// Any closure taking the register address, the 24 bit content and the direction is an observer.
+pub trait RegisterObserver {
+    /// Observes a register access.
+    fn observe(&mut self, reg_addr: u8, value: u32, direction: Direction);
+}
+
+impl<F> RegisterObserver for F
+where
+    F: FnMut(u8, u32, Direction),
+{
+    fn observe(&mut self, reg_addr: u8, value: u32, direction: Direction) {
+        self(reg_addr, value, direction);
+    }
+}
+
+impl<I2C, MODE> AFE4404<I2C, MODE>
+where
+    I2C: I2c<SevenBitAddress>,
+    MODE: LedMode,
+{
+    /// Sets the observer of the register accesses, replacing the previous one.
+    pub fn set_register_observer<O>(&mut self, observer: O)
+    where
+        O: RegisterObserver + Send + 'static,
+    {
+        self.registers.observer = Some(Box::new(observer));
+    }
+
+    /// Removes the observer of the register accesses.
+    pub fn clear_register_observer(&mut self) {
+        self.registers.observer = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{sync::Arc, vec::Vec};
+
+    use spin::Mutex;
+
+    use super::*;
+    use crate::{mock::MockAfe, system::State};
+
+    #[test]
+    fn observer_sees_reads_and_writes() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+        let accesses = Arc::new(Mutex::new(Vec::new()));
+
+        let log = Arc::clone(&accesses);
+        afe.set_register_observer(move |reg_addr, value, direction| {
+            log.lock().push((reg_addr, value, direction));
+        });
+        afe.set_register_cache(State::Enabled);
+
+        afe.set_averaging(4).unwrap();
+        // The second read is served by the register cache.
+        afe.get_averaging().unwrap();
+
+        assert_eq!(
+            *accesses.lock(),
+            [
+                (0x1E, 0, Direction::Read),
+                (0x1E, mock.register(0x1E), Direction::Write)
+            ]
+        );
+
+        afe.clear_register_observer();
+        afe.set_averaging(2).unwrap();
+        assert_eq!(accesses.lock().len(), 2);
+    }
+}
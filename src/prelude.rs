@@ -0,0 +1,84 @@
+//! This module re-exports the most common types of the crate.
+//!
+//! ```
+//! # use embedded_hal::i2c::{ErrorType, I2c, Operation, SevenBitAddress};
+//! # /// A bus ignoring the writes and reading zeros.
+//! # struct Bus;
+//! # impl ErrorType for Bus {
+//! #     type Error = core::convert::Infallible;
+//! # }
+//! # impl I2c<SevenBitAddress> for Bus {
+//! #     fn read(&mut self, _: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+//! #         read.fill(0);
+//! #         Ok(())
+//! #     }
+//! #     fn write(&mut self, _: u8, _: &[u8]) -> Result<(), Self::Error> {
+//! #         Ok(())
+//! #     }
+//! #     fn write_iter<B: IntoIterator<Item = u8>>(&mut self, _: u8, _: B) -> Result<(), Self::Error> {
+//! #         Ok(())
+//! #     }
+//! #     fn write_read(&mut self, _: u8, _: &[u8], read: &mut [u8]) -> Result<(), Self::Error> {
+//! #         read.fill(0);
+//! #         Ok(())
+//! #     }
+//! #     fn write_iter_read<B: IntoIterator<Item = u8>>(
+//! #         &mut self,
+//! #         _: u8,
+//! #         _: B,
+//! #         read: &mut [u8],
+//! #     ) -> Result<(), Self::Error> {
+//! #         read.fill(0);
+//! #         Ok(())
+//! #     }
+//! #     fn transaction(&mut self, _: u8, operations: &mut [Operation<'_>]) -> Result<(), Self::Error> {
+//! #         for operation in operations {
+//! #             if let Operation::Read(read) = operation {
+//! #                 read.fill(0);
+//! #             }
+//! #         }
+//! #         Ok(())
+//! #     }
+//! #     fn transaction_iter<'a, O: IntoIterator<Item = Operation<'a>>>(
+//! #         &mut self,
+//! #         _: u8,
+//! #         _: O,
+//! #     ) -> Result<(), Self::Error> {
+//! #         Ok(())
+//! #     }
+//! # }
+//! # struct Delay;
+//! # impl embedded_hal::delay::DelayUs for Delay {
+//! #     type Error = core::convert::Infallible;
+//! #     fn delay_us(&mut self, _: u32) -> Result<(), Self::Error> {
+//! #         Ok(())
+//! #     }
+//! # }
+//! # let (i2c, mut delay) = (Bus, Delay);
+//! use afe4404::prelude::*;
+//! use uom::si::{f32::Frequency, frequency::megahertz};
+//!
+//! let mut frontend = AFE4404::with_three_leds(i2c, 0x58u8, Frequency::new::<megahertz>(4.0))
+//!     .init(&QuickSetup::default(), &mut delay)
+//!     .expect("Failed to initialize the AFE4404");
+//!
+//! frontend
+//!     .set_averaging(4)
+//!     .expect("Failed to set the averaging");
+//! ```
+
+pub use crate::{
+    channel::{ChannelMap, LedChannel, Wavelength},
+    clock::ClockConfiguration,
+    device::AFE4404,
+    errors::{AfeError, TransitionError},
+    led_current::{LedCurrentConfiguration, OffsetCurrentConfiguration},
+    measurement_window::{
+        ActiveTiming, AmbientTiming, LedTiming, MeasurementWindowConfiguration, PowerDownTiming,
+    },
+    modes::{Active, LedMode, OneLedMode, PoweredDown, ThreeLedsMode, TwoLedsMode, Uninitialized},
+    setup::{AfeConfiguration, QuickSetup},
+    system::{DynamicConfiguration, RxControl, State},
+    tia::{CapacitorConfiguration, ResistorConfiguration},
+    value_reading::{AveragedReadings, NamedReadings, Readings},
+};
@@ -1,32 +1,70 @@
 //! This module contains the register communication via I2C functions.
 
-use alloc::sync::Arc;
+use alloc::boxed::Box;
 
 use embedded_hal::i2c::{I2c, SevenBitAddress};
-use spin::Mutex;
 
-use crate::{errors::AfeError, RegisterWritable};
+use crate::{
+    cache::RegisterCache,
+    errors::AfeError,
+    observer::{Direction, RegisterObserver},
+    retry::RetryPolicy,
+    RegisterWritable,
+};
 
-/// Represents a register inside the AFE4404.
-pub(crate) struct Register<I2C, BF> {
+/// Checks whether a register can only be read with the `REG_READ` bit of `R00h` set.
+pub(crate) fn is_configuration_register(reg_addr: u8) -> bool {
+    reg_addr < 0x2a || (reg_addr > 0x2f && reg_addr < 0x3f)
+}
+
+/// Checks whether a register holds an ADC value, so that it is read without setting the `REG_READ` bit of `R00h`.
+///
+/// # Notes
+///
+/// Every other address, including the reserved ones, is read with the `REG_READ` bit set.
+pub(crate) fn is_value_register(reg_addr: u8) -> bool {
+    (0x2a..=0x2f).contains(&reg_addr) || (0x3f..=0x40).contains(&reg_addr)
+}
+
+/// The content of `R00h` enabling the register reading, with `REG_READ` set.
+pub(crate) const READ_MODE: [u8; 4] = [0x00, 0x00, 0x00, 0x01];
+
+/// The content of `R00h` restoring the register writing, with `REG_READ` cleared.
+pub(crate) const WRITE_MODE: [u8; 4] = [0x00, 0x00, 0x00, 0x00];
+
+/// Represents a register inside the AFE4404, borrowing the I2C interface for the duration of the access.
+pub(crate) struct Register<'a, I2C, BF> {
     _p: core::marker::PhantomData<BF>,
     reg_addr: u8,
     phy_addr: SevenBitAddress,
-    i2c: Arc<Mutex<I2C>>,
+    i2c: &'a mut I2C,
+    cache: &'a mut RegisterCache,
+    retry_policy: Option<&'a mut RetryPolicy>,
+    observer: Option<&'a mut Box<dyn RegisterObserver + Send>>,
 }
 
-impl<I2C, BF> Register<I2C, BF>
+impl<'a, I2C, BF> Register<'a, I2C, BF>
 where
     I2C: I2c,
     BF: RegisterWritable,
 {
-    /// Creates a new [`Register<I2C, BF>`] given a physical and memory address, associated to the specified I2C interface.
-    pub(crate) fn new(reg_addr: u8, phy_addr: SevenBitAddress, i2c: Arc<Mutex<I2C>>) -> Self {
+    /// Creates a new [`Register<I2C, BF>`] given a physical and memory address, associated to the specified I2C interface, register cache, retry policy and observer.
+    pub(crate) fn new(
+        reg_addr: u8,
+        phy_addr: SevenBitAddress,
+        i2c: &'a mut I2C,
+        cache: &'a mut RegisterCache,
+        retry_policy: Option<&'a mut RetryPolicy>,
+        observer: Option<&'a mut Box<dyn RegisterObserver + Send>>,
+    ) -> Self {
         Self {
             _p: core::marker::PhantomData::default(),
             reg_addr,
             phy_addr,
             i2c,
+            cache,
+            retry_policy,
+            observer,
         }
     }
 
@@ -36,28 +74,110 @@ where
     ///
     /// This function will return an error if an I2C transaction fails.
     pub(crate) fn read(&mut self) -> Result<BF, AfeError<I2C::Error>> {
-        // Enable register reading flag for configuration registers.
-        if self.reg_addr < 0x2a || (self.reg_addr > 0x2f && self.reg_addr < 0x3f) {
-            self.i2c
-                .lock()
-                .write(self.phy_addr, [0, 0, 0, 1].as_slice())?;
-        }
+        let receive_buffer = match self.cache.get(self.reg_addr) {
+            Some(value) => {
+                let [_, high, middle, low] = value.to_be_bytes();
+                [high, middle, low]
+            }
+            None => self.read_bytes(self.reg_addr)?,
+        };
+
+        Ok(BF::from_reg_bytes(receive_buffer))
+    }
+
+    /// Reads the contents of this [`Register<I2C, BF>`] from the device, bypassing the register cache and the staged writes.
+    ///
+    /// # Notes
+    ///
+    /// Use this function wherever the device itself is checked, the register cache is left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if an I2C transaction fails.
+    pub(crate) fn read_uncached(&mut self) -> Result<BF, AfeError<I2C::Error>> {
+        Ok(BF::from_reg_bytes(self.fetch_bytes(self.reg_addr)?))
+    }
+
+    /// Reads the 24 bit content of any address, using the I2C interface and physical address of this [`Register<I2C, BF>`].
+    ///
+    /// # Notes
+    ///
+    /// The address is always read from the device, the register cache is refreshed with the read value unless a write is staged.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if an I2C transaction fails.
+    pub(crate) fn read_address(&mut self, reg_addr: u8) -> Result<u32, AfeError<I2C::Error>> {
+        let [high, middle, low] = self.read_bytes(reg_addr)?;
+
+        Ok(u32::from_be_bytes([0, high, middle, low]))
+    }
 
-        let output_buffer = [self.reg_addr];
-        let mut receive_buffer: [u8; 3] = [0, 0, 0];
+    /// Reads the 24 bit content of any address, bypassing the register cache.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if an I2C transaction fails.
+    pub(crate) fn read_address_uncached(
+        &mut self,
+        reg_addr: u8,
+    ) -> Result<u32, AfeError<I2C::Error>> {
+        let [high, middle, low] = self.fetch_bytes(reg_addr)?;
 
-        self.i2c.lock().write(self.phy_addr, &output_buffer)?;
+        Ok(u32::from_be_bytes([0, high, middle, low]))
+    }
 
-        self.i2c.lock().read(self.phy_addr, &mut receive_buffer)?;
+    /// Reads the raw bytes of the given address from the device, storing them into the register cache.
+    fn read_bytes(&mut self, reg_addr: u8) -> Result<[u8; 3], AfeError<I2C::Error>> {
+        let receive_buffer = self.fetch_bytes(reg_addr)?;
 
-        // Disable register reading flag for configuration registers.
-        if self.reg_addr < 0x2a || (self.reg_addr > 0x2f && self.reg_addr < 0x3f) {
-            self.i2c
-                .lock()
-                .write(self.phy_addr, [0, 0, 0, 0].as_slice())?;
+        let [high, middle, low] = receive_buffer;
+        self.cache
+            .refresh(reg_addr, u32::from_be_bytes([0, high, middle, low]));
+
+        Ok(receive_buffer)
+    }
+
+    /// Reads the raw bytes of the given address from the device, reporting them to the observer.
+    fn fetch_bytes(&mut self, reg_addr: u8) -> Result<[u8; 3], AfeError<I2C::Error>> {
+        let receive_buffer = self.retrying(|i2c, phy_addr| {
+            let read = |i2c: &mut I2C| {
+                let mut receive_buffer: [u8; 3] = [0, 0, 0];
+
+                i2c.write(phy_addr, &[reg_addr])?;
+                i2c.read(phy_addr, &mut receive_buffer)?;
+
+                Ok(receive_buffer)
+            };
+
+            if is_value_register(reg_addr) {
+                return read(i2c);
+            }
+
+            // Enable register reading flag for configuration and reserved registers.
+            i2c.write(phy_addr, READ_MODE.as_slice())?;
+
+            let receive_buffer = read(i2c);
+
+            // Disable register reading flag even if the reading failed, otherwise the following writes are ignored.
+            let restored = i2c.write(phy_addr, WRITE_MODE.as_slice());
+
+            let receive_buffer = receive_buffer?;
+            restored?;
+
+            Ok(receive_buffer)
+        })?;
+
+        if let Some(observer) = self.observer.as_mut() {
+            let [high, middle, low] = receive_buffer;
+            observer.observe(
+                reg_addr,
+                u32::from_be_bytes([0, high, middle, low]),
+                Direction::Read,
+            );
         }
 
-        Ok(BF::from_reg_bytes(receive_buffer))
+        Ok(receive_buffer)
     }
 
     /// Writes a new value to the specified register.
@@ -66,11 +186,66 @@ where
     ///
     /// This function will return an error if if an I2C transaction fails.
     pub(crate) fn write(&mut self, value: BF) -> Result<(), AfeError<I2C::Error>> {
-        let mut buffer: [u8; 4] = [self.reg_addr, 0, 0, 0];
-        buffer[1..=3].copy_from_slice(&value.into_reg_bytes());
+        let [high, middle, low] = value.into_reg_bytes();
 
-        self.i2c.lock().write(self.phy_addr, buffer.as_slice())?;
+        self.write_address(self.reg_addr, u32::from_be_bytes([0, high, middle, low]))
+    }
+
+    /// Writes the 24 bit content of any address, using the I2C interface and physical address of this [`Register<I2C, BF>`].
+    ///
+    /// # Notes
+    ///
+    /// The register cache is updated with the written value, while staging the write is only kept in the register cache.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if an I2C transaction fails.
+    pub(crate) fn write_address(
+        &mut self,
+        reg_addr: u8,
+        value: u32,
+    ) -> Result<(), AfeError<I2C::Error>> {
+        if self.cache.stage(reg_addr, value) {
+            return Ok(());
+        }
+
+        let [_, high, middle, low] = value.to_be_bytes();
+
+        self.retrying(|i2c, phy_addr| i2c.write(phy_addr, &[reg_addr, high, middle, low]))?;
+
+        self.cache.store(reg_addr, value);
+
+        if let Some(observer) = self.observer.as_mut() {
+            observer.observe(reg_addr, value & 0x00FF_FFFF, Direction::Write);
+        }
 
         Ok(())
     }
+
+    /// Runs the given I2C transactions, repeating them according to the retry policy when they fail with a transient error.
+    fn retrying<T>(
+        &mut self,
+        mut transactions: impl FnMut(&mut I2C, SevenBitAddress) -> Result<T, I2C::Error>,
+    ) -> Result<T, AfeError<I2C::Error>> {
+        let Some(policy) = self.retry_policy.as_deref_mut() else {
+            return Ok(transactions(self.i2c, self.phy_addr)?);
+        };
+
+        let mut retries = 0;
+        loop {
+            match transactions(self.i2c, self.phy_addr) {
+                Ok(value) => return Ok(value),
+                Err(error) if !RetryPolicy::is_transient(&error) => {
+                    return Err(AfeError::I2CError(error))
+                }
+                Err(error) if retries == policy.retries() => {
+                    return Err(AfeError::BusRetriesExhausted(error))
+                }
+                Err(_) => {
+                    retries += 1;
+                    policy.back_off();
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,444 @@
+//! This module contains the map of the [`AFE4404`](crate::device::AFE4404) registers.
+//!
+//! Each register is declared once, with its address and its fields from the least significant one,
+//! the [`register_map`] macro generates from the declarations:
+//! - a bitfield struct for each register, inside the `register_structs` module;
+//! - the `RegisterBlock` giving access to each register through the I2C bus, inside the `register_block` module;
+//! - the [`RegisterAddress`] enum, with the names and the fields of the registers.
+//!
+//! The macro, the register structs and the register block are internal to the driver, so the map cannot be extended by other crates.
+//! The fields not covered by the typed functions are reached through [`RegisterAddress`],
+//! with [`AFE4404::read_register`](crate::device::AFE4404::read_register) and [`AFE4404::write_register`](crate::device::AFE4404::write_register),
+//! the undocumented addresses with [`AFE4404::read_reserved`](crate::device::AFE4404::read_reserved).
+
+/// Generates the register structs, the register block and the register addresses from the register declarations.
+///
+/// Each declaration has the form `RXXh = 0xXX => rXXh { field: Type, ... }`,
+/// with the fields from the least significant one and the reserved bits declared as `#[skip] __N: BN`.
+/// The macro is not exported, since the generated register block relies on the private register cache, retry policy and observer.
+macro_rules! register_map {
+    ($(
+        $name:ident = $addr:literal => $accessor:ident {
+            $($(#[$field_attr:meta])* $field:ident: $ty:ty),* $(,)?
+        }
+    ),* $(,)?) => {
+        /// Represents a register struct convertible to and from the bytes sent on the I2C bus.
+        pub(crate) trait RegisterWritable {
+            /// Converts the register into the bytes sent on the I2C bus, from the most significant one.
+            fn into_reg_bytes(self) -> [u8; 3];
+
+            /// Converts the bytes received from the I2C bus, from the most significant one, into the register.
+            fn from_reg_bytes(bytes: [u8; 3]) -> Self;
+        }
+
+        #[allow(clippy::fn_params_excessive_bools)]
+        #[allow(clippy::no_effect_underscore_binding)]
+        #[allow(clippy::too_many_arguments)]
+        #[allow(dead_code)]
+        #[allow(unreachable_pub)]
+        pub(crate) mod register_structs {
+            use modular_bitfield::prelude::*;
+
+            use super::RegisterWritable;
+
+            $(
+                #[bitfield]
+                #[derive(Copy, Clone)]
+                pub(crate) struct $name {
+                    $($(#[$field_attr])* pub(crate) $field: $ty,)*
+                }
+
+                impl RegisterWritable for $name {
+                    fn into_reg_bytes(self) -> [u8; 3] {
+                        // Fields are saved in reversed order due to bitfield endianness.
+                        let mut reversed = self.into_bytes();
+                        reversed.reverse();
+                        reversed
+                    }
+
+                    fn from_reg_bytes(bytes: [u8; 3]) -> Self {
+                        let mut reversed = bytes;
+                        reversed.reverse();
+                        Self::from_bytes(reversed)
+                    }
+                }
+            )*
+        }
+
+        #[allow(dead_code)]
+        #[allow(non_snake_case)]
+        pub(crate) mod register_block {
+            use alloc::boxed::Box;
+
+            use embedded_hal::i2c::{I2c, SevenBitAddress};
+
+            use crate::{
+                cache::RegisterCache, observer::RegisterObserver, register::Register,
+                retry::RetryPolicy,
+            };
+
+            use super::register_structs::{$($name),*};
+
+            /// Represents the registers of the device, reached through the owned I2C bus.
+            pub(crate) struct RegisterBlock<I2C> {
+                phy_addr: SevenBitAddress,
+                i2c: I2C,
+                pub(crate) cache: RegisterCache,
+                pub(crate) retry_policy: Option<RetryPolicy>,
+                pub(crate) observer: Option<Box<dyn RegisterObserver + Send>>,
+            }
+
+            impl<I2C> RegisterBlock<I2C>
+            where
+                I2C: I2c,
+            {
+                /// Creates a new `RegisterBlock` given the physical address and the I2C bus.
+                pub(crate) fn new(phy_addr: SevenBitAddress, i2c: I2C) -> Self {
+                    Self {
+                        phy_addr,
+                        i2c,
+                        cache: RegisterCache::new(),
+                        retry_policy: None,
+                        observer: None,
+                    }
+                }
+
+                /// Releases the I2C bus.
+                pub(crate) fn release(self) -> I2C {
+                    self.i2c
+                }
+
+                $(
+                    #[doc = concat!("Gets the `", stringify!($name), "` register.")]
+                    pub(crate) fn $accessor(&mut self) -> Register<'_, I2C, $name> {
+                        Register::new(
+                            $addr,
+                            self.phy_addr,
+                            &mut self.i2c,
+                            &mut self.cache,
+                            self.retry_policy.as_mut(),
+                            self.observer.as_mut(),
+                        )
+                    }
+                )*
+            }
+        }
+
+        /// Represents the address of a register of the [`AFE4404`](crate::device::AFE4404).
+        #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+        #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+        #[repr(u8)]
+        pub enum RegisterAddress {
+            $(
+                #[doc = concat!("The `", stringify!($name), "` register.")]
+                $name = $addr,
+            )*
+        }
+
+        impl RegisterAddress {
+            /// The number of registers.
+            pub const COUNT: usize = [$(stringify!($name)),*].len();
+
+            /// All the registers, sorted by address.
+            pub const ALL: [Self; Self::COUNT] = [$(Self::$name),*];
+
+            /// Gets the register with the given address.
+            pub fn from_address(reg_addr: u8) -> Option<Self> {
+                match reg_addr {
+                    $($addr => Some(Self::$name),)*
+                    _ => None,
+                }
+            }
+
+            /// Gets the name of the register.
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Self::$name => stringify!($name),)*
+                }
+            }
+
+            /// Gets the name, the offset and the width of the fields of the register, from the most significant one.
+            ///
+            /// # Notes
+            ///
+            /// The reserved bits are skipped.
+            pub fn fields(self) -> impl Iterator<Item = (&'static str, u8, u8)> {
+                use modular_bitfield::prelude::*;
+
+                #[allow(clippy::cast_possible_truncation)]
+                let fields: &'static [(&'static str, u8)] = match self {
+                    $(Self::$name => &[$((
+                        stringify!($field),
+                        <$ty as Specifier>::BITS as u8,
+                    )),*],)*
+                };
+
+                // The registers are 24 bit wide, the fields are declared from the least significant one.
+                fields
+                    .iter()
+                    .rev()
+                    .scan(24, |end, &(name, width)| {
+                        *end -= width;
+                        Some((name, *end, width))
+                    })
+                    .filter(|(name, _, _)| !name.starts_with("__"))
+            }
+        }
+    };
+}
+
+register_map! {
+    R00h = 0x00 => r00h {
+        reg_read: bool,
+        tm_count_rst: bool,
+        #[skip] __0: B1,
+        sw_reset: bool,
+        #[skip] __1: B20,
+    },
+    R01h = 0x01 => r01h {
+        led2stc: u16,
+        #[skip] __0: B8,
+    },
+    R02h = 0x02 => r02h {
+        led2endc: u16,
+        #[skip] __0: B8,
+    },
+    R03h = 0x03 => r03h {
+        led1ledstc: u16,
+        #[skip] __0: B8,
+    },
+    R04h = 0x04 => r04h {
+        led1ledendc: u16,
+        #[skip] __0: B8,
+    },
+    R05h = 0x05 => r05h {
+        aled2stc_or_led3stc: u16,
+        #[skip] __0: B8,
+    },
+    R06h = 0x06 => r06h {
+        aled2endc_or_led3endc: u16,
+        #[skip] __0: B8,
+    },
+    R07h = 0x07 => r07h {
+        led1stc: u16,
+        #[skip] __0: B8,
+    },
+    R08h = 0x08 => r08h {
+        led1endc: u16,
+        #[skip] __0: B8,
+    },
+    R09h = 0x09 => r09h {
+        led2ledstc: u16,
+        #[skip] __0: B8,
+    },
+    R0Ah = 0x0A => r0Ah {
+        led2ledendc: u16,
+        #[skip] __0: B8,
+    },
+    R0Bh = 0x0B => r0Bh {
+        aled1stc: u16,
+        #[skip] __0: B8,
+    },
+    R0Ch = 0x0C => r0Ch {
+        aled1endc: u16,
+        #[skip] __0: B8,
+    },
+    R0Dh = 0x0D => r0Dh {
+        led2convst: u16,
+        #[skip] __0: B8,
+    },
+    R0Eh = 0x0E => r0Eh {
+        led2convend: u16,
+        #[skip] __0: B8,
+    },
+    R0Fh = 0x0F => r0Fh {
+        aled2convst_or_led3convst: u16,
+        #[skip] __0: B8,
+    },
+    R10h = 0x10 => r10h {
+        aled2convend_or_led3convend: u16,
+        #[skip] __0: B8,
+    },
+    R11h = 0x11 => r11h {
+        led1convst: u16,
+        #[skip] __0: B8,
+    },
+    R12h = 0x12 => r12h {
+        led1convend: u16,
+        #[skip] __0: B8,
+    },
+    R13h = 0x13 => r13h {
+        aled1convst: u16,
+        #[skip] __0: B8,
+    },
+    R14h = 0x14 => r14h {
+        aled1convend: u16,
+        #[skip] __0: B8,
+    },
+    R15h = 0x15 => r15h {
+        adcrststct0: u16,
+        #[skip] __0: B8,
+    },
+    R16h = 0x16 => r16h {
+        adcrstendct0: u16,
+        #[skip] __0: B8,
+    },
+    R17h = 0x17 => r17h {
+        adcrststct1: u16,
+        #[skip] __0: B8,
+    },
+    R18h = 0x18 => r18h {
+        adcrstendct1: u16,
+        #[skip] __0: B8,
+    },
+    R19h = 0x19 => r19h {
+        adcrststct2: u16,
+        #[skip] __0: B8,
+    },
+    R1Ah = 0x1A => r1Ah {
+        adcrstendct2: u16,
+        #[skip] __0: B8,
+    },
+    R1Bh = 0x1B => r1Bh {
+        adcrststct3: u16,
+        #[skip] __0: B8,
+    },
+    R1Ch = 0x1C => r1Ch {
+        adcrstendct3: u16,
+        #[skip] __0: B8,
+    },
+    R1Dh = 0x1D => r1Dh {
+        prpct: u16,
+        #[skip] __0: B8,
+    },
+    R1Eh = 0x1E => r1Eh {
+        numav: B4,
+        #[skip] __0: B4,
+        timeren: bool,
+        #[skip] __1: B15,
+    },
+    R20h = 0x20 => r20h {
+        tia_gain_sep: B3,
+        tia_cf_sep: B3,
+        #[skip] __0: B9,
+        ensepgain: bool,
+        #[skip] __1: B8,
+    },
+    R21h = 0x21 => r21h {
+        tia_gain: B3,
+        tia_cf: B3,
+        #[skip] __0: B2,
+        prog_tg_en: bool,
+        #[skip] __1: B15,
+    },
+    R22h = 0x22 => r22h {
+        iled1: B6,
+        iled2: B6,
+        iled3: B6,
+        #[skip] __0: B6,
+    },
+    R23h = 0x23 => r23h {
+        pdnafe: bool,
+        pdnrx: bool,
+        #[skip] __0: B1,
+        dynamic4: bool,
+        dynamic3: bool,
+        #[skip] __1: B4,
+        osc_enable: bool,
+        #[skip] __2: B4,
+        dynamic2: bool,
+        #[skip] __3: B2,
+        iled_2x: bool,
+        #[skip] __4: B2,
+        dynamic1: bool,
+        #[skip] __5: B3,
+    },
+    R28h = 0x28 => r28h {
+        #[skip] __0: B24,
+    },
+    R29h = 0x29 => r29h {
+        #[skip] __0: B1,
+        clkdiv_clkout: B4,
+        #[skip] __1: B4,
+        enable_clkout: bool,
+        #[skip] __2: B14,
+    },
+    R2Ah = 0x2A => r2Ah {
+        led2val: B24,
+    },
+    R2Bh = 0x2B => r2Bh {
+        aled2val_or_led3val: B24,
+    },
+    R2Ch = 0x2C => r2Ch {
+        led1val: B24,
+    },
+    R2Dh = 0x2D => r2Dh {
+        aled1val: B24,
+    },
+    R2Eh = 0x2E => r2Eh {
+        led2_minus_aled2val: B24,
+    },
+    R2Fh = 0x2F => r2Fh {
+        led1_minus_aled1val: B24,
+    },
+    R31h = 0x31 => r31h {
+        clkdiv_extmode: B3,
+        #[skip] __0: B2,
+        enable_input_short: bool,
+        #[skip] __1: B4,
+        pd_disconnect: bool,
+        #[skip] __2: B13,
+    },
+    R32h = 0x32 => r32h {
+        pdncyclestc: u16,
+        #[skip] __0: B8,
+    },
+    R33h = 0x33 => r33h {
+        pdncycleendc: u16,
+        #[skip] __0: B8,
+    },
+    R34h = 0x34 => r34h {
+        prog_tg_stc: u16,
+        #[skip] __0: B8,
+    },
+    R35h = 0x35 => r35h {
+        prog_tg_endc: u16,
+        #[skip] __0: B8,
+    },
+    R36h = 0x36 => r36h {
+        led3ledstc: u16,
+        #[skip] __0: B8,
+    },
+    R37h = 0x37 => r37h {
+        led3ledendc: u16,
+        #[skip] __0: B8,
+    },
+    R39h = 0x39 => r39h {
+        clkdiv_prf: B3,
+        #[skip] __0: B21,
+    },
+    R3Ah = 0x3A => r3Ah {
+        i_offdac_amb2_or_i_offdac_led3: B4,
+        pol_offdac_amb2_or_pol_offdac_led3: bool,
+        i_offdac_led1: B4,
+        pol_offdac_led1: bool,
+        i_offdac_amb1: B4,
+        pol_offdac_amb1: bool,
+        i_offdac_led2: B4,
+        pol_offdac_led2: bool,
+        #[skip] __0: B4,
+    },
+    R3Dh = 0x3D => r3Dh {
+        #[skip] __0: B1,
+        dec_factor: B3,
+        #[skip] __1: B1,
+        dec_en: bool,
+        #[skip] __2: B18,
+    },
+    R3Fh = 0x3F => r3Fh {
+        avg_led2_minus_aled2val: B24,
+    },
+    R40h = 0x40 => r40h {
+        avg_led1_minus_aled1val: B24,
+    },
+}
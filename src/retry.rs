@@ -0,0 +1,180 @@
+//! This module contains the retry policy of the register accesses.
+
+use alloc::boxed::Box;
+
+use embedded_hal::delay::DelayUs;
+use embedded_hal::i2c::{Error, ErrorKind, I2c, SevenBitAddress};
+use uom::si::{f32::Time, time::microsecond};
+
+use crate::{device::AFE4404, modes::LedMode};
+
+/// Represents how the register accesses failing with a transient bus error are retried.
+///
+/// # Notes
+///
+/// The bus errors, the arbitration losses and the missing acknowledges are considered transient.
+/// A failed register access is repeated as a whole, including the toggling of the `REG_READ` bit.
+pub struct RetryPolicy {
+    retries: u8,
+    backoff: Option<Box<dyn FnMut() + Send>>,
+}
+
+impl RetryPolicy {
+    /// Creates a new `RetryPolicy` retrying up to `retries` times, waiting `backoff` with the given delay before each retry.
+    ///
+    /// # Notes
+    ///
+    /// The backoff is rounded up to the microsecond, the errors of the delay are ignored.
+    pub fn new<D>(retries: u8, backoff: Time, mut delay: D) -> Self
+    where
+        D: DelayUs + Send + 'static,
+    {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let backoff_us = backoff.get::<microsecond>().ceil() as u32;
+
+        Self {
+            retries,
+            backoff: Some(Box::new(move || {
+                let _ = delay.delay_us(backoff_us);
+            })),
+        }
+    }
+
+    /// Creates a new `RetryPolicy` retrying up to `retries` times immediately.
+    pub fn without_backoff(retries: u8) -> Self {
+        Self {
+            retries,
+            backoff: None,
+        }
+    }
+
+    /// Gets the maximum number of retries.
+    pub fn retries(&self) -> u8 {
+        self.retries
+    }
+
+    /// Checks whether a bus error is worth retrying.
+    pub(crate) fn is_transient<E: Error>(error: &E) -> bool {
+        matches!(
+            error.kind(),
+            ErrorKind::Bus | ErrorKind::ArbitrationLoss | ErrorKind::NoAcknowledge(_)
+        )
+    }
+
+    /// Waits before the next retry.
+    pub(crate) fn back_off(&mut self) {
+        if let Some(backoff) = self.backoff.as_mut() {
+            backoff();
+        }
+    }
+}
+
+impl<I2C, MODE> AFE4404<I2C, MODE>
+where
+    I2C: I2c<SevenBitAddress>,
+    MODE: LedMode,
+{
+    /// Sets the retry policy of the register accesses, `None` disables the retries.
+    ///
+    /// # Notes
+    ///
+    /// Without a retry policy, which is the default, any bus error aborts the current function.
+    pub fn set_retry_policy(&mut self, policy: Option<RetryPolicy>) {
+        self.registers.retry_policy = policy;
+    }
+
+    /// Gets the retry policy of the register accesses.
+    pub fn get_retry_policy(&self) -> Option<&RetryPolicy> {
+        self.registers.retry_policy.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::sync::Arc;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    use embedded_hal::i2c::NoAcknowledgeSource;
+
+    use super::*;
+    use crate::{
+        errors::AfeError,
+        mock::{BusError, MockAfe},
+    };
+
+    /// Represents a delay counting its calls.
+    struct CountingDelay(Arc<AtomicUsize>);
+
+    impl DelayUs for CountingDelay {
+        type Error = core::convert::Infallible;
+
+        fn delay_us(&mut self, _us: u32) -> Result<(), Self::Error> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+
+            Ok(())
+        }
+    }
+
+    const NACK: ErrorKind = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+
+    #[test]
+    fn transient_errors_retried_with_backoff() {
+        let mock = MockAfe::new();
+        let backoffs = Arc::new(AtomicUsize::new(0));
+        let mut afe = mock.flaky_three_leds(3, NACK);
+        afe.set_retry_policy(Some(RetryPolicy::new(
+            3,
+            Time::new::<microsecond>(10.0),
+            CountingDelay(backoffs.clone()),
+        )));
+
+        afe.set_averaging(4).unwrap();
+
+        assert_eq!(mock.register(0x1E) & 0x0F, 3);
+        assert_eq!(backoffs.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn retries_exhausted_after_policy_limit() {
+        let mock = MockAfe::new();
+        let backoffs = Arc::new(AtomicUsize::new(0));
+        let mut afe = mock.flaky_three_leds(4, ErrorKind::Bus);
+        afe.set_retry_policy(Some(RetryPolicy::new(
+            3,
+            Time::new::<microsecond>(10.0),
+            CountingDelay(backoffs.clone()),
+        )));
+
+        assert!(matches!(
+            afe.set_averaging(4),
+            Err(AfeError::BusRetriesExhausted(BusError(ErrorKind::Bus)))
+        ));
+        assert_eq!(backoffs.load(Ordering::Relaxed), 3);
+        assert_eq!(mock.transactions(), 0);
+    }
+
+    #[test]
+    fn permanent_errors_not_retried() {
+        let mock = MockAfe::new();
+        let mut afe = mock.flaky_three_leds(1, ErrorKind::Overrun);
+        afe.set_retry_policy(Some(RetryPolicy::without_backoff(3)));
+
+        assert!(matches!(
+            afe.set_averaging(4),
+            Err(AfeError::I2CError(BusError(ErrorKind::Overrun)))
+        ));
+        assert_eq!(mock.transactions(), 0);
+    }
+
+    #[test]
+    fn transient_errors_fail_without_policy() {
+        let mock = MockAfe::new();
+        let mut afe = mock.flaky_three_leds(1, NACK);
+
+        assert!(afe.get_retry_policy().is_none());
+        assert!(matches!(
+            afe.set_averaging(4),
+            Err(AfeError::I2CError(BusError(NACK)))
+        ));
+    }
+}
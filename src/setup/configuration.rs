@@ -0,0 +1,75 @@
+use crate::{
+    clock::ClockConfiguration,
+    led_current::{LedCurrentConfiguration, OffsetCurrentConfiguration},
+    measurement_window::MeasurementWindowConfiguration,
+    modes::LedMode,
+    system::DynamicConfiguration,
+    tia::{CapacitorConfiguration, ResistorConfiguration},
+};
+
+/// Represents the most common settings of the [`AFE4404`], applied at once.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct QuickSetup<MODE: LedMode> {
+    /// The clock source.
+    pub clock: ClockConfiguration,
+    /// The measurement window.
+    pub window: MeasurementWindowConfiguration<MODE>,
+    /// The number of averages performed by the adc.
+    pub averages: u8,
+    /// The TIA resistors.
+    pub resistors: ResistorConfiguration<MODE>,
+    /// The TIA capacitors.
+    pub capacitors: CapacitorConfiguration<MODE>,
+    /// The LEDs current.
+    pub leds_current: LedCurrentConfiguration<MODE>,
+}
+
+/// Uses the internal clock, the datasheet reference measurement window at 100 Hz, no averaging, the TIA gains after a reset and a moderate LEDs current.
+///
+/// # Notes
+///
+/// The setup gives a working acquisition out of the box, to be tuned for the optical path of the board.
+impl<MODE> Default for QuickSetup<MODE>
+where
+    MODE: LedMode,
+    MeasurementWindowConfiguration<MODE>: Default,
+    LedCurrentConfiguration<MODE>: Default,
+{
+    fn default() -> Self {
+        Self {
+            clock: ClockConfiguration::Internal,
+            window: MeasurementWindowConfiguration::default(),
+            averages: 1,
+            resistors: ResistorConfiguration::default(),
+            capacitors: CapacitorConfiguration::default(),
+            leds_current: LedCurrentConfiguration::default(),
+        }
+    }
+}
+
+/// Represents the complete configuration of the [`AFE4404`], so that it can be saved, compared and applied again.
+///
+/// # Notes
+///
+/// The measurement window includes the dynamic power-down window, while `dynamic` selects the blocks powered down during it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AfeConfiguration<MODE: LedMode> {
+    /// The clock source.
+    pub clock: ClockConfiguration,
+    /// The measurement window.
+    pub window: MeasurementWindowConfiguration<MODE>,
+    /// The number of averages performed by the adc.
+    pub averages: u8,
+    /// The TIA resistors.
+    pub resistors: ResistorConfiguration<MODE>,
+    /// The TIA capacitors.
+    pub capacitors: CapacitorConfiguration<MODE>,
+    /// The LEDs current.
+    pub leds_current: LedCurrentConfiguration<MODE>,
+    /// The offset cancellation currents.
+    pub offset_current: OffsetCurrentConfiguration<MODE>,
+    /// The blocks powered down during the dynamic power-down window.
+    pub dynamic: DynamicConfiguration,
+}
@@ -0,0 +1,545 @@
+//! This module contains the functions to apply the most common settings at once.
+
+use embedded_hal::delay::DelayUs;
+use embedded_hal::i2c::I2c;
+use embedded_hal::i2c::SevenBitAddress;
+use uom::si::time::microsecond;
+
+use crate::{
+    device::AFE4404,
+    errors::{AfeError, TransitionError},
+    modes::{LedMode, OneLedMode, ThreeLedsMode, TwoLedsMode, Uninitialized},
+    system::SettleCause,
+};
+
+pub use configuration::{AfeConfiguration, QuickSetup};
+
+mod configuration;
+
+impl<I2C> AFE4404<I2C, ThreeLedsMode>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    /// Applies the clock, the measurement window, the averaging, the TIA gains and the LEDs current, then waits for the readings to settle.
+    ///
+    /// # Notes
+    ///
+    /// The clock source is applied first, since the measurement window depends on it.
+    /// The wait time is the one returned by `settle_time()` for a gain change, which covers the LEDs current change too.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// This function returns an error if the delay encounters an error.
+    /// Setting a value outside its allowed range will result in an error.
+    pub fn quick_setup<D: DelayUs>(
+        &mut self,
+        setup: &QuickSetup<ThreeLedsMode>,
+        delay: &mut D,
+    ) -> Result<(), AfeError<I2C::Error>> {
+        self.apply_quick_setup(setup)?;
+
+        self.wait_settle(SettleCause::GainChange, delay)
+    }
+
+    /// Applies the clock, the measurement window, the averaging, the TIA gains and the LEDs current.
+    fn apply_quick_setup(
+        &mut self,
+        setup: &QuickSetup<ThreeLedsMode>,
+    ) -> Result<(), AfeError<I2C::Error>> {
+        self.set_clock_source(setup.clock)?;
+        self.set_measurement_window(&setup.window)?;
+        self.set_averaging(setup.averages)?;
+        self.set_tia_resistors(&setup.resistors)?;
+        self.set_tia_capacitors(&setup.capacitors)?;
+        self.set_leds_current(&setup.leds_current)?;
+
+        Ok(())
+    }
+}
+
+impl<I2C> AFE4404<I2C, ThreeLedsMode, Uninitialized>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    /// Initializes the [`AFE4404`] following the startup sequence of the datasheet, returning the configured device.
+    ///
+    /// # Notes
+    ///
+    /// The device is software reset, then the setup is applied as by `quick_setup()` function.
+    /// The function waits the `tCHANNEL` time returned by `settle_time()` for a power-up, so the first readings are already accurate.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// This function returns an error if the delay encounters an error.
+    /// Setting a value outside its allowed range will result in an error.
+    /// The uninitialized device is given back with the error.
+    // The device is returned in both cases, so the error is not larger than the value.
+    #[allow(clippy::result_large_err)]
+    pub fn init<D: DelayUs>(
+        self,
+        setup: &QuickSetup<ThreeLedsMode>,
+        delay: &mut D,
+    ) -> Result<AFE4404<I2C, ThreeLedsMode>, TransitionError<Self, I2C::Error>> {
+        let mut afe: AFE4404<I2C, ThreeLedsMode> = self.into_state();
+
+        match afe.startup(delay, |afe| afe.apply_quick_setup(setup)) {
+            Ok(()) => Ok(afe),
+            Err(error) => Err(TransitionError {
+                device: afe.into_state(),
+                error,
+            }),
+        }
+    }
+}
+
+impl<I2C> AFE4404<I2C, TwoLedsMode>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    /// Applies the clock, the measurement window, the averaging, the TIA gains and the LEDs current, then waits for the readings to settle.
+    ///
+    /// # Notes
+    ///
+    /// The clock source is applied first, since the measurement window depends on it.
+    /// The wait time is the one returned by `settle_time()` for a gain change, which covers the LEDs current change too.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// This function returns an error if the delay encounters an error.
+    /// Setting a value outside its allowed range will result in an error.
+    pub fn quick_setup<D: DelayUs>(
+        &mut self,
+        setup: &QuickSetup<TwoLedsMode>,
+        delay: &mut D,
+    ) -> Result<(), AfeError<I2C::Error>> {
+        self.apply_quick_setup(setup)?;
+
+        self.wait_settle(SettleCause::GainChange, delay)
+    }
+
+    /// Applies the clock, the measurement window, the averaging, the TIA gains and the LEDs current.
+    fn apply_quick_setup(
+        &mut self,
+        setup: &QuickSetup<TwoLedsMode>,
+    ) -> Result<(), AfeError<I2C::Error>> {
+        self.set_clock_source(setup.clock)?;
+        self.set_measurement_window(&setup.window)?;
+        self.set_averaging(setup.averages)?;
+        self.set_tia_resistors(&setup.resistors)?;
+        self.set_tia_capacitors(&setup.capacitors)?;
+        self.set_leds_current(&setup.leds_current)?;
+
+        Ok(())
+    }
+}
+
+impl<I2C> AFE4404<I2C, TwoLedsMode, Uninitialized>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    /// Initializes the [`AFE4404`] following the startup sequence of the datasheet, returning the configured device.
+    ///
+    /// # Notes
+    ///
+    /// The device is software reset, then the setup is applied as by `quick_setup()` function.
+    /// The function waits the `tCHANNEL` time returned by `settle_time()` for a power-up, so the first readings are already accurate.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// This function returns an error if the delay encounters an error.
+    /// Setting a value outside its allowed range will result in an error.
+    /// The uninitialized device is given back with the error.
+    // The device is returned in both cases, so the error is not larger than the value.
+    #[allow(clippy::result_large_err)]
+    pub fn init<D: DelayUs>(
+        self,
+        setup: &QuickSetup<TwoLedsMode>,
+        delay: &mut D,
+    ) -> Result<AFE4404<I2C, TwoLedsMode>, TransitionError<Self, I2C::Error>> {
+        let mut afe: AFE4404<I2C, TwoLedsMode> = self.into_state();
+
+        match afe.startup(delay, |afe| afe.apply_quick_setup(setup)) {
+            Ok(()) => Ok(afe),
+            Err(error) => Err(TransitionError {
+                device: afe.into_state(),
+                error,
+            }),
+        }
+    }
+}
+
+impl<I2C> AFE4404<I2C, OneLedMode>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    /// Applies the clock, the measurement window, the averaging, the TIA gains and the LEDs current, then waits for the readings to settle.
+    ///
+    /// # Notes
+    ///
+    /// The clock source is applied first, since the measurement window depends on it.
+    /// The wait time is the one returned by `settle_time()` for a gain change, which covers the LEDs current change too.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// This function returns an error if the delay encounters an error.
+    /// Setting a value outside its allowed range will result in an error.
+    pub fn quick_setup<D: DelayUs>(
+        &mut self,
+        setup: &QuickSetup<OneLedMode>,
+        delay: &mut D,
+    ) -> Result<(), AfeError<I2C::Error>> {
+        self.apply_quick_setup(setup)?;
+
+        self.wait_settle(SettleCause::GainChange, delay)
+    }
+
+    /// Applies the clock, the measurement window, the averaging, the TIA gains and the LEDs current.
+    fn apply_quick_setup(
+        &mut self,
+        setup: &QuickSetup<OneLedMode>,
+    ) -> Result<(), AfeError<I2C::Error>> {
+        self.set_clock_source(setup.clock)?;
+        self.set_measurement_window(&setup.window)?;
+        self.set_averaging(setup.averages)?;
+        self.set_tia_resistors(&setup.resistors)?;
+        self.set_tia_capacitors(&setup.capacitors)?;
+        self.set_leds_current(&setup.leds_current)?;
+
+        Ok(())
+    }
+}
+
+impl<I2C> AFE4404<I2C, OneLedMode, Uninitialized>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    /// Initializes the [`AFE4404`] following the startup sequence of the datasheet, returning the configured device.
+    ///
+    /// # Notes
+    ///
+    /// The device is software reset, then the setup is applied as by `quick_setup()` function.
+    /// The function waits the `tCHANNEL` time returned by `settle_time()` for a power-up, so the first readings are already accurate.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// This function returns an error if the delay encounters an error.
+    /// Setting a value outside its allowed range will result in an error.
+    /// The uninitialized device is given back with the error.
+    // The device is returned in both cases, so the error is not larger than the value.
+    #[allow(clippy::result_large_err)]
+    pub fn init<D: DelayUs>(
+        self,
+        setup: &QuickSetup<OneLedMode>,
+        delay: &mut D,
+    ) -> Result<AFE4404<I2C, OneLedMode>, TransitionError<Self, I2C::Error>> {
+        let mut afe: AFE4404<I2C, OneLedMode> = self.into_state();
+
+        match afe.startup(delay, |afe| afe.apply_quick_setup(setup)) {
+            Ok(()) => Ok(afe),
+            Err(error) => Err(TransitionError {
+                device: afe.into_state(),
+                error,
+            }),
+        }
+    }
+}
+
+impl AfeConfiguration<ThreeLedsMode> {
+    /// Applies the whole configuration to the [`AFE4404`], returning the configuration actually applied.
+    ///
+    /// # Notes
+    ///
+    /// The clock source is applied first, since the measurement window depends on it.
+    /// The returned configuration holds the rounded values, so it equals the one returned by `capture()` function.
+    /// After calling this function, a wait time of `tCHANNEL` should be applied before high-accuracy readings.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// Setting a value outside its allowed range will result in an error.
+    pub fn apply<I2C>(
+        &self,
+        afe: &mut AFE4404<I2C, ThreeLedsMode>,
+    ) -> Result<Self, AfeError<I2C::Error>>
+    where
+        I2C: I2c<SevenBitAddress>,
+    {
+        Ok(Self {
+            clock: afe.set_clock_source(self.clock)?,
+            window: afe.set_measurement_window(&self.window)?,
+            averages: afe.set_averaging(self.averages)?,
+            resistors: afe.set_tia_resistors(&self.resistors)?,
+            capacitors: afe.set_tia_capacitors(&self.capacitors)?,
+            leds_current: afe.set_leds_current(&self.leds_current)?,
+            offset_current: afe.set_offset_current(&self.offset_current)?,
+            dynamic: afe.set_dynamic(&self.dynamic)?,
+        })
+    }
+
+    /// Reads the whole configuration from the [`AFE4404`].
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub fn capture<I2C>(afe: &mut AFE4404<I2C, ThreeLedsMode>) -> Result<Self, AfeError<I2C::Error>>
+    where
+        I2C: I2c<SevenBitAddress>,
+    {
+        Ok(Self {
+            clock: afe.get_clock_source()?,
+            window: afe.get_measurement_window()?,
+            averages: afe.get_averaging()?,
+            resistors: afe.get_tia_resistors()?,
+            capacitors: afe.get_tia_capacitors()?,
+            leds_current: afe.get_leds_current()?,
+            offset_current: afe.get_offset_current()?,
+            dynamic: afe.get_dynamic()?,
+        })
+    }
+}
+
+impl AfeConfiguration<TwoLedsMode> {
+    /// Applies the whole configuration to the [`AFE4404`], returning the configuration actually applied.
+    ///
+    /// # Notes
+    ///
+    /// The clock source is applied first, since the measurement window depends on it.
+    /// The returned configuration holds the rounded values, so it equals the one returned by `capture()` function.
+    /// After calling this function, a wait time of `tCHANNEL` should be applied before high-accuracy readings.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// Setting a value outside its allowed range will result in an error.
+    pub fn apply<I2C>(
+        &self,
+        afe: &mut AFE4404<I2C, TwoLedsMode>,
+    ) -> Result<Self, AfeError<I2C::Error>>
+    where
+        I2C: I2c<SevenBitAddress>,
+    {
+        Ok(Self {
+            clock: afe.set_clock_source(self.clock)?,
+            window: afe.set_measurement_window(&self.window)?,
+            averages: afe.set_averaging(self.averages)?,
+            resistors: afe.set_tia_resistors(&self.resistors)?,
+            capacitors: afe.set_tia_capacitors(&self.capacitors)?,
+            leds_current: afe.set_leds_current(&self.leds_current)?,
+            offset_current: afe.set_offset_current(&self.offset_current)?,
+            dynamic: afe.set_dynamic(&self.dynamic)?,
+        })
+    }
+
+    /// Reads the whole configuration from the [`AFE4404`].
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub fn capture<I2C>(afe: &mut AFE4404<I2C, TwoLedsMode>) -> Result<Self, AfeError<I2C::Error>>
+    where
+        I2C: I2c<SevenBitAddress>,
+    {
+        Ok(Self {
+            clock: afe.get_clock_source()?,
+            window: afe.get_measurement_window()?,
+            averages: afe.get_averaging()?,
+            resistors: afe.get_tia_resistors()?,
+            capacitors: afe.get_tia_capacitors()?,
+            leds_current: afe.get_leds_current()?,
+            offset_current: afe.get_offset_current()?,
+            dynamic: afe.get_dynamic()?,
+        })
+    }
+}
+
+impl AfeConfiguration<OneLedMode> {
+    /// Applies the whole configuration to the [`AFE4404`], returning the configuration actually applied.
+    ///
+    /// # Notes
+    ///
+    /// The clock source is applied first, since the measurement window depends on it.
+    /// The returned configuration holds the rounded values, so it equals the one returned by `capture()` function.
+    /// After calling this function, a wait time of `tCHANNEL` should be applied before high-accuracy readings.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// Setting a value outside its allowed range will result in an error.
+    pub fn apply<I2C>(
+        &self,
+        afe: &mut AFE4404<I2C, OneLedMode>,
+    ) -> Result<Self, AfeError<I2C::Error>>
+    where
+        I2C: I2c<SevenBitAddress>,
+    {
+        Ok(Self {
+            clock: afe.set_clock_source(self.clock)?,
+            window: afe.set_measurement_window(&self.window)?,
+            averages: afe.set_averaging(self.averages)?,
+            resistors: afe.set_tia_resistors(&self.resistors)?,
+            capacitors: afe.set_tia_capacitors(&self.capacitors)?,
+            leds_current: afe.set_leds_current(&self.leds_current)?,
+            offset_current: afe.set_offset_current(&self.offset_current)?,
+            dynamic: afe.set_dynamic(&self.dynamic)?,
+        })
+    }
+
+    /// Reads the whole configuration from the [`AFE4404`].
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub fn capture<I2C>(afe: &mut AFE4404<I2C, OneLedMode>) -> Result<Self, AfeError<I2C::Error>>
+    where
+        I2C: I2c<SevenBitAddress>,
+    {
+        Ok(Self {
+            clock: afe.get_clock_source()?,
+            window: afe.get_measurement_window()?,
+            averages: afe.get_averaging()?,
+            resistors: afe.get_tia_resistors()?,
+            capacitors: afe.get_tia_capacitors()?,
+            leds_current: afe.get_leds_current()?,
+            offset_current: afe.get_offset_current()?,
+            dynamic: afe.get_dynamic()?,
+        })
+    }
+}
+
+impl<I2C, MODE> AFE4404<I2C, MODE>
+where
+    I2C: I2c<SevenBitAddress>,
+    MODE: LedMode,
+{
+    /// Runs the startup sequence of the datasheet, applying the setup with `apply`.
+    fn startup<D: DelayUs>(
+        &mut self,
+        delay: &mut D,
+        apply: impl FnOnce(&mut Self) -> Result<(), AfeError<I2C::Error>>,
+    ) -> Result<(), AfeError<I2C::Error>> {
+        self.sw_reset()?;
+        delay.delay_ms(1).map_err(|_| AfeError::DelayError)?;
+
+        apply(self)?;
+
+        self.wait_settle(SettleCause::PowerUp, delay)
+    }
+
+    /// Waits the time returned by `settle_time()` for the given cause.
+    fn wait_settle<D: DelayUs>(
+        &mut self,
+        cause: SettleCause,
+        delay: &mut D,
+    ) -> Result<(), AfeError<I2C::Error>> {
+        let settle = self.settle_time(cause)?;
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let settle_us = settle.get::<microsecond>().ceil() as u32;
+
+        delay
+            .delay_us(settle_us)
+            .map_err(|_| AfeError::DelayError)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uom::si::{
+        capacitance::picofarad,
+        electric_current::milliampere,
+        electrical_resistance::kiloohm,
+        f32::{Capacitance, ElectricCurrent, ElectricalResistance, Frequency},
+        frequency::megahertz,
+    };
+
+    use super::*;
+    use crate::{
+        clock::ClockConfiguration,
+        device::DEFAULT_ADDRESS,
+        led_current::LedCurrentConfiguration,
+        mock::{MockAfe, NoDelay},
+        tia::{CapacitorConfiguration, ResistorConfiguration},
+    };
+
+    #[test]
+    fn quick_setup_matches_registers() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+        let setup = QuickSetup::<ThreeLedsMode> {
+            clock: ClockConfiguration::InternalToOutput { division_ratio: 4 },
+            averages: 4,
+            resistors: ResistorConfiguration::<ThreeLedsMode>::new(
+                ElectricalResistance::new::<kiloohm>(100.0),
+                ElectricalResistance::new::<kiloohm>(50.0),
+            ),
+            capacitors: CapacitorConfiguration::<ThreeLedsMode>::new(
+                Capacitance::new::<picofarad>(10.0),
+                Capacitance::new::<picofarad>(20.0),
+            ),
+            leds_current: LedCurrentConfiguration::<ThreeLedsMode>::new(
+                ElectricCurrent::new::<milliampere>(20.0),
+                ElectricCurrent::new::<milliampere>(40.0),
+                ElectricCurrent::new::<milliampere>(10.0),
+            ),
+            ..QuickSetup::default()
+        };
+
+        afe.quick_setup(&setup, &mut NoDelay).unwrap();
+
+        assert_eq!(afe.get_clock_source().unwrap(), setup.clock);
+        assert_eq!(mock.register(0x1E) & 0x0F, 3);
+        assert_eq!(afe.get_averaging().unwrap(), setup.averages);
+        assert_eq!(afe.get_tia_resistors().unwrap(), setup.resistors);
+        assert_eq!(afe.get_tia_capacitors().unwrap(), setup.capacitors);
+
+        let window = afe.get_measurement_window().unwrap();
+        let quantisation = afe.get_timing_quantisation().unwrap();
+        assert!((*window.period() - *setup.window.period()).abs() <= quantisation);
+        for (timing, expected) in window.timings().into_iter().zip(setup.window.timings()) {
+            assert!((timing - expected).abs() <= quantisation);
+        }
+
+        let leds_current = afe.get_leds_current().unwrap();
+        let step = afe.led_current_step().unwrap();
+        for (current, expected) in [
+            (leds_current.led1(), setup.leds_current.led1()),
+            (leds_current.led2(), setup.leds_current.led2()),
+            (leds_current.led3(), setup.leds_current.led3()),
+        ] {
+            assert!((*current - *expected).abs() <= step / 2.0);
+        }
+    }
+
+    #[test]
+    fn failed_init_gives_device_back() {
+        let mock = MockAfe::new();
+        let afe = AFE4404::with_two_leds(
+            mock.clone(),
+            DEFAULT_ADDRESS,
+            Frequency::new::<megahertz>(4.0),
+        );
+        let setup = QuickSetup::<TwoLedsMode> {
+            averages: 0,
+            ..QuickSetup::default()
+        };
+
+        let Err(TransitionError { device, error }) = afe.init(&setup, &mut NoDelay) else {
+            panic!("the initialization should fail");
+        };
+        assert!(matches!(
+            error,
+            AfeError::NumberOfAveragesOutsideAllowedRange
+        ));
+
+        let afe = device.init(&QuickSetup::default(), &mut NoDelay).unwrap();
+        afe.release();
+    }
+}
@@ -0,0 +1,115 @@
+//! This module contains the functions to drive several [`AFE4404`] on the same I2C bus.
+//!
+//! Each device owns its bus, so a shared bus is given to the devices as one `I2c` implementor each,
+//! such as the `RefCellDevice`, `CriticalSectionDevice` or `MutexDevice` of `embedded-hal-bus`.
+//!
+//! Two frontends sharing the bus and the clock:
+//!
+//! ```ignore
+//! let bus = RefCell::new(i2c);
+//!
+//! let [leader, follower] = AFE4404::array_with_three_leds(
+//!     [RefCellDevice::new(&bus), RefCellDevice::new(&bus)],
+//!     [0x58u8, 0x59u8],
+//!     Frequency::new::<megahertz>(4.0),
+//! );
+//!
+//! let setup = QuickSetup::<ThreeLedsMode> { /* ... */ };
+//! let mut leader = leader.init(&setup, &mut delay).expect("Cannot initialize the leader");
+//! let mut follower = follower.init(&setup, &mut delay).expect("Cannot initialize the follower");
+//!
+//! leader.drive_clock_of(&mut follower).expect("Cannot share the clock");
+//!
+//! let leader_sample = leader.read();
+//! let follower_sample = follower.read();
+//! ```
+
+use embedded_hal::i2c::{I2c, SevenBitAddress};
+use uom::si::f32::Frequency;
+
+use crate::{
+    clock::ClockConfiguration,
+    device::AFE4404,
+    errors::AfeError,
+    modes::{LedMode, ThreeLedsMode, TwoLedsMode, Uninitialized, UninitializedMode},
+};
+
+impl<I2C> AFE4404<I2C, UninitializedMode>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    /// Creates several uninitialized AFE4404 instances with three LEDs, one for each bus and address pair.
+    ///
+    /// # Notes
+    ///
+    /// The address of the [`AFE4404`] is fixed to [`DEFAULT_ADDRESS`](crate::device::DEFAULT_ADDRESS),
+    /// so devices sharing the same bus must be given different addresses by an address translator or an I2C multiplexer.
+    pub fn array_with_three_leds<const N: usize>(
+        buses: [I2C; N],
+        addresses: [SevenBitAddress; N],
+        clock: Frequency,
+    ) -> [AFE4404<I2C, ThreeLedsMode, Uninitialized>; N] {
+        let mut index = 0;
+
+        buses.map(|i2c| {
+            let address = addresses[index];
+            index += 1;
+
+            AFE4404::with_three_leds(i2c, address, clock)
+        })
+    }
+
+    /// Creates several uninitialized AFE4404 instances with two LEDs, one for each bus and address pair.
+    ///
+    /// # Notes
+    ///
+    /// The address of the [`AFE4404`] is fixed to [`DEFAULT_ADDRESS`](crate::device::DEFAULT_ADDRESS),
+    /// so devices sharing the same bus must be given different addresses by an address translator or an I2C multiplexer.
+    pub fn array_with_two_leds<const N: usize>(
+        buses: [I2C; N],
+        addresses: [SevenBitAddress; N],
+        clock: Frequency,
+    ) -> [AFE4404<I2C, TwoLedsMode, Uninitialized>; N] {
+        let mut index = 0;
+
+        buses.map(|i2c| {
+            let address = addresses[index];
+            index += 1;
+
+            AFE4404::with_two_leds(i2c, address, clock)
+        })
+    }
+}
+
+impl<I2C, MODE> AFE4404<I2C, MODE>
+where
+    I2C: I2c<SevenBitAddress>,
+    MODE: LedMode,
+{
+    /// Outputs the clock of this [`AFE4404`] to another one, so that their timers run at the same rate.
+    ///
+    /// # Notes
+    ///
+    /// The `CLK` pin of this device must be wired to the `CLK` pin of the follower.
+    /// This device outputs its internal oscillator undivided and the follower is set to the external clock, with the same frequency.
+    /// The timers are not started at the same instant, but their offset stays constant once both are running.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Using a clock frequency different from 4MHz on this device will result in an error.
+    pub fn drive_clock_of<FOLLOWER>(
+        &mut self,
+        follower: &mut AFE4404<I2C, FOLLOWER>,
+    ) -> Result<(), AfeError<I2C::Error>>
+    where
+        FOLLOWER: LedMode,
+    {
+        self.set_clock_source(ClockConfiguration::InternalToOutput { division_ratio: 1 })?;
+
+        follower.set_clock_source(ClockConfiguration::External)?;
+        follower.set_clock_frequency(self.clock);
+
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 /// Represents the dynamic blocks inside the [`AFE4404`].
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct DynamicConfiguration {
     /// Supply voltage for LEDs.
     pub transmitter: State,
@@ -11,8 +12,33 @@ pub struct DynamicConfiguration {
     pub rest_of_adc: State,
 }
 
+/// Represents the RX control of the [`AFE4404`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RxControl {
+    /// Photodiode.
+    pub photodiode: State,
+    /// Whether the TIA inputs are shorted.
+    pub input_short: bool,
+    /// The division ratio of the external clock, as encoded in the register.
+    pub clkdiv_extmode: u8,
+}
+
+/// Represents the cause of a settling wait before high-accuracy readings.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SettleCause {
+    /// The [`AFE4404`] or its RX portion was powered up.
+    PowerUp,
+    /// The TIA resistors, capacitors or the offset currents were changed.
+    GainChange,
+    /// The LEDs current was changed.
+    CurrentChange,
+}
+
 /// Represents the state of a block.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum State {
     /// The block is enabled.
     Enabled,
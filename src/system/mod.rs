@@ -1,11 +1,20 @@
 //! This module contains the system related functions.
 
+use embedded_hal::delay::DelayUs;
 use embedded_hal::i2c::I2c;
 use embedded_hal::i2c::SevenBitAddress;
+use uom::si::{f32::Time, time::millisecond};
 
-use crate::{device::AFE4404, errors::AfeError, modes::LedMode, register_structs::R00h};
+use crate::{
+    device::AFE4404,
+    errors::{AfeError, TransitionError},
+    modes::{DeviceState, LedMode, PoweredDown},
+    register::is_configuration_register,
+    register_structs::{R00h, R1Dh},
+    RegisterAddress,
+};
 
-pub use configuration::{DynamicConfiguration, State};
+pub use configuration::{DynamicConfiguration, RxControl, SettleCause, State};
 
 mod configuration;
 
@@ -20,43 +29,87 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     pub fn sw_reset(&mut self) -> Result<(), AfeError<I2C::Error>> {
-        self.registers.r00h.write(R00h::new().with_sw_reset(true))?;
+        self.registers
+            .r00h()
+            .write(R00h::new().with_sw_reset(true))?;
+        self.timing_quantisation = None;
 
         Ok(())
     }
 
-    /// Software powers down the entire [`AFE4404`].
+    /// Probes the [`AFE4404`] writing a test pattern and reading it back.
     ///
     /// # Notes
     ///
-    /// To resume the entire [`AFE4404`] call `sw_power_up()` function.
+    /// The pattern is written into the window period register, which is restored afterwards.
+    /// Both reads reach the device, bypassing the register cache, so the writes must not be staged while probing.
     ///
     /// # Errors
     ///
     /// This function returns an error if the I2C bus encounters an error.
-    pub fn sw_power_down(&mut self) -> Result<(), AfeError<I2C::Error>> {
-        let r23h_prev = self.registers.r23h.read()?;
+    /// This function returns an error if the pattern read back differs from the written one.
+    pub fn probe(&mut self) -> Result<(), AfeError<I2C::Error>> {
+        const PATTERN: u16 = 0xA55A;
+
+        let r1dh_prev = self.registers.r1Dh().read_uncached()?;
+
+        self.registers
+            .r1Dh()
+            .write(R1Dh::new().with_prpct(PATTERN))?;
+        let r1dh_probe = self.registers.r1Dh().read_uncached()?;
+        self.registers.r1Dh().write(r1dh_prev)?;
 
-        self.registers.r23h.write(r23h_prev.with_pdnafe(true))?;
+        if r1dh_probe.prpct() != PATTERN {
+            return Err(AfeError::ProbeFailed);
+        }
 
         Ok(())
     }
 
-    /// Software powers up the entire [`AFE4404`].
+    /// Software resets the [`AFE4404`], waits for the reset to complete and probes the device.
     ///
     /// # Notes
     ///
-    /// After calling this function, a wait time of `tCHANNEL` should be applied before high-accuracy readings.
+    /// Use this function to recover after a brownout, every register is restored to its default value.
     ///
     /// # Errors
     ///
     /// This function returns an error if the I2C bus encounters an error.
-    pub fn sw_power_up(&mut self) -> Result<(), AfeError<I2C::Error>> {
-        let r23h_prev = self.registers.r23h.read()?;
+    /// This function returns an error if the delay encounters an error.
+    /// This function returns an error if the device does not answer the probe correctly.
+    pub fn reset_and_verify<D: DelayUs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), AfeError<I2C::Error>> {
+        self.sw_reset()?;
 
-        self.registers.r23h.write(r23h_prev.with_pdnafe(false))?;
+        delay.delay_ms(1).map_err(|_| AfeError::DelayError)?;
 
-        Ok(())
+        self.probe()
+    }
+
+    /// Software powers down the entire [`AFE4404`], returning the powered down device.
+    ///
+    /// # Notes
+    ///
+    /// The powered down device can only be powered up again, with `sw_power_up()` function, or released,
+    /// so that it cannot be configured or read while the readings are meaningless.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error, giving back the active device.
+    // The device is returned in both cases, so the error is not larger than the value.
+    #[allow(clippy::result_large_err)]
+    pub fn sw_power_down(
+        mut self,
+    ) -> Result<AFE4404<I2C, MODE, PoweredDown>, TransitionError<Self, I2C::Error>> {
+        match self.set_afe_power_down(true) {
+            Ok(()) => Ok(self.into_state()),
+            Err(error) => Err(TransitionError {
+                device: self,
+                error,
+            }),
+        }
     }
 
     /// Software powers down the RX portion of the [`AFE4404`].
@@ -69,9 +122,9 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     pub fn sw_power_down_rx(&mut self) -> Result<(), AfeError<I2C::Error>> {
-        let r23h_prev = self.registers.r23h.read()?;
+        let r23h_prev = self.registers.r23h().read()?;
 
-        self.registers.r23h.write(r23h_prev.with_pdnrx(true))?;
+        self.registers.r23h().write(r23h_prev.with_pdnrx(true))?;
 
         Ok(())
     }
@@ -86,13 +139,206 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     pub fn sw_power_up_rx(&mut self) -> Result<(), AfeError<I2C::Error>> {
-        let r23h_prev = self.registers.r23h.read()?;
+        let r23h_prev = self.registers.r23h().read()?;
+
+        self.registers.r23h().write(r23h_prev.with_pdnrx(false))?;
+
+        Ok(())
+    }
+
+    /// Enters a low-power idle state between measurement bursts.
+    ///
+    /// # Notes
+    ///
+    /// The transmitter, the ADC, the TIA and the rest of the ADC are disabled during dynamic power down and the RX portion is powered down.
+    /// The previous dynamic configuration is saved and restored by `exit_idle()` function.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub fn enter_idle(&mut self) -> Result<(), AfeError<I2C::Error>> {
+        let dynamic_prev = self.get_dynamic()?;
+
+        self.set_dynamic(&DynamicConfiguration {
+            transmitter: State::Disabled,
+            adc: State::Disabled,
+            tia: State::Disabled,
+            rest_of_adc: State::Disabled,
+        })?;
+        self.sw_power_down_rx()?;
+
+        if self.idle_dynamic.is_none() {
+            self.idle_dynamic = Some(dynamic_prev);
+        }
+
+        Ok(())
+    }
+
+    /// Exits the low-power idle state entered with `enter_idle()` function.
+    ///
+    /// # Notes
+    ///
+    /// The RX portion is powered up and the saved dynamic configuration is restored, then the function waits 1 ms for the blocks to settle.
+    /// A wait time of `tCHANNEL` should still be applied before high-accuracy readings.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// This function returns an error if the delay encounters an error.
+    pub fn exit_idle<D: DelayUs>(&mut self, delay: &mut D) -> Result<(), AfeError<I2C::Error>> {
+        self.sw_power_up_rx()?;
+
+        if let Some(dynamic) = self.idle_dynamic.take() {
+            self.set_dynamic(&dynamic)?;
+        }
+
+        delay.delay_ms(1).map_err(|_| AfeError::DelayError)?;
+
+        Ok(())
+    }
+
+    /// Reads the 24 bit content of any register address, including the reserved ones.
+    ///
+    /// # Notes
+    ///
+    /// Warning: the reserved registers are not documented and may change between silicon revisions, use this function only for experiments.
+    /// The register reading flag is set while reading any address outside the value registers `R2Ah`-`R2Fh` and `R3Fh`-`R40h`,
+    /// as for the known configuration registers.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub fn read_reserved(&mut self, reg_addr: u8) -> Result<u32, AfeError<I2C::Error>> {
+        // Any register can be used to reach the bus.
+        self.registers.r00h().read_address(reg_addr)
+    }
+
+    /// Reads the 24 bit content of a register.
+    ///
+    /// # Notes
+    ///
+    /// Use this function to access the fields not covered by the typed functions yet.
+    /// The register reading flag is handled as for the typed functions.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Reading the write only control register `R00h` will result in an error.
+    pub fn read_register(&mut self, address: RegisterAddress) -> Result<u32, AfeError<I2C::Error>> {
+        if address == RegisterAddress::R00h {
+            return Err(AfeError::WriteOnlyRegister {
+                reg_addr: address as u8,
+            });
+        }
+
+        // Any register can be used to reach the bus.
+        self.registers.r00h().read_address(address as u8)
+    }
 
-        self.registers.r23h.write(r23h_prev.with_pdnrx(false))?;
+    /// Writes the 24 bit content of a register.
+    ///
+    /// # Notes
+    ///
+    /// Warning: the driver does not track the written value, so it can break the assumptions of the typed functions,
+    /// for example by changing the clock or the LED mode.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Writing a value wider than 24 bits will result in an error.
+    pub fn write_register(
+        &mut self,
+        address: RegisterAddress,
+        value: u32,
+    ) -> Result<(), AfeError<I2C::Error>> {
+        if value > 0x00FF_FFFF {
+            return Err(AfeError::InvalidRegisterValue {
+                reg_addr: address as u8,
+            });
+        }
+
+        // Any register can be used to reach the bus.
+        self.registers.r00h().write_address(address as u8, value)
+    }
+
+    /// Reads the 24 bit content of all the registers, as address and value pairs sorted by address.
+    ///
+    /// # Notes
+    ///
+    /// The dump can be stored to capture the state of a device in the field, or restored by `restore_registers()` after a power-down.
+    /// The write only control register `R00h` is skipped.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub fn dump_registers(
+        &mut self,
+    ) -> Result<[(u8, u32); RegisterAddress::COUNT - 1], AfeError<I2C::Error>> {
+        let mut dump = [(0, 0); RegisterAddress::COUNT - 1];
+
+        let addresses = RegisterAddress::ALL
+            .into_iter()
+            .filter(|&address| address != RegisterAddress::R00h);
+        for (entry, address) in dump.iter_mut().zip(addresses) {
+            *entry = (address as u8, self.read_register(address)?);
+        }
+
+        Ok(dump)
+    }
+
+    /// Writes back the registers of a dump, as returned by `dump_registers()`.
+    ///
+    /// # Notes
+    ///
+    /// Only the configuration registers are written, in the given order.
+    /// The control register `R00h` and the read-only registers, as the ADC values, are skipped.
+    /// The clock frequency given to the driver is not changed, restore a dump taken with the same clock.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Restoring a value wider than 24 bits will result in an error.
+    pub fn restore_registers(&mut self, dump: &[(u8, u32)]) -> Result<(), AfeError<I2C::Error>> {
+        for &(reg_addr, value) in dump {
+            if reg_addr == 0x00 || !is_configuration_register(reg_addr) {
+                continue;
+            }
+
+            if value > 0x00FF_FFFF {
+                return Err(AfeError::InvalidRegisterValue { reg_addr });
+            }
+
+            // Any register can be used to reach the bus.
+            self.registers.r00h().write_address(reg_addr, value)?;
+        }
+
+        self.timing_quantisation = None;
 
         Ok(())
     }
 
+    /// Gets the recommended wait time `tCHANNEL` before high-accuracy readings.
+    ///
+    /// # Notes
+    ///
+    /// A LED current change is settled within the lighting phase, so one sample interval is enough to get a complete sample.
+    /// A gain change has to settle through the TIA and the ADC, so two sample intervals are needed.
+    /// After a power-up the blocks need 1 ms to wake up before the two sample intervals.
+    /// The sample interval is the window period multiplied by the decimation factor.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub fn settle_time(&mut self, cause: SettleCause) -> Result<Time, AfeError<I2C::Error>> {
+        let sample_interval = self.sample_interval()?;
+
+        Ok(match cause {
+            SettleCause::PowerUp => Time::new::<millisecond>(1.0) + 2.0 * sample_interval,
+            SettleCause::GainChange => 2.0 * sample_interval,
+            SettleCause::CurrentChange => sample_interval,
+        })
+    }
+
     /// Sets the functional blocks to disable during dynamic power down.
     ///
     /// # Errors
@@ -102,9 +348,9 @@ where
         &mut self,
         configuration: &DynamicConfiguration,
     ) -> Result<DynamicConfiguration, AfeError<I2C::Error>> {
-        let r23h_prev = self.registers.r23h.read()?;
+        let r23h_prev = self.registers.r23h().read()?;
 
-        self.registers.r23h.write(
+        self.registers.r23h().write(
             r23h_prev
                 .with_dynamic1(configuration.transmitter.into())
                 .with_dynamic2(configuration.adc.into())
@@ -121,7 +367,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     pub fn get_dynamic(&mut self) -> Result<DynamicConfiguration, AfeError<I2C::Error>> {
-        let r23h_prev = self.registers.r23h.read()?;
+        let r23h_prev = self.registers.r23h().read()?;
 
         Ok(DynamicConfiguration {
             transmitter: r23h_prev.dynamic1().into(),
@@ -141,10 +387,10 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     pub fn set_photodiode(&mut self, state: State) -> Result<State, AfeError<I2C::Error>> {
-        let r31h_prev = self.registers.r31h.read()?;
+        let r31h_prev = self.registers.r31h().read()?;
 
         self.registers
-            .r31h
+            .r31h()
             .write(r31h_prev.with_pd_disconnect(state.into()))?;
 
         Ok(state)
@@ -160,8 +406,307 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     pub fn get_photodiode(&mut self) -> Result<State, AfeError<I2C::Error>> {
-        let r31h_prev = self.registers.r31h.read()?;
+        let r31h_prev = self.registers.r31h().read()?;
 
         Ok(r31h_prev.pd_disconnect().into())
     }
+
+    /// Sets the whole RX control register.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a `clkdiv_extmode` value greater than 7 will result in an error.
+    pub fn set_rx_control(
+        &mut self,
+        configuration: &RxControl,
+    ) -> Result<RxControl, AfeError<I2C::Error>> {
+        let r31h_prev = self.registers.r31h().read()?;
+
+        if configuration.clkdiv_extmode > 7 {
+            return Err(AfeError::ClockDivisionRatioOutsideAllowedRange);
+        }
+
+        self.registers.r31h().write(
+            r31h_prev
+                .with_pd_disconnect(configuration.photodiode.into())
+                .with_enable_input_short(configuration.input_short)
+                .with_clkdiv_extmode(configuration.clkdiv_extmode),
+        )?;
+
+        Ok(*configuration)
+    }
+
+    /// Gets the whole RX control register.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub fn get_rx_control(&mut self) -> Result<RxControl, AfeError<I2C::Error>> {
+        let r31h_prev = self.registers.r31h().read()?;
+
+        Ok(RxControl {
+            photodiode: r31h_prev.pd_disconnect().into(),
+            input_short: r31h_prev.enable_input_short(),
+            clkdiv_extmode: r31h_prev.clkdiv_extmode(),
+        })
+    }
+}
+
+impl<I2C, MODE> AFE4404<I2C, MODE, PoweredDown>
+where
+    I2C: I2c<SevenBitAddress>,
+    MODE: LedMode,
+{
+    /// Software powers up the entire [`AFE4404`], returning the active device.
+    ///
+    /// # Notes
+    ///
+    /// The configuration is kept while powered down, so the device resumes with the configuration it had before `sw_power_down()`.
+    /// After calling this function, a wait time of `tCHANNEL` should be applied before high-accuracy readings.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error, giving back the powered down device.
+    // The device is returned in both cases, so the error is not larger than the value.
+    #[allow(clippy::result_large_err)]
+    pub fn sw_power_up(mut self) -> Result<AFE4404<I2C, MODE>, TransitionError<Self, I2C::Error>> {
+        match self.set_afe_power_down(false) {
+            Ok(()) => Ok(self.into_state()),
+            Err(error) => Err(TransitionError {
+                device: self,
+                error,
+            }),
+        }
+    }
+}
+
+impl<I2C, MODE, STATE> AFE4404<I2C, MODE, STATE>
+where
+    I2C: I2c<SevenBitAddress>,
+    MODE: LedMode,
+    STATE: DeviceState,
+{
+    /// Sets the power down bit of the entire [`AFE4404`].
+    fn set_afe_power_down(&mut self, power_down: bool) -> Result<(), AfeError<I2C::Error>> {
+        let r23h_prev = self.registers.r23h().read()?;
+
+        self.registers
+            .r23h()
+            .write(r23h_prev.with_pdnafe(power_down))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal::delay::DelayUs;
+
+    use crate::{
+        errors::AfeError,
+        mock::MockAfe,
+        system::{RxControl, State},
+        RegisterAddress,
+    };
+
+    /// Represents a delay recording the content of `R1Dh` and the number of transactions when called.
+    struct RecordingDelay {
+        mock: MockAfe,
+        recorded: Option<(u32, usize)>,
+    }
+
+    impl DelayUs for RecordingDelay {
+        type Error = core::convert::Infallible;
+
+        fn delay_us(&mut self, _us: u32) -> Result<(), Self::Error> {
+            self.recorded = Some((self.mock.register(0x1D), self.mock.transactions()));
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn dump_skips_control_register_and_keeps_staged_writes() {
+        let mock = MockAfe::new();
+        let mut afe = mock.two_leds();
+
+        afe.stage_writes();
+        afe.set_averaging(4).unwrap();
+
+        let dump = afe.dump_registers().unwrap();
+        assert!(dump.iter().all(|&(reg_addr, _)| reg_addr != 0x00));
+        assert_eq!(mock.register(0x1E) & 0x0F, 0);
+
+        afe.commit().unwrap();
+        assert_eq!(mock.register(0x1E) & 0x0F, 3);
+    }
+
+    #[test]
+    fn power_down_round_trip() {
+        let mock = MockAfe::new();
+        let afe = mock.two_leds();
+
+        let afe = afe.sw_power_down().unwrap();
+        assert_eq!(mock.register(0x23) & 1, 1);
+
+        afe.sw_power_up().unwrap();
+        assert_eq!(mock.register(0x23) & 1, 0);
+    }
+
+    #[test]
+    fn control_register_cannot_be_read() {
+        let mut afe = MockAfe::new().two_leds();
+
+        assert!(matches!(
+            afe.read_register(RegisterAddress::R00h),
+            Err(AfeError::WriteOnlyRegister { reg_addr: 0x00 })
+        ));
+    }
+
+    #[test]
+    fn reserved_registers_read_with_reading_flag() {
+        let mock = MockAfe::new();
+        let mut afe = mock.two_leds();
+
+        mock.set_register(0x38, 0x12_3456);
+        assert_eq!(afe.read_reserved(0x38).unwrap(), 0x12_3456);
+
+        // The flag is set and cleared around the address and content transactions.
+        for reg_addr in [0x38, 0x50] {
+            mock.reset_transactions();
+            afe.read_reserved(reg_addr).unwrap();
+            assert_eq!(mock.transactions(), 4);
+        }
+
+        mock.reset_transactions();
+        afe.read_reserved(0x2A).unwrap();
+        assert_eq!(mock.transactions(), 2);
+    }
+
+    #[test]
+    fn reset_delay_and_probe_run_in_order() {
+        let mock = MockAfe::new();
+        let mut afe = mock.two_leds();
+        afe.set_window_counter(1234).unwrap();
+        mock.reset_transactions();
+
+        let mut delay = RecordingDelay {
+            mock: mock.clone(),
+            recorded: None,
+        };
+        afe.reset_and_verify(&mut delay).unwrap();
+
+        // Only the reset reached the device before the delay, the probe followed it.
+        assert_eq!(delay.recorded, Some((0, 1)));
+        assert_eq!(mock.transactions(), 1 + 4 + 1 + 4 + 1);
+        assert_eq!(mock.register(0x1D), 0);
+    }
+
+    #[test]
+    fn probe_reads_device_with_register_cache() {
+        let mock = MockAfe::new();
+        let mut afe = mock.two_leds();
+        afe.set_register_cache(State::Enabled);
+
+        afe.set_window_counter(1234).unwrap();
+        afe.probe().unwrap();
+        assert_eq!(mock.register(0x1D), 1234);
+
+        mock.set_writes_ignored(true);
+        assert!(matches!(afe.probe(), Err(AfeError::ProbeFailed)));
+    }
+
+    #[test]
+    fn rx_control_round_trips() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        for photodiode in [State::Enabled, State::Disabled] {
+            for input_short in [false, true] {
+                for clkdiv_extmode in 0..8 {
+                    let configuration = RxControl {
+                        photodiode,
+                        input_short,
+                        clkdiv_extmode,
+                    };
+
+                    assert_eq!(afe.set_rx_control(&configuration).unwrap(), configuration);
+                    assert_eq!(afe.get_rx_control().unwrap(), configuration);
+                }
+            }
+        }
+
+        // PD_DISCONNECT is bit 10, ENABLE_INPUT_SHORT is bit 5 and CLKDIV_EXTMODE bits 0-2 of R31h.
+        assert_eq!(mock.register(0x31), 1 << 10 | 1 << 5 | 7);
+    }
+
+    #[test]
+    fn invalid_rx_control_rejected() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        let result = afe.set_rx_control(&RxControl {
+            photodiode: State::Disabled,
+            input_short: true,
+            clkdiv_extmode: 8,
+        });
+
+        assert!(matches!(
+            result,
+            Err(AfeError::ClockDivisionRatioOutsideAllowedRange)
+        ));
+        assert_eq!(mock.register(0x31), 0);
+    }
+
+    #[test]
+    fn idle_powers_down_and_restores_power_register() {
+        let mock = MockAfe::new();
+        // Oscillator enabled, LED range doubled and ADC already disabled during dynamic power down.
+        let r23h = 1 << 9 | 1 << 17 | 1 << 14;
+        mock.set_register(0x23, r23h);
+        let mut afe = mock.three_leds();
+
+        afe.enter_idle().unwrap();
+        // RX powered down and every block disabled during dynamic power down.
+        assert_eq!(
+            mock.register(0x23),
+            r23h | 1 << 1 | 1 << 3 | 1 << 4 | 1 << 20
+        );
+
+        // Entering twice keeps the configuration saved the first time.
+        afe.enter_idle().unwrap();
+
+        let mut delay = RecordingDelay {
+            mock: mock.clone(),
+            recorded: None,
+        };
+        afe.exit_idle(&mut delay).unwrap();
+        assert_eq!(mock.register(0x23), r23h);
+        // The settle happens after the last register write.
+        assert_eq!(delay.recorded.unwrap().1, mock.transactions());
+    }
+
+    #[test]
+    fn settle_time_for_each_cause() {
+        use uom::si::time::millisecond;
+
+        use crate::system::SettleCause;
+
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+        // A 10 ms window decimated by 4 gives a 40 ms sample interval.
+        afe.set_window_counter(39_999).unwrap();
+        afe.set_decimation(4).unwrap();
+
+        for (cause, expected) in [
+            (SettleCause::PowerUp, 81.0),
+            (SettleCause::GainChange, 80.0),
+            (SettleCause::CurrentChange, 40.0),
+        ] {
+            let settle_time = afe.settle_time(cause).unwrap();
+            assert!(
+                (settle_time.get::<millisecond>() - expected).abs() < 1e-3,
+                "{cause:?}"
+            );
+        }
+    }
 }
@@ -1,15 +1,36 @@
-use uom::si::f32::{Capacitance, ElectricalResistance};
+use uom::si::{
+    capacitance::picofarad,
+    electrical_resistance::kiloohm,
+    f32::{Capacitance, ElectricalResistance},
+};
 
-use crate::modes::{LedMode, ThreeLedsMode, TwoLedsMode};
+use crate::modes::{LedMode, OneLedMode, ThreeLedsMode, TwoLedsMode};
 
 /// Represents the feedback resistors of the TIA inside the [`AFE4404`].
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ResistorConfiguration<MODE: LedMode> {
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     resistor1: ElectricalResistance,
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     resistor2: ElectricalResistance,
     mode: core::marker::PhantomData<MODE>,
 }
 
+/// Uses 500 kΩ resistors, as after a reset.
+impl<MODE> Default for ResistorConfiguration<MODE>
+where
+    MODE: LedMode,
+{
+    fn default() -> Self {
+        Self {
+            resistor1: ElectricalResistance::new::<kiloohm>(500.0),
+            resistor2: ElectricalResistance::new::<kiloohm>(500.0),
+            mode: core::marker::PhantomData,
+        }
+    }
+}
+
 impl ResistorConfiguration<ThreeLedsMode> {
     /// Creates a new `ResistorConfiguration`.
     ///
@@ -82,14 +103,56 @@ impl ResistorConfiguration<TwoLedsMode> {
     }
 }
 
+impl ResistorConfiguration<OneLedMode> {
+    /// Creates a new `ResistorConfiguration`.
+    ///
+    /// # Notes
+    ///
+    /// `resistor` is used during sample LED1 and sample Ambient phases.
+    pub fn new(resistor: ElectricalResistance) -> Self {
+        Self {
+            resistor1: resistor,
+            resistor2: resistor,
+            mode: core::marker::PhantomData,
+        }
+    }
+
+    /// Gets an immutable reference of the resistor used during sample LED1 and sample Ambient phases.
+    pub fn resistor(&self) -> &ElectricalResistance {
+        &self.resistor1
+    }
+
+    /// Gets a mutable reference of the resistor used during sample LED1 and sample Ambient phases.
+    pub fn resistor_mut(&mut self) -> &mut ElectricalResistance {
+        &mut self.resistor1
+    }
+}
+
 /// Represents the feedback capacitors of the TIA inside the [`AFE4404`].
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct CapacitorConfiguration<MODE: LedMode> {
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     capacitor1: Capacitance,
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     capacitor2: Capacitance,
     mode: core::marker::PhantomData<MODE>,
 }
 
+/// Uses 5 pF capacitors, as after a reset.
+impl<MODE> Default for CapacitorConfiguration<MODE>
+where
+    MODE: LedMode,
+{
+    fn default() -> Self {
+        Self {
+            capacitor1: Capacitance::new::<picofarad>(5.0),
+            capacitor2: Capacitance::new::<picofarad>(5.0),
+            mode: core::marker::PhantomData,
+        }
+    }
+}
+
 impl CapacitorConfiguration<ThreeLedsMode> {
     /// Creates a new `CapacitorConfiguration`.
     ///
@@ -161,3 +224,57 @@ impl CapacitorConfiguration<TwoLedsMode> {
         &mut self.capacitor2
     }
 }
+
+impl CapacitorConfiguration<OneLedMode> {
+    /// Creates a new `CapacitorConfiguration`.
+    ///
+    /// # Notes
+    ///
+    /// `capacitor` is used during sample LED1 and sample Ambient phases.
+    pub fn new(capacitor: Capacitance) -> Self {
+        Self {
+            capacitor1: capacitor,
+            capacitor2: capacitor,
+            mode: core::marker::PhantomData,
+        }
+    }
+
+    /// Gets an immutable reference of the capacitor used during sample LED1 and sample Ambient phases.
+    pub fn capacitor(&self) -> &Capacitance {
+        &self.capacitor1
+    }
+
+    /// Gets a mutable reference of the capacitor used during sample LED1 and sample Ambient phases.
+    pub fn capacitor_mut(&mut self) -> &mut Capacitance {
+        &mut self.capacitor1
+    }
+}
+
+/// Represents the TIA control registers of the [`AFE4404`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TiaConfiguration<MODE: LedMode> {
+    /// The feedback resistors.
+    pub resistors: ResistorConfiguration<MODE>,
+    /// The feedback capacitors.
+    pub capacitors: CapacitorConfiguration<MODE>,
+    /// Whether `resistor2` and `capacitor2` are used, otherwise `resistor1` and `capacitor1` are used in all the phases.
+    pub separate_gain: bool,
+    /// Whether the `ADC_RDY` output is replaced by the programmable timing signal.
+    pub programmable_timing: bool,
+}
+
+/// Uses 500 kΩ resistors and 5 pF capacitors in all the phases, as after a reset.
+impl<MODE> Default for TiaConfiguration<MODE>
+where
+    MODE: LedMode,
+{
+    fn default() -> Self {
+        Self {
+            resistors: ResistorConfiguration::default(),
+            capacitors: CapacitorConfiguration::default(),
+            separate_gain: false,
+            programmable_timing: false,
+        }
+    }
+}
@@ -55,6 +55,16 @@ where
         })
     }
 
+    /// Gets the resistor value corresponding to a register code.
+    ///
+    /// # Notes
+    ///
+    /// This is the inverse of the rounding done by the resistor setters, the codes are not sorted by resistor value.
+    /// `None` is returned for codes greater than 7.
+    pub fn resistor_for_code(code: u8) -> Option<ElectricalResistance> {
+        Self::into_resistor(code, 0x21).ok()
+    }
+
     /// Converts a `Capacitance` into a tuple of `Capacitance` rounded to the closest actual value and register value.
     pub(crate) fn from_capacitor(
         capacitor: Capacitance,
@@ -105,8 +115,8 @@ where
         &mut self,
         resistor: ElectricalResistance,
     ) -> Result<ElectricalResistance, AfeError<I2C::Error>> {
-        let r20h_prev = self.registers.r20h.read()?;
-        let r21h_prev = self.registers.r21h.read()?;
+        let r20h_prev = self.registers.r20h().read()?;
+        let r21h_prev = self.registers.r21h().read()?;
 
         let value = Self::from_resistor(resistor)?;
 
@@ -114,10 +124,10 @@ where
             (value.1 != r20h_prev.tia_gain_sep()) || (r21h_prev.tia_cf() != r20h_prev.tia_cf_sep());
 
         self.registers
-            .r20h
+            .r20h()
             .write(r20h_prev.with_ensepgain(separate_resistor))?;
         self.registers
-            .r21h
+            .r21h()
             .write(r21h_prev.with_tia_gain(value.1))?;
 
         Ok(value.0)
@@ -132,8 +142,8 @@ where
         &mut self,
         resistor: ResistorValue<I2C>,
     ) -> Result<(), AfeError<I2C::Error>> {
-        let r20h_prev = self.registers.r20h.read()?;
-        let r21h_prev = self.registers.r21h.read()?;
+        let r20h_prev = self.registers.r20h().read()?;
+        let r21h_prev = self.registers.r21h().read()?;
 
         let value = resistor.try_into()?;
 
@@ -141,9 +151,11 @@ where
             (value != r20h_prev.tia_gain_sep()) || (r21h_prev.tia_cf() != r20h_prev.tia_cf_sep());
 
         self.registers
-            .r20h
+            .r20h()
             .write(r20h_prev.with_ensepgain(separate_resistor))?;
-        self.registers.r21h.write(r21h_prev.with_tia_gain(value))?;
+        self.registers
+            .r21h()
+            .write(r21h_prev.with_tia_gain(value))?;
 
         Ok(())
     }
@@ -162,15 +174,15 @@ where
         &mut self,
         resistor: ElectricalResistance,
     ) -> Result<ElectricalResistance, AfeError<I2C::Error>> {
-        let r20h_prev = self.registers.r20h.read()?;
-        let r21h_prev = self.registers.r21h.read()?;
+        let r20h_prev = self.registers.r20h().read()?;
+        let r21h_prev = self.registers.r21h().read()?;
 
         let value = Self::from_resistor(resistor)?;
 
         let separate_resistor: bool =
             (r21h_prev.tia_gain() != value.1) || (r21h_prev.tia_cf() != r20h_prev.tia_cf_sep());
 
-        self.registers.r20h.write(
+        self.registers.r20h().write(
             r20h_prev
                 .with_ensepgain(separate_resistor)
                 .with_tia_gain_sep(value.1),
@@ -188,15 +200,15 @@ where
         &mut self,
         resistor: ResistorValue<I2C>,
     ) -> Result<(), AfeError<I2C::Error>> {
-        let r20h_prev = self.registers.r20h.read()?;
-        let r21h_prev = self.registers.r21h.read()?;
+        let r20h_prev = self.registers.r20h().read()?;
+        let r21h_prev = self.registers.r21h().read()?;
 
         let value = resistor.try_into()?;
 
         let separate_resistor: bool =
             (r21h_prev.tia_gain() != value) || (r21h_prev.tia_cf() != r20h_prev.tia_cf_sep());
 
-        self.registers.r20h.write(
+        self.registers.r20h().write(
             r20h_prev
                 .with_ensepgain(separate_resistor)
                 .with_tia_gain_sep(value),
@@ -211,7 +223,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_tia_resistor1(&mut self) -> Result<ElectricalResistance, AfeError<I2C::Error>> {
-        let r21h_prev = self.registers.r21h.read()?;
+        let r21h_prev = self.registers.r21h().read()?;
 
         let value = Self::into_resistor(r21h_prev.tia_gain(), 0x21)?;
 
@@ -224,20 +236,42 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     pub fn get_tia_resistor1_enum(&mut self) -> Result<ResistorValue<I2C>, AfeError<I2C::Error>> {
-        let r21h_prev = self.registers.r21h.read()?;
+        let r21h_prev = self.registers.r21h().read()?;
 
         let value = r21h_prev.tia_gain().try_into()?;
 
         Ok(value)
     }
 
+    /// Gets the tia resistor1 value as a `ResistorValue`, reporting unmapped codes as invalid register data.
+    ///
+    /// # Notes
+    ///
+    /// Unlike [`AFE4404::get_tia_resistor1_enum`], a code that does not map to a `ResistorValue` is reported as a corrupt register.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub fn get_tia_resistor1_enum_checked(
+        &mut self,
+    ) -> Result<ResistorValue<I2C>, AfeError<I2C::Error>> {
+        let r21h_prev = self.registers.r21h().read()?;
+
+        let value = r21h_prev
+            .tia_gain()
+            .try_into()
+            .map_err(|_| AfeError::InvalidRegisterValue { reg_addr: 0x21 })?;
+
+        Ok(value)
+    }
+
     /// Gets the tia resistor2 value.
     ///
     /// # Errors
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_tia_resistor2(&mut self) -> Result<ElectricalResistance, AfeError<I2C::Error>> {
-        let r20h_prev = self.registers.r20h.read()?;
+        let r20h_prev = self.registers.r20h().read()?;
 
         let value = Self::into_resistor(r20h_prev.tia_gain_sep(), 0x20)?;
 
@@ -250,7 +284,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     pub fn get_tia_resistor2_enum(&mut self) -> Result<ResistorValue<I2C>, AfeError<I2C::Error>> {
-        let r20h_prev = self.registers.r20h.read()?;
+        let r20h_prev = self.registers.r20h().read()?;
 
         let value = r20h_prev.tia_gain_sep().try_into()?;
 
@@ -271,8 +305,8 @@ where
         &mut self,
         capacitor: Capacitance,
     ) -> Result<Capacitance, AfeError<I2C::Error>> {
-        let r20h_prev = self.registers.r20h.read()?;
-        let r21h_prev = self.registers.r21h.read()?;
+        let r20h_prev = self.registers.r20h().read()?;
+        let r21h_prev = self.registers.r21h().read()?;
 
         let value = Self::from_capacitor(capacitor)?;
 
@@ -280,9 +314,11 @@ where
             || (value.1 != r20h_prev.tia_cf_sep());
 
         self.registers
-            .r20h
+            .r20h()
             .write(r20h_prev.with_ensepgain(separate_capacitor))?;
-        self.registers.r21h.write(r21h_prev.with_tia_cf(value.1))?;
+        self.registers
+            .r21h()
+            .write(r21h_prev.with_tia_cf(value.1))?;
 
         Ok(value.0)
     }
@@ -296,8 +332,8 @@ where
         &mut self,
         capacitor: CapacitorValue<I2C>,
     ) -> Result<(), AfeError<I2C::Error>> {
-        let r20h_prev = self.registers.r20h.read()?;
-        let r21h_prev = self.registers.r21h.read()?;
+        let r20h_prev = self.registers.r20h().read()?;
+        let r21h_prev = self.registers.r21h().read()?;
 
         let value = capacitor.try_into()?;
 
@@ -305,9 +341,9 @@ where
             (r21h_prev.tia_gain() != r20h_prev.tia_gain_sep()) || (value != r20h_prev.tia_cf_sep());
 
         self.registers
-            .r20h
+            .r20h()
             .write(r20h_prev.with_ensepgain(separate_capacitor))?;
-        self.registers.r21h.write(r21h_prev.with_tia_cf(value))?;
+        self.registers.r21h().write(r21h_prev.with_tia_cf(value))?;
 
         Ok(())
     }
@@ -326,8 +362,8 @@ where
         &mut self,
         capacitor: Capacitance,
     ) -> Result<Capacitance, AfeError<I2C::Error>> {
-        let r20h_prev = self.registers.r20h.read()?;
-        let r21h_prev = self.registers.r21h.read()?;
+        let r20h_prev = self.registers.r20h().read()?;
+        let r21h_prev = self.registers.r21h().read()?;
 
         let value = Self::from_capacitor(capacitor)?;
 
@@ -335,9 +371,11 @@ where
             (r21h_prev.tia_gain() != r20h_prev.tia_gain_sep()) || (r21h_prev.tia_cf() != value.1);
 
         self.registers
-            .r20h
+            .r20h()
             .write(r20h_prev.with_ensepgain(separate_capacitor))?;
-        self.registers.r21h.write(r21h_prev.with_tia_cf(value.1))?;
+        self.registers
+            .r21h()
+            .write(r21h_prev.with_tia_cf(value.1))?;
 
         Ok(value.0)
     }
@@ -351,8 +389,8 @@ where
         &mut self,
         capacitor: CapacitorValue<I2C>,
     ) -> Result<(), AfeError<I2C::Error>> {
-        let r20h_prev = self.registers.r20h.read()?;
-        let r21h_prev = self.registers.r21h.read()?;
+        let r20h_prev = self.registers.r20h().read()?;
+        let r21h_prev = self.registers.r21h().read()?;
 
         let value = capacitor.try_into()?;
 
@@ -360,9 +398,9 @@ where
             (r21h_prev.tia_gain() != r20h_prev.tia_gain_sep()) || (r21h_prev.tia_cf() != value);
 
         self.registers
-            .r20h
+            .r20h()
             .write(r20h_prev.with_ensepgain(separate_capacitor))?;
-        self.registers.r21h.write(r21h_prev.with_tia_cf(value))?;
+        self.registers.r21h().write(r21h_prev.with_tia_cf(value))?;
 
         Ok(())
     }
@@ -373,7 +411,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_tia_capacitor1(&mut self) -> Result<Capacitance, AfeError<I2C::Error>> {
-        let r21h_prev = self.registers.r21h.read()?;
+        let r21h_prev = self.registers.r21h().read()?;
 
         let value = Self::into_capacitor(r21h_prev.tia_cf(), 0x21)?;
 
@@ -386,7 +424,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     pub fn get_tia_capacitor1_enum(&mut self) -> Result<CapacitorValue<I2C>, AfeError<I2C::Error>> {
-        let r21h_prev = self.registers.r21h.read()?;
+        let r21h_prev = self.registers.r21h().read()?;
 
         let value = r21h_prev.tia_cf().try_into()?;
 
@@ -399,7 +437,7 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
     pub fn get_tia_capacitor2(&mut self) -> Result<Capacitance, AfeError<I2C::Error>> {
-        let r20h_prev = self.registers.r20h.read()?;
+        let r20h_prev = self.registers.r20h().read()?;
 
         let value = Self::into_capacitor(r20h_prev.tia_cf_sep(), 0x20)?;
 
@@ -412,10 +450,76 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     pub fn get_tia_capacitor2_enum(&mut self) -> Result<CapacitorValue<I2C>, AfeError<I2C::Error>> {
-        let r20h_prev = self.registers.r20h.read()?;
+        let r20h_prev = self.registers.r20h().read()?;
 
         let value = r20h_prev.tia_cf_sep().try_into()?;
 
         Ok(value)
     }
+
+    /// Gets whether a separate capacitor is in effect for the LED2 and Ambient2 (LED3) phases.
+    ///
+    /// # Notes
+    ///
+    /// The `ENSEPGAIN` bit enables the separate resistor and the separate capacitor together.
+    /// The capacitor setters enable it whenever either the resistors or the capacitors differ, so this function returns `true` only if `ENSEPGAIN` is set and the capacitor2 differs from the capacitor1.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub fn get_separate_capacitor(&mut self) -> Result<bool, AfeError<I2C::Error>> {
+        let r20h_prev = self.registers.r20h().read()?;
+        let r21h_prev = self.registers.r21h().read()?;
+
+        Ok(r20h_prev.ensepgain() && r20h_prev.tia_cf_sep() != r21h_prev.tia_cf())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock::MockAfe, modes::ThreeLedsMode};
+
+    type Afe = AFE4404<MockAfe, ThreeLedsMode>;
+
+    #[test]
+    fn every_resistor_code_round_trips() {
+        for code in 0..8 {
+            let resistor = Afe::resistor_for_code(code).unwrap();
+
+            assert_eq!(Afe::from_resistor(resistor).unwrap(), (resistor, code));
+        }
+        assert_eq!(Afe::resistor_for_code(8), None);
+    }
+
+    #[test]
+    fn every_capacitor_code_round_trips() {
+        for code in 0..8 {
+            let capacitor = Afe::into_capacitor(code, 0x21).unwrap();
+
+            assert_eq!(Afe::from_capacitor(capacitor).unwrap(), (capacitor, code));
+        }
+        assert!(matches!(
+            Afe::into_capacitor(8, 0x21),
+            Err(AfeError::InvalidRegisterValue { reg_addr: 0x21 })
+        ));
+    }
+
+    #[test]
+    fn checked_resistor1_enum_decodes_every_code() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        for code in 0..8 {
+            // The capacitor and the reserved bits are filled, only the 3 bit gain code is decoded.
+            mock.set_register(0x21, 0x00FF_FFF8 | u32::from(code));
+
+            let value: u8 = afe
+                .get_tia_resistor1_enum_checked()
+                .unwrap()
+                .try_into()
+                .unwrap();
+            assert_eq!(value, code);
+        }
+    }
 }
@@ -2,19 +2,46 @@
 
 use embedded_hal::i2c::I2c;
 use embedded_hal::i2c::SevenBitAddress;
+use uom::si::f32::Frequency;
 
 use crate::{
     device::AFE4404,
     errors::AfeError,
-    modes::{ThreeLedsMode, TwoLedsMode},
+    modes::{LedMode, OneLedMode, ThreeLedsMode, TwoLedsMode},
 };
 
-pub use configuration::{CapacitorConfiguration, ResistorConfiguration};
+pub use configuration::{CapacitorConfiguration, ResistorConfiguration, TiaConfiguration};
 
 mod configuration;
 pub mod low_level;
 pub mod values;
 
+impl<I2C, MODE> AFE4404<I2C, MODE>
+where
+    I2C: I2c<SevenBitAddress>,
+    MODE: LedMode,
+{
+    /// Gets the TIA bandwidth, computed as 1/(2πRC).
+    ///
+    /// # Notes
+    ///
+    /// The first value is the bandwidth obtained with `resistor1` and `capacitor1`,
+    /// the second value is the bandwidth obtained with `resistor2` and `capacitor2`.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub fn get_tia_bandwidth(&mut self) -> Result<(Frequency, Frequency), AfeError<I2C::Error>> {
+        let resistors = (self.get_tia_resistor1()?, self.get_tia_resistor2()?);
+        let capacitors = (self.get_tia_capacitor1()?, self.get_tia_capacitor2()?);
+
+        Ok((
+            1.0 / (2.0 * core::f32::consts::PI * resistors.0 * capacitors.0),
+            1.0 / (2.0 * core::f32::consts::PI * resistors.1 * capacitors.1),
+        ))
+    }
+}
+
 impl<I2C> AFE4404<I2C, ThreeLedsMode>
 where
     I2C: I2c<SevenBitAddress>,
@@ -33,8 +60,8 @@ where
         &mut self,
         configuration: &ResistorConfiguration<ThreeLedsMode>,
     ) -> Result<ResistorConfiguration<ThreeLedsMode>, AfeError<I2C::Error>> {
-        let r20h_prev = self.registers.r20h.read()?;
-        let r21h_prev = self.registers.r21h.read()?;
+        let r20h_prev = self.registers.r20h().read()?;
+        let r21h_prev = self.registers.r21h().read()?;
 
         let values = [
             Self::from_resistor(*configuration.resistor1())?,
@@ -44,13 +71,13 @@ where
         let separate_resistor: bool =
             (values[0] != values[1]) || (r21h_prev.tia_cf() != r20h_prev.tia_cf_sep());
 
-        self.registers.r20h.write(
+        self.registers.r20h().write(
             r20h_prev
                 .with_ensepgain(separate_resistor)
                 .with_tia_gain_sep(values[1].1),
         )?;
         self.registers
-            .r21h
+            .r21h()
             .write(r21h_prev.with_tia_gain(values[0].1))?;
 
         Ok(ResistorConfiguration::<ThreeLedsMode>::new(
@@ -87,8 +114,8 @@ where
         &mut self,
         configuration: &CapacitorConfiguration<ThreeLedsMode>,
     ) -> Result<CapacitorConfiguration<ThreeLedsMode>, AfeError<I2C::Error>> {
-        let r20h_prev = self.registers.r20h.read()?;
-        let r21h_prev = self.registers.r21h.read()?;
+        let r20h_prev = self.registers.r20h().read()?;
+        let r21h_prev = self.registers.r21h().read()?;
 
         let values = [
             Self::from_capacitor(*configuration.capacitor1())?,
@@ -98,13 +125,13 @@ where
         let separate_capacitor: bool =
             (values[0] != values[1]) || (r21h_prev.tia_gain() != r20h_prev.tia_gain_sep());
 
-        self.registers.r20h.write(
+        self.registers.r20h().write(
             r20h_prev
                 .with_ensepgain(separate_capacitor)
                 .with_tia_cf_sep(values[1].1),
         )?;
         self.registers
-            .r21h
+            .r21h()
             .write(r21h_prev.with_tia_cf(values[0].1))?;
 
         Ok(CapacitorConfiguration::<ThreeLedsMode>::new(
@@ -126,6 +153,83 @@ where
             self.get_tia_capacitor2()?,
         ))
     }
+
+    /// Sets the TIA control registers at once.
+    ///
+    /// # Notes
+    ///
+    /// This function automatically rounds the resistors and capacitors value to the closest actual value.
+    /// Unlike the resistors and capacitors setters, the separate gain is written as given.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a resistor value outside the range 10-2000 kOhm will result in an error.
+    /// Setting a capacitor value outside the range 2.5-25 pF will result in an error.
+    pub fn set_tia_configuration(
+        &mut self,
+        configuration: &TiaConfiguration<ThreeLedsMode>,
+    ) -> Result<TiaConfiguration<ThreeLedsMode>, AfeError<I2C::Error>> {
+        let r20h_prev = self.registers.r20h().read()?;
+        let r21h_prev = self.registers.r21h().read()?;
+
+        let resistors = [
+            Self::from_resistor(*configuration.resistors.resistor1())?,
+            Self::from_resistor(*configuration.resistors.resistor2())?,
+        ];
+        let capacitors = [
+            Self::from_capacitor(*configuration.capacitors.capacitor1())?,
+            Self::from_capacitor(*configuration.capacitors.capacitor2())?,
+        ];
+
+        self.registers.r20h().write(
+            r20h_prev
+                .with_ensepgain(configuration.separate_gain)
+                .with_tia_gain_sep(resistors[1].1)
+                .with_tia_cf_sep(capacitors[1].1),
+        )?;
+        self.registers.r21h().write(
+            r21h_prev
+                .with_prog_tg_en(configuration.programmable_timing)
+                .with_tia_gain(resistors[0].1)
+                .with_tia_cf(capacitors[0].1),
+        )?;
+
+        Ok(TiaConfiguration {
+            resistors: ResistorConfiguration::<ThreeLedsMode>::new(resistors[0].0, resistors[1].0),
+            capacitors: CapacitorConfiguration::<ThreeLedsMode>::new(
+                capacitors[0].0,
+                capacitors[1].0,
+            ),
+            separate_gain: configuration.separate_gain,
+            programmable_timing: configuration.programmable_timing,
+        })
+    }
+
+    /// Gets the TIA control registers at once.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub fn get_tia_configuration(
+        &mut self,
+    ) -> Result<TiaConfiguration<ThreeLedsMode>, AfeError<I2C::Error>> {
+        let r20h_prev = self.registers.r20h().read()?;
+        let r21h_prev = self.registers.r21h().read()?;
+
+        Ok(TiaConfiguration {
+            resistors: ResistorConfiguration::<ThreeLedsMode>::new(
+                Self::into_resistor(r21h_prev.tia_gain(), 0x21)?,
+                Self::into_resistor(r20h_prev.tia_gain_sep(), 0x20)?,
+            ),
+            capacitors: CapacitorConfiguration::<ThreeLedsMode>::new(
+                Self::into_capacitor(r21h_prev.tia_cf(), 0x21)?,
+                Self::into_capacitor(r20h_prev.tia_cf_sep(), 0x20)?,
+            ),
+            separate_gain: r20h_prev.ensepgain(),
+            programmable_timing: r21h_prev.prog_tg_en(),
+        })
+    }
 }
 
 impl<I2C> AFE4404<I2C, TwoLedsMode>
@@ -146,8 +250,8 @@ where
         &mut self,
         configuration: &ResistorConfiguration<TwoLedsMode>,
     ) -> Result<ResistorConfiguration<TwoLedsMode>, AfeError<I2C::Error>> {
-        let r20h_prev = self.registers.r20h.read()?;
-        let r21h_prev = self.registers.r21h.read()?;
+        let r20h_prev = self.registers.r20h().read()?;
+        let r21h_prev = self.registers.r21h().read()?;
 
         let values = [
             Self::from_resistor(*configuration.resistor1())?,
@@ -157,13 +261,13 @@ where
         let separate_resistor: bool =
             (values[0] != values[1]) || (r21h_prev.tia_cf() != r20h_prev.tia_cf_sep());
 
-        self.registers.r20h.write(
+        self.registers.r20h().write(
             r20h_prev
                 .with_ensepgain(separate_resistor)
                 .with_tia_gain_sep(values[1].1),
         )?;
         self.registers
-            .r21h
+            .r21h()
             .write(r21h_prev.with_tia_gain(values[0].1))?;
 
         Ok(ResistorConfiguration::<TwoLedsMode>::new(
@@ -200,8 +304,8 @@ where
         &mut self,
         configuration: &CapacitorConfiguration<TwoLedsMode>,
     ) -> Result<CapacitorConfiguration<TwoLedsMode>, AfeError<I2C::Error>> {
-        let r20h_prev = self.registers.r20h.read()?;
-        let r21h_prev = self.registers.r21h.read()?;
+        let r20h_prev = self.registers.r20h().read()?;
+        let r21h_prev = self.registers.r21h().read()?;
 
         let values = [
             Self::from_capacitor(*configuration.capacitor1())?,
@@ -211,13 +315,13 @@ where
         let separate_capacitor: bool =
             (values[0] != values[1]) || (r21h_prev.tia_gain() != r20h_prev.tia_gain_sep());
 
-        self.registers.r20h.write(
+        self.registers.r20h().write(
             r20h_prev
                 .with_ensepgain(separate_capacitor)
                 .with_tia_cf_sep(values[1].1),
         )?;
         self.registers
-            .r21h
+            .r21h()
             .write(r21h_prev.with_tia_cf(values[0].1))?;
 
         Ok(CapacitorConfiguration::<TwoLedsMode>::new(
@@ -239,4 +343,290 @@ where
             self.get_tia_capacitor2()?,
         ))
     }
+
+    /// Sets the TIA control registers at once.
+    ///
+    /// # Notes
+    ///
+    /// This function automatically rounds the resistors and capacitors value to the closest actual value.
+    /// Unlike the resistors and capacitors setters, the separate gain is written as given.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a resistor value outside the range 10-2000 kOhm will result in an error.
+    /// Setting a capacitor value outside the range 2.5-25 pF will result in an error.
+    pub fn set_tia_configuration(
+        &mut self,
+        configuration: &TiaConfiguration<TwoLedsMode>,
+    ) -> Result<TiaConfiguration<TwoLedsMode>, AfeError<I2C::Error>> {
+        let r20h_prev = self.registers.r20h().read()?;
+        let r21h_prev = self.registers.r21h().read()?;
+
+        let resistors = [
+            Self::from_resistor(*configuration.resistors.resistor1())?,
+            Self::from_resistor(*configuration.resistors.resistor2())?,
+        ];
+        let capacitors = [
+            Self::from_capacitor(*configuration.capacitors.capacitor1())?,
+            Self::from_capacitor(*configuration.capacitors.capacitor2())?,
+        ];
+
+        self.registers.r20h().write(
+            r20h_prev
+                .with_ensepgain(configuration.separate_gain)
+                .with_tia_gain_sep(resistors[1].1)
+                .with_tia_cf_sep(capacitors[1].1),
+        )?;
+        self.registers.r21h().write(
+            r21h_prev
+                .with_prog_tg_en(configuration.programmable_timing)
+                .with_tia_gain(resistors[0].1)
+                .with_tia_cf(capacitors[0].1),
+        )?;
+
+        Ok(TiaConfiguration {
+            resistors: ResistorConfiguration::<TwoLedsMode>::new(resistors[0].0, resistors[1].0),
+            capacitors: CapacitorConfiguration::<TwoLedsMode>::new(
+                capacitors[0].0,
+                capacitors[1].0,
+            ),
+            separate_gain: configuration.separate_gain,
+            programmable_timing: configuration.programmable_timing,
+        })
+    }
+
+    /// Gets the TIA control registers at once.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub fn get_tia_configuration(
+        &mut self,
+    ) -> Result<TiaConfiguration<TwoLedsMode>, AfeError<I2C::Error>> {
+        let r20h_prev = self.registers.r20h().read()?;
+        let r21h_prev = self.registers.r21h().read()?;
+
+        Ok(TiaConfiguration {
+            resistors: ResistorConfiguration::<TwoLedsMode>::new(
+                Self::into_resistor(r21h_prev.tia_gain(), 0x21)?,
+                Self::into_resistor(r20h_prev.tia_gain_sep(), 0x20)?,
+            ),
+            capacitors: CapacitorConfiguration::<TwoLedsMode>::new(
+                Self::into_capacitor(r21h_prev.tia_cf(), 0x21)?,
+                Self::into_capacitor(r20h_prev.tia_cf_sep(), 0x20)?,
+            ),
+            separate_gain: r20h_prev.ensepgain(),
+            programmable_timing: r21h_prev.prog_tg_en(),
+        })
+    }
+}
+
+impl<I2C> AFE4404<I2C, OneLedMode>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    /// Sets the tia resistor value.
+    ///
+    /// # Notes
+    ///
+    /// This function automatically rounds the resistor value to the closest actual value.
+    /// The separate gain is disabled, so the same resistor is used during every phase.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a resistor value outside the range 10-2000 kOhm will result in an error.
+    pub fn set_tia_resistors(
+        &mut self,
+        configuration: &ResistorConfiguration<OneLedMode>,
+    ) -> Result<ResistorConfiguration<OneLedMode>, AfeError<I2C::Error>> {
+        let r20h_prev = self.registers.r20h().read()?;
+        let r21h_prev = self.registers.r21h().read()?;
+
+        let value = Self::from_resistor(*configuration.resistor())?;
+
+        self.registers
+            .r20h()
+            .write(r20h_prev.with_ensepgain(false))?;
+        self.registers
+            .r21h()
+            .write(r21h_prev.with_tia_gain(value.1))?;
+
+        Ok(ResistorConfiguration::<OneLedMode>::new(value.0))
+    }
+
+    /// Gets the tia resistor value.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub fn get_tia_resistors(
+        &mut self,
+    ) -> Result<ResistorConfiguration<OneLedMode>, AfeError<I2C::Error>> {
+        Ok(ResistorConfiguration::<OneLedMode>::new(
+            self.get_tia_resistor1()?,
+        ))
+    }
+
+    /// Sets the tia capacitor value.
+    ///
+    /// # Notes
+    ///
+    /// This function automatically rounds the capacitor value to the closest actual value.
+    /// The separate gain is disabled, so the same capacitor is used during every phase.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Setting a capacitor value outside the range 2.5-25 pF will result in an error.
+    pub fn set_tia_capacitors(
+        &mut self,
+        configuration: &CapacitorConfiguration<OneLedMode>,
+    ) -> Result<CapacitorConfiguration<OneLedMode>, AfeError<I2C::Error>> {
+        let r20h_prev = self.registers.r20h().read()?;
+        let r21h_prev = self.registers.r21h().read()?;
+
+        let value = Self::from_capacitor(*configuration.capacitor())?;
+
+        self.registers
+            .r20h()
+            .write(r20h_prev.with_ensepgain(false))?;
+        self.registers
+            .r21h()
+            .write(r21h_prev.with_tia_cf(value.1))?;
+
+        Ok(CapacitorConfiguration::<OneLedMode>::new(value.0))
+    }
+
+    /// Gets the tia capacitor value.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    pub fn get_tia_capacitors(
+        &mut self,
+    ) -> Result<CapacitorConfiguration<OneLedMode>, AfeError<I2C::Error>> {
+        Ok(CapacitorConfiguration::<OneLedMode>::new(
+            self.get_tia_capacitor1()?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uom::si::{
+        capacitance::picofarad,
+        electrical_resistance::kiloohm,
+        f32::{Capacitance, ElectricalResistance},
+        frequency::hertz,
+    };
+
+    use super::*;
+    use crate::mock::MockAfe;
+
+    #[test]
+    fn tia_bandwidth_at_known_values() {
+        let mut afe = MockAfe::new().three_leds();
+
+        afe.set_tia_resistors(&ResistorConfiguration::<ThreeLedsMode>::new(
+            ElectricalResistance::new::<kiloohm>(100.0),
+            ElectricalResistance::new::<kiloohm>(500.0),
+        ))
+        .unwrap();
+        afe.set_tia_capacitors(&CapacitorConfiguration::<ThreeLedsMode>::new(
+            Capacitance::new::<picofarad>(5.0),
+            Capacitance::new::<picofarad>(10.0),
+        ))
+        .unwrap();
+
+        let (bandwidth1, bandwidth2) = afe.get_tia_bandwidth().unwrap();
+        // 1/(2π × 100 kΩ × 5 pF) and 1/(2π × 500 kΩ × 10 pF).
+        for (bandwidth, expected) in [(bandwidth1, 318_309.9), (bandwidth2, 31_830.99)] {
+            assert!((bandwidth.get::<hertz>() / expected - 1.0).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn tia_bandwidth_in_one_led_mode() {
+        let mut afe = MockAfe::new().one_led();
+
+        afe.set_tia_resistors(&ResistorConfiguration::<OneLedMode>::new(
+            ElectricalResistance::new::<kiloohm>(250.0),
+        ))
+        .unwrap();
+        afe.set_tia_capacitors(&CapacitorConfiguration::<OneLedMode>::new(
+            Capacitance::new::<picofarad>(20.0),
+        ))
+        .unwrap();
+
+        let (bandwidth1, _) = afe.get_tia_bandwidth().unwrap();
+        // 1/(2π × 250 kΩ × 20 pF).
+        assert!((bandwidth1.get::<hertz>() / 31_830.99 - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn separate_capacitor_round_trips() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        afe.set_tia_capacitors(&CapacitorConfiguration::<ThreeLedsMode>::new(
+            Capacitance::new::<picofarad>(5.0),
+            Capacitance::new::<picofarad>(10.0),
+        ))
+        .unwrap();
+        assert!(afe.get_separate_capacitor().unwrap());
+
+        afe.set_tia_capacitors(&CapacitorConfiguration::<ThreeLedsMode>::new(
+            Capacitance::new::<picofarad>(5.0),
+            Capacitance::new::<picofarad>(5.0),
+        ))
+        .unwrap();
+        assert!(!afe.get_separate_capacitor().unwrap());
+
+        // Separate resistors enable ENSEPGAIN, but the capacitors are still shared.
+        afe.set_tia_resistors(&ResistorConfiguration::<ThreeLedsMode>::new(
+            ElectricalResistance::new::<kiloohm>(100.0),
+            ElectricalResistance::new::<kiloohm>(500.0),
+        ))
+        .unwrap();
+        assert_ne!(mock.register(0x20) & 1 << 15, 0);
+        assert!(!afe.get_separate_capacitor().unwrap());
+    }
+
+    #[test]
+    fn tia_configuration_round_trips() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+        let configuration = TiaConfiguration::<ThreeLedsMode> {
+            resistors: ResistorConfiguration::<ThreeLedsMode>::new(
+                ElectricalResistance::new::<kiloohm>(100.0),
+                ElectricalResistance::new::<kiloohm>(500.0),
+            ),
+            capacitors: CapacitorConfiguration::<ThreeLedsMode>::new(
+                Capacitance::new::<picofarad>(10.0),
+                Capacitance::new::<picofarad>(5.0),
+            ),
+            separate_gain: true,
+            programmable_timing: true,
+        };
+
+        assert_eq!(
+            afe.set_tia_configuration(&configuration).unwrap(),
+            configuration
+        );
+        // 100 kΩ and 10 pF are both code 2, 500 kΩ and 5 pF are both code 0.
+        assert_eq!(mock.register(0x20), 1 << 15);
+        assert_eq!(mock.register(0x21), 1 << 8 | 2 << 3 | 2);
+        assert_eq!(afe.get_tia_configuration().unwrap(), configuration);
+
+        afe.set_tia_configuration(&TiaConfiguration::default())
+            .unwrap();
+        assert_eq!(mock.register(0x20), 0);
+        assert_eq!(mock.register(0x21), 0);
+        assert_eq!(
+            afe.get_tia_configuration().unwrap(),
+            TiaConfiguration::default()
+        );
+    }
 }
@@ -11,6 +11,7 @@ use crate::errors::AfeError;
 /// The values are encoded as inside the [`AFE4404`] registers.
 #[repr(u8)]
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum ResistorValue<I2C>
 where
     I2C: I2c<SevenBitAddress>,
@@ -84,6 +85,7 @@ where
 /// The values are encoded as inside the [`AFE4404`] registers.
 #[repr(u8)]
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum CapacitorValue<I2C>
 where
     I2C: I2c<SevenBitAddress>,
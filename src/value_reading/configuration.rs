@@ -1,13 +1,21 @@
-use uom::si::f32::ElectricPotential;
+use uom::si::{electric_potential::volt, f32::ElectricPotential};
 
-use crate::modes::{LedMode, ThreeLedsMode, TwoLedsMode};
+use crate::{
+    channel::{ChannelMap, LedChannel, Wavelength},
+    modes::{DeviceMode, LedMode, OneLedMode, ThreeLedsMode, TwoLedsMode},
+};
 
 /// Represents the values read from the [`AFE4404`].
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Readings<MODE: LedMode> {
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     led1: ElectricPotential,
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     led2: ElectricPotential,
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     ambient1: ElectricPotential,
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
     ambient2_or_led3: ElectricPotential,
     mode: core::marker::PhantomData<MODE>,
 }
@@ -21,9 +29,38 @@ where
         &self.led1
     }
 
-    /// Gets an immutable reference of the LED2 value.
-    pub fn led2(&self) -> &ElectricPotential {
-        &self.led2
+    /// Gets the values in the order LED1, LED2, Ambient1, Ambient2 (LED3 in three LEDs mode).
+    pub(crate) fn values(&self) -> [ElectricPotential; 4] {
+        [self.led1, self.led2, self.ambient1, self.ambient2_or_led3]
+    }
+
+    /// Gets an immutable reference of the value of the given channel.
+    ///
+    /// # Notes
+    ///
+    /// `None` is returned if the channel is not sampled in the current lighting mode.
+    pub fn channel(&self, channel: LedChannel) -> Option<&ElectricPotential> {
+        match (MODE::MODE, channel) {
+            (DeviceMode::Three | DeviceMode::Two | DeviceMode::One, LedChannel::Led1) => {
+                Some(&self.led1)
+            }
+            (DeviceMode::Three | DeviceMode::Two, LedChannel::Led2) => Some(&self.led2),
+            (DeviceMode::Three | DeviceMode::Two | DeviceMode::One, LedChannel::Ambient1) => {
+                Some(&self.ambient1)
+            }
+            (DeviceMode::Three, LedChannel::Led3) | (DeviceMode::Two, LedChannel::Ambient2) => {
+                Some(&self.ambient2_or_led3)
+            }
+            _ => None,
+        }
+    }
+
+    /// Names the values after the wavelengths of the LEDs, following the wiring of the board.
+    pub fn named(self, map: ChannelMap) -> NamedReadings<MODE> {
+        NamedReadings {
+            readings: self,
+            map,
+        }
     }
 }
 
@@ -43,6 +80,11 @@ impl Readings<ThreeLedsMode> {
         }
     }
 
+    /// Gets an immutable reference of the LED2 value.
+    pub fn led2(&self) -> &ElectricPotential {
+        &self.led2
+    }
+
     /// Gets an immutable reference of the LED3 value.
     pub fn led3(&self) -> &ElectricPotential {
         &self.ambient2_or_led3
@@ -52,6 +94,11 @@ impl Readings<ThreeLedsMode> {
     pub fn ambient(&self) -> &ElectricPotential {
         &self.ambient1
     }
+
+    /// Returns an iterator over the channels and values in the order LED1, LED2, LED3, Ambient.
+    pub fn iter(&self) -> core::array::IntoIter<(LedChannel, ElectricPotential), 4> {
+        self.into_iter()
+    }
 }
 
 impl Readings<TwoLedsMode> {
@@ -70,6 +117,11 @@ impl Readings<TwoLedsMode> {
         }
     }
 
+    /// Gets an immutable reference of the LED2 value.
+    pub fn led2(&self) -> &ElectricPotential {
+        &self.led2
+    }
+
     /// Gets an immutable reference of the Ambient1 value.
     pub fn ambient1(&self) -> &ElectricPotential {
         &self.ambient1
@@ -79,4 +131,384 @@ impl Readings<TwoLedsMode> {
     pub fn ambient2(&self) -> &ElectricPotential {
         &self.ambient2_or_led3
     }
+
+    /// Returns an iterator over the channels and values in the order LED1, LED2, Ambient1, Ambient2.
+    pub fn iter(&self) -> core::array::IntoIter<(LedChannel, ElectricPotential), 4> {
+        self.into_iter()
+    }
+}
+
+impl Readings<OneLedMode> {
+    pub(crate) fn new(led1: ElectricPotential, ambient: ElectricPotential) -> Self {
+        Self {
+            led1,
+            led2: ElectricPotential::new::<volt>(0.0),
+            ambient1: ambient,
+            ambient2_or_led3: ElectricPotential::new::<volt>(0.0),
+            mode: core::marker::PhantomData,
+        }
+    }
+
+    /// Gets an immutable reference of the Ambient value.
+    pub fn ambient(&self) -> &ElectricPotential {
+        &self.ambient1
+    }
+
+    /// Returns an iterator over the channels and values in the order LED1, Ambient.
+    pub fn iter(&self) -> core::array::IntoIter<(LedChannel, ElectricPotential), 2> {
+        self.into_iter()
+    }
+}
+
+/// Iterates over the channels and values in the order LED1, LED2, LED3, Ambient.
+impl IntoIterator for &Readings<ThreeLedsMode> {
+    type Item = (LedChannel, ElectricPotential);
+    type IntoIter = core::array::IntoIter<Self::Item, 4>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        [
+            (LedChannel::Led1, self.led1),
+            (LedChannel::Led2, self.led2),
+            (LedChannel::Led3, self.ambient2_or_led3),
+            (LedChannel::Ambient1, self.ambient1),
+        ]
+        .into_iter()
+    }
+}
+
+/// Iterates over the channels and values in the order LED1, LED2, Ambient1, Ambient2.
+impl IntoIterator for &Readings<TwoLedsMode> {
+    type Item = (LedChannel, ElectricPotential);
+    type IntoIter = core::array::IntoIter<Self::Item, 4>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        [
+            (LedChannel::Led1, self.led1),
+            (LedChannel::Led2, self.led2),
+            (LedChannel::Ambient1, self.ambient1),
+            (LedChannel::Ambient2, self.ambient2_or_led3),
+        ]
+        .into_iter()
+    }
+}
+
+/// Iterates over the channels and values in the order LED1, Ambient.
+impl IntoIterator for &Readings<OneLedMode> {
+    type Item = (LedChannel, ElectricPotential);
+    type IntoIter = core::array::IntoIter<Self::Item, 2>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        [
+            (LedChannel::Led1, self.led1),
+            (LedChannel::Ambient1, self.ambient1),
+        ]
+        .into_iter()
+    }
+}
+
+/// Represents the values read from the [`AFE4404`], accessed by the wavelength of the LEDs.
+///
+/// # Notes
+///
+/// The wavelengths are resolved through a [`ChannelMap`], so the same code works across boards wired differently.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NamedReadings<MODE: LedMode> {
+    readings: Readings<MODE>,
+    map: ChannelMap,
+}
+
+impl<MODE> NamedReadings<MODE>
+where
+    MODE: LedMode,
+{
+    /// Gets an immutable reference of the value of the given wavelength.
+    ///
+    /// # Notes
+    ///
+    /// `None` is returned if the wavelength is not mapped or its channel is not sampled in the current lighting mode.
+    pub fn wavelength(&self, wavelength: Wavelength) -> Option<&ElectricPotential> {
+        self.map
+            .channel(wavelength)
+            .and_then(|channel| self.readings.channel(channel))
+    }
+
+    /// Gets an immutable reference of the red value.
+    pub fn red(&self) -> Option<&ElectricPotential> {
+        self.wavelength(Wavelength::Red)
+    }
+
+    /// Gets an immutable reference of the infrared value.
+    pub fn infrared(&self) -> Option<&ElectricPotential> {
+        self.wavelength(Wavelength::Infrared)
+    }
+
+    /// Gets an immutable reference of the green value.
+    pub fn green(&self) -> Option<&ElectricPotential> {
+        self.wavelength(Wavelength::Green)
+    }
+
+    /// Gets an immutable reference of the Ambient1 value.
+    pub fn ambient(&self) -> &ElectricPotential {
+        &self.readings.ambient1
+    }
+
+    /// Gets an immutable reference of the underlying readings.
+    pub fn readings(&self) -> &Readings<MODE> {
+        &self.readings
+    }
+
+    /// Gets an immutable reference of the channel map.
+    pub fn map(&self) -> &ChannelMap {
+        &self.map
+    }
+}
+
+/// Represents the averaged values read from the [`AFE4404`].
+///
+/// # Notes
+///
+/// The values are averaged over a number of samples set by the decimation factor.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AveragedReadings<MODE: LedMode> {
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
+    led1_minus_ambient1: ElectricPotential,
+    #[cfg_attr(feature = "defmt", defmt(Debug2Format))]
+    led2_minus_ambient2: ElectricPotential,
+    mode: core::marker::PhantomData<MODE>,
+}
+
+impl AveragedReadings<ThreeLedsMode> {
+    pub(crate) fn new(led1_minus_ambient: ElectricPotential) -> Self {
+        Self {
+            led1_minus_ambient1: led1_minus_ambient,
+            led2_minus_ambient2: ElectricPotential::new::<volt>(0.0),
+            mode: core::marker::PhantomData,
+        }
+    }
+
+    /// Gets an immutable reference of the averaged LED1 minus Ambient value.
+    pub fn led1_minus_ambient(&self) -> &ElectricPotential {
+        &self.led1_minus_ambient1
+    }
+}
+
+impl AveragedReadings<TwoLedsMode> {
+    pub(crate) fn new(
+        led1_minus_ambient1: ElectricPotential,
+        led2_minus_ambient2: ElectricPotential,
+    ) -> Self {
+        Self {
+            led1_minus_ambient1,
+            led2_minus_ambient2,
+            mode: core::marker::PhantomData,
+        }
+    }
+
+    /// Gets an immutable reference of the averaged LED1 minus Ambient1 value.
+    pub fn led1_minus_ambient1(&self) -> &ElectricPotential {
+        &self.led1_minus_ambient1
+    }
+
+    /// Gets an immutable reference of the averaged LED2 minus Ambient2 value.
+    pub fn led2_minus_ambient2(&self) -> &ElectricPotential {
+        &self.led2_minus_ambient2
+    }
+}
+
+impl AveragedReadings<OneLedMode> {
+    pub(crate) fn new(led1_minus_ambient: ElectricPotential) -> Self {
+        Self {
+            led1_minus_ambient1: led1_minus_ambient,
+            led2_minus_ambient2: ElectricPotential::new::<volt>(0.0),
+            mode: core::marker::PhantomData,
+        }
+    }
+
+    /// Gets an immutable reference of the averaged LED1 minus Ambient value.
+    pub fn led1_minus_ambient(&self) -> &ElectricPotential {
+        &self.led1_minus_ambient1
+    }
+}
+
+/// Represents the sampled values together with the averaged values.
+pub type BothReadings<MODE> = (Readings<MODE>, AveragedReadings<MODE>);
+
+/// Represents a fixed-size record of the values read from the [`AFE4404`], suitable for storage.
+///
+/// # Notes
+///
+/// The codes are stored in the order LED1, LED2, Ambient1, Ambient2 (LED3 in three LEDs mode).
+/// Each code is the signed ADC output, the voltage is obtained multiplying it by 1.2 V / 2097151.
+/// When the `bytemuck` feature is enabled the record can be cast to and from bytes.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "bytemuck", derive(bytemuck::Pod, bytemuck::Zeroable))]
+pub struct SampleRecord {
+    /// The sequence number of the record.
+    pub sequence: u32,
+    /// The raw ADC codes.
+    pub codes: [i32; 4],
+}
+
+/// Represents a raw code of the [`AFE4404`] ADC.
+///
+/// # Notes
+///
+/// The code is the signed 22 bit ADC output, the full scale of ±1.2 V corresponds to ±2097151.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AdcCode(pub i32);
+
+impl AdcCode {
+    /// The code corresponding to the positive full scale of 1.2 V.
+    pub(crate) const FULL_SCALE: i32 = 2_097_151;
+
+    /// Gets the voltage corresponding to a single code.
+    pub fn quantisation() -> ElectricPotential {
+        #[allow(clippy::cast_precision_loss)]
+        let full_scale = Self::FULL_SCALE as f32;
+
+        ElectricPotential::new::<volt>(1.2) / full_scale
+    }
+
+    /// Decodes the 24 bit content of a value register into a code.
+    ///
+    /// # Notes
+    ///
+    /// The 22 bit reading is sign extended, `None` is returned if the three most significant bits do not agree.
+    pub(crate) fn from_register(register_value: u32) -> Option<Self> {
+        let sign_extension_bits = ((register_value & 0x00FF_FFFF) >> 21) as u8;
+
+        // We allow wraps since we take the sign into account.
+        #[allow(clippy::cast_possible_wrap)]
+        match sign_extension_bits {
+            0b000 => Some(Self(register_value as i32)), // The value is positive.
+            0b111 => Some(Self((register_value | 0xFF00_0000) as i32)), // Extend the sign of the negative value.
+            _ => None,
+        }
+    }
+
+    /// Converts the code into a voltage.
+    pub fn to_voltage(self) -> ElectricPotential {
+        // We are converting a 22 bit reading (stored in a 32 bit register) to a 32 bit float.
+        // Since the 32 bit float has a 23 bits, we allow a precision loss.
+        #[allow(clippy::cast_precision_loss)]
+        let value = self.0 as f32 * Self::quantisation();
+
+        value
+    }
+
+    /// Converts a voltage into the closest code.
+    pub fn from_voltage(voltage: ElectricPotential) -> Self {
+        #[allow(clippy::cast_possible_truncation)]
+        let value = (voltage / Self::quantisation()).value.round() as i32;
+
+        Self(value)
+    }
+}
+
+impl From<AdcCode> for ElectricPotential {
+    fn from(code: AdcCode) -> Self {
+        code.to_voltage()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    fn volts(value: f32) -> ElectricPotential {
+        ElectricPotential::new::<volt>(value)
+    }
+
+    #[test]
+    fn three_leds_readings_iterated_in_order() {
+        let readings =
+            Readings::<ThreeLedsMode>::new(volts(0.1), volts(0.2), volts(0.3), volts(0.4));
+
+        let values: Vec<_> = (&readings).into_iter().collect();
+        assert_eq!(
+            values,
+            [
+                (LedChannel::Led1, volts(0.1)),
+                (LedChannel::Led2, volts(0.2)),
+                (LedChannel::Led3, volts(0.3)),
+                (LedChannel::Ambient1, volts(0.4)),
+            ]
+        );
+        assert!(readings.iter().eq(values));
+    }
+
+    #[test]
+    fn two_leds_readings_iterated_in_order() {
+        let readings = Readings::<TwoLedsMode>::new(volts(0.1), volts(0.2), volts(0.3), volts(0.4));
+
+        let values: Vec<_> = (&readings).into_iter().collect();
+        assert_eq!(
+            values,
+            [
+                (LedChannel::Led1, volts(0.1)),
+                (LedChannel::Led2, volts(0.2)),
+                (LedChannel::Ambient1, volts(0.3)),
+                (LedChannel::Ambient2, volts(0.4)),
+            ]
+        );
+        assert!(readings.iter().eq(values));
+    }
+
+    #[test]
+    fn one_led_readings_iterated_in_order() {
+        let readings = Readings::<OneLedMode>::new(volts(0.1), volts(0.4));
+
+        let values: Vec<_> = (&readings).into_iter().collect();
+        assert_eq!(
+            values,
+            [
+                (LedChannel::Led1, volts(0.1)),
+                (LedChannel::Ambient1, volts(0.4)),
+            ]
+        );
+        assert!(readings.iter().eq(values));
+    }
+
+    #[test]
+    fn adc_codes_round_trip_through_volts() {
+        for code in [-2_097_151, -1000, -1, 0, 1, 1000, 2_097_151] {
+            assert_eq!(
+                AdcCode::from_voltage(AdcCode(code).to_voltage()),
+                AdcCode(code)
+            );
+        }
+    }
+
+    #[test]
+    fn adc_full_scale_is_1_2_volts() {
+        let full_scale = ElectricPotential::from(AdcCode(AdcCode::FULL_SCALE));
+
+        assert!((full_scale.get::<volt>() - 1.2).abs() < 1e-6);
+        assert_eq!(
+            AdcCode::from_voltage(-full_scale),
+            AdcCode(-AdcCode::FULL_SCALE)
+        );
+    }
+
+    #[test]
+    fn adc_codes_sign_extended_from_registers() {
+        assert_eq!(
+            AdcCode::from_register(0x001F_FFFF),
+            Some(AdcCode(2_097_151))
+        );
+        assert_eq!(AdcCode::from_register(0x00FF_FFFF), Some(AdcCode(-1)));
+        assert_eq!(
+            AdcCode::from_register(0x00E0_0001),
+            Some(AdcCode(-2_097_151))
+        );
+        assert_eq!(AdcCode::from_register(0x0020_0000), None);
+        assert_eq!(AdcCode::from_register(0x00C0_0000), None);
+    }
 }
@@ -1,6 +1,14 @@
 //! This module contains the low level functions for reading values from the AFE4404.
 
-use crate::{device::AFE4404, errors::AfeError, modes::LedMode};
+use embedded_hal::i2c::{I2c, SevenBitAddress};
+use uom::si::f32::ElectricPotential;
+
+use crate::{
+    device::AFE4404,
+    errors::AfeError,
+    modes::{LedMode, ThreeLedsMode, TwoLedsMode},
+    value_reading::AdcCode,
+};
 
 impl<I2C, MODE> AFE4404<I2C, MODE>
 where
@@ -18,21 +26,12 @@ where
     /// This function returns an error if the I2C bus encounters an error.
     /// This function returns an error if the ADC reading falls outside the allowed range.
     pub fn read_led1(&mut self) -> Result<ElectricPotential, AfeError<I2C::Error>> {
-        let r2ch_prev = self.registers.r2Ch.read()?;
-
-        let quantisation: ElectricPotential = ElectricPotential::new::<volt>(1.2) / 2_097_151.0;
+        let r2ch_prev = self.registers.r2Ch().read()?;
 
-        // We are converting a 22 bit reading (stored in a 32 bit register) to a 32 bit float.
-        // Since the 32 bit float has a 23 bits, we allow a precision loss.
-        // We also allow wraps since we take the sign into account.
-        let sign_extension_bits = ((r2ch_prev.led1val() & 0x00FF_FFFF) >> 21) as u8;
-        let signed_value = match sign_extension_bits {
-            0b000 => register_value as i32, // The value is positive.
-            0b111 => (register_value | 0xFF00_0000) as i32, // Extend the sign of the negative value.
-            _ => return Err(AfeError::AdcReadingOutsideAllowedRange),
-        };
+        let code = AdcCode::from_register(r2ch_prev.led1val())
+            .ok_or(AfeError::AdcReadingOutsideAllowedRange)?;
 
-        Ok(signed_value as f32 * quantisation);
+        Ok(AdcCode(self.apply_reading_polarity(code.0)).to_voltage())
     }
 
     /// Reads the LED2 sampled value.
@@ -46,21 +45,12 @@ where
     /// This function returns an error if the I2C bus encounters an error.
     /// This function returns an error if the ADC reading falls outside the allowed range.
     pub fn read_led2(&mut self) -> Result<ElectricPotential, AfeError<I2C::Error>> {
-        let r2ah_prev = self.registers.r2Ah.read()?;
+        let r2ah_prev = self.registers.r2Ah().read()?;
 
-        let quantisation: ElectricPotential = ElectricPotential::new::<volt>(1.2) / 2_097_151.0;
+        let code = AdcCode::from_register(r2ah_prev.led2val())
+            .ok_or(AfeError::AdcReadingOutsideAllowedRange)?;
 
-        // We are converting a 22 bit reading (stored in a 32 bit register) to a 32 bit float.
-        // Since the 32 bit float has a 23 bits, we allow a precision loss.
-        // We also allow wraps since we take the sign into account.
-        let sign_extension_bits = ((r2ah_prev.led2val() & 0x00FF_FFFF) >> 21) as u8;
-        let signed_value = match sign_extension_bits {
-            0b000 => register_value as i32, // The value is positive.
-            0b111 => (register_value | 0xFF00_0000) as i32, // Extend the sign of the negative value.
-            _ => return Err(AfeError::AdcReadingOutsideAllowedRange),
-        };
-
-        Ok(signed_value as f32 * quantisation);
+        Ok(AdcCode(self.apply_reading_polarity(code.0)).to_voltage())
     }
 }
 
@@ -73,27 +63,19 @@ where
     /// # Notes
     ///
     /// Call this function after an `ADC_RDY` pulse, data will remain valid until next `ADC_RDY` pulse.
+    /// In three LEDs mode `R2Bh` holds the LED3 sample, the same register holds the Ambient2 sample in two LEDs mode.
     ///
     /// # Errors
     ///
     /// This function returns an error if the I2C bus encounters an error.
     /// This function returns an error if the ADC reading falls outside the allowed range.
     pub fn read_led3(&mut self) -> Result<ElectricPotential, AfeError<I2C::Error>> {
-        let r2bh_prev = self.registers.r2Bh.read()?;
+        let r2bh_prev = self.registers.r2Bh().read()?;
 
-        let quantisation: ElectricPotential = ElectricPotential::new::<volt>(1.2) / 2_097_151.0;
+        let code = AdcCode::from_register(r2bh_prev.aled2val_or_led3val())
+            .ok_or(AfeError::AdcReadingOutsideAllowedRange)?;
 
-        // We are converting a 22 bit reading (stored in a 32 bit register) to a 32 bit float.
-        // Since the 32 bit float has a 23 bits, we allow a precision loss.
-        // We also allow wraps since we take the sign into account.
-        let sign_extension_bits = ((r2bh_prev.aled2val_or_led3val() & 0x00FF_FFFF) >> 21) as u8;
-        let signed_value = match sign_extension_bits {
-            0b000 => register_value as i32, // The value is positive.
-            0b111 => (register_value | 0xFF00_0000) as i32, // Extend the sign of the negative value.
-            _ => return Err(AfeError::AdcReadingOutsideAllowedRange),
-        };
-
-        Ok(signed_value as f32 * quantisation);
+        Ok(AdcCode(self.apply_reading_polarity(code.0)).to_voltage())
     }
 
     /// Reads the Ambient sampled value.
@@ -106,22 +88,24 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     /// This function returns an error if the ADC reading falls outside the allowed range.
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_wrap)]
     pub fn read_ambient(&mut self) -> Result<ElectricPotential, AfeError<I2C::Error>> {
-        let r2dh_prev = self.registers.r2Dh.read()?;
+        let r2dh_prev = self.registers.r2Dh().read()?;
 
-        let quantisation: ElectricPotential = ElectricPotential::new::<volt>(1.2) / 2_097_151.0;
+        let quantisation: ElectricPotential = AdcCode::quantisation();
 
         // We are converting a 22 bit reading (stored in a 32 bit register) to a 32 bit float.
         // Since the 32 bit float has a 23 bits, we allow a precision loss.
         // We also allow wraps since we take the sign into account.
-        let sign_extension_bits = ((r2dh_prev.aled1val() & 0x00FF_FFFF) >> 21) as u8;
+        let register_value = r2dh_prev.aled1val();
+        let sign_extension_bits = ((register_value & 0x00FF_FFFF) >> 21) as u8;
         let signed_value = match sign_extension_bits {
             0b000 => register_value as i32, // The value is positive.
             0b111 => (register_value | 0xFF00_0000) as i32, // Extend the sign of the negative value.
             _ => return Err(AfeError::AdcReadingOutsideAllowedRange),
         };
 
-        Ok(signed_value as f32 * quantisation);
+        Ok(self.apply_reading_polarity(signed_value) as f32 * quantisation)
     }
 
     /// Reads the LED1 minus Ambient sampled value.
@@ -134,22 +118,24 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     /// This function returns an error if the ADC reading falls outside the allowed range.
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_wrap)]
     pub fn read_led1_minus_ambient(&mut self) -> Result<ElectricPotential, AfeError<I2C::Error>> {
-        let r2fh_prev = self.registers.r2Fh.read()?;
+        let r2fh_prev = self.registers.r2Fh().read()?;
 
-        let quantisation: ElectricPotential = ElectricPotential::new::<volt>(1.2) / 2_097_151.0;
+        let quantisation: ElectricPotential = AdcCode::quantisation();
 
         // We are converting a 22 bit reading (stored in a 32 bit register) to a 32 bit float.
         // Since the 32 bit float has a 23 bits, we allow a precision loss.
         // We also allow wraps since we take the sign into account.
-        let sign_extension_bits = ((r2fh_prev.led1_minus_aled1val() & 0x00FF_FFFF) >> 21) as u8;
+        let register_value = r2fh_prev.led1_minus_aled1val();
+        let sign_extension_bits = ((register_value & 0x00FF_FFFF) >> 21) as u8;
         let signed_value = match sign_extension_bits {
             0b000 => register_value as i32, // The value is positive.
             0b111 => (register_value | 0xFF00_0000) as i32, // Extend the sign of the negative value.
             _ => return Err(AfeError::AdcReadingOutsideAllowedRange),
         };
 
-        Ok(signed_value as f32 * quantisation);
+        Ok(self.apply_reading_polarity(signed_value) as f32 * quantisation)
     }
 
     /// Reads the LED1 minus Ambient value averaged over a number of samples set by the `decimation_factor`.
@@ -157,29 +143,53 @@ where
     /// # Notes
     ///
     /// When the decimation factor is greater than one, call this function after an `ADC_RDY` pulse, data will remain valid untill next `ADC_RDY` pulse.
+    /// When the decimation factor is one, the value mirrors the instantaneous one, see `averaged_readers_valid()` function.
     ///
     /// # Errors
     ///
     /// This function returns an error if the I2C bus encounters an error.
     /// This function returns an error if the ADC reading falls outside the allowed range.
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_wrap)]
     pub fn read_averaged_led1_minus_ambient(
         &mut self,
     ) -> Result<ElectricPotential, AfeError<I2C::Error>> {
-        let r40h_prev = self.registers.r40h.read()?;
+        let r40h_prev = self.registers.r40h().read()?;
 
-        let quantisation: ElectricPotential = ElectricPotential::new::<volt>(1.2) / 2_097_151.0;
+        let quantisation: ElectricPotential = AdcCode::quantisation();
 
         // We are converting a 22 bit reading (stored in a 32 bit register) to a 32 bit float.
         // Since the 32 bit float has a 23 bits, we allow a precision loss.
         // We also allow wraps since we take the sign into account.
-        let sign_extension_bits = ((r40h_prev.avg_led1_minus_aled1val() & 0x00FF_FFFF) >> 21) as u8;
+        let register_value = r40h_prev.avg_led1_minus_aled1val();
+        let sign_extension_bits = ((register_value & 0x00FF_FFFF) >> 21) as u8;
         let signed_value = match sign_extension_bits {
             0b000 => register_value as i32, // The value is positive.
             0b111 => (register_value | 0xFF00_0000) as i32, // Extend the sign of the negative value.
             _ => return Err(AfeError::AdcReadingOutsideAllowedRange),
         };
 
-        Ok(signed_value as f32 * quantisation);
+        Ok(self.apply_reading_polarity(signed_value) as f32 * quantisation)
+    }
+
+    /// Reads the LED1 minus Ambient averaged value together with the decimation factor it was averaged over.
+    ///
+    /// # Notes
+    ///
+    /// When the decimation factor is greater than one, call this function after an `ADC_RDY` pulse, data will remain valid untill next `ADC_RDY` pulse.
+    /// When the decimation factor is one, the value mirrors the instantaneous one, see `averaged_readers_valid()` function.
+    /// The decimation factor allows downstream normalization of the averaged value.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// This function returns an error if the ADC reading falls outside the allowed range.
+    pub fn read_averaged_led1_minus_ambient_detailed(
+        &mut self,
+    ) -> Result<(ElectricPotential, u8), AfeError<I2C::Error>> {
+        Ok((
+            self.read_averaged_led1_minus_ambient()?,
+            self.get_decimation()?,
+        ))
     }
 }
 
@@ -197,22 +207,24 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     /// This function returns an error if the ADC reading falls outside the allowed range.
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_wrap)]
     pub fn read_ambient1(&mut self) -> Result<ElectricPotential, AfeError<I2C::Error>> {
-        let r2dh_prev = self.registers.r2Dh.read()?;
+        let r2dh_prev = self.registers.r2Dh().read()?;
 
-        let quantisation: ElectricPotential = ElectricPotential::new::<volt>(1.2) / 2_097_151.0;
+        let quantisation: ElectricPotential = AdcCode::quantisation();
 
         // We are converting a 22 bit reading (stored in a 32 bit register) to a 32 bit float.
         // Since the 32 bit float has a 23 bits, we allow a precision loss.
         // We also allow wraps since we take the sign into account.
-        let sign_extension_bits = ((r2dh_prev.aled1val() & 0x00FF_FFFF) >> 21) as u8;
+        let register_value = r2dh_prev.aled1val();
+        let sign_extension_bits = ((register_value & 0x00FF_FFFF) >> 21) as u8;
         let signed_value = match sign_extension_bits {
             0b000 => register_value as i32, // The value is positive.
             0b111 => (register_value | 0xFF00_0000) as i32, // Extend the sign of the negative value.
             _ => return Err(AfeError::AdcReadingOutsideAllowedRange),
         };
 
-        Ok(signed_value as f32 * quantisation);
+        Ok(self.apply_reading_polarity(signed_value) as f32 * quantisation)
     }
 
     /// Reads the Ambient2 sampled value.
@@ -220,27 +232,19 @@ where
     /// # Notes
     ///
     /// Call this function after an `ADC_RDY` pulse, data will remain valid until next `ADC_RDY` pulse.
+    /// In two LEDs mode `R2Bh` holds the Ambient2 sample, the same register holds the LED3 sample in three LEDs mode.
     ///
     /// # Errors
     ///
     /// This function returns an error if the I2C bus encounters an error.
     /// This function returns an error if the ADC reading falls outside the allowed range.
     pub fn read_ambient2(&mut self) -> Result<ElectricPotential, AfeError<I2C::Error>> {
-        let r2bh_prev = self.registers.r2Bh.read()?;
+        let r2bh_prev = self.registers.r2Bh().read()?;
 
-        let quantisation: ElectricPotential = ElectricPotential::new::<volt>(1.2) / 2_097_151.0;
+        let code = AdcCode::from_register(r2bh_prev.aled2val_or_led3val())
+            .ok_or(AfeError::AdcReadingOutsideAllowedRange)?;
 
-        // We are converting a 22 bit reading (stored in a 32 bit register) to a 32 bit float.
-        // Since the 32 bit float has a 23 bits, we allow a precision loss.
-        // We also allow wraps since we take the sign into account.
-        let sign_extension_bits = ((r2bh_prev.aled2val_or_led3val() & 0x00FF_FFFF) >> 21) as u8;
-        let signed_value = match sign_extension_bits {
-            0b000 => register_value as i32, // The value is positive.
-            0b111 => (register_value | 0xFF00_0000) as i32, // Extend the sign of the negative value.
-            _ => return Err(AfeError::AdcReadingOutsideAllowedRange),
-        };
-
-        Ok(signed_value as f32 * quantisation);
+        Ok(AdcCode(self.apply_reading_polarity(code.0)).to_voltage())
     }
 
     /// Reads the LED1 minus Ambient1 value.
@@ -253,22 +257,24 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     /// This function returns an error if the ADC reading falls outside the allowed range.
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_wrap)]
     pub fn read_led1_minus_ambient1(&mut self) -> Result<ElectricPotential, AfeError<I2C::Error>> {
-        let r2fh_prev = self.registers.r2Fh.read()?;
+        let r2fh_prev = self.registers.r2Fh().read()?;
 
-        let quantisation: ElectricPotential = ElectricPotential::new::<volt>(1.2) / 2_097_151.0;
+        let quantisation: ElectricPotential = AdcCode::quantisation();
 
         // We are converting a 22 bit reading (stored in a 32 bit register) to a 32 bit float.
         // Since the 32 bit float has a 23 bits, we allow a precision loss.
         // We also allow wraps since we take the sign into account.
-        let sign_extension_bits = ((r2fh_prev.led1_minus_aled1val() & 0x00FF_FFFF) >> 21) as u8;
+        let register_value = r2fh_prev.led1_minus_aled1val();
+        let sign_extension_bits = ((register_value & 0x00FF_FFFF) >> 21) as u8;
         let signed_value = match sign_extension_bits {
             0b000 => register_value as i32, // The value is positive.
             0b111 => (register_value | 0xFF00_0000) as i32, // Extend the sign of the negative value.
             _ => return Err(AfeError::AdcReadingOutsideAllowedRange),
         };
 
-        Ok(signed_value as f32 * quantisation);
+        Ok(self.apply_reading_polarity(signed_value) as f32 * quantisation)
     }
 
     /// Reads the LED2 minus Ambient2 value.
@@ -281,22 +287,24 @@ where
     ///
     /// This function returns an error if the I2C bus encounters an error.
     /// This function returns an error if the ADC reading falls outside the allowed range.
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_wrap)]
     pub fn read_led2_minus_ambient2(&mut self) -> Result<ElectricPotential, AfeError<I2C::Error>> {
-        let r2eh_prev = self.registers.r2Eh.read()?;
+        let r2eh_prev = self.registers.r2Eh().read()?;
 
-        let quantisation: ElectricPotential = ElectricPotential::new::<volt>(1.2) / 2_097_151.0;
+        let quantisation: ElectricPotential = AdcCode::quantisation();
 
         // We are converting a 22 bit reading (stored in a 32 bit register) to a 32 bit float.
         // Since the 32 bit float has a 23 bits, we allow a precision loss.
         // We also allow wraps since we take the sign into account.
-        let sign_extension_bits = ((r2eh_prev.led2_minus_aled2val() & 0x00FF_FFFF) >> 21) as u8;
+        let register_value = r2eh_prev.led2_minus_aled2val();
+        let sign_extension_bits = ((register_value & 0x00FF_FFFF) >> 21) as u8;
         let signed_value = match sign_extension_bits {
             0b000 => register_value as i32, // The value is positive.
             0b111 => (register_value | 0xFF00_0000) as i32, // Extend the sign of the negative value.
             _ => return Err(AfeError::AdcReadingOutsideAllowedRange),
         };
 
-        Ok(signed_value as f32 * quantisation);
+        Ok(self.apply_reading_polarity(signed_value) as f32 * quantisation)
     }
 
     /// Reads the LED1 minus Ambient1 value averaged over a number of samples set by the `decimation_factor`.
@@ -304,29 +312,53 @@ where
     /// # Notes
     ///
     /// When the decimation factor is greater than one, call this function after an `ADC_RDY` pulse, data will remain valid untill next `ADC_RDY` pulse.
+    /// When the decimation factor is one, the value mirrors the instantaneous one, see `averaged_readers_valid()` function.
     ///
     /// # Errors
     ///
     /// This function returns an error if the I2C bus encounters an error.
     /// This function returns an error if the ADC reading falls outside the allowed range.
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_wrap)]
     pub fn read_averaged_led1_minus_ambient1(
         &mut self,
     ) -> Result<ElectricPotential, AfeError<I2C::Error>> {
-        let r40h_prev = self.registers.r40h.read()?;
+        let r40h_prev = self.registers.r40h().read()?;
 
-        let quantisation: ElectricPotential = ElectricPotential::new::<volt>(1.2) / 2_097_151.0;
+        let quantisation: ElectricPotential = AdcCode::quantisation();
 
         // We are converting a 22 bit reading (stored in a 32 bit register) to a 32 bit float.
         // Since the 32 bit float has a 23 bits, we allow a precision loss.
         // We also allow wraps since we take the sign into account.
-        let sign_extension_bits = ((r40h_prev.avg_led1_minus_aled1val() & 0x00FF_FFFF) >> 21) as u8;
+        let register_value = r40h_prev.avg_led1_minus_aled1val();
+        let sign_extension_bits = ((register_value & 0x00FF_FFFF) >> 21) as u8;
         let signed_value = match sign_extension_bits {
             0b000 => register_value as i32, // The value is positive.
             0b111 => (register_value | 0xFF00_0000) as i32, // Extend the sign of the negative value.
             _ => return Err(AfeError::AdcReadingOutsideAllowedRange),
         };
 
-        Ok(signed_value as f32 * quantisation);
+        Ok(self.apply_reading_polarity(signed_value) as f32 * quantisation)
+    }
+
+    /// Reads the LED1 minus Ambient1 averaged value together with the decimation factor it was averaged over.
+    ///
+    /// # Notes
+    ///
+    /// When the decimation factor is greater than one, call this function after an `ADC_RDY` pulse, data will remain valid untill next `ADC_RDY` pulse.
+    /// When the decimation factor is one, the value mirrors the instantaneous one, see `averaged_readers_valid()` function.
+    /// The decimation factor allows downstream normalization of the averaged value.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// This function returns an error if the ADC reading falls outside the allowed range.
+    pub fn read_averaged_led1_minus_ambient1_detailed(
+        &mut self,
+    ) -> Result<(ElectricPotential, u8), AfeError<I2C::Error>> {
+        Ok((
+            self.read_averaged_led1_minus_ambient1()?,
+            self.get_decimation()?,
+        ))
     }
 
     /// Reads the LED2 minus Ambient2 value averaged over a number of samples set by the `decimation_factor`.
@@ -334,28 +366,102 @@ where
     /// # Notes
     ///
     /// When the decimation factor is greater than one, call this function after an `ADC_RDY` pulse, data will remain valid untill next `ADC_RDY` pulse.
+    /// When the decimation factor is one, the value mirrors the instantaneous one, see `averaged_readers_valid()` function.
     ///
     /// # Errors
     ///
     /// This function returns an error if the I2C bus encounters an error.
     /// This function returns an error if the ADC reading falls outside the allowed range.
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_wrap)]
     pub fn read_averaged_led2_minus_ambient2(
         &mut self,
     ) -> Result<ElectricPotential, AfeError<I2C::Error>> {
-        let r41h_prev = self.registers.r41h.read()?;
+        let r3fh_prev = self.registers.r3Fh().read()?;
 
-        let quantisation: ElectricPotential = ElectricPotential::new::<volt>(1.2) / 2_097_151.0;
+        let quantisation: ElectricPotential = AdcCode::quantisation();
 
         // We are converting a 22 bit reading (stored in a 32 bit register) to a 32 bit float.
         // Since the 32 bit float has a 23 bits, we allow a precision loss.
         // We also allow wraps since we take the sign into account.
-        let sign_extension_bits = ((r41h_prev.avg_led2_minus_aled2val() & 0x00FF_FFFF) >> 21) as u8;
+        let register_value = r3fh_prev.avg_led2_minus_aled2val();
+        let sign_extension_bits = ((register_value & 0x00FF_FFFF) >> 21) as u8;
         let signed_value = match sign_extension_bits {
             0b000 => register_value as i32, // The value is positive.
             0b111 => (register_value | 0xFF00_0000) as i32, // Extend the sign of the negative value.
             _ => return Err(AfeError::AdcReadingOutsideAllowedRange),
         };
 
-        Ok(signed_value as f32 * quantisation);
+        Ok(self.apply_reading_polarity(signed_value) as f32 * quantisation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockAfe;
+
+    /// The contents of a value register with the corresponding signed codes.
+    const SIGNED_VALUES: [(u32, i32); 5] = [
+        (0x00_0000, 0),
+        (0x00_03E8, 1000),
+        (0xFF_FC18, -1000),
+        (0x1F_FFFF, 2_097_151),
+        (0xE0_0000, -2_097_152),
+    ];
+
+    fn check_read_led2<MODE: LedMode>(mock: &MockAfe, mut afe: AFE4404<MockAfe, MODE>) {
+        for (register_value, code) in SIGNED_VALUES {
+            mock.set_register(0x2A, register_value);
+
+            assert_eq!(afe.read_led2().unwrap(), AdcCode(code).to_voltage());
+        }
+    }
+
+    #[test]
+    fn read_led2_in_every_mode() {
+        let mock = MockAfe::new();
+
+        check_read_led2(&mock, mock.three_leds());
+        check_read_led2(&mock, mock.two_leds());
+        check_read_led2(&mock, mock.one_led());
+    }
+
+    #[test]
+    fn read_led3_uses_shared_register() {
+        let mock = MockAfe::new();
+        let mut three_leds = mock.three_leds();
+        let mut two_leds = mock.two_leds();
+
+        // The other value registers hold a different code, so that a wrong register is detected.
+        for reg_addr in [0x2A, 0x2C, 0x2D] {
+            mock.set_register(reg_addr, 0x00_0001);
+        }
+
+        for (register_value, code) in SIGNED_VALUES {
+            mock.set_register(0x2B, register_value);
+
+            assert_eq!(three_leds.read_led3().unwrap(), AdcCode(code).to_voltage());
+            assert_eq!(
+                two_leds.read_ambient2().unwrap(),
+                AdcCode(code).to_voltage()
+            );
+        }
+    }
+
+    #[test]
+    fn averaged_led1_minus_ambient_read_with_decimation() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        for (decimation, (register_value, code)) in [1, 2, 4, 8, 16].into_iter().zip(SIGNED_VALUES)
+        {
+            afe.set_decimation(decimation).unwrap();
+            mock.set_register(0x40, register_value);
+
+            assert_eq!(
+                afe.read_averaged_led1_minus_ambient_detailed().unwrap(),
+                (AdcCode(code).to_voltage(), decimation)
+            );
+        }
     }
 }
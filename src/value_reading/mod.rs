@@ -2,44 +2,44 @@
 
 use embedded_hal::i2c::I2c;
 use embedded_hal::i2c::SevenBitAddress;
-use uom::si::electric_potential::volt;
-use uom::si::f32::ElectricPotential;
+use uom::si::f32::{ElectricCurrent, ElectricPotential};
 
 use crate::{
+    channel::LedChannel,
     device::AFE4404,
     errors::AfeError,
-    modes::{LedMode, ThreeLedsMode, TwoLedsMode},
+    modes::{LedMode, OneLedMode, ThreeLedsMode, TwoLedsMode},
+    system::State,
 };
 
-pub use configuration::Readings;
+pub use configuration::{
+    AdcCode, AveragedReadings, BothReadings, NamedReadings, Readings, SampleRecord,
+};
+pub use stream::{CounterWait, DelayWait, FnWait, PinWait, ReadingsStream, WaitStrategy};
 
 mod configuration;
+pub mod low_level;
+mod stream;
 
 impl<I2C, MODE> AFE4404<I2C, MODE>
 where
     I2C: I2c<SevenBitAddress>,
     MODE: LedMode,
 {
-    /// Returns an array of raw readings from the frontend.
+    /// Returns an array of raw ADC codes from the frontend.
     ///
     /// # Errors
     ///
     /// This function will return an error if the I2C bus encounters an error.
     #[allow(clippy::similar_names)]
-    fn get_raw_readings(&mut self) -> Result<[ElectricPotential; 8], AfeError<I2C::Error>> {
-        let r2ah_prev = self.registers.r2Ah.read()?;
-        let r2bh_prev = self.registers.r2Bh.read()?;
-        let r2ch_prev = self.registers.r2Ch.read()?;
-        let r2dh_prev = self.registers.r2Dh.read()?;
+    pub(crate) fn get_raw_codes(&mut self) -> Result<[i32; 4], AfeError<I2C::Error>> {
+        let r2ah_prev = self.registers.r2Ah().read()?;
+        let r2bh_prev = self.registers.r2Bh().read()?;
+        let r2ch_prev = self.registers.r2Ch().read()?;
+        let r2dh_prev = self.registers.r2Dh().read()?;
 
-        let quantisation: ElectricPotential = ElectricPotential::new::<volt>(1.2) / 2_097_151.0;
-
-        let mut values: [ElectricPotential; 8] = Default::default();
+        let mut codes: [i32; 4] = [0; 4];
 
-        // We are converting a 22 bit reading (stored in a 32 bit register) to a 32 bit float.
-        // Since the 32 bit float has a 23 bits, we allow a precision loss.
-        // We also allow wraps since we take the sign into account.
-        #[allow(clippy::cast_precision_loss, clippy::cast_possible_wrap)]
         for (i, &register_value) in [
             r2ch_prev.led1val(),
             r2ah_prev.led2val(),
@@ -49,17 +49,238 @@ where
         .iter()
         .enumerate()
         {
-            let sign_extension_bits = ((register_value & 0x00FF_FFFF) >> 21) as u8;
-            let signed_value = match sign_extension_bits {
-                0b000 => register_value as i32, // The value is positive.
-                0b111 => (register_value | 0xFF00_0000) as i32, // Extend the sign of the negative value.
-                _ => return Err(AfeError::AdcReadingOutsideAllowedRange),
-            };
-            values[i] = signed_value as f32 * quantisation;
+            let code = AdcCode::from_register(register_value)
+                .ok_or(AfeError::AdcReadingOutsideAllowedRange)?;
+            codes[i] = self.apply_reading_polarity(code.0);
+        }
+
+        Ok(codes)
+    }
+
+    /// Reads the raw contents of the value registers, without any sign interpretation.
+    ///
+    /// # Notes
+    ///
+    /// Call this function after an `ADC_RDY` pulse, data will remain valid until next `ADC_RDY` pulse.
+    /// The 24 bit contents are in register order: `R2Ah` (LED2), `R2Bh` (Ambient2 or LED3), `R2Ch` (LED1), `R2Dh` (Ambient1).
+    /// The reading polarity is not applied.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    #[allow(clippy::similar_names)]
+    pub fn read_raw_registers(&mut self) -> Result<[u32; 4], AfeError<I2C::Error>> {
+        let r2ah_prev = self.registers.r2Ah().read()?;
+        let r2bh_prev = self.registers.r2Bh().read()?;
+        let r2ch_prev = self.registers.r2Ch().read()?;
+        let r2dh_prev = self.registers.r2Dh().read()?;
+
+        Ok([
+            r2ah_prev.led2val(),
+            r2bh_prev.aled2val_or_led3val(),
+            r2ch_prev.led1val(),
+            r2dh_prev.aled1val(),
+        ])
+    }
+
+    /// Negates a code if the reading polarity is inverted.
+    pub(crate) fn apply_reading_polarity(&self, code: i32) -> i32 {
+        if self.inverted_readings {
+            -code
+        } else {
+            code
+        }
+    }
+
+    /// Sets the polarity of the readings.
+    ///
+    /// # Notes
+    ///
+    /// This is not a register operation: when `inverted` is `true` the sign of every value read from the [`AFE4404`] is flipped by the driver.
+    /// Use it on boards wiring the photodiode with inverted polarity.
+    pub fn set_reading_polarity(&mut self, inverted: bool) {
+        self.inverted_readings = inverted;
+    }
+
+    /// Gets the polarity of the readings, `true` if the readings are inverted by the driver.
+    pub fn get_reading_polarity(&self) -> bool {
+        self.inverted_readings
+    }
+
+    /// Returns an array of raw readings from the frontend.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the I2C bus encounters an error.
+    fn get_raw_readings(&mut self) -> Result<[ElectricPotential; 8], AfeError<I2C::Error>> {
+        let codes = self.get_raw_codes()?;
+
+        let mut values: [ElectricPotential; 8] = Default::default();
+
+        for (i, &code) in codes.iter().enumerate() {
+            values[i] = AdcCode(code).to_voltage();
         }
 
         Ok(values)
     }
+
+    /// Reads the raw ADC codes.
+    ///
+    /// # Notes
+    ///
+    /// Call this function after an `ADC_RDY` pulse, data will remain valid until next `ADC_RDY` pulse.
+    /// The codes are in the order LED1, LED2, Ambient1, Ambient2 (LED3 in three LEDs mode).
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// This function returns an error if the ADC reading falls outside the allowed range.
+    pub fn read_raw(&mut self) -> Result<[AdcCode; 4], AfeError<I2C::Error>> {
+        Ok(self.get_raw_codes()?.map(AdcCode))
+    }
+
+    /// Returns an error if the input short is enabled.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// This function returns an error if the input short is enabled.
+    fn ensure_input_not_shorted(&mut self) -> Result<(), AfeError<I2C::Error>> {
+        let r31h_prev = self.registers.r31h().read()?;
+
+        if r31h_prev.enable_input_short() {
+            return Err(AfeError::InputShortEnabled);
+        }
+
+        Ok(())
+    }
+
+    /// Reads the sampled values of all the channels as input-referred currents.
+    ///
+    /// # Notes
+    ///
+    /// Call this function after an `ADC_RDY` pulse, data will remain valid until next `ADC_RDY` pulse.
+    /// The currents are in the order LED1, LED2, Ambient1, Ambient2 (LED3 in three LEDs mode).
+    /// Each value is divided by the TIA resistor in use during its phase:
+    /// `resistor1` for LED1 and Ambient1, `resistor2` for LED2 and Ambient2 (LED3) when separate gains are enabled.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// This function returns an error if the ADC reading falls outside the allowed range.
+    pub fn read_currents(&mut self) -> Result<[ElectricCurrent; 4], AfeError<I2C::Error>> {
+        let values = self.get_raw_readings()?;
+
+        let resistor1 = self.get_tia_resistor1()?;
+        let resistor2 = if self.registers.r20h().read()?.ensepgain() {
+            self.get_tia_resistor2()?
+        } else {
+            resistor1
+        };
+
+        Ok([
+            values[0] / resistor1,
+            values[1] / resistor2,
+            values[2] / resistor1,
+            values[3] / resistor2,
+        ])
+    }
+
+    /// Estimates the signal to noise ratio of a channel from repeated readings.
+    ///
+    /// # Notes
+    ///
+    /// Before each reading the function busy-waits until `ready` returns `true`, typically when an `ADC_RDY` pulse is detected.
+    /// The SNR is computed as `20 log10(|mean| / std)` over `samples` readings and returned in dB.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// This function returns an error if the ADC reading falls outside the allowed range.
+    /// Requesting less than two samples will result in an error.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn measure_snr(
+        &mut self,
+        channel: LedChannel,
+        samples: usize,
+        mut ready: impl FnMut() -> bool,
+    ) -> Result<f32, AfeError<I2C::Error>> {
+        if samples < 2 {
+            return Err(AfeError::NotEnoughSamples);
+        }
+
+        let index = match channel {
+            LedChannel::Led1 => 0,
+            LedChannel::Led2 => 1,
+            LedChannel::Ambient1 => 2,
+            LedChannel::Led3 | LedChannel::Ambient2 => 3,
+        };
+
+        // Welford's online algorithm, so that no buffer is needed.
+        let mut mean = 0.0_f32;
+        let mut m2 = 0.0_f32;
+        for n in 1..=samples {
+            while !ready() {}
+
+            let value = self.get_raw_codes()?[index] as f32;
+            let delta = value - mean;
+            mean += delta / n as f32;
+            m2 += delta * (value - mean);
+        }
+        let std = (m2 / (samples - 1) as f32).sqrt();
+
+        Ok(20.0 * (mean.abs() / std).log10())
+    }
+
+    /// Checks whether the readings look like the photodiode is disconnected.
+    ///
+    /// # Notes
+    ///
+    /// When the photodiode is disconnected the readings are determined only by the offset currents.
+    /// The function returns `true` if the photodiode is disabled in the register,
+    /// or if all the readings are identical within one ADC code without being saturated.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    pub fn likely_photodiode_disconnected(
+        &mut self,
+        readings: &Readings<MODE>,
+    ) -> Result<bool, AfeError<I2C::Error>> {
+        if self.get_photodiode()? == State::Disabled {
+            return Ok(true);
+        }
+
+        let values = readings.values();
+        let resolution = AdcCode::quantisation();
+        let full_scale = AdcCode(AdcCode::FULL_SCALE).to_voltage();
+
+        let flat = values
+            .iter()
+            .all(|value| (*value - values[0]).abs() <= resolution);
+        let saturated = values
+            .iter()
+            .any(|value| value.abs() >= full_scale - resolution);
+
+        Ok(flat && !saturated)
+    }
+
+    /// Reads the sampled values as a fixed-size record tagged with a sequence number.
+    ///
+    /// # Notes
+    ///
+    /// Call this function after an `ADC_RDY` pulse, data will remain valid until next `ADC_RDY` pulse.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// This function returns an error if the ADC reading falls outside the allowed range.
+    pub fn read_record(&mut self, seq: u32) -> Result<SampleRecord, AfeError<I2C::Error>> {
+        Ok(SampleRecord {
+            sequence: seq,
+            codes: self.get_raw_codes()?,
+        })
+    }
 }
 
 impl<I2C> AFE4404<I2C, ThreeLedsMode>
@@ -83,6 +304,51 @@ where
             values[0], values[1], values[3], values[2],
         ))
     }
+
+    /// Reads the sampled values, refusing to read while the input short is enabled.
+    ///
+    /// # Notes
+    ///
+    /// Call this function after an `ADC_RDY` pulse, data will remain valid until next `ADC_RDY` pulse.
+    /// While the input short is enabled the readings do not contain any photocurrent,
+    /// use [`AFE4404::read`] to read them anyway for diagnostic purposes.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// This function returns an error if the ADC reading falls outside the allowed range.
+    /// This function returns an error if the input short is enabled.
+    pub fn read_strict(&mut self) -> Result<Readings<ThreeLedsMode>, AfeError<I2C::Error>> {
+        self.ensure_input_not_shorted()?;
+
+        self.read()
+    }
+
+    /// Reads the sampled values and the averaged values together.
+    ///
+    /// # Notes
+    ///
+    /// Call this function after an `ADC_RDY` pulse, data will remain valid until next `ADC_RDY` pulse.
+    /// The value registers `R2Ah`-`R2Dh` are read first, followed by the averaged value register `R40h`.
+    /// When the decimation factor is one, the averaged values mirror the instantaneous ones, see `averaged_readers_valid()` function.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// This function returns an error if the ADC reading falls outside the allowed range.
+    pub fn read_both(&mut self) -> Result<BothReadings<ThreeLedsMode>, AfeError<I2C::Error>> {
+        let readings = self.read()?;
+        let led1_minus_ambient =
+            AdcCode::from_register(self.registers.r40h().read()?.avg_led1_minus_aled1val())
+                .ok_or(AfeError::AdcReadingOutsideAllowedRange)?;
+
+        Ok((
+            readings,
+            AveragedReadings::<ThreeLedsMode>::new(
+                AdcCode(self.apply_reading_polarity(led1_minus_ambient.0)).to_voltage(),
+            ),
+        ))
+    }
 }
 
 impl<I2C> AFE4404<I2C, TwoLedsMode>
@@ -107,4 +373,421 @@ where
             values[0], values[1], values[2], values[3],
         ))
     }
+
+    /// Reads the sampled values, refusing to read while the input short is enabled.
+    ///
+    /// # Notes
+    ///
+    /// Call this function after an `ADC_RDY` pulse, data will remain valid until next `ADC_RDY` pulse.
+    /// While the input short is enabled the readings do not contain any photocurrent,
+    /// use [`AFE4404::read`] to read them anyway for diagnostic purposes.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// This function returns an error if the ADC reading falls outside the allowed range.
+    /// This function returns an error if the input short is enabled.
+    pub fn read_strict(&mut self) -> Result<Readings<TwoLedsMode>, AfeError<I2C::Error>> {
+        self.ensure_input_not_shorted()?;
+
+        self.read()
+    }
+
+    /// Reads the sampled values and the averaged values together.
+    ///
+    /// # Notes
+    ///
+    /// Call this function after an `ADC_RDY` pulse, data will remain valid until next `ADC_RDY` pulse.
+    /// The value registers `R2Ah`-`R2Dh` are read first, followed by the averaged value registers `R3Fh`-`R40h`.
+    /// When the decimation factor is one, the averaged values mirror the instantaneous ones, see `averaged_readers_valid()` function.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// This function returns an error if the ADC reading falls outside the allowed range.
+    pub fn read_both(&mut self) -> Result<BothReadings<TwoLedsMode>, AfeError<I2C::Error>> {
+        let readings = self.read()?;
+        let led2_minus_ambient2 =
+            AdcCode::from_register(self.registers.r3Fh().read()?.avg_led2_minus_aled2val())
+                .ok_or(AfeError::AdcReadingOutsideAllowedRange)?;
+        let led1_minus_ambient1 =
+            AdcCode::from_register(self.registers.r40h().read()?.avg_led1_minus_aled1val())
+                .ok_or(AfeError::AdcReadingOutsideAllowedRange)?;
+
+        Ok((
+            readings,
+            AveragedReadings::<TwoLedsMode>::new(
+                AdcCode(self.apply_reading_polarity(led1_minus_ambient1.0)).to_voltage(),
+                AdcCode(self.apply_reading_polarity(led2_minus_ambient2.0)).to_voltage(),
+            ),
+        ))
+    }
+}
+
+impl<I2C> AFE4404<I2C, OneLedMode>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    /// Reads the sampled values.
+    ///
+    /// # Notes
+    ///
+    /// Call this function after an `ADC_RDY` pulse, data will remain valid until next `ADC_RDY` pulse.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// This function returns an error if the ADC reading falls outside the allowed range.
+    pub fn read(&mut self) -> Result<Readings<OneLedMode>, AfeError<I2C::Error>> {
+        let values = self.get_raw_readings()?;
+
+        Ok(Readings::<OneLedMode>::new(values[0], values[2]))
+    }
+
+    /// Reads the sampled values, refusing to read while the input short is enabled.
+    ///
+    /// # Notes
+    ///
+    /// Call this function after an `ADC_RDY` pulse, data will remain valid until next `ADC_RDY` pulse.
+    /// While the input short is enabled the readings do not contain any photocurrent,
+    /// use [`AFE4404::read`] to read them anyway for diagnostic purposes.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// This function returns an error if the ADC reading falls outside the allowed range.
+    /// This function returns an error if the input short is enabled.
+    pub fn read_strict(&mut self) -> Result<Readings<OneLedMode>, AfeError<I2C::Error>> {
+        self.ensure_input_not_shorted()?;
+
+        self.read()
+    }
+
+    /// Reads the sampled values and the averaged values together.
+    ///
+    /// # Notes
+    ///
+    /// Call this function after an `ADC_RDY` pulse, data will remain valid until next `ADC_RDY` pulse.
+    /// The value registers `R2Ah`-`R2Dh` are read first, followed by the averaged value register `R40h`.
+    /// When the decimation factor is one, the averaged values mirror the instantaneous ones, see `averaged_readers_valid()` function.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// This function returns an error if the ADC reading falls outside the allowed range.
+    pub fn read_both(&mut self) -> Result<BothReadings<OneLedMode>, AfeError<I2C::Error>> {
+        let readings = self.read()?;
+        let led1_minus_ambient =
+            AdcCode::from_register(self.registers.r40h().read()?.avg_led1_minus_aled1val())
+                .ok_or(AfeError::AdcReadingOutsideAllowedRange)?;
+
+        Ok((
+            readings,
+            AveragedReadings::<OneLedMode>::new(
+                AdcCode(self.apply_reading_polarity(led1_minus_ambient.0)).to_voltage(),
+            ),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock::MockAfe;
+
+    use super::*;
+
+    /// Loads LED1, LED2, Ambient1 and Ambient2 (or LED3) codes of 100, 200, -300 and 400.
+    fn load_codes(mock: &MockAfe) {
+        mock.set_register(0x2C, 100);
+        mock.set_register(0x2A, 200);
+        mock.set_register(0x2D, 0x00FF_FED4);
+        mock.set_register(0x2B, 400);
+    }
+
+    #[test]
+    fn record_holds_codes_in_documented_order() {
+        let mock = MockAfe::new();
+        load_codes(&mock);
+
+        assert_eq!(
+            mock.three_leds().read_record(1).unwrap(),
+            SampleRecord {
+                sequence: 1,
+                codes: [100, 200, -300, 400],
+            }
+        );
+    }
+
+    #[cfg(feature = "bytemuck")]
+    fn round_trip<MODE: LedMode>(afe: &mut AFE4404<MockAfe, MODE>) {
+        let record = afe.read_record(7).unwrap();
+        assert_eq!(record.codes, [100, 200, -300, 400]);
+        let bytes = bytemuck::bytes_of(&record);
+        assert_eq!(bytes.len(), 20);
+        assert_eq!(bytemuck::pod_read_unaligned::<SampleRecord>(bytes), record);
+    }
+
+    #[test]
+    #[cfg(feature = "bytemuck")]
+    fn three_leds_record_round_trips_through_bytes() {
+        let mock = MockAfe::new();
+        load_codes(&mock);
+
+        round_trip(&mut mock.three_leds());
+    }
+
+    #[test]
+    #[cfg(feature = "bytemuck")]
+    fn two_leds_record_round_trips_through_bytes() {
+        let mock = MockAfe::new();
+        load_codes(&mock);
+
+        round_trip(&mut mock.two_leds());
+    }
+
+    #[test]
+    #[cfg(feature = "bytemuck")]
+    fn one_led_record_round_trips_through_bytes() {
+        let mock = MockAfe::new();
+        load_codes(&mock);
+
+        round_trip(&mut mock.one_led());
+    }
+
+    #[test]
+    fn strict_read_rejected_with_input_short() {
+        let mock = MockAfe::new();
+        load_codes(&mock);
+        // ENABLE_INPUT_SHORT is bit 5 of R31h.
+        mock.set_register(0x31, 1 << 5);
+
+        assert!(matches!(
+            mock.three_leds().read_strict(),
+            Err(AfeError::InputShortEnabled)
+        ));
+        assert!(matches!(
+            mock.two_leds().read_strict(),
+            Err(AfeError::InputShortEnabled)
+        ));
+        assert!(matches!(
+            mock.one_led().read_strict(),
+            Err(AfeError::InputShortEnabled)
+        ));
+
+        // The diagnostic reader still reads the shorted input.
+        assert!(mock.three_leds().read().is_ok());
+    }
+
+    #[test]
+    fn strict_read_matches_read_without_input_short() {
+        let mock = MockAfe::new();
+        load_codes(&mock);
+        let mut afe = mock.three_leds();
+
+        assert_eq!(afe.read_strict().unwrap(), afe.read().unwrap());
+    }
+
+    #[test]
+    fn raw_codes_match_read_voltages() {
+        let mock = MockAfe::new();
+        load_codes(&mock);
+        let mut afe = mock.three_leds();
+
+        let codes = afe.read_raw().unwrap();
+        assert_eq!(codes, [100, 200, -300, 400].map(AdcCode));
+
+        let readings = afe.read().unwrap();
+        assert_eq!(codes[0].to_voltage(), *readings.led1());
+        assert_eq!(codes[1].to_voltage(), *readings.led2());
+        assert_eq!(codes[2].to_voltage(), *readings.ambient());
+        assert_eq!(codes[3].to_voltage(), *readings.led3());
+    }
+
+    #[test]
+    fn snr_estimated_from_mean_and_noise() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+        let seed = mock.clone();
+        let mut sample = 0_u32;
+        let mut high = true;
+
+        // A mean of 1000 codes with a ±10 codes noise on LED2, seeded whenever a reading is ready.
+        let snr = afe
+            .measure_snr(LedChannel::Led2, 100, || {
+                seed.set_register(0x2A, if high { 1010 } else { 990 });
+                high = !high;
+                sample += 1;
+                true
+            })
+            .unwrap();
+
+        // 20 log10(1000 / (10 × sqrt(100 / 99))).
+        assert!((snr - 39.957).abs() < 1e-2);
+        assert_eq!(sample, 100);
+    }
+
+    #[test]
+    fn snr_waits_for_ready() {
+        let mock = MockAfe::new();
+        load_codes(&mock);
+        let mut afe = mock.three_leds();
+        let mut polls = 0;
+
+        afe.measure_snr(LedChannel::Led1, 2, || {
+            polls += 1;
+            polls == 3 || polls == 6
+        })
+        .unwrap();
+
+        assert_eq!(polls, 6);
+    }
+
+    #[test]
+    fn snr_needs_two_samples() {
+        let mut afe = MockAfe::new().three_leds();
+
+        assert!(matches!(
+            afe.measure_snr(LedChannel::Led1, 1, || true),
+            Err(AfeError::NotEnoughSamples)
+        ));
+    }
+
+    #[test]
+    fn photodiode_disconnected_flagged_from_register() {
+        let mock = MockAfe::new();
+        load_codes(&mock);
+        let mut afe = mock.three_leds();
+        let readings = afe.read().unwrap();
+
+        assert!(!afe.likely_photodiode_disconnected(&readings).unwrap());
+
+        afe.set_photodiode(State::Disabled).unwrap();
+        assert!(afe.likely_photodiode_disconnected(&readings).unwrap());
+    }
+
+    #[test]
+    fn photodiode_disconnected_flagged_from_flat_readings() {
+        let mock = MockAfe::new();
+        for reg_addr in 0x2A..=0x2D {
+            mock.set_register(reg_addr, 1234);
+        }
+        let mut afe = mock.two_leds();
+        let readings = afe.read().unwrap();
+
+        assert!(afe.likely_photodiode_disconnected(&readings).unwrap());
+
+        // Saturated readings are flat because of the light, not because of the photodiode.
+        for reg_addr in 0x2A..=0x2D {
+            mock.set_register(reg_addr, 0x1F_FFFF);
+        }
+        let readings = afe.read().unwrap();
+        assert!(!afe.likely_photodiode_disconnected(&readings).unwrap());
+    }
+
+    #[test]
+    fn readings_negated_with_inverted_polarity() {
+        let mock = MockAfe::new();
+        load_codes(&mock);
+        mock.set_register(0x40, 500);
+        let mut afe = mock.three_leds();
+
+        let readings = afe.read().unwrap();
+        let averaged = afe.read_averaged_led1_minus_ambient().unwrap();
+
+        afe.set_reading_polarity(true);
+        assert!(afe.get_reading_polarity());
+
+        let inverted = afe.read().unwrap();
+        assert_eq!(*inverted.led1(), -*readings.led1());
+        assert_eq!(*inverted.led2(), -*readings.led2());
+        assert_eq!(*inverted.led3(), -*readings.led3());
+        assert_eq!(*inverted.ambient(), -*readings.ambient());
+        assert_eq!(
+            afe.read_raw().unwrap(),
+            [-100, -200, 300, -400].map(AdcCode)
+        );
+        assert_eq!(afe.read_averaged_led1_minus_ambient().unwrap(), -averaged);
+        assert_eq!(mock.register(0x2C), 100);
+
+        afe.set_reading_polarity(false);
+        assert_eq!(afe.read().unwrap(), readings);
+    }
+
+    #[test]
+    fn raw_registers_passed_through_unmodified() {
+        let mock = MockAfe::new();
+        // Register contents outside the ADC range, which a sign interpretation would reject.
+        mock.set_register(0x2A, 0x00AB_CDEF);
+        mock.set_register(0x2B, 0x0080_0000);
+        mock.set_register(0x2C, 0x00FF_FFFF);
+        mock.set_register(0x2D, 0x0012_3456);
+        let mut afe = mock.three_leds();
+        afe.set_reading_polarity(true);
+
+        assert_eq!(
+            afe.read_raw_registers().unwrap(),
+            [0x00AB_CDEF, 0x0080_0000, 0x00FF_FFFF, 0x0012_3456]
+        );
+        assert!(afe.read_raw().is_err());
+    }
+
+    /// Asserts that currents match the voltages of the loaded codes divided by the given resistors, in channel order.
+    fn assert_currents(
+        currents: [ElectricCurrent; 4],
+        resistors: [uom::si::f32::ElectricalResistance; 4],
+    ) {
+        for ((current, code), resistor) in currents
+            .into_iter()
+            .zip([100, 200, -300, 400])
+            .zip(resistors)
+        {
+            let expected = AdcCode(code).to_voltage() / resistor;
+            assert!(((current / expected).value - 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn currents_divided_by_phase_resistor() {
+        use uom::si::{electrical_resistance::kiloohm, f32::ElectricalResistance};
+
+        use crate::tia::ResistorConfiguration;
+
+        let mock = MockAfe::new();
+        load_codes(&mock);
+        let mut afe = mock.three_leds();
+        let resistor1 = ElectricalResistance::new::<kiloohm>(100.0);
+        let resistor2 = ElectricalResistance::new::<kiloohm>(500.0);
+
+        // Separate gains: LED2 and LED3 use the second resistor.
+        afe.set_tia_resistors(&ResistorConfiguration::<ThreeLedsMode>::new(
+            resistor1, resistor2,
+        ))
+        .unwrap();
+        assert_currents(
+            afe.read_currents().unwrap(),
+            [resistor1, resistor2, resistor1, resistor2],
+        );
+
+        // Separate gains disabled: every channel uses the first resistor, whatever the second one is.
+        mock.set_register(0x20, mock.register(0x20) & !(1 << 15));
+        assert_currents(afe.read_currents().unwrap(), [resistor1; 4]);
+    }
+
+    #[test]
+    fn both_readings_come_from_distinct_registers() {
+        let mock = MockAfe::new();
+        load_codes(&mock);
+        mock.set_register(0x3F, 1000);
+        mock.set_register(0x40, 2000);
+        let mut afe = mock.two_leds();
+
+        let (readings, averaged) = afe.read_both().unwrap();
+
+        assert_eq!(*readings.led1(), AdcCode(100).to_voltage());
+        assert_eq!(*readings.led2(), AdcCode(200).to_voltage());
+        assert_eq!(*readings.ambient1(), AdcCode(-300).to_voltage());
+        assert_eq!(*readings.ambient2(), AdcCode(400).to_voltage());
+        assert_eq!(*averaged.led1_minus_ambient1(), AdcCode(2000).to_voltage());
+        assert_eq!(*averaged.led2_minus_ambient2(), AdcCode(1000).to_voltage());
+    }
 }
@@ -0,0 +1,367 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use embedded_hal::delay::DelayUs;
+use embedded_hal::digital::InputPin;
+use embedded_hal::i2c::{ErrorType, I2c, SevenBitAddress};
+use uom::si::{f32::Time, time::microsecond};
+
+use crate::{
+    device::AFE4404,
+    errors::AfeError,
+    modes::{LedMode, OneLedMode, ThreeLedsMode, TwoLedsMode},
+    value_reading::Readings,
+};
+
+/// Represents a function reading the sampled values.
+type Reader<I2C, MODE> =
+    fn(&mut AFE4404<I2C, MODE>) -> Result<Readings<MODE>, AfeError<<I2C as ErrorType>::Error>>;
+
+/// Represents a way of waiting for the next `ADC_RDY` pulse.
+pub trait WaitStrategy {
+    /// The error returned while waiting.
+    type Error;
+
+    /// Blocks until the next `ADC_RDY` pulse.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the underlying pin, delay or callback encounters an error.
+    fn wait(&mut self) -> Result<(), Self::Error>;
+
+    /// Checks whether a new `ADC_RDY` pulse arrived since [`WaitStrategy::wait`] returned.
+    ///
+    /// # Notes
+    ///
+    /// Strategies that cannot detect it always return `false`.
+    fn overrun(&mut self) -> bool {
+        false
+    }
+}
+
+/// Represents a wait polling the pin connected to the `ADC_RDY` output.
+///
+/// # Notes
+///
+/// A pulse already in progress is skipped and the pulse must be longer than the polling interval, otherwise it may be missed.
+pub struct PinWait<PIN> {
+    pin: PIN,
+}
+
+impl<PIN> PinWait<PIN> {
+    /// Creates a new `PinWait` from the pin connected to the `ADC_RDY` output.
+    pub fn new(pin: PIN) -> Self {
+        Self { pin }
+    }
+
+    /// Releases the pin.
+    pub fn release(self) -> PIN {
+        self.pin
+    }
+}
+
+impl<PIN> WaitStrategy for PinWait<PIN>
+where
+    PIN: InputPin,
+{
+    type Error = PIN::Error;
+
+    fn wait(&mut self) -> Result<(), Self::Error> {
+        while self.pin.is_high()? {}
+        while self.pin.is_low()? {}
+
+        Ok(())
+    }
+}
+
+/// Represents a wait of a fixed interval, typically the sample interval.
+///
+/// # Notes
+///
+/// The interval is not synchronised with the `ADC_RDY` pulses, so the readings can be read twice or skipped.
+pub struct DelayWait<D> {
+    delay: D,
+    interval_us: u32,
+}
+
+impl<D> DelayWait<D> {
+    /// Creates a new `DelayWait` waiting `interval` every time, rounded up to the microsecond.
+    pub fn new(delay: D, interval: Time) -> Self {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let interval_us = interval.get::<microsecond>().ceil() as u32;
+
+        Self { delay, interval_us }
+    }
+
+    /// Releases the delay.
+    pub fn release(self) -> D {
+        self.delay
+    }
+}
+
+impl<D> WaitStrategy for DelayWait<D>
+where
+    D: DelayUs,
+{
+    type Error = D::Error;
+
+    fn wait(&mut self) -> Result<(), Self::Error> {
+        self.delay.delay_us(self.interval_us)
+    }
+}
+
+/// Represents a wait on a counter incremented by the `ADC_RDY` interrupt handler.
+///
+/// # Notes
+///
+/// The wait spins until the counter changes, a change of the counter during the reading is reported as an overrun.
+pub struct CounterWait<'a> {
+    counter: &'a AtomicUsize,
+    seen: usize,
+}
+
+impl<'a> CounterWait<'a> {
+    /// Creates a new `CounterWait` on the counter incremented by the `ADC_RDY` interrupt handler.
+    pub fn new(counter: &'a AtomicUsize) -> Self {
+        Self {
+            seen: counter.load(Ordering::Acquire),
+            counter,
+        }
+    }
+}
+
+impl WaitStrategy for CounterWait<'_> {
+    type Error = core::convert::Infallible;
+
+    fn wait(&mut self) -> Result<(), Self::Error> {
+        loop {
+            let count = self.counter.load(Ordering::Acquire);
+            if count != self.seen {
+                self.seen = count;
+                return Ok(());
+            }
+            core::hint::spin_loop();
+        }
+    }
+
+    fn overrun(&mut self) -> bool {
+        self.counter.load(Ordering::Acquire) != self.seen
+    }
+}
+
+/// Represents a wait delegated to a callback, which returns when the next `ADC_RDY` pulse arrives.
+pub struct FnWait<F> {
+    callback: F,
+}
+
+impl<F> FnWait<F> {
+    /// Creates a new `FnWait` from a callback returning when the next `ADC_RDY` pulse arrives.
+    pub fn new(callback: F) -> Self {
+        Self { callback }
+    }
+}
+
+impl<F, E> WaitStrategy for FnWait<F>
+where
+    F: FnMut() -> Result<(), E>,
+{
+    type Error = E;
+
+    fn wait(&mut self) -> Result<(), Self::Error> {
+        (self.callback)()
+    }
+}
+
+/// Represents an endless stream of readings, each read after an `ADC_RDY` pulse.
+///
+/// # Notes
+///
+/// The stream never ends, an error is yielded in place of the readings when the wait, the bus or the reading fails.
+/// Readings interrupted by a new pulse may mix two samples, so they are replaced by `AfeError::ReadingOverrun`.
+pub struct ReadingsStream<'a, I2C, MODE, W>
+where
+    MODE: LedMode,
+{
+    afe: &'a mut AFE4404<I2C, MODE>,
+    wait: W,
+}
+
+impl<I2C, MODE> AFE4404<I2C, MODE>
+where
+    I2C: I2c<SevenBitAddress>,
+    MODE: LedMode,
+{
+    /// Returns an endless stream of readings, waiting for each `ADC_RDY` pulse with the given strategy.
+    pub fn readings<W>(&mut self, wait: W) -> ReadingsStream<'_, I2C, MODE, W>
+    where
+        W: WaitStrategy,
+    {
+        ReadingsStream { afe: self, wait }
+    }
+}
+
+impl<I2C, MODE, W> ReadingsStream<'_, I2C, MODE, W>
+where
+    I2C: I2c<SevenBitAddress>,
+    MODE: LedMode,
+    W: WaitStrategy,
+{
+    /// Waits for the next pulse and reads the values with the given reader, checking for overruns.
+    fn next_with(
+        &mut self,
+        read: Reader<I2C, MODE>,
+    ) -> Result<Readings<MODE>, AfeError<I2C::Error>> {
+        self.wait.wait().map_err(|_| AfeError::WaitError)?;
+
+        let readings = read(self.afe)?;
+
+        if self.wait.overrun() {
+            return Err(AfeError::ReadingOverrun);
+        }
+
+        Ok(readings)
+    }
+
+    /// Releases the wait strategy.
+    pub fn release(self) -> W {
+        self.wait
+    }
+}
+
+impl<I2C, W> Iterator for ReadingsStream<'_, I2C, ThreeLedsMode, W>
+where
+    I2C: I2c<SevenBitAddress>,
+    W: WaitStrategy,
+{
+    type Item = Result<Readings<ThreeLedsMode>, AfeError<I2C::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_with(AFE4404::<I2C, ThreeLedsMode>::read))
+    }
+}
+
+impl<I2C, W> Iterator for ReadingsStream<'_, I2C, TwoLedsMode, W>
+where
+    I2C: I2c<SevenBitAddress>,
+    W: WaitStrategy,
+{
+    type Item = Result<Readings<TwoLedsMode>, AfeError<I2C::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_with(AFE4404::<I2C, TwoLedsMode>::read))
+    }
+}
+
+impl<I2C, W> Iterator for ReadingsStream<'_, I2C, OneLedMode, W>
+where
+    I2C: I2c<SevenBitAddress>,
+    W: WaitStrategy,
+{
+    type Item = Result<Readings<OneLedMode>, AfeError<I2C::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_with(AFE4404::<I2C, OneLedMode>::read))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock::MockAfe, value_reading::AdcCode};
+
+    /// Represents a wait always reporting a new pulse during the reading.
+    struct OverrunWait;
+
+    impl WaitStrategy for OverrunWait {
+        type Error = core::convert::Infallible;
+
+        fn wait(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn overrun(&mut self) -> bool {
+            true
+        }
+    }
+
+    /// Represents a delay always failing.
+    struct FailingDelay;
+
+    impl DelayUs for FailingDelay {
+        type Error = ();
+
+        fn delay_us(&mut self, _us: u32) -> Result<(), Self::Error> {
+            Err(())
+        }
+    }
+
+    #[test]
+    fn wait_error_yielded_and_stream_continues() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+        let mut calls = 0;
+
+        let mut stream = afe.readings(FnWait::new(|| {
+            calls += 1;
+            if calls == 1 {
+                Err(())
+            } else {
+                Ok(())
+            }
+        }));
+
+        assert!(matches!(stream.next(), Some(Err(AfeError::WaitError))));
+        assert!(matches!(stream.next(), Some(Ok(_))));
+    }
+
+    #[test]
+    fn delay_error_yielded_as_wait_error() {
+        let mut afe = MockAfe::new().one_led();
+
+        let mut stream = afe.readings(DelayWait::new(
+            FailingDelay,
+            Time::new::<microsecond>(100.0),
+        ));
+
+        assert!(matches!(stream.next(), Some(Err(AfeError::WaitError))));
+    }
+
+    #[test]
+    fn overrun_replaces_readings() {
+        let mut afe = MockAfe::new().two_leds();
+
+        let mut stream = afe.readings(OverrunWait);
+
+        assert!(matches!(stream.next(), Some(Err(AfeError::ReadingOverrun))));
+    }
+
+    #[test]
+    fn counter_change_during_reading_is_overrun() {
+        let counter = AtomicUsize::new(0);
+        let mut wait = CounterWait::new(&counter);
+
+        counter.fetch_add(1, Ordering::Release);
+        wait.wait().unwrap();
+        assert!(!wait.overrun());
+
+        counter.fetch_add(1, Ordering::Release);
+        assert!(wait.overrun());
+    }
+
+    #[test]
+    fn invalid_reading_yielded_and_stream_continues() {
+        let mock = MockAfe::new();
+        mock.set_register(0x2C, 0x0040_0000);
+        let mut afe = mock.three_leds();
+        let seed = mock.clone();
+
+        let mut stream = afe.readings(FnWait::new(|| Ok::<(), ()>(())));
+
+        assert!(matches!(
+            stream.next(),
+            Some(Err(AfeError::AdcReadingOutsideAllowedRange))
+        ));
+        seed.set_register(0x2C, 100);
+        let readings = stream.next().unwrap().unwrap();
+        assert_eq!(*readings.led1(), AdcCode(100).to_voltage());
+    }
+}
@@ -0,0 +1,133 @@
+//! This module contains the watchdog checking the integrity of the critical registers.
+
+use alloc::vec::Vec;
+
+use embedded_hal::i2c::{I2c, SevenBitAddress};
+
+use crate::{cache::RegisterMismatch, device::AFE4404, errors::AfeError, modes::LedMode};
+
+/// Represents a group of registers checked by the [`RegisterWatchdog`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WatchedGroup {
+    /// The measurement window, the timer and the averaging registers.
+    Timing,
+    /// The TIA gain registers.
+    Tia,
+    /// The LEDs current and offset current registers.
+    Current,
+}
+
+impl WatchedGroup {
+    /// Gets the addresses of the registers in the group.
+    fn registers(self) -> Vec<u8> {
+        match self {
+            WatchedGroup::Timing => (0x01..=0x1E).chain(0x32..=0x37).chain([0x39]).collect(),
+            WatchedGroup::Tia => Vec::from([0x20, 0x21]),
+            WatchedGroup::Current => Vec::from([0x22, 0x23, 0x3A]),
+        }
+    }
+}
+
+/// Represents what the [`RegisterWatchdog`] does with a register differing from the expected content.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DriftAction {
+    /// The register is only reported.
+    Flag,
+    /// The register is reported and written again with the expected content.
+    Rewrite,
+}
+
+/// Represents the statistics collected by the [`RegisterWatchdog`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WatchdogStatistics {
+    /// The number of polls.
+    pub polls: u32,
+    /// The number of polls finding at least one corrupted register.
+    pub corruption_events: u32,
+    /// The total number of corrupted registers found.
+    pub corrupted_registers: u32,
+    /// The number of registers written again.
+    pub rewrites: u32,
+}
+
+/// Represents a watchdog checking a subset of the configuration registers against the content known to the driver.
+///
+/// # Notes
+///
+/// The watchdog relies on the register cache, which must be enabled before configuring the device.
+/// The registers not in the register cache yet are skipped, since their expected content is unknown.
+pub struct RegisterWatchdog {
+    registers: Vec<u8>,
+    action: DriftAction,
+    statistics: WatchdogStatistics,
+}
+
+impl RegisterWatchdog {
+    /// Creates a new `RegisterWatchdog` checking the given groups of registers.
+    pub fn new(groups: &[WatchedGroup], action: DriftAction) -> Self {
+        let mut registers: Vec<u8> = groups.iter().flat_map(|group| group.registers()).collect();
+        registers.sort_unstable();
+        registers.dedup();
+
+        Self {
+            registers,
+            action,
+            statistics: WatchdogStatistics::default(),
+        }
+    }
+
+    /// Gets the statistics collected since the creation or the last reset.
+    pub fn statistics(&self) -> WatchdogStatistics {
+        self.statistics
+    }
+
+    /// Resets the statistics.
+    pub fn reset_statistics(&mut self) {
+        self.statistics = WatchdogStatistics::default();
+    }
+
+    /// Checks the watched registers of the device, returning the corrupted ones sorted by address.
+    ///
+    /// # Notes
+    ///
+    /// With [`DriftAction::Rewrite`] the corrupted registers are written again before returning.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error.
+    /// Polling a device with the register cache disabled will result in an error.
+    pub fn poll<I2C, MODE>(
+        &mut self,
+        afe: &mut AFE4404<I2C, MODE>,
+    ) -> Result<Vec<RegisterMismatch>, AfeError<I2C::Error>>
+    where
+        I2C: I2c<SevenBitAddress>,
+        MODE: LedMode,
+    {
+        let mismatches =
+            afe.compare_configuration(|reg_addr| self.registers.binary_search(&reg_addr).is_ok())?;
+
+        self.statistics.polls = self.statistics.polls.saturating_add(1);
+        if !mismatches.is_empty() {
+            self.statistics.corruption_events = self.statistics.corruption_events.saturating_add(1);
+            #[allow(clippy::cast_possible_truncation)]
+            let count = mismatches.len() as u32;
+            self.statistics.corrupted_registers =
+                self.statistics.corrupted_registers.saturating_add(count);
+        }
+
+        if self.action == DriftAction::Rewrite {
+            for mismatch in &mismatches {
+                afe.registers
+                    .r00h()
+                    .write_address(mismatch.reg_addr, mismatch.expected)?;
+                self.statistics.rewrites = self.statistics.rewrites.saturating_add(1);
+            }
+        }
+
+        Ok(mismatches)
+    }
+}
@@ -0,0 +1,316 @@
+//! This module contains the compact configuration of the [`AFE4404`] used for over-the-air updates.
+
+use embedded_hal::i2c::{I2c, SevenBitAddress};
+use serde::{Deserialize, Serialize};
+use uom::si::{
+    capacitance::picofarad,
+    electric_current::{microampere, milliampere},
+    electrical_resistance::kiloohm,
+    f32::{Capacitance, ElectricCurrent, ElectricalResistance},
+};
+
+use crate::{
+    device::AFE4404,
+    errors::AfeError,
+    led_current::{LedCurrentConfiguration, OffsetCurrentConfiguration},
+    modes::{LedMode, ThreeLedsMode, TwoLedsMode},
+    tia::{CapacitorConfiguration, ResistorConfiguration},
+};
+
+/// Represents the sensor settings of the [`AFE4404`] in a compact, serializable form.
+///
+/// # Notes
+///
+/// The values are plain numbers in fixed units, so that the serialized form does not depend on `uom`.
+/// The offset currents follow the order LED1, LED2, Ambient1, Ambient2 or LED3.
+/// In two LEDs mode the third LED current is ignored.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WireConfig {
+    /// The LEDs current in mA.
+    pub leds_current: [f32; 3],
+    /// The offset cancellation currents in µA.
+    pub offset_currents: [f32; 4],
+    /// The TIA resistors in kOhm.
+    pub tia_resistors: [f32; 2],
+    /// The TIA capacitors in pF.
+    pub tia_capacitors: [f32; 2],
+}
+
+#[cfg(feature = "postcard")]
+impl WireConfig {
+    /// Deserializes a `WireConfig` from postcard bytes.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the bytes are not a valid postcard encoding of a `WireConfig`.
+    pub fn from_postcard(bytes: &[u8]) -> Result<Self, postcard::Error> {
+        postcard::from_bytes(bytes)
+    }
+
+    /// Serializes the `WireConfig` into postcard bytes.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the serialization fails.
+    pub fn to_postcard(&self) -> Result<alloc::vec::Vec<u8>, postcard::Error> {
+        postcard::to_allocvec(self)
+    }
+}
+
+/// The number of I2C transactions needed to read a configuration register.
+///
+/// The `REG_READ` bit is set, the register address is written, the content is read and the `REG_READ` bit is cleared.
+const CONFIGURATION_READ_TRANSACTIONS: usize = 4;
+
+/// The number of I2C transactions needed to write a register.
+const WRITE_TRANSACTIONS: usize = 1;
+
+impl<I2C, MODE> AFE4404<I2C, MODE>
+where
+    I2C: I2c<SevenBitAddress>,
+    MODE: LedMode,
+{
+    /// Gets the number of I2C transactions issued by [`apply_configuration`](Self::apply_configuration).
+    ///
+    /// # Notes
+    ///
+    /// The registers are accessed one at a time, no burst access is used.
+    /// The count is the same in both modes and does not depend on the configuration.
+    /// The count assumes the register cache is disabled, with the cache enabled and filled only the writes are issued.
+    pub fn estimated_reconfigure_transactions(&self) -> usize {
+        // Each step as (configuration register reads, register writes).
+        let steps: [(usize, usize); 4] = [
+            (1, 2), // LEDs current: R23h read, R22h and R23h written.
+            (0, 1), // Offset currents: R3Ah written.
+            (2, 2), // TIA resistors: R20h and R21h read and written.
+            (2, 2), // TIA capacitors: R20h and R21h read and written.
+        ];
+
+        steps
+            .iter()
+            .map(|(reads, writes)| {
+                reads * CONFIGURATION_READ_TRANSACTIONS + writes * WRITE_TRANSACTIONS
+            })
+            .sum()
+    }
+}
+
+impl<I2C> AFE4404<I2C, ThreeLedsMode>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    /// Applies a compact configuration.
+    ///
+    /// # Notes
+    ///
+    /// This function automatically rounds the values to the closest actual values, the applied configuration is returned.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// Setting a value outside its allowed range will result in an error.
+    pub fn apply_configuration(
+        &mut self,
+        configuration: &WireConfig,
+    ) -> Result<WireConfig, AfeError<I2C::Error>> {
+        let [led1, led2, led3] = configuration
+            .leds_current
+            .map(ElectricCurrent::new::<milliampere>);
+        let [offset_led1, offset_led2, offset_ambient, offset_led3] = configuration
+            .offset_currents
+            .map(ElectricCurrent::new::<microampere>);
+        let [resistor1, resistor2] = configuration
+            .tia_resistors
+            .map(ElectricalResistance::new::<kiloohm>);
+        let [capacitor1, capacitor2] = configuration
+            .tia_capacitors
+            .map(Capacitance::new::<picofarad>);
+
+        let applied_leds = self.set_leds_current(
+            &LedCurrentConfiguration::<ThreeLedsMode>::new(led1, led2, led3),
+        )?;
+        let applied_offsets =
+            self.set_offset_current(&OffsetCurrentConfiguration::<ThreeLedsMode>::new(
+                offset_led1,
+                offset_led2,
+                offset_led3,
+                offset_ambient,
+            ))?;
+        let applied_resistors = self.set_tia_resistors(
+            &ResistorConfiguration::<ThreeLedsMode>::new(resistor1, resistor2),
+        )?;
+        let applied_capacitors = self.set_tia_capacitors(
+            &CapacitorConfiguration::<ThreeLedsMode>::new(capacitor1, capacitor2),
+        )?;
+
+        Ok(WireConfig {
+            leds_current: [
+                applied_leds.led1(),
+                applied_leds.led2(),
+                applied_leds.led3(),
+            ]
+            .map(ElectricCurrent::get::<milliampere>),
+            offset_currents: [
+                applied_offsets.led1(),
+                applied_offsets.led2(),
+                applied_offsets.ambient(),
+                applied_offsets.led3(),
+            ]
+            .map(ElectricCurrent::get::<microampere>),
+            tia_resistors: [applied_resistors.resistor1(), applied_resistors.resistor2()]
+                .map(ElectricalResistance::get::<kiloohm>),
+            tia_capacitors: [
+                applied_capacitors.capacitor1(),
+                applied_capacitors.capacitor2(),
+            ]
+            .map(Capacitance::get::<picofarad>),
+        })
+    }
+}
+
+impl<I2C> AFE4404<I2C, TwoLedsMode>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    /// Applies a compact configuration.
+    ///
+    /// # Notes
+    ///
+    /// This function automatically rounds the values to the closest actual values, the applied configuration is returned.
+    /// The third LED current is ignored and returned as zero.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the I2C bus encounters an error or if the [`AFE4404`] contains invalid data.
+    /// Setting a value outside its allowed range will result in an error.
+    pub fn apply_configuration(
+        &mut self,
+        configuration: &WireConfig,
+    ) -> Result<WireConfig, AfeError<I2C::Error>> {
+        let [led1, led2, _] = configuration
+            .leds_current
+            .map(ElectricCurrent::new::<milliampere>);
+        let [offset_led1, offset_led2, offset_ambient1, offset_ambient2] = configuration
+            .offset_currents
+            .map(ElectricCurrent::new::<microampere>);
+        let [resistor1, resistor2] = configuration
+            .tia_resistors
+            .map(ElectricalResistance::new::<kiloohm>);
+        let [capacitor1, capacitor2] = configuration
+            .tia_capacitors
+            .map(Capacitance::new::<picofarad>);
+
+        let applied_leds =
+            self.set_leds_current(&LedCurrentConfiguration::<TwoLedsMode>::new(led1, led2))?;
+        let applied_offsets =
+            self.set_offset_current(&OffsetCurrentConfiguration::<TwoLedsMode>::new(
+                offset_led1,
+                offset_led2,
+                offset_ambient1,
+                offset_ambient2,
+            ))?;
+        let applied_resistors = self.set_tia_resistors(
+            &ResistorConfiguration::<TwoLedsMode>::new(resistor1, resistor2),
+        )?;
+        let applied_capacitors = self.set_tia_capacitors(
+            &CapacitorConfiguration::<TwoLedsMode>::new(capacitor1, capacitor2),
+        )?;
+
+        Ok(WireConfig {
+            leds_current: [
+                applied_leds.led1().get::<milliampere>(),
+                applied_leds.led2().get::<milliampere>(),
+                0.0,
+            ],
+            offset_currents: [
+                applied_offsets.led1(),
+                applied_offsets.led2(),
+                applied_offsets.ambient1(),
+                applied_offsets.ambient2(),
+            ]
+            .map(ElectricCurrent::get::<microampere>),
+            tia_resistors: [applied_resistors.resistor1(), applied_resistors.resistor2()]
+                .map(ElectricalResistance::get::<kiloohm>),
+            tia_capacitors: [
+                applied_capacitors.capacitor1(),
+                applied_capacitors.capacitor2(),
+            ]
+            .map(Capacitance::get::<picofarad>),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockAfe;
+
+    const CONFIGURATION: WireConfig = WireConfig {
+        leds_current: [20.0, 40.0, 10.0],
+        offset_currents: [1.0, -2.0, 3.0, -4.0],
+        tia_resistors: [250.0, 100.0],
+        tia_capacitors: [5.0, 10.0],
+    };
+
+    #[test]
+    fn estimated_transactions_match_three_leds_mode() {
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        afe.apply_configuration(&CONFIGURATION).unwrap();
+
+        assert_eq!(
+            mock.transactions(),
+            afe.estimated_reconfigure_transactions()
+        );
+    }
+
+    #[test]
+    fn estimated_transactions_match_two_leds_mode() {
+        let mock = MockAfe::new();
+        let mut afe = mock.two_leds();
+
+        afe.apply_configuration(&CONFIGURATION).unwrap();
+
+        assert_eq!(
+            mock.transactions(),
+            afe.estimated_reconfigure_transactions()
+        );
+    }
+
+    #[cfg(feature = "postcard")]
+    fn assert_close(applied: &[f32], requested: &[f32], tolerance: f32) {
+        for (applied, requested) in applied.iter().zip(requested) {
+            assert!((applied - requested).abs() < tolerance);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "postcard")]
+    fn postcard_configuration_applied_to_device() {
+        let bytes = CONFIGURATION.to_postcard().unwrap();
+        let received = WireConfig::from_postcard(&bytes).unwrap();
+        assert_eq!(received, CONFIGURATION);
+
+        let mock = MockAfe::new();
+        let mut afe = mock.three_leds();
+
+        let applied = afe.apply_configuration(&received).unwrap();
+
+        assert_close(&applied.leds_current, &received.leds_current, 1.0);
+        assert_close(&applied.offset_currents, &received.offset_currents, 0.5);
+        assert_close(&applied.tia_resistors, &received.tia_resistors, 0.1);
+        assert_close(&applied.tia_capacitors, &received.tia_capacitors, 0.1);
+        assert_ne!(mock.register(0x22), 0);
+        assert_ne!(mock.register(0x3A), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "postcard")]
+    fn truncated_postcard_configuration_rejected() {
+        let bytes = CONFIGURATION.to_postcard().unwrap();
+
+        assert!(WireConfig::from_postcard(&bytes[..bytes.len() - 1]).is_err());
+    }
+}